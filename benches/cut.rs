@@ -0,0 +1,340 @@
+//! Benchmarks of cutting generated input with the library, reported as throughput in bytes of
+//! input per second, so that runs before and after a change can be compared. Run with
+//! `cargo bench`, or e.g. `cargo bench -- fields` for a single group.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rut::cut::{Buffers, Mode, Options, RunState};
+use rut::range::Ranges;
+use std::io::{self, BufReader};
+
+/// Approximate size of each generated input.
+const INPUT_SIZE: usize = 1 << 20;
+
+/// Returns lines generated by `line`, given the number of each line, until there are at least
+/// [`INPUT_SIZE`] bytes.
+fn generate(line: impl Fn(usize) -> String) -> Vec<u8> {
+    generate_lines(usize::MAX, line)
+}
+
+/// Returns up to `count` lines generated by `line`, stopping once there are at least
+/// [`INPUT_SIZE`] bytes.
+fn generate_lines(count: usize, line: impl Fn(usize) -> String) -> Vec<u8> {
+    let mut input = Vec::new();
+    let mut n = 0;
+    while n < count && input.len() < INPUT_SIZE {
+        input.extend_from_slice(line(n).as_bytes());
+        input.push(b'\n');
+        n += 1;
+    }
+    input
+}
+
+/// Returns `len` characters, taken in turn from `alphabet`, starting at a different character for
+/// each line.
+fn text(alphabet: &[char], len: usize, n: usize) -> String {
+    alphabet.iter().cycle().skip(n).take(len).collect()
+}
+
+/// Returns a reader of the input which reads it in chunks, as files and stdin are read, rather than
+/// all at once, as reading the slice directly would.
+fn reader(input: &[u8]) -> BufReader<&[u8]> {
+    BufReader::new(input)
+}
+
+fn ranges(ranges: &str) -> Ranges {
+    ranges.parse().unwrap()
+}
+
+/// Benchmarks each mode with the same input, in a group named `name`.
+fn bench_modes(c: &mut Criterion, name: &str, input: &[u8], modes: &[(&str, Mode)]) {
+    let mut group = c.benchmark_group(name);
+    group.throughput(Throughput::Bytes(input.len() as u64));
+    for (id, mode) in modes {
+        group.bench_with_input(BenchmarkId::from_parameter(id), input, |b, input| {
+            b.iter(|| {
+                let (options, mut state) = (Options::default(), RunState::default());
+                mode.cut(&mut reader(input), &mut io::sink(), &options, &mut state)
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+fn bytes(c: &mut Criterion) {
+    let alphabet: Vec<char> = ('a'..='z').chain('0'..='9').collect();
+    let input = generate(|n| text(&alphabet, 80, n));
+    bench_modes(
+        c,
+        "bytes",
+        &input,
+        &[
+            ("narrow", Mode::bytes(ranges("3-6"))),
+            ("prefix", Mode::bytes(ranges("1-40"))),
+            ("scattered", Mode::bytes(ranges("1,9,17,33,65"))),
+            ("wide", Mode::bytes(ranges("2-79"))),
+            ("all", Mode::bytes(ranges("1-"))),
+        ],
+    );
+
+    // Long lines truncated to their first bytes.
+    let input = generate(|n| text(&alphabet, 1000, n));
+    bench_modes(
+        c,
+        "bytes-long",
+        &input,
+        &[
+            ("prefix", Mode::bytes(ranges("1-80"))),
+            ("middle", Mode::bytes(ranges("41-120"))),
+        ],
+    );
+}
+
+fn characters(c: &mut Criterion) {
+    let ascii: Vec<char> = ('a'..='z').collect();
+    let multi_byte: Vec<char> = "αβγδεζηθικλμνξοπρστυφχψω中文字符😀😁😂".chars().collect();
+    let cjk: Vec<char> = ('\u{4e00}'..='\u{4eff}').collect();
+    let mut group = c.benchmark_group("characters");
+    let alphabets = [("ascii", ascii), ("multi-byte", multi_byte), ("cjk", cjk)];
+    for (id, alphabet) in &alphabets {
+        let input = generate(|n| text(alphabet, 60, n));
+        let mode = Mode::characters(ranges("2-20,40-"));
+        group.throughput(Throughput::Bytes(input.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(id), &input, |b, input| {
+            b.iter(|| {
+                let (options, mut state) = (Options::default(), RunState::default());
+                mode.cut(&mut reader(input), &mut io::sink(), &options, &mut state)
+                    .unwrap()
+            })
+        });
+    }
+    group.finish();
+
+    // Very long lines, from which only the first characters are selected.
+    let input = generate(|n| text(&alphabets[1].1, 10_000, n));
+    bench_modes(
+        c,
+        "characters-long",
+        &input,
+        &[
+            ("first", Mode::characters(ranges("1-10"))),
+            ("middle", Mode::characters(ranges("100-200"))),
+            ("to-end", Mode::characters(ranges("9990-"))),
+        ],
+    );
+}
+
+fn fields(c: &mut Criterion) {
+    // Wide rows of 50 tab separated fields.
+    let input = generate(|n| {
+        (0..50)
+            .map(|field| format!("r{}f{}", n, field))
+            .collect::<Vec<String>>()
+            .join("\t")
+    });
+    bench_modes(
+        c,
+        "fields",
+        &input,
+        &[
+            ("first", Mode::fields('\t', ranges("1"))),
+            ("scattered", Mode::fields('\t', ranges("2,10,30,49"))),
+            ("to-end", Mode::fields('\t', ranges("5-"))),
+            ("all", Mode::fields('\t', ranges("1-"))),
+        ],
+    );
+
+    // Very wide rows of 500 fields, from which few are selected.
+    let input = generate(|n| {
+        (0..500)
+            .map(|field| format!("{}", (n + field) % 1000))
+            .collect::<Vec<String>>()
+            .join("\t")
+    });
+    bench_modes(
+        c,
+        "fields-500",
+        &input,
+        &[
+            ("first", Mode::fields('\t', ranges("1"))),
+            ("scattered", Mode::fields('\t', ranges("2,100,250,499"))),
+            ("last", Mode::fields('\t', ranges("500"))),
+            ("all", Mode::fields('\t', ranges("1-"))),
+        ],
+    );
+
+    // Long lines where the only delimiter is near the end, or there is none.
+    let alphabet: Vec<char> = ('a'..='z').collect();
+    for (name, delimited) in &[("fields-late", true), ("fields-absent", false)] {
+        let input = generate(|n| {
+            let mut line = text(&alphabet, 10_000, n);
+            if *delimited {
+                line.insert(9_990, '\t');
+            }
+            line
+        });
+        let mut suppressed = Mode::fields('\t', ranges("2"));
+        if let Mode::FieldsChar(_, _, field_options) = &mut suppressed {
+            field_options.suppress = true;
+        }
+        bench_modes(
+            c,
+            name,
+            &input,
+            &[
+                ("second", Mode::fields('\t', ranges("2"))),
+                ("suppress", suppressed),
+            ],
+        );
+    }
+}
+
+fn short_records(c: &mut Criterion) {
+    // Many short records, where the cost of each record matters more than the cost of each byte.
+    let input = generate(|n| format!("{},{}", n % 10, n % 7));
+    bench_modes(
+        c,
+        "short-records",
+        &input,
+        &[
+            ("bytes", Mode::bytes(ranges("1,3"))),
+            ("characters", Mode::characters(ranges("1,3"))),
+            ("fields", Mode::fields(',', ranges("2"))),
+        ],
+    );
+}
+
+fn many_files(c: &mut Criterion) {
+    // Many small inputs, cut with new buffers for each, or with the same buffers for all of them.
+    let inputs: Vec<Vec<u8>> = (0..1000)
+        .map(|file| generate_lines(5, |n| format!("f{}r{},b,c,d", file, n)))
+        .collect();
+    let size: usize = inputs.iter().map(Vec::len).sum();
+    let mut group = c.benchmark_group("many-files");
+    group.throughput(Throughput::Bytes(size as u64));
+    let modes = [
+        ("bytes", Mode::bytes(ranges("2-5"))),
+        ("characters", Mode::characters(ranges("2-5"))),
+        ("fields", Mode::fields(',', ranges("1,3"))),
+    ];
+    for (id, mode) in &modes {
+        group.bench_with_input(BenchmarkId::new("new", id), &inputs, |b, inputs| {
+            b.iter(|| {
+                for input in inputs {
+                    let (options, mut state) = (Options::default(), RunState::default());
+                    mode.cut(&mut &input[..], &mut io::sink(), &options, &mut state)
+                        .unwrap()
+                }
+            })
+        });
+        group.bench_with_input(BenchmarkId::new("reused", id), &inputs, |b, inputs| {
+            let mut buffers = Buffers::default();
+            b.iter(|| {
+                for input in inputs {
+                    let (options, mut state) = (Options::default(), RunState::default());
+                    mode.cut_with(
+                        &mut &input[..],
+                        &mut io::sink(),
+                        &options,
+                        &mut state,
+                        &mut buffers,
+                    )
+                    .unwrap()
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+fn many_ranges(c: &mut Criterion) {
+    // A machine generated spec of 5,000 disjoint ranges, of which only the first 40 can select
+    // anything from the 80 character lines, compared with the same spec cut short.
+    let spec = |count: usize| {
+        (0..count)
+            .map(|n| (n * 2 + 1).to_string())
+            .collect::<Vec<String>>()
+            .join(",")
+    };
+    let alphabet: Vec<char> = ('a'..='z').collect();
+    let input = generate(|n| {
+        let line = text(&alphabet, 80, n);
+        line.chars()
+            .enumerate()
+            .map(|(i, c)| if i % 8 == 7 { '\t' } else { c })
+            .collect()
+    });
+    let mut modes = Vec::new();
+    for (id, count) in [("5000", 5000), ("40", 40)] {
+        modes.push((format!("bytes-{}", id), Mode::bytes(ranges(&spec(count)))));
+        modes.push((
+            format!("characters-{}", id),
+            Mode::characters(ranges(&spec(count))),
+        ));
+        modes.push((
+            format!("fields-{}", id),
+            Mode::fields('\t', ranges(&spec(count))),
+        ));
+    }
+    let modes: Vec<(&str, Mode)> = modes
+        .iter()
+        .map(|(id, mode)| (id.as_str(), mode.clone()))
+        .collect();
+    bench_modes(c, "many-ranges", &input, &modes);
+}
+
+#[cfg(feature = "regex")]
+fn fields_regex(c: &mut Criterion) {
+    use rut::cut::FieldOptions;
+
+    // Fields separated by a varying number of spaces and tabs.
+    let input = generate(|n| {
+        (0..20)
+            .map(|field| format!("r{}f{}{}", n, field, [" ", "  ", "\t", " \t "][field % 4]))
+            .collect()
+    });
+    let mode = |selected: &str| {
+        let mut field_options = FieldOptions::default();
+        field_options.output_delimiter = b" ".to_vec();
+        let delimiter = regex::bytes::Regex::new(r"\s+").unwrap();
+        Mode::FieldsRegex(ranges(selected), delimiter, field_options)
+    };
+    bench_modes(
+        c,
+        "fields-regex",
+        &input,
+        &[("scattered", mode("2,4,10")), ("all", mode("1-"))],
+    );
+}
+
+#[cfg(not(feature = "regex"))]
+fn fields_regex(_: &mut Criterion) {}
+
+fn parse_ranges(c: &mut Criterion) {
+    let units: Vec<String> = (1..=1000).map(|n| (n * 2).to_string()).collect();
+    let mixed: Vec<String> = (1..=1000)
+        .map(|n| match n % 4 {
+            0 => format!("{}", n * 5),
+            1 => format!("{}-{}", n * 5, n * 5 + 3),
+            2 => format!("-{}", n),
+            _ => format!("{}-", n * 7),
+        })
+        .collect();
+
+    let mut group = c.benchmark_group("parse-ranges");
+    for (id, spec) in &[("units", units.join(",")), ("mixed", mixed.join(","))] {
+        group.throughput(Throughput::Bytes(spec.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(id), spec, |b, spec| {
+            b.iter(|| spec.parse::<Ranges>().unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    // Only the text report is used, and plotting the summary of a group can panic in criterion 0.3.
+    config = Criterion::default().without_plots();
+    targets = bytes, characters, fields, short_records, many_files, many_ranges, fields_regex, parse_ranges
+}
+criterion_main!(benches);
@@ -0,0 +1,102 @@
+//! Records build metadata for `rut --version` as environment variables read with `env!`.
+
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    set_env(
+        "RUT_COMMIT",
+        &commit().unwrap_or_else(|| String::from("unknown")),
+    );
+    set_env("RUT_BUILD_DATE", &build_date());
+    set_env("RUT_TARGET", &env::var("TARGET").unwrap_or_default());
+    set_env(
+        "RUT_RUSTC_VERSION",
+        &rustc_version().unwrap_or_else(|| String::from("unknown")),
+    );
+    set_env("RUT_FEATURES", &features());
+
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    // Rebuild when the checked out commit changes. Paths which do not exist would cause a rebuild
+    // every time, e.g. when building from a package rather than a clone.
+    for path in &[".git/HEAD", ".git/refs/heads"] {
+        if Path::new(path).exists() {
+            println!("cargo:rerun-if-changed={}", path);
+        }
+    }
+}
+
+fn set_env(name: &str, value: &str) {
+    println!("cargo:rustc-env={}={}", name, value);
+}
+
+/// Returns the output of a command, if it succeeds and is not empty.
+fn output(command: &mut Command) -> Option<String> {
+    let output = command
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let output = String::from_utf8(output.stdout).ok()?;
+    Option::Some(output.trim().to_string()).filter(|output| !output.is_empty())
+}
+
+/// Returns the abbreviated hash of the commit being built, if building from a git repository.
+fn commit() -> Option<String> {
+    output(Command::new("git").args(["rev-parse", "--short=12", "HEAD"]))
+}
+
+fn rustc_version() -> Option<String> {
+    let rustc = env::var_os("RUSTC").unwrap_or_else(|| "rustc".into());
+    output(Command::new(rustc).arg("--version"))
+}
+
+/// Returns the enabled cargo features, other than "default", separated by commas, or "none".
+fn features() -> String {
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .filter(|feature| feature != "default")
+        .collect();
+    features.sort();
+    if features.is_empty() {
+        String::from("none")
+    } else {
+        features.join(",")
+    }
+}
+
+/// Returns the UTC date of the build as YYYY-MM-DD. SOURCE_DATE_EPOCH is used instead of the
+/// current time if it is set, for reproducible builds.
+fn build_date() -> String {
+    let seconds = env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.parse::<u64>().ok())
+        .unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |duration| duration.as_secs())
+        });
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Converts a number of days since 1970-01-01 to a date in the proleptic Gregorian calendar, using
+/// Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month + 2) / 5 + 1) as u32;
+    let month = (if month < 10 { month + 3 } else { month - 9 }) as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
@@ -0,0 +1,35 @@
+//! Example of using `rut` from JavaScript, by building for `wasm32-unknown-unknown` with
+//! wasm-bindgen, e.g. with `wasm-pack build examples/wasm`.
+
+use rut::cut::cut_line_fields;
+use rut::range::{ParseRangesError, Ranges};
+use wasm_bindgen::prelude::*;
+
+/// Returns the fields of `line`, separated by `delimiter`, which are selected by `spec`, e.g.
+/// "1,3-", joined by the same delimiter. A line without the delimiter is returned whole.
+///
+/// Throws an error if `spec` is not a valid list of ranges.
+#[wasm_bindgen]
+pub fn cut_fields(line: &str, spec: &str, delimiter: char) -> Result<String, JsError> {
+    select_fields(line, spec, delimiter).map_err(|error| JsError::new(&error.annotate(spec)))
+}
+
+fn select_fields(line: &str, spec: &str, delimiter: char) -> Result<String, ParseRangesError> {
+    let ranges: Ranges = spec.parse()?;
+    let mut output_delimiter = [0; 4];
+    let output_delimiter = delimiter.encode_utf8(&mut output_delimiter);
+    Result::Ok(cut_line_fields(line, delimiter, &ranges, output_delimiter).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::select_fields;
+
+    #[test]
+    fn cut_fields() {
+        assert_eq!(select_fields("a,b,c,d", "2-3", ',').unwrap(), "b,c");
+        assert_eq!(select_fields("a\tb\tc", "1,3", '\t').unwrap(), "a\tc");
+        assert_eq!(select_fields("abc", "2", ',').unwrap(), "abc");
+        assert!(select_fields("a,b", "0", ',').is_err());
+    }
+}
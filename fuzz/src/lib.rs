@@ -0,0 +1,55 @@
+//! Checks shared by the fuzz targets and by `tests/test_fuzz.rs`, which runs them on stable Rust
+//! for the seed corpus and a bounded number of generated inputs.
+
+use rut::range::{MergedRange, Ranges};
+
+/// Parses `input` as ranges, and panics if the parser panics or the result is not consistent.
+///
+/// Parsed ranges must be sorted, disjoint and not adjacent, and formatting them must give a string
+/// which parses to the same ranges. Errors must have a span within `input`, and describing them
+/// must not panic.
+pub fn check_parse_ranges(input: &str) {
+    match input.parse::<Ranges>() {
+        Result::Ok(ranges) => {
+            let merged: Vec<MergedRange> = ranges.iter().copied().collect();
+            assert!(!merged.is_empty(), "{:?} parsed to no ranges", input);
+            for range in &merged {
+                if let MergedRange::Closed(start, end) = *range {
+                    assert!(start <= end, "{:?} parsed to {:?}", input, merged);
+                }
+            }
+            for pair in merged.windows(2) {
+                let end = pair[0].end();
+                assert!(
+                    end.is_some_and(|end| end < pair[1].start().saturating_sub(1)),
+                    "{:?} parsed to {:?}",
+                    input,
+                    merged
+                );
+            }
+
+            let formatted = ranges.to_string();
+            assert_eq!(
+                formatted.parse::<Ranges>().as_ref(),
+                Result::Ok(&ranges),
+                "{:?} was formatted as {:?}",
+                input,
+                formatted
+            );
+        }
+        Result::Err(err) => {
+            let span = err.span();
+            assert!(
+                span.start <= span.end && span.end <= input.len(),
+                "{:?} has error span {:?}",
+                input,
+                span
+            );
+            let _ = err.text(input);
+            let _ = err.position(input);
+            let _ = err.annotate(input);
+            let _ = err.gnu_message(input, true);
+            let _ = err.gnu_message(input, false);
+        }
+    }
+}
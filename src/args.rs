@@ -1,280 +1,2169 @@
-use crate::range::Ranges;
-
-use clap::{crate_version, App, Arg, ArgGroup, ArgMatches};
-use regex::Regex;
-
-static BYTES: &str = "bytes";
-static CHARACTERS: &str = "characters";
-static FIELDS: &str = "fields";
-static CHAR_DELIMITER: &str = "char_delimiter";
-static REGEX_DELIMITER: &str = "regex_delimiter";
-static OUTPUT_DELIMITER: &str = "output_delimiter";
-static COMPLEMENT: &str = "complement";
-static SUPPRESS: &str = "suppress";
-static ZERO_TERMINATED: &str = "zero_terminated";
-static NO_SPLIT: &str = "no_split";
-static FILE: &str = "file";
+use crate::config::{self, Config};
+use crate::files::{validate_stdin_once, FileList, ListFormat};
+use crate::input::InputOptions;
+use crate::report::ErrorFormat;
+use rut::cut::{
+    self, Base64Alphabet, BetweenOptions, ByteFormat, FieldOptions, HeaderOut, HexFormat,
+    InvalidUtf8, Options, SubFields,
+};
+use rut::range::{MergedRange, ParseRangesError, Ranges};
+use rut::template::{ParseTemplateError, Template};
+
+use clap::builder::NonEmptyStringValueParser;
+use clap::{value_parser, ArgAction, ArgGroup, CommandFactory, Parser, ValueEnum};
+use clap_complete::Shell;
+use clap_mangen::roff::{bold, roman, Roff};
+use clap_mangen::Man;
+use encoding_rs::{Encoding, UTF_8};
+#[cfg(feature = "regex")]
+use regex::bytes::Regex;
+use std::convert::TryFrom;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use unicode_segmentation::UnicodeSegmentation;
+
 static USAGE: &str = r"rut -b <ranges> [file]...
-    rut -c <ranges> [file]...
-    rut -f <ranges> [OPTIONS] [file]...";
+       rut -c <ranges> [file]...
+       rut -f <ranges> [OPTIONS] [file]...
+       rut -e <ranges> [OPTIONS] [file]...
+       rut --between <start> <end> [OPTIONS] [file]...
+       rut --format <template> [OPTIONS] [file]...";
+
+static ABOUT: &str = "select bytes, characters, or fields from each line of files";
+
+static DESCRIPTION: &str =
+    "Select bytes, characters, or fields from each line of the input files and \
+write them to stdout. Fields may be delimited by a single character or by a regular expression.";
+
+/// Syntax of the ranges given to -b, -c and -f, with a description of each form.
+static RANGE_SYNTAX: &[(&str, &str)] = &[
+    ("N", "The Nth byte, character, or field, counted from 1."),
+    ("N-", "From the Nth to the end of the line."),
+    ("N-M", "From the Nth to the Mth, inclusive."),
+    ("-M", "From the first to the Mth, inclusive."),
+];
+
+/// Examples shown in the man page, as a description and a command.
+static EXAMPLES: &[(&str, &str)] = &[
+    ("Select bytes from a file:", "rut -b1-5 file.txt"),
+    ("Select from stdin:", "cat file.txt | rut -b1-5"),
+    ("Select characters rather than bytes:", "rut -c1-4 file.txt"),
+    (
+        "Select fields delimited by '_', skipping lines without a delimiter:",
+        "rut -f2,4,6 -d'_' -s file.txt",
+    ),
+    (
+        "Select fields delimited by a regular expression, joined with '#':",
+        "rut -f2-4,6-8 -r '[ _:]' -o# file.txt",
+    ),
+    (
+        "Write fields using a template:",
+        "rut --format '{2} <{1}>' -d, file.csv",
+    ),
+    (
+        "Select from a file whose name starts with '-':",
+        "rut -b1-3 -- -strange.txt",
+    ),
+];
+
+/// Version printed by --version: the package version, commit and build date, on one line.
+const VERSION: &str = concat!(
+    env!("CARGO_PKG_VERSION"),
+    " (",
+    env!("RUT_COMMIT"),
+    " ",
+    env!("RUT_BUILD_DATE"),
+    ")"
+);
+
+/// Command line arguments, as parsed by clap. Use [`parse_args`] to validate them and convert them
+/// into [`Args`].
+///
+/// Flags use `ArgAction::Count` so that they may be repeated, as with `cut`.
+#[derive(Parser, Debug)]
+#[command(
+    name = "rut",
+    version = VERSION,
+    about = None,
+    long_about = None,
+    override_usage = USAGE,
+    // -e alone selects every field except those excluded, so it may stand in for a mode.
+    group(
+        ArgGroup::new("selection")
+            .args([
+                "bytes",
+                "characters",
+                "fields",
+                "format",
+                "between",
+                "between_regex",
+                "completions",
+                "man",
+                "exclude",
+            ])
+            .required(true)
+            .multiple(true)
+    )
+)]
+pub(crate) struct Cli {
+    #[command(flatten)]
+    mode: Mode,
+
+    #[arg(
+        long,
+        value_name = "N",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        conflicts_with = "format",
+        help = "Complement the set of selected bytes, characters, or fields. With --complement=N, only the first N are selected from, e.g. when the number of fields in each line is known."
+    )]
+    complement: Vec<String>,
+
+    #[arg(
+        short = 'e',
+        long,
+        value_name = "ranges",
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        conflicts_with = "format",
+        help = "Exclude a list of bytes, characters, or fields from the selection, after any --complement. Without -b, -c, or -f, every field is selected except those excluded. May be repeated to exclude the union of the lists."
+    )]
+    exclude: Vec<String>,
+
+    #[command(flatten)]
+    field_args: FieldArgs,
+
+    #[command(flatten)]
+    byte_args: ByteArgs,
+
+    #[command(flatten)]
+    between_args: BetweenArgs,
+
+    #[arg(
+        short = 'z',
+        long,
+        action = ArgAction::Count,
+        help = "Delimits items with a zero byte rather than a newline (0x0A)"
+    )]
+    zero_terminated: u8,
+
+    #[arg(
+        long,
+        value_name = "string",
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        conflicts_with = "zero_terminated",
+        help = "Delimit lines with a string rather than a newline, e.g. \\r\\n or ;;. Supports the same escapes as --prefix."
+    )]
+    line_delimiter: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "string",
+        allow_hyphen_values = true,
+        help = "Write a string after each output line, instead of the line delimiter. Supports the same escapes as --prefix."
+    )]
+    output_line_delimiter: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "string",
+        allow_hyphen_values = true,
+        help = "Write a string before each output line. Supports the escapes \\t, \\n, \\r, \\0, \\\\ and \\xNN."
+    )]
+    prefix: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "string",
+        allow_hyphen_values = true,
+        help = "Write a string after each output line, before the line delimiter. Supports the same escapes as --prefix."
+    )]
+    suffix: Option<String>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Validate arguments and input files without producing any output."
+    )]
+    check: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Do not decompress gzip compressed input. Normally, input which begins with the gzip magic bytes is decompressed."
+    )]
+    no_decompress: u8,
+
+    #[arg(
+        long,
+        value_name = "level",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "6",
+        conflicts_with = "check",
+        help = "Compress the output with gzip, at a level from 0 (no compression) to 9 (best compression). Defaults to 6. Writing compressed output to a terminal requires --force."
+    )]
+    compress_output: Option<String>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        requires = "compress_output",
+        help = "Write compressed output even if stdout is a terminal."
+    )]
+    force: u8,
+
+    #[arg(
+        long,
+        value_name = "label",
+        help = "Decode input from the specified encoding (e.g. latin1, windows-1252, utf-16le, utf-16be) rather than UTF-8. Output is always UTF-8."
+    )]
+    encoding: Option<String>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Keep a UTF-8 byte order mark at the start of each input file. Normally, it is removed before processing."
+    )]
+    keep_bom: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Keep a carriage return at the end of lines delimited by CRLF. Normally, it is removed before processing and not written to the output."
+    )]
+    keep_cr: u8,
+
+    #[arg(
+        long,
+        value_name = "file",
+        value_parser = value_parser!(PathBuf),
+        help = "Read the names of the input files from a file, separated by zero bytes (e.g. the output of find -print0). Use '-' to read the names from stdin."
+    )]
+    files0_from: Option<PathBuf>,
+
+    #[arg(
+        long,
+        value_name = "file",
+        value_parser = value_parser!(PathBuf),
+        conflicts_with = "files0_from",
+        help = "Read the names of the input files from a file, one per line. Blank lines and lines starting with '#' are skipped. Use '-' to read the names from stdin."
+    )]
+    files_from: Option<PathBuf>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Allow '-' (stdin) to be specified more than once. Stdin is read once and replayed for each occurrence."
+    )]
+    reuse_stdin: u8,
+
+    #[arg(
+        short = 'R',
+        long,
+        action = ArgAction::Count,
+        help = "Cut every regular file in directory arguments, recursively, in order by name."
+    )]
+    recursive: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        requires = "recursive",
+        help = "Follow symbolic links found in directories with -R. Normally, they are skipped."
+    )]
+    follow_symlinks: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        conflicts_with_all = ["files0_from", "files_from"],
+        help = "Expand file names containing '*', '?' or '[...]' as glob patterns, in order by name, for shells which do not expand them, such as cmd.exe. A pattern which matches nothing is an error."
+    )]
+    glob: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        requires = "glob",
+        help = "Ignore glob patterns which match nothing with --glob."
+    )]
+    glob_allow_empty: u8,
+
+    #[arg(
+        short = 'F',
+        long,
+        action = ArgAction::Count,
+        help = "Keep reading the last file as it grows, like tail -F. The file is reopened if it is truncated or replaced. Implies --line-buffered."
+    )]
+    follow: u8,
+
+    #[arg(
+        long,
+        value_name = "ms",
+        requires = "follow",
+        help = "How often to check for more input with -F, in milliseconds. Defaults to 1000."
+    )]
+    follow_interval: Option<String>,
+
+    #[arg(long, action = ArgAction::Count, help = "Flush output after each line.")]
+    line_buffered: u8,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Skip the first N lines of each input file."
+    )]
+    skip_lines: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Skip the first N bytes of each input file. Bytes are skipped before lines, when used with --skip-lines."
+    )]
+    skip_bytes: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        help = "Stop after writing N lines, across all input files. Lines suppressed by -s are not counted."
+    )]
+    max_lines: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with = "max_lines",
+        help = "Stop reading each input file after writing N lines from it."
+    )]
+    max_lines_per_file: Option<String>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Stop at the first file which cannot be opened or read. Normally, the error is reported and processing continues with the next file."
+    )]
+    fail_fast: u8,
+
+    #[arg(
+        short = 'q',
+        long,
+        action = ArgAction::Count,
+        help = "Do not report files which cannot be opened or read, or lines skipped by --skip-invalid. They are still reflected in the exit status."
+    )]
+    quiet: u8,
+
+    #[arg(
+        short = 'v',
+        long,
+        action = ArgAction::Count,
+        conflicts_with = "quiet",
+        help = "Report each file as it is read, and how many lines were written from it. Repeat for more detail, such as the config file and the time taken for each file."
+    )]
+    verbose: u8,
+
+    #[arg(
+        long,
+        value_name = "format",
+        default_value = "human",
+        help = "Write errors and other diagnostics to stderr as text (human) or as one JSON object per line (json)."
+    )]
+    error_format: ErrorFormatArg,
+
+    #[arg(
+        long,
+        value_name = "implementation",
+        help = "Behave like another implementation of cut. gnu uses the error messages of GNU cut, allows only one list of ranges, writes input bytes as they are, keeping carriage returns and byte order marks and not decompressing gzip input, and ignores rut's escape sequences, environment variables and config file. Implied when run as cut."
+    )]
+    compat: Option<CompatArg>,
+
+    #[command(flatten)]
+    invalid_utf8_args: InvalidUtf8Args,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Memory map regular input files rather than reading them through a buffer. This can be faster for large files. Other inputs, such as stdin and pipes, are read as usual. Files must not be modified while they are read."
+    )]
+    mmap: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Copy regular input files to stdout in the kernel, without reading them, when every byte is selected and no option changes the lines, e.g. with -b1- and --keep-cr or -z. Other inputs are cut as usual. Only supported on Linux; elsewhere, and with -v, files are always cut as usual."
+    )]
+    zero_copy: u8,
+
+    #[arg(
+        long,
+        value_name = "path",
+        value_parser = value_parser!(PathBuf),
+        conflicts_with = "no_config",
+        help = "Read option defaults from the config file at path, rather than $RUT_CONFIG or ~/.config/rut/config.toml."
+    )]
+    config: Option<PathBuf>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Do not read option defaults from a config file."
+    )]
+    no_config: u8,
+
+    #[arg(
+        short = 'j',
+        long,
+        value_name = "N",
+        conflicts_with_all = ["follow", "max_lines", "reuse_stdin", "header_out"],
+        help = "Process up to N files at the same time. Output is written in the order the files are given."
+    )]
+    jobs: Option<String>,
+
+    #[arg(
+        value_name = "file",
+        help = "Files to cut. Files will be processed in order. Use '-' to indicate stdin. Arguments after '--' are always treated as files, even if they start with '-'. Defaults to '-'."
+    )]
+    files: Vec<PathBuf>,
+}
+
+/// How the input is cut. At most one mode may be given, and one is required unless -e is given, as
+/// checked by the "selection" group of [`Cli`]. --completions and --man are also members, since no
+/// mode is needed to print a completion script or man page.
+#[derive(clap::Args, Debug)]
+#[group(id = "modes", required = false, multiple = false)]
+struct Mode {
+    #[arg(
+        short = 'b',
+        long,
+        value_name = "ranges",
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        help = "Cut based on list of bytes. May be repeated to select the union of the lists."
+    )]
+    bytes: Vec<String>,
+
+    #[arg(
+        short = 'c',
+        long,
+        value_name = "ranges",
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        help = "Cut based on a list of characters. May be repeated to select the union of the lists."
+    )]
+    characters: Vec<String>,
+
+    #[arg(
+        short = 'f',
+        long,
+        value_name = "ranges",
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        help = "Cut based on a list of fields, assumed to be separated by a delimiter character. May be repeated to select the union of the lists."
+    )]
+    fields: Vec<String>,
+
+    #[arg(
+        long,
+        value_name = "template",
+        value_parser = NonEmptyStringValueParser::new(),
+        help = "Write fields using a template instead of selecting them with -f. {N} is replaced by field N, {N:-default} by field N or default if it is missing, and {{ and }} by literal braces."
+    )]
+    format: Option<String>,
+
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["start", "end"],
+        action = ArgAction::Set,
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        conflicts_with_all = BETWEEN_CONFLICTS,
+        help = "Select the text between the first occurrence of start and the next occurrence of end after it, or nothing if either is missing. Lines without both are suppressed by -s."
+    )]
+    between: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        num_args = 2,
+        value_names = ["start", "end"],
+        action = ArgAction::Set,
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        conflicts_with_all = BETWEEN_CONFLICTS,
+        help = "Select the text between matches of regular expressions, as --between does. Matches of the empty string, e.g. of '$', are markers."
+    )]
+    between_regex: Option<Vec<String>>,
+
+    #[arg(
+        long,
+        value_name = "shell",
+        exclusive = true,
+        help = "Print a completion script for the specified shell and exit."
+    )]
+    completions: Option<Shell>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        exclusive = true,
+        help = "Print a man page and exit."
+    )]
+    man: u8,
+}
+
+/// Options which only apply to field mode (-f or --format).
+#[derive(clap::Args, Debug)]
+#[group(conflicts_with_all = ["bytes", "characters"])]
+struct FieldArgs {
+    #[arg(
+        short = 'd',
+        long,
+        value_name = "delim",
+        value_parser = NonEmptyStringValueParser::new(),
+        conflicts_with = "regex_delimiter",
+        help = "Set the field delimiter to the character delim. Supports the same escapes as --prefix."
+    )]
+    delimiter: Option<String>,
+
+    #[arg(
+        short = 'r',
+        long,
+        value_name = "regex",
+        value_parser = NonEmptyStringValueParser::new(),
+        help = "Set the field delimiter to the regular expression. Matches of the empty string, e.g. of 'x*' between two other characters, are not delimiters. '^' and '$' match at the start and end of each line, so a match at the start is preceded by an empty first field."
+    )]
+    regex_delimiter: Option<String>,
+
+    #[arg(
+        short = 'o',
+        long,
+        value_name = "output-delim",
+        value_parser = NonEmptyStringValueParser::new(),
+        conflicts_with = "format",
+        help = "Set the string used to delimit selected fields (-f). Supports the same escapes as --prefix."
+    )]
+    output_delimiter: Option<String>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Allow an output delimiter which is the same as the line delimiter, so that fields cannot be told apart from lines in the output."
+    )]
+    allow_ambiguous_output: u8,
+
+    #[arg(
+        short = 's',
+        long,
+        action = ArgAction::Count,
+        help = "Suppress lines with no delimiter characters, when used with the -f option."
+    )]
+    only_delimited: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        conflicts_with = "format",
+        help = "Output the byte offsets (0-based, end exclusive) of selected fields rather than their contents (-f)."
+    )]
+    byte_offsets: u8,
+
+    #[arg(
+        long,
+        value_name = "names",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "",
+        conflicts_with = "format",
+        help = "Write a header line naming the selected fields before the first line (-f). Names default to the field positions (f1, f2, ...), or may be given as a comma separated list with --header-out=<names>."
+    )]
+    header_out: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "delim",
+        value_parser = NonEmptyStringValueParser::new(),
+        requires = "sub_fields",
+        conflicts_with_all = ["format", "byte_offsets"],
+        help = "Split each selected field (-f) again at the character delim, and select the sub-fields given by --sub-fields. Fields without delim are written whole. Supports the same escapes as --prefix."
+    )]
+    sub_delimiter: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "ranges",
+        allow_hyphen_values = true,
+        value_parser = NonEmptyStringValueParser::new(),
+        requires = "sub_delimiter",
+        help = "Select a list of sub-fields, separated by --sub-delimiter, from each selected field."
+    )]
+    sub_fields: Option<String>,
+
+    #[arg(
+        long,
+        value_name = "output-delim",
+        value_parser = NonEmptyStringValueParser::new(),
+        requires = "sub_fields",
+        help = "Set the string used to delimit selected sub-fields. Defaults to the sub-delimiter. Supports the same escapes as --prefix."
+    )]
+    sub_output_delimiter: Option<String>,
+}
+
+/// Options which do not apply to --between or --between-regex. Of the options for field mode, only
+/// -s, -o and --allow-ambiguous-output apply.
+const BETWEEN_CONFLICTS: [&str; 7] = [
+    "complement",
+    "exclude",
+    "delimiter",
+    "regex_delimiter",
+    "byte_offsets",
+    "header_out",
+    "sub_delimiter",
+];
+
+/// Options which only apply to --between or --between-regex.
+#[derive(clap::Args, Debug)]
+#[group(conflicts_with_all = ["bytes", "characters", "fields", "format", "exclude"])]
+struct BetweenArgs {
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Select the text between every pair of markers (--between), joined by the output delimiter, rather than only the first. The search for the next start continues after each end."
+    )]
+    all_occurrences: u8,
+}
+
+/// Options which only apply to byte mode (-b).
+#[derive(clap::Args, Debug)]
+#[group(conflicts_with_all = ["characters", "fields", "format"])]
+struct ByteArgs {
+    #[arg(
+        short = 'n',
+        action = ArgAction::Count,
+        help = "Do not split multi-byte characters. Not yet implemented (no-op)."
+    )]
+    no_split: u8,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Output selected bytes as lowercase hexadecimal digits (-b)."
+    )]
+    hex: u8,
+
+    #[arg(
+        long,
+        value_name = "format",
+        requires = "hex",
+        help = "Set the layout of --hex output: plain (0a1b), spaced (0a 1b), or prefixed (0x0a 0x1b)."
+    )]
+    hex_format: Option<HexFormatArg>,
+
+    #[arg(
+        long,
+        value_name = "alphabet",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "standard",
+        conflicts_with = "hex",
+        help = "Output the selected bytes of each line as base64 (-b). Use --base64=url for the URL-safe alphabet without padding."
+    )]
+    base64: Option<Base64Arg>,
+}
+
+/// Options for input which is not valid UTF-8, which do not apply to byte mode (-b) or to fields
+/// separated by a regular expression (-r).
+#[derive(clap::Args, Debug)]
+#[group(conflicts_with = "bytes")]
+struct InvalidUtf8Args {
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        help = "Skip lines which are not valid UTF-8, with a warning, rather than stopping at the first one (-c, -f). The exit status is 1 if any lines are skipped."
+    )]
+    skip_invalid: u8,
+
+    #[arg(
+        long,
+        value_name = "file",
+        value_parser = value_parser!(PathBuf),
+        requires = "skip_invalid",
+        help = "Copy lines skipped by --skip-invalid to a file."
+    )]
+    rejects: Option<PathBuf>,
+
+    #[arg(
+        long,
+        action = ArgAction::Count,
+        conflicts_with = "skip_invalid",
+        help = "Replace invalid UTF-8 with U+FFFD rather than stopping at the first invalid line (-c, -f)."
+    )]
+    lossy: u8,
+}
+
+/// Value of --hex-format.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum HexFormatArg {
+    Plain,
+    Spaced,
+    Prefixed,
+}
+
+impl From<HexFormatArg> for HexFormat {
+    fn from(value: HexFormatArg) -> Self {
+        match value {
+            HexFormatArg::Plain => HexFormat::Plain,
+            HexFormatArg::Spaced => HexFormat::Spaced,
+            HexFormatArg::Prefixed => HexFormat::Prefixed,
+        }
+    }
+}
+
+/// Value of --compat.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum CompatArg {
+    Rut,
+    Gnu,
+}
+
+/// Implementation of cut whose behavior is followed, where they differ.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Compat {
+    Rut,
+    Gnu,
+}
+
+impl Compat {
+    /// Returns the name of the program, which prefixes diagnostics.
+    pub(crate) fn program(self) -> &'static str {
+        match self {
+            Compat::Rut => "rut",
+            Compat::Gnu => "cut",
+        }
+    }
+}
+
+impl From<CompatArg> for Compat {
+    fn from(value: CompatArg) -> Self {
+        match value {
+            CompatArg::Rut => Compat::Rut,
+            CompatArg::Gnu => Compat::Gnu,
+        }
+    }
+}
+
+/// Value of --error-format.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ErrorFormatArg {
+    Human,
+    Json,
+}
+
+impl From<ErrorFormatArg> for ErrorFormat {
+    fn from(value: ErrorFormatArg) -> Self {
+        match value {
+            ErrorFormatArg::Human => ErrorFormat::Human,
+            ErrorFormatArg::Json => ErrorFormat::Json,
+        }
+    }
+}
+
+/// Value of --base64.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Base64Arg {
+    Standard,
+    Url,
+}
+
+impl From<Base64Arg> for Base64Alphabet {
+    fn from(value: Base64Arg) -> Self {
+        match value {
+            Base64Arg::Standard => Base64Alphabet::Standard,
+            Base64Arg::Url => Base64Alphabet::Url,
+        }
+    }
+}
+
+/// Mode selected by the mode group, which clap guarantees has exactly one member. Each mode which
+/// takes ranges may be repeated, so has one or more lists of ranges.
+enum Selection {
+    Bytes(Vec<String>),
+    Characters(Vec<String>),
+    Fields(Vec<String>),
+    Format(String),
+    /// Start and end markers.
+    Between(String, String),
+    /// Start and end regular expressions.
+    BetweenRegex(String, String),
+}
+
+impl Mode {
+    /// Returns the selected mode, or an error if there is none, which clap should not allow. Fields
+    /// mode, with every field selected, is implied if there is no mode but there are exclusions.
+    fn selection(self, exclude: &[String]) -> Result<Selection, ArgsError> {
+        match self {
+            Mode { bytes, .. } if !bytes.is_empty() => Result::Ok(Selection::Bytes(bytes)),
+            Mode { characters, .. } if !characters.is_empty() => {
+                Result::Ok(Selection::Characters(characters))
+            }
+            Mode { fields, .. } if !fields.is_empty() => Result::Ok(Selection::Fields(fields)),
+            Mode {
+                format: Option::Some(template),
+                ..
+            } => Result::Ok(Selection::Format(template)),
+            Mode {
+                between: Option::Some(markers),
+                ..
+            } => {
+                let [start, end] = markers_pair(markers)?;
+                Result::Ok(Selection::Between(start, end))
+            }
+            Mode {
+                between_regex: Option::Some(markers),
+                ..
+            } => {
+                let [start, end] = markers_pair(markers)?;
+                Result::Ok(Selection::BetweenRegex(start, end))
+            }
+            Mode { .. } if !exclude.is_empty() => {
+                Result::Ok(Selection::Fields(vec![String::from("1-")]))
+            }
+            // --completions and --man are handled before the arguments are parsed further.
+            Mode { .. } => Result::Err(ArgsError::MissingMode),
+        }
+    }
+}
+
+/// Returns the start and end markers given to --between or --between-regex, or an error if there
+/// are not exactly two, which clap should not allow.
+fn markers_pair(markers: Vec<String>) -> Result<[String; 2], ArgsError> {
+    <[String; 2]>::try_from(markers).map_err(|_| ArgsError::MissingMode)
+}
 
 pub(crate) struct Args {
-    pub(crate) mode_args: ModeArgs,
-    pub(crate) line_delimiter: u8,
-    pub(crate) filenames: Vec<String>,
-}
-
-pub(crate) enum ModeArgs {
-    Bytes(Ranges),
-    Characters(Ranges),
-    FieldsChar(Ranges, char, String, bool),
-    FieldsRegex(Ranges, Regex, String, bool),
-}
-
-pub(crate) fn get_matches<'a>() -> ArgMatches<'a> {
-    get_app().get_matches()
-}
-
-fn get_app<'a, 'b>() -> App<'a, 'b> {
-    App::new("rut")
-        .version(crate_version!())
-        .usage(USAGE)
-        .arg(
-            Arg::with_name(BYTES)
-                .short("b")
-                .long("bytes")
-                .allow_hyphen_values(true)
-                .value_name("ranges")
-                .help("Cut based on list of bytes")
-                .takes_value(true)
-                .empty_values(false)
-                .display_order(0)
-        )
-        .arg(
-            Arg::with_name(CHARACTERS)
-                .short("c")
-                .long("characters")
-                .allow_hyphen_values(true)
-                .value_name("ranges")
-                .help("Cut based on a list of characters.")
-                .takes_value(true)
-                .empty_values(false)
-                .display_order(1)
-        )
-        .arg(
-            Arg::with_name(FIELDS)
-                .short("f")
-                .long("fields")
-                .allow_hyphen_values(true)
-                .value_name("ranges")
-                .help("Cut based on a list of fields, assumed to be separated by a delimiter character.")
-                .takes_value(true)
-                .empty_values(false)
-                .display_order(2)
-        )
-        .group(
-            ArgGroup::with_name("modes")
-                .arg(BYTES)
-                .arg(CHARACTERS)
-                .arg(FIELDS)
-                .required(true)
-        )
-        .arg(
-            Arg::with_name(CHAR_DELIMITER)
-                .short("d")
-                .long("delimiter")
-                .value_name("delim")
-                .help("Set the field delimiter to the character delim.")
-                .takes_value(true)
-                .empty_values(false)
-                .conflicts_with_all(&[BYTES, CHARACTERS, REGEX_DELIMITER])
-                .display_order(3)
-        )
-        .arg(
-            Arg::with_name(REGEX_DELIMITER)
-                .short("r")
-                .long("regex-delimiter")
-                .value_name("regex")
-                .help("Set the field delimiter to the regular expression.")
-                .takes_value(true)
-                .empty_values(false)
-                .conflicts_with_all(&[BYTES, CHARACTERS, CHAR_DELIMITER])
-                .display_order(4)
-        )
-        .arg(
-            Arg::with_name(OUTPUT_DELIMITER)
-                .short("o")
-                .long("output-delimiter")
-                .value_name("output-delim")
-                .help("Set the string used to delimit selected fields (-f).")
-                .takes_value(true)
-                .empty_values(false)
-                .conflicts_with_all(&[BYTES, CHARACTERS])
-                .display_order(5)
-        )
-        .arg(
-            Arg::with_name(COMPLEMENT)
-                .long("complement")
-                .help("Complement the set of selected bytes, characters, or fields.")
-                .takes_value(false)
-                .multiple(true)
-                .display_order(0)
-        )
-        .arg(
-            Arg::with_name(SUPPRESS)
-                .short("s")
-                .long("only-delimited")
-                .help("Suppress lines with no delimiter characters, when used with the -f option.")
-                .takes_value(false)
-                .multiple(true)
-                .conflicts_with_all(&[BYTES, CHARACTERS])
-                .display_order(1)
-        )
-        .arg(
-            Arg::with_name(ZERO_TERMINATED)
-                .short("z")
-                .long("zero-terminated")
-                .help("Delimits items with a zero byte rather than a newline (0x0A)")
-                .multiple(true)
-                .takes_value(false)
-                .display_order(2)
-        )
-        .arg(
-            Arg::with_name(NO_SPLIT)
-                .short("n")
-                .help("Do not split multi-byte characters. Not yet implemented (no-op).")
-                .multiple(true)
-                .takes_value(false)
-                .conflicts_with_all(&[CHARACTERS, FIELDS])
-                .display_order(3)
-        )
-        .arg(
-            Arg::with_name(FILE)
-                .index(1)
-                .help("Files to cut. Files will be processed in order. Use '-' to indicate stdin.")
-                .multiple(true)
-                .default_value("-")
+    pub(crate) mode: cut::Mode,
+    pub(crate) options: Options,
+    pub(crate) filenames: Vec<PathBuf>,
+    /// File which lists the input files, replacing `filenames` once read.
+    pub(crate) file_list: Option<FileList>,
+    pub(crate) check: bool,
+    pub(crate) fail_fast: bool,
+    /// Whether to suppress errors and warnings for individual files.
+    pub(crate) quiet: bool,
+    /// Level of detail of diagnostics written to stderr, or 0 for none.
+    pub(crate) verbosity: u8,
+    /// File which lines skipped by --skip-invalid are copied to.
+    pub(crate) rejects: Option<PathBuf>,
+    pub(crate) recursive: bool,
+    pub(crate) follow_symlinks: bool,
+    /// Whether file names containing wildcards are expanded as glob patterns.
+    pub(crate) glob: bool,
+    /// Whether a glob pattern which matches nothing is ignored, rather than an error.
+    pub(crate) glob_allow_empty: bool,
+    /// Maximum number of files to process at the same time.
+    pub(crate) jobs: usize,
+    pub(crate) input_options: InputOptions,
+    /// Level which the output is compressed with gzip at, or `None` if it is not compressed.
+    pub(crate) compress_output: Option<u32>,
+    /// Whether compressed output is written even if stdout is a terminal.
+    pub(crate) force: bool,
+}
+
+/// Parses the command line arguments. Returns an error if they cannot be parsed, or if the help or
+/// version should be printed instead.
+pub(crate) fn get_cli() -> Result<Cli, clap::Error> {
+    Cli::try_parse()
+}
+
+impl Cli {
+    /// Returns the shell to print a completion script for, if --completions was specified.
+    pub(crate) fn completions(&self) -> Option<Shell> {
+        self.mode.completions
+    }
+
+    /// Returns the config file to read, and whether it must exist.
+    pub(crate) fn config_path(&self) -> Option<(PathBuf, bool)> {
+        config::config_path(self.config.as_deref(), self.no_config > 0)
+    }
+
+    /// Returns the format of diagnostics.
+    pub(crate) fn error_format(&self) -> ErrorFormat {
+        self.error_format.into()
+    }
+
+    /// Returns the implementation given by --compat, or GNU cut if rut was run as "cut".
+    pub(crate) fn compat(&self) -> Compat {
+        self.compat.map_or_else(
+            || program_compat(std::env::args_os().next().as_deref()),
+            Compat::from,
         )
+    }
+
+    /// Returns whether --man was specified.
+    pub(crate) fn man(&self) -> bool {
+        self.mode.man > 0
+    }
+}
+
+/// Writes a completion script for the shell, generated from the argument definitions.
+pub(crate) fn write_completions<W: std::io::Write>(
+    shell: Shell,
+    output: &mut W,
+) -> std::io::Result<()> {
+    // The script is generated in memory, since clap_complete panics if it cannot be written.
+    let mut script = Vec::new();
+    clap_complete::generate(shell, &mut Cli::command(), "rut", &mut script);
+    output.write_all(&script)
+}
+
+/// Writes a man page, generated from the argument definitions, with sections describing the range
+/// syntax and giving examples.
+pub(crate) fn write_man<W: std::io::Write>(output: &mut W) -> std::io::Result<()> {
+    let man = Man::new(Cli::command().about(ABOUT).long_about(DESCRIPTION));
+    man.render_title(output)?;
+    man.render_name_section(output)?;
+    man.render_synopsis_section(output)?;
+    man.render_description_section(output)?;
+    man.render_options_section(output)?;
+
+    let mut roff = Roff::new();
+    roff.control("SH", ["RANGES"]).text([roman(
+        "Each of -b, -c and -f takes a list of ranges, separated by commas. Each range is one of:",
+    )]);
+    for (syntax, description) in RANGE_SYNTAX {
+        roff.control("TP", [])
+            .text([bold(*syntax)])
+            .text([roman(*description)]);
+    }
+    roff.control("SH", ["EXAMPLES"]);
+    for (description, command) in EXAMPLES {
+        roff.control("TP", [])
+            .text([roman(*description)])
+            .text([bold(*command)]);
+    }
+    roff.to_writer(output)?;
+
+    man.render_version_section(output)
+}
+
+/// Returns the version printed by --version --verbose, with one field of build metadata per line.
+pub(crate) fn long_version() -> String {
+    format!(
+        "rut {}\ncommit: {}\nbuild date: {}\ntarget: {}\nrustc: {}\nfeatures: {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("RUT_COMMIT"),
+        env!("RUT_BUILD_DATE"),
+        env!("RUT_TARGET"),
+        env!("RUT_RUSTC_VERSION"),
+        env!("RUT_FEATURES")
+    )
+}
+
+/// Returns whether -v or --verbose was given, for printing the version, which clap does before the
+/// remaining arguments are parsed. Arguments after "--" are not options.
+pub(crate) fn verbose_from_args<I, T>(args: I) -> bool
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString>,
+{
+    args.into_iter()
+        .skip(1)
+        .map(Into::into)
+        .take_while(|arg| arg != "--")
+        .any(|arg| match arg.to_str() {
+            Option::Some("--verbose") => true,
+            Option::Some(arg) => arg
+                .strip_prefix('-')
+                .is_some_and(|flags| !flags.is_empty() && flags.chars().all(|ch| ch == 'v')),
+            Option::None => false,
+        })
+}
+
+/// Returns the format of diagnostics given by --error-format, for reporting errors in arguments
+/// which could not be parsed. Arguments after "--" are not options.
+pub(crate) fn error_format_from_args<I, T>(args: I) -> ErrorFormat
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    option_from_args(&args, "--error-format")
+        .and_then(|value| ErrorFormatArg::from_str(&value, false).ok())
+        .map_or(ErrorFormat::Human, ErrorFormat::from)
+}
+
+/// Returns the implementation given by --compat, or GNU cut if rut was run as "cut", for reporting
+/// errors in arguments which could not be parsed.
+pub(crate) fn compat_from_args<I, T>(args: I) -> Compat
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    match option_from_args(&args, "--compat") {
+        Option::Some(value) => CompatArg::from_str(&value, false).map_or(Compat::Rut, Compat::from),
+        Option::None => program_compat(args.first().map(OsString::as_os_str)),
+    }
 }
 
-pub(crate) fn parse_args(matches: &ArgMatches) -> Result<Args, String> {
-    let complement = matches.is_present(COMPLEMENT);
-
-    let mode_args = if let Some(ranges) = matches.value_of(BYTES) {
-        ModeArgs::Bytes(validate_ranges(ranges, complement)?)
-    } else if let Some(ranges) = matches.value_of(CHARACTERS) {
-        ModeArgs::Characters(validate_ranges(ranges, complement)?)
-    } else if let Some(ranges) = matches.value_of(FIELDS) {
-        let ranges = validate_ranges(ranges, complement)?;
-        let suppress = matches.is_present(SUPPRESS);
-        match (
-            matches.value_of(REGEX_DELIMITER),
-            matches.value_of(OUTPUT_DELIMITER),
-        ) {
-            // Regex delimiter and joiner specified.
-            (Some(regex), Some(joiner)) => {
-                let delimiter = validate_regex_delimiter(regex)?;
-                ModeArgs::FieldsRegex(ranges, delimiter, String::from(joiner), suppress)
+/// Returns the last value of a long option, from the program name and arguments, before they are
+/// parsed. Arguments after "--" are not options.
+fn option_from_args(args: &[OsString], name: &str) -> Option<String> {
+    let mut value = Option::None;
+    let mut args = args.iter().skip(1);
+    while let Option::Some(arg) = args.next() {
+        match arg.to_str() {
+            Option::Some("--") => break,
+            Option::Some(arg) if arg == name => {
+                value = args
+                    .next()
+                    .map(|value| value.to_string_lossy().into_owned())
             }
-            // Regex delimiter specified. Use "\t" as joiner by default.
-            (Some(regex), None) => {
-                let delimiter = validate_regex_delimiter(regex)?;
-                ModeArgs::FieldsRegex(ranges, delimiter, String::from("\t"), suppress)
+            Option::Some(arg) => {
+                if let Option::Some(option) =
+                    arg.strip_prefix(name).and_then(|arg| arg.strip_prefix('='))
+                {
+                    value = Option::Some(option.to_string());
+                }
             }
-            // Joiner specified without regex delimiter. Use character delimiter; \t by default.
-            (None, Some(output_delimiter)) => {
-                let field_delimiter =
-                    validate_char_delimiter(matches.value_of(CHAR_DELIMITER).unwrap_or("\t"))?;
-                ModeArgs::FieldsChar(
-                    ranges,
-                    field_delimiter,
-                    String::from(output_delimiter),
-                    suppress,
-                )
+            Option::None => {}
+        }
+    }
+    value
+}
+
+/// Returns GNU cut if the program name is "cut", e.g. if rut is installed as cut.
+fn program_compat(program: Option<&OsStr>) -> Compat {
+    let stem = program.and_then(|program| Path::new(program).file_stem());
+    if stem.is_some_and(|stem| stem == "cut") {
+        Compat::Gnu
+    } else {
+        Compat::Rut
+    }
+}
+
+/// Returns the message GNU cut gives for invalid arguments, where it differs from clap's.
+pub(crate) fn gnu_message(err: &clap::Error) -> Option<String> {
+    use clap::error::{ContextKind, ContextValue, ErrorKind};
+    let arg = |kind| match err.get(kind) {
+        Option::Some(ContextValue::String(arg)) => arg.as_str(),
+        Option::Some(ContextValue::Strings(args)) => args.first().map_or("", String::as_str),
+        _ => "",
+    };
+    let is_mode = |arg: &str| {
+        ["--bytes", "--characters", "--fields", "--format"]
+            .iter()
+            .any(|mode| arg.starts_with(mode))
+    };
+    match err.kind() {
+        ErrorKind::MissingRequiredArgument => Option::Some(String::from(
+            "you must specify a list of bytes, characters, or fields",
+        )),
+        ErrorKind::ArgumentConflict => {
+            let (arg, prior) = (arg(ContextKind::InvalidArg), arg(ContextKind::PriorArg));
+            if [arg, prior]
+                .iter()
+                .any(|arg| arg.starts_with("--delimiter"))
+            {
+                Option::Some(String::from(
+                    "an input delimiter may be specified only when operating on fields",
+                ))
+            } else if [arg, prior]
+                .iter()
+                .any(|arg| arg.starts_with("--only-delimited"))
+            {
+                Option::Some(String::from(
+                    "suppressing non-delimited lines makes sense\n\tonly when operating on fields",
+                ))
+            } else if is_mode(arg) && is_mode(prior) {
+                Option::Some(String::from("only one list may be specified"))
+            } else {
+                Option::None
+            }
+        }
+        ErrorKind::UnknownArgument => {
+            let arg = arg(ContextKind::InvalidArg);
+            match arg.strip_prefix('-') {
+                Option::Some(long) if long.starts_with('-') => {
+                    Option::Some(format!("unrecognized option '{}'", arg))
+                }
+                Option::Some(short) => Option::Some(format!(
+                    "invalid option -- '{}'",
+                    short.chars().next().unwrap_or('-')
+                )),
+                Option::None => Option::None,
             }
-            // No regex or joiner specified. Use character delimiter; \t by default.
-            (None, None) => {
-                // Use specified character delimiter, or '\t' by default.
-                let field_delimiter =
-                    validate_char_delimiter(matches.value_of(CHAR_DELIMITER).unwrap_or("\t"))?;
-                // Use field delimiter as output delimiter.
-                ModeArgs::FieldsChar(
-                    ranges,
-                    field_delimiter,
-                    field_delimiter.to_string(),
-                    suppress,
+        }
+        _ => Option::None,
+    }
+}
+
+/// Returns the usage message.
+pub(crate) fn usage() -> String {
+    Cli::command().render_usage().to_string()
+}
+
+/// Validates the arguments and converts them into [`Args`]. Options which are not given default to
+/// their environment variables, then to the config file.
+pub(crate) fn parse_args(cli: Cli, config: &Config) -> Result<Args, ArgsError> {
+    let compat = cli.compat();
+    let gnu = compat == Compat::Gnu;
+    let complement = validate_complement(&cli.complement)?;
+    let file_list = validate_file_list(&cli)?;
+    let field_args = cli.field_args;
+    let allow_ambiguous_output = field_args.allow_ambiguous_output > 0;
+
+    let exclude = cli.exclude;
+    let mode = match cli.mode.selection(&exclude)? {
+        Selection::Bytes(ranges) => {
+            let byte_args = cli.byte_args;
+            let format = if byte_args.hex > 0 {
+                ByteFormat::Hex(
+                    byte_args
+                        .hex_format
+                        .map_or(HexFormat::Plain, HexFormat::from),
                 )
+            } else if let Option::Some(alphabet) = byte_args.base64 {
+                ByteFormat::Base64(alphabet.into())
+            } else {
+                ByteFormat::Raw
+            };
+            cut::Mode::Bytes(
+                validate_ranges(&ranges, complement, &exclude, compat, false)?,
+                format,
+            )
+        }
+        Selection::Characters(ranges) => cut::Mode::Characters(validate_ranges(
+            &ranges, complement, &exclude, compat, false,
+        )?),
+        Selection::Between(start, end) => {
+            let options = between_options(field_args, cli.between_args, gnu, config)?;
+            cut::Mode::Between(start, end, options)
+        }
+        Selection::BetweenRegex(start, end) => {
+            let options = between_options(field_args, cli.between_args, gnu, config)?;
+            between_regex_mode(&start, &end, options)?
+        }
+        selection => {
+            let (ranges, template) = match selection {
+                Selection::Format(template) => {
+                    let template = validate_template(&template)?;
+                    (template.ranges(), Option::Some(template))
+                }
+                Selection::Fields(ranges) => (
+                    validate_ranges(&ranges, complement, &exclude, compat, true)?,
+                    Option::None,
+                ),
+                _ => unreachable!(),
+            };
+            let suppress = field_args.only_delimited > 0;
+            let byte_offsets = field_args.byte_offsets > 0;
+            let sub_fields = match (field_args.sub_delimiter, field_args.sub_fields) {
+                (Option::Some(delimiter), Option::Some(ranges)) => {
+                    Option::Some(validate_sub_fields(
+                        &delimiter,
+                        &ranges,
+                        field_args.sub_output_delimiter.as_deref(),
+                        compat,
+                    )?)
+                }
+                _ => Option::None,
+            };
+            let header = field_args
+                .header_out
+                .as_deref()
+                .map(|names| validate_header_out(Option::Some(names), &ranges))
+                .transpose()?;
+            // Like -o, the default output delimiter does not apply to --byte-offsets or --format.
+            let output_delimiter = output_delimiter_arg(
+                field_args.output_delimiter,
+                !byte_offsets && template.is_none(),
+                gnu,
+                config,
+            )?;
+            match (field_args.regex_delimiter, output_delimiter) {
+                // Regex delimiter specified. Use "\t" as joiner by default.
+                (Some(regex), joiner) => {
+                    let mut options = FieldOptions::default();
+                    options.output_delimiter = joiner.unwrap_or_else(|| b"\t".to_vec());
+                    options.suppress = suppress;
+                    options.byte_offsets = byte_offsets;
+                    options.header = header;
+                    options.template = template;
+                    options.sub_fields = sub_fields;
+                    regex_fields_mode(ranges, &regex, options)?
+                }
+                // No regex specified. Use character delimiter; \t by default.
+                (None, output_delimiter) => {
+                    let field_delimiter = match field_args.delimiter {
+                        Option::Some(delimiter) if gnu => {
+                            validate_gnu_delimiter(&delimiter)?.to_string()
+                        }
+                        Option::Some(delimiter) => {
+                            validate_char_delimiter(&delimiter, "--delimiter <delim>")?
+                        }
+                        Option::None if gnu => String::from("\t"),
+                        Option::None => {
+                            match option_default(ENV_DELIMITER, &config.delimiter, config)? {
+                                Option::Some((delimiter, origin)) => {
+                                    validate_char_delimiter(&delimiter, "--delimiter <delim>")
+                                        .map_err(|err| err.in_default(origin))?
+                                }
+                                Option::None => String::from("\t"),
+                            }
+                        }
+                    };
+                    // Use field delimiter as output delimiter by default.
+                    let mut options = FieldOptions::default();
+                    options.output_delimiter =
+                        output_delimiter.unwrap_or_else(|| field_delimiter.clone().into_bytes());
+                    options.suppress = suppress;
+                    options.byte_offsets = byte_offsets;
+                    options.header = header;
+                    options.template = template;
+                    options.sub_fields = sub_fields;
+                    char_fields_mode(ranges, field_delimiter, options)
+                }
             }
         }
+    };
+
+    let line_delimiter = match cli.line_delimiter {
+        Option::Some(value) => validate_escaped(&value, "--line-delimiter <string>")?,
+        Option::None if cli.zero_terminated > 0 => vec![0],
+        Option::None => vec![b'\n'],
+    };
+    let output_line_delimiter = cli
+        .output_line_delimiter
+        .map(|value| validate_escaped(&value, "--output-line-delimiter <string>"))
+        .transpose()?;
+    // GNU cut allows any delimiters, however ambiguous.
+    if !gnu {
+        let output_terminator = output_line_delimiter.as_ref().unwrap_or(&line_delimiter);
+        validate_delimiters(
+            &mode,
+            &line_delimiter,
+            output_terminator,
+            allow_ambiguous_output,
+        )?;
+    }
+
+    let prefix = cli
+        .prefix
+        .map(|value| validate_escaped(&value, "--prefix <string>"))
+        .transpose()?;
+    let suffix = cli
+        .suffix
+        .map(|value| validate_escaped(&value, "--suffix <string>"))
+        .transpose()?;
+    let invalid_utf8_args = cli.invalid_utf8_args;
+    let mut options = Options::default();
+    options.line_delimiter = line_delimiter;
+    options.output_line_delimiter = output_line_delimiter;
+    options.prefix = prefix.unwrap_or_default();
+    options.suffix = suffix.unwrap_or_default();
+    // GNU cut writes every byte of the input, so carriage returns, byte order marks and compressed
+    // input are kept as they are.
+    options.keep_cr = gnu || cli.keep_cr > 0;
+    options.line_buffered = cli.line_buffered > 0
+        || cli.follow > 0
+        || (!gnu
+            && match env_default(ENV_LINE_BUFFERED)? {
+                Option::Some(value) => validate_bool(&value, ENV_LINE_BUFFERED)?,
+                Option::None => config.line_buffered.unwrap_or(false),
+            });
+    options.skip_lines = validate_count(cli.skip_lines.as_deref(), "--skip-lines")?;
+    options.max_lines = if cli.max_lines_per_file.is_some() {
+        Option::Some(validate_count(
+            cli.max_lines_per_file.as_deref(),
+            "--max-lines-per-file",
+        )?)
+    } else if cli.max_lines.is_some() {
+        Option::Some(validate_count(cli.max_lines.as_deref(), "--max-lines")?)
+    } else {
+        Option::None
+    };
+    options.max_lines_per_file = cli.max_lines_per_file.is_some();
+    options.invalid_utf8 = if invalid_utf8_args.skip_invalid > 0 {
+        InvalidUtf8::Skip
+    } else if invalid_utf8_args.lossy > 0 {
+        InvalidUtf8::Lossy
     } else {
-        // Clap should guarantee that at least one mode flag is set.
-        panic!("Mode is not defined.");
+        InvalidUtf8::Fail
     };
 
-    let line_delimiter = if matches.is_present(ZERO_TERMINATED) {
-        0
+    let filenames = if cli.files.is_empty() {
+        vec![PathBuf::from("-")]
     } else {
-        b'\n'
+        cli.files
     };
+    let input_options = InputOptions {
+        decompress: !gnu && cli.no_decompress == 0,
+        encoding: cli
+            .encoding
+            .as_deref()
+            .map(validate_encoding)
+            .transpose()?
+            .flatten(),
+        keep_bom: gnu || cli.keep_bom > 0,
+        reuse_stdin: cli.reuse_stdin > 0,
+        skip_bytes: validate_count(cli.skip_bytes.as_deref(), "--skip-bytes")? as u64,
+        follow: if cli.follow > 0 {
+            Option::Some(validate_follow_interval(cli.follow_interval.as_deref())?)
+        } else {
+            Option::None
+        },
+        mmap: cli.mmap > 0,
+        zero_copy: cli.zero_copy > 0,
+    };
+    if !input_options.reuse_stdin {
+        validate_stdin_once(&filenames)?;
+    }
 
-    // Safe to unwrap FILE value since a default value is specified.
-    let filenames = matches.values_of(FILE).unwrap().map(String::from).collect();
     Result::Ok(Args {
-        mode_args,
-        line_delimiter,
+        mode,
+        options,
         filenames,
+        file_list,
+        check: cli.check > 0,
+        fail_fast: cli.fail_fast > 0,
+        quiet: cli.quiet > 0,
+        verbosity: cli.verbose,
+        rejects: invalid_utf8_args.rejects,
+        recursive: cli.recursive > 0,
+        follow_symlinks: cli.follow_symlinks > 0,
+        glob: cli.glob > 0,
+        glob_allow_empty: cli.glob_allow_empty > 0,
+        jobs: validate_jobs(cli.jobs.as_deref())?,
+        input_options,
+        compress_output: cli
+            .compress_output
+            .as_deref()
+            .map(validate_compression_level)
+            .transpose()?,
+        force: cli.force > 0,
     })
 }
 
-/// Validates and returns the value as ranges, or returns an error message if validation fails.
-fn validate_ranges(value: &str, complement: bool) -> Result<Ranges, String> {
-    // Clap should ensure that a non-empty range is provided.
-    debug_assert!(!value.is_empty());
+/// Environment variable which sets the field delimiter when neither -d nor -r is given.
+const ENV_DELIMITER: &str = "RUT_DELIMITER";
+/// Environment variable which sets the output delimiter when -o is not given.
+const ENV_OUTPUT_DELIMITER: &str = "RUT_OUTPUT_DELIMITER";
+/// Environment variable which enables --line-buffered.
+const ENV_LINE_BUFFERED: &str = "RUT_LINE_BUFFERED";
 
-    value
-        .parse::<Ranges>()
-        .map(|ranges| {
-            if complement {
-                ranges.complement()
-            } else {
-                ranges
+/// Returns the value of an environment variable which provides a default for an option, or `None`
+/// if it is not set or empty.
+fn env_default(name: &'static str) -> Result<Option<String>, ArgsError> {
+    match std::env::var(name) {
+        Result::Ok(value) if value.is_empty() => Result::Ok(Option::None),
+        Result::Ok(value) => Result::Ok(Option::Some(value)),
+        Result::Err(std::env::VarError::NotPresent) => Result::Ok(Option::None),
+        Result::Err(std::env::VarError::NotUnicode(_)) => {
+            Result::Err(ArgsError::EnvNotUnicode { name })
+        }
+    }
+}
+
+/// Returns the default for an option from its environment variable, or else from the config file,
+/// along with a description of where it came from for error messages.
+fn option_default(
+    name: &'static str,
+    value: &Option<String>,
+    config: &Config,
+) -> Result<Option<(String, String)>, ArgsError> {
+    Result::Ok(match env_default(name)? {
+        Option::Some(value) => Option::Some((value, format!("environment variable {}", name))),
+        Option::None => value.clone().map(|value| {
+            let path = config.path.as_deref().unwrap_or_default();
+            (value, format!("config file {}", path))
+        }),
+    })
+}
+
+/// Validates and returns the value of a boolean environment variable.
+fn validate_bool(value: &str, name: &'static str) -> Result<bool, ArgsError> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Result::Ok(true),
+        "0" | "false" | "no" | "off" => Result::Ok(false),
+        _ => Result::Err(ArgsError::InvalidBool {
+            name,
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Validates and returns the output delimiter given by -o, or else the default from the environment
+/// or config file if `use_default` is true, or `None` if neither is given.
+fn output_delimiter_arg(
+    value: Option<String>,
+    use_default: bool,
+    gnu: bool,
+    config: &Config,
+) -> Result<Option<Vec<u8>>, ArgsError> {
+    match value {
+        // GNU cut does not support escape sequences.
+        Option::Some(output_delimiter) if gnu => {
+            Result::Ok(Option::Some(output_delimiter.into_bytes()))
+        }
+        Option::Some(output_delimiter) => {
+            validate_escaped(&output_delimiter, "--output-delimiter <output-delim>")
+                .map(Option::Some)
+        }
+        Option::None if use_default && !gnu => {
+            match option_default(ENV_OUTPUT_DELIMITER, &config.output_delimiter, config)? {
+                Option::Some((value, origin)) => Result::Ok(Option::Some(
+                    validate_escaped(&value, "--output-delimiter <output-delim>")
+                        .map_err(|err| err.in_default(origin))?,
+                )),
+                Option::None => Result::Ok(Option::None),
             }
+        }
+        Option::None => Result::Ok(Option::None),
+    }
+}
+
+/// Validates and returns the sub-fields to select from each field, or returns an error if the
+/// sub-delimiter is not a single character, the list of ranges is not valid, or the output
+/// delimiter is not valid.
+fn validate_sub_fields(
+    delimiter: &str,
+    ranges: &str,
+    output_delimiter: Option<&str>,
+    compat: Compat,
+) -> Result<SubFields, ArgsError> {
+    let delimiter = validate_char_delimiter(delimiter, "--sub-delimiter <delim>")?;
+    let ranges = parse_range_lists(&[ranges.to_string()], compat, true)?;
+    let mut sub_fields = SubFields::new(&delimiter, ranges);
+    if let Option::Some(output_delimiter) = output_delimiter {
+        sub_fields.output_delimiter =
+            validate_escaped(output_delimiter, "--sub-output-delimiter <output-delim>")?;
+    }
+    Result::Ok(sub_fields)
+}
+
+/// Returns the options for --between or --between-regex, which joins the text between each pair of
+/// markers with "\t" unless another output delimiter is given.
+fn between_options(
+    field_args: FieldArgs,
+    between_args: BetweenArgs,
+    gnu: bool,
+    config: &Config,
+) -> Result<BetweenOptions, ArgsError> {
+    let output_delimiter = output_delimiter_arg(field_args.output_delimiter, true, gnu, config)?;
+    let mut options = BetweenOptions::default();
+    options.output_delimiter = output_delimiter.unwrap_or_else(|| b"\t".to_vec());
+    options.suppress = field_args.only_delimited > 0;
+    options.all_occurrences = between_args.all_occurrences > 0;
+    Result::Ok(options)
+}
+
+/// Validates the values of --complement, which may be repeated, and returns `None` if it was not
+/// given, or the bound to complement within, if any. Returns an error if a bound is not a number,
+/// or different bounds are given.
+fn validate_complement(values: &[String]) -> Result<Option<Option<usize>>, ArgsError> {
+    if values.is_empty() {
+        return Result::Ok(Option::None);
+    }
+    let mut bound = Option::None;
+    for value in values.iter().filter(|value| !value.is_empty()) {
+        let parsed = value
+            .parse::<usize>()
+            .map_err(|_| ArgsError::InvalidComplement {
+                value: value.clone(),
+            })?;
+        if let Option::Some(first) = bound.filter(|&bound| bound != parsed) {
+            return Result::Err(ArgsError::ConflictingComplement {
+                first,
+                second: parsed,
+            });
+        }
+        bound = Option::Some(parsed);
+    }
+    Result::Ok(Option::Some(bound))
+}
+
+/// Validates and returns the union of the lists of ranges, or returns an error if any list is
+/// invalid. The complement, if requested, is of the union, and within the bound if there is one.
+/// The union of the excluded lists is then removed from the selection.
+fn validate_ranges(
+    values: &[String],
+    complement: Option<Option<usize>>,
+    exclude: &[String],
+    compat: Compat,
+    fields: bool,
+) -> Result<Ranges, ArgsError> {
+    // Clap should ensure that at least one non-empty list of ranges is provided.
+    debug_assert!(!values.is_empty() && values.iter().all(|value| !value.is_empty()));
+
+    if compat == Compat::Gnu && values.len() > 1 {
+        return Result::Err(ArgsError::MultipleRangeLists);
+    }
+    let ranges = parse_range_lists(values, compat, fields)?;
+    let ranges = match complement {
+        Option::None => ranges,
+        Option::Some(Option::None) => ranges.complement(),
+        Option::Some(Option::Some(bound)) => ranges.complement_within(bound),
+    };
+    if exclude.is_empty() {
+        Result::Ok(ranges)
+    } else {
+        Result::Ok(ranges.difference(&parse_range_lists(exclude, compat, fields)?))
+    }
+}
+
+/// Parses and returns the union of the lists of ranges, or returns an error if any list is invalid.
+fn parse_range_lists(values: &[String], compat: Compat, fields: bool) -> Result<Ranges, ArgsError> {
+    let ranges = values
+        .iter()
+        .map(|value| {
+            value
+                .parse::<Ranges>()
+                .map_err(|source| ArgsError::InvalidRanges {
+                    spec: value.clone(),
+                    source,
+                    compat,
+                    fields,
+                })
         })
-        .map_err(|e| e.to_string())
+        .collect::<Result<Vec<Ranges>, ArgsError>>()?;
+    Result::Ok(Ranges::union(ranges))
 }
 
-/// Validates and returns the value as a character, or returns an error message if it is not a single character.
-fn validate_char_delimiter(value: &str) -> Result<char, String> {
-    let mut chars = value.chars();
-    let delimiter = if let Some(ch) = chars.next() {
-        ch
+/// Validates and returns the header to write before the first line, or returns an error if the
+/// number of names does not match the number of selected fields. Names are only required to
+/// match for bounded selections; see [`HeaderOut`].
+fn validate_header_out(value: Option<&str>, ranges: &Ranges) -> Result<HeaderOut, ArgsError> {
+    let names: Vec<String> = match value {
+        Some(value) if !value.is_empty() => value.split(',').map(String::from).collect(),
+        _ => Vec::new(),
+    };
+
+    let mut indices = Vec::new();
+    for range in ranges.clone() {
+        match range {
+            MergedRange::Closed(start, end) => indices.extend(start..=end),
+            MergedRange::ToEnd(_) => return Result::Ok(HeaderOut::unbounded(names)),
+        }
+    }
+
+    if names.is_empty() {
+        let names = indices
+            .into_iter()
+            .map(HeaderOut::positional_name)
+            .collect();
+        Result::Ok(HeaderOut::bounded(names))
+    } else if names.len() == indices.len() {
+        Result::Ok(HeaderOut::bounded(names))
+    } else {
+        Result::Err(ArgsError::HeaderOutCount {
+            fields: indices.len(),
+            names: names.len(),
+        })
+    }
+}
+
+/// Validates and returns the value as an output template, or returns an error if it is not a valid
+/// template.
+fn validate_template(value: &str) -> Result<Template, ArgsError> {
+    value
+        .parse()
+        .map_err(|source| ArgsError::InvalidTemplate { source })
+}
+
+/// Returns the file list specified by --files0-from or --files-from, if any, or returns an error if
+/// file arguments are also specified.
+fn validate_file_list(cli: &Cli) -> Result<Option<FileList>, ArgsError> {
+    let (option, path, format) = if let Option::Some(path) = &cli.files0_from {
+        ("--files0-from", path, ListFormat::Nul)
+    } else if let Option::Some(path) = &cli.files_from {
+        ("--files-from", path, ListFormat::Lines)
     } else {
-        // Clap should guarantee that a value is provided.
-        panic!("A delimiter was not provided.")
+        return Result::Ok(Option::None);
     };
-    // Delimiter must not be more than one character.
-    if chars.next().is_some() {
-        return Result::Err(format!(
-            "'--delimiter <delim>' must be a single character, but was \"{}\"",
-            &value
-        ));
+
+    if !cli.files.is_empty() {
+        return Result::Err(ArgsError::FileListWithFiles { option });
+    }
+    Result::Ok(Option::Some(FileList {
+        path: path.clone(),
+        format,
+    }))
+}
+
+/// Validates and returns the value of the specified option as a count, or returns an error if it is
+/// not a non-negative number. Defaults to zero.
+fn validate_count(value: Option<&str>, option: &'static str) -> Result<usize, ArgsError> {
+    match value {
+        Option::None => Result::Ok(0),
+        Option::Some(value) => value.parse().map_err(|_| ArgsError::InvalidCount {
+            option,
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Validates and returns the value as a number of jobs, or returns an error if it is not a positive
+/// number. Defaults to one.
+fn validate_jobs(value: Option<&str>) -> Result<usize, ArgsError> {
+    match value {
+        Option::None => Result::Ok(1),
+        Option::Some(value) => match value.parse::<usize>() {
+            Result::Ok(jobs) if jobs > 0 => Result::Ok(jobs),
+            _ => Result::Err(ArgsError::InvalidJobs {
+                value: value.to_string(),
+            }),
+        },
+    }
+}
+
+/// Validates and returns the value as a gzip compression level, or returns an error if it is not a
+/// number from 0 to 9.
+fn validate_compression_level(value: &str) -> Result<u32, ArgsError> {
+    match value.parse::<u32>() {
+        Result::Ok(level) if level <= 9 => Result::Ok(level),
+        _ => Result::Err(ArgsError::InvalidCompressionLevel {
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Validates and returns the value as a follow interval in milliseconds, or returns an error if it
+/// is not a positive number. Defaults to one second.
+fn validate_follow_interval(value: Option<&str>) -> Result<Duration, ArgsError> {
+    match value {
+        Option::None => Result::Ok(Duration::from_secs(1)),
+        Option::Some(value) => match value.parse::<u64>() {
+            Result::Ok(millis) if millis > 0 => Result::Ok(Duration::from_millis(millis)),
+            _ => Result::Err(ArgsError::InvalidFollowInterval {
+                value: value.to_string(),
+            }),
+        },
+    }
+}
+
+/// Validates and returns the value as an input encoding, or returns an error if it is not a known
+/// encoding label. Returns `None` for UTF-8, since input is read as UTF-8 by default.
+fn validate_encoding(value: &str) -> Result<Option<&'static Encoding>, ArgsError> {
+    match Encoding::for_label(value.as_bytes()) {
+        Option::Some(encoding) if encoding == UTF_8 => Result::Ok(Option::None),
+        Option::Some(encoding) => Result::Ok(Option::Some(encoding)),
+        Option::None => Result::Err(ArgsError::UnknownEncoding {
+            value: value.to_string(),
+        }),
+    }
+}
+
+/// Validates and returns the value of the specified option with escape sequences replaced, or returns
+/// an error if it contains an invalid escape sequence.
+fn validate_escaped(value: &str, option: &'static str) -> Result<Vec<u8>, ArgsError> {
+    unescape(value).map_err(|source| ArgsError::InvalidEscape { option, source })
+}
+
+/// Replaces the escape sequences `\t`, `\n`, `\r`, `\0`, `\\` and `\xNN` in the value, or returns
+/// an error if the value contains an unknown or incomplete escape sequence.
+fn unescape(value: &str) -> Result<Vec<u8>, EscapeError> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Option::Some(ch) = chars.next() {
+        if ch != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Option::Some('t') => bytes.push(b'\t'),
+            Option::Some('n') => bytes.push(b'\n'),
+            Option::Some('r') => bytes.push(b'\r'),
+            Option::Some('0') => bytes.push(0),
+            Option::Some('\\') => bytes.push(b'\\'),
+            Option::Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Result::Ok(byte)
+                        if hex.len() == 2 && hex.chars().all(|ch| ch.is_ascii_hexdigit()) =>
+                    {
+                        bytes.push(byte)
+                    }
+                    _ => return Result::Err(EscapeError::InvalidHex(hex)),
+                }
+            }
+            Option::Some(other) => return Result::Err(EscapeError::Unknown(other)),
+            Option::None => return Result::Err(EscapeError::Incomplete),
+        }
+    }
+    Result::Ok(bytes)
+}
+
+/// Validates and returns the value as a character, with escape sequences replaced, or returns an
+/// error if it is not a single character or contains an invalid escape sequence. A character is an
+/// extended grapheme cluster, so it may be several Unicode scalar values, such as an emoji joined
+/// by zero width joiners or a letter followed by a combining mark.
+fn validate_char_delimiter(value: &str, option: &'static str) -> Result<String, ArgsError> {
+    let unescaped = validate_escaped(value, option)?;
+    let unescaped = String::from_utf8(unescaped).unwrap_or_default();
+    let mut graphemes = unescaped.graphemes(true);
+    // Delimiter must be exactly one character. Clap does not allow an empty -d, but defaults may be
+    // empty.
+    match (graphemes.next(), graphemes.next()) {
+        (Option::Some(delimiter), Option::None) => Result::Ok(delimiter.to_string()),
+        _ => Result::Err(ArgsError::DelimiterNotSingleChar {
+            option,
+            value: value.to_string(),
+            compat: Compat::Rut,
+        }),
     }
+}
+
+/// Validates and returns the value as a character, or returns an error with GNU cut's message if it
+/// is not a single character. Unlike [`validate_char_delimiter`], escape sequences are not replaced.
+fn validate_gnu_delimiter(value: &str) -> Result<char, ArgsError> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Option::Some(delimiter), Option::None) => Result::Ok(delimiter),
+        _ => Result::Err(ArgsError::DelimiterNotSingleChar {
+            option: "--delimiter <delim>",
+            value: value.to_string(),
+            compat: Compat::Gnu,
+        }),
+    }
+}
+
+/// Validates that the field delimiters do not conflict with the line delimiters, or returns an error
+/// if they do. Lines are split before fields, so a field delimiter which contains the line
+/// delimiter would never be found. An output delimiter which is the same as the output line
+/// delimiter is only allowed with --allow-ambiguous-output.
+fn validate_delimiters(
+    mode: &cut::Mode,
+    line_delimiter: &[u8],
+    output_line_delimiter: &[u8],
+    allow_ambiguous_output: bool,
+) -> Result<(), ArgsError> {
+    let contains_line_delimiter = |delimiter: &[u8]| {
+        delimiter
+            .windows(line_delimiter.len())
+            .any(|window| window == line_delimiter)
+    };
+    let output_delimiter = match mode {
+        cut::Mode::FieldsChar(_, delimiter, options) => {
+            if contains_line_delimiter(delimiter.to_string().as_bytes()) {
+                return Result::Err(ArgsError::DelimiterIsLineDelimiter {
+                    line_delimiter: line_delimiter.to_vec(),
+                });
+            }
+            &options.output_delimiter
+        }
+        cut::Mode::FieldsStr(_, delimiter, options) => {
+            if contains_line_delimiter(delimiter.as_bytes()) {
+                return Result::Err(ArgsError::DelimiterIsLineDelimiter {
+                    line_delimiter: line_delimiter.to_vec(),
+                });
+            }
+            &options.output_delimiter
+        }
+        #[cfg(feature = "regex")]
+        cut::Mode::FieldsRegex(_, delimiter, options) => {
+            if cut::regex_needs_line_delimiter(delimiter, line_delimiter) {
+                return Result::Err(ArgsError::RegexNeedsLineDelimiter {
+                    line_delimiter: line_delimiter.to_vec(),
+                });
+            }
+            &options.output_delimiter
+        }
+        // The output delimiter only joins the text between markers with --all-occurrences.
+        cut::Mode::Between(_, _, options) if options.all_occurrences => &options.output_delimiter,
+        #[cfg(feature = "regex")]
+        cut::Mode::BetweenRegex(_, _, options) if options.all_occurrences => {
+            &options.output_delimiter
+        }
+        _ => return Result::Ok(()),
+    };
+    if !allow_ambiguous_output && output_delimiter.as_slice() == output_line_delimiter {
+        return Result::Err(ArgsError::AmbiguousOutputDelimiter {
+            line_delimiter: output_line_delimiter.to_vec(),
+        });
+    }
+    Result::Ok(())
+}
+
+/// Returns the mode which selects fields separated by a delimiter validated by
+/// [`validate_char_delimiter`]. Most delimiters are a single `char`, which is split faster than a
+/// string.
+fn char_fields_mode(ranges: Ranges, delimiter: String, options: FieldOptions) -> cut::Mode {
+    let mut chars = delimiter.chars();
+    match (chars.next(), chars.next()) {
+        (Option::Some(delimiter), Option::None) => {
+            cut::Mode::FieldsChar(ranges, delimiter, options)
+        }
+        _ => cut::Mode::FieldsStr(ranges, delimiter, options),
+    }
+}
+
+/// Returns the mode which selects fields separated by matches of the regular expression, or an
+/// error if it is not a valid expression.
+#[cfg(feature = "regex")]
+fn regex_fields_mode(
+    ranges: Ranges,
+    regex: &str,
+    options: FieldOptions,
+) -> Result<cut::Mode, ArgsError> {
+    let delimiter = validate_regex(regex, "--regex-delimiter <regex>")?;
+    Result::Ok(cut::Mode::FieldsRegex(ranges, delimiter, options))
+}
+
+/// Returns an error, since regular expressions are not supported without the `regex` feature. The
+/// option is still accepted, so that it is not mistaken for a typo.
+#[cfg(not(feature = "regex"))]
+fn regex_fields_mode(_: Ranges, _: &str, _: FieldOptions) -> Result<cut::Mode, ArgsError> {
+    Result::Err(ArgsError::RegexUnavailable {
+        option: "--regex-delimiter <regex>",
+    })
+}
+
+/// Returns the mode which selects the text between matches of the regular expressions, or an error
+/// if either is not a valid expression.
+#[cfg(feature = "regex")]
+fn between_regex_mode(
+    start: &str,
+    end: &str,
+    options: BetweenOptions,
+) -> Result<cut::Mode, ArgsError> {
+    let option = "--between-regex <start> <end>";
+    let start = validate_regex(start, option)?;
+    let end = validate_regex(end, option)?;
+    Result::Ok(cut::Mode::BetweenRegex(start, end, options))
+}
+
+/// Returns an error, since regular expressions are not supported without the `regex` feature.
+#[cfg(not(feature = "regex"))]
+fn between_regex_mode(_: &str, _: &str, _: BetweenOptions) -> Result<cut::Mode, ArgsError> {
+    Result::Err(ArgsError::RegexUnavailable {
+        option: "--between-regex <start> <end>",
+    })
+}
+
+/// Validates and returns the value of an option as a regular expression, or returns an error if it
+/// is not a valid expression.
+#[cfg(feature = "regex")]
+fn validate_regex(value: &str, option: &'static str) -> Result<Regex, ArgsError> {
+    Regex::new(value).map_err(|source| ArgsError::InvalidRegex {
+        option,
+        pattern: value.to_string(),
+        source,
+    })
+}
+
+/// Invalid arguments, which were accepted by clap but failed further validation. Each error is
+/// reported with a message naming the option, except for `MissingMode`, which means that clap's
+/// configuration does not match what is expected here.
+#[derive(Debug)]
+pub(crate) enum ArgsError {
+    /// A list of ranges given to -b, -c or -f is not valid. The message depends on the
+    /// implementation being followed, so the error is kept until it is reported.
+    InvalidRanges {
+        spec: String,
+        source: ParseRangesError,
+        compat: Compat,
+        /// Whether the ranges select fields, rather than bytes or characters.
+        fields: bool,
+    },
+    /// More than one list of ranges was given, with --compat=gnu.
+    MultipleRangeLists,
+    /// None of -b, -c, -f or --format was given.
+    MissingMode,
+    /// The bound given to --complement is not a number.
+    InvalidComplement {
+        value: String,
+    },
+    /// --complement was given more than once, with different bounds.
+    ConflictingComplement {
+        first: usize,
+        second: usize,
+    },
+    /// --header-out was given a different number of names than the number of selected fields.
+    HeaderOutCount {
+        fields: usize,
+        names: usize,
+    },
+    InvalidTemplate {
+        source: ParseTemplateError,
+    },
+    /// --files0-from or --files-from was given along with file arguments.
+    FileListWithFiles {
+        option: &'static str,
+    },
+    /// "-" was given more than once, without --reuse-stdin.
+    StdinRepeated,
+    /// An option which takes a count was not given a non-negative number.
+    InvalidCount {
+        option: &'static str,
+        value: String,
+    },
+    InvalidJobs {
+        value: String,
+    },
+    InvalidFollowInterval {
+        value: String,
+    },
+    InvalidCompressionLevel {
+        value: String,
+    },
+    UnknownEncoding {
+        value: String,
+    },
+    /// The value of an option, named along with its value name, has an invalid escape sequence.
+    InvalidEscape {
+        option: &'static str,
+        source: EscapeError,
+    },
+    DelimiterNotSingleChar {
+        option: &'static str,
+        value: String,
+        compat: Compat,
+    },
+    #[cfg(feature = "regex")]
+    InvalidRegex {
+        option: &'static str,
+        pattern: String,
+        source: regex::Error,
+    },
+    /// --regex-delimiter or --between-regex was given, without the `regex` feature.
+    #[cfg(not(feature = "regex"))]
+    RegexUnavailable {
+        option: &'static str,
+    },
+    DelimiterIsLineDelimiter {
+        line_delimiter: Vec<u8>,
+    },
+    #[cfg(feature = "regex")]
+    RegexNeedsLineDelimiter {
+        line_delimiter: Vec<u8>,
+    },
+    /// The output delimiter is the output line delimiter, without --allow-ambiguous-output.
+    AmbiguousOutputDelimiter {
+        line_delimiter: Vec<u8>,
+    },
+    EnvNotUnicode {
+        name: &'static str,
+    },
+    /// A boolean environment variable has a value which is not recognized.
+    InvalidBool {
+        name: &'static str,
+        value: String,
+    },
+    /// A default from an environment variable or the config file, described by `origin`, is not
+    /// valid.
+    InvalidDefault {
+        source: Box<ArgsError>,
+        origin: String,
+    },
+}
+
+impl ArgsError {
+    /// Returns this error for a value which came from the environment variable or config file
+    /// described by `origin`.
+    fn in_default(self, origin: String) -> ArgsError {
+        ArgsError::InvalidDefault {
+            source: Box::new(self),
+            origin,
+        }
+    }
+}
+
+impl fmt::Display for ArgsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // Escaped, since the line delimiter is usually a newline or a NUL.
+        let name = |line_delimiter: &[u8]| -> String {
+            line_delimiter
+                .iter()
+                .map(|&byte| match byte {
+                    0 => String::from("\\0"),
+                    _ => byte.escape_ascii().to_string(),
+                })
+                .collect()
+        };
+        match self {
+            ArgsError::InvalidRanges {
+                spec,
+                source,
+                compat: Compat::Rut,
+                ..
+            } => write!(f, "{}", source.annotate(spec)),
+            ArgsError::InvalidRanges {
+                spec,
+                source,
+                compat: Compat::Gnu,
+                fields,
+            } => write!(f, "{}", source.gnu_message(spec, *fields)),
+            ArgsError::MultipleRangeLists => write!(f, "only one list may be specified"),
+            ArgsError::MissingMode => write!(
+                f,
+                "one of '--bytes', '--characters', '--fields' or '--format' is required"
+            ),
+            ArgsError::InvalidComplement { value } => write!(
+                f,
+                "'--complement=<N>' must be a non-negative number, but was \"{}\"",
+                value
+            ),
+            ArgsError::ConflictingComplement { first, second } => write!(
+                f,
+                "'--complement=<N>' was given different bounds, {} and {}",
+                first, second
+            ),
+            ArgsError::HeaderOutCount { fields, names } => write!(
+                f,
+                "'--header-out=<names>' must name each of the {} selected fields, but named {}",
+                fields, names
+            ),
+            ArgsError::InvalidTemplate { source } => write!(
+                f,
+                "'--format <template>' is not a valid template: {}",
+                source
+            ),
+            ArgsError::FileListWithFiles { option } => write!(
+                f,
+                "'{} <file>' cannot be used with file arguments",
+                option
+            ),
+            ArgsError::StdinRepeated => write!(
+                f,
+                "'-' (stdin) was specified more than once. Use --reuse-stdin to read it once and replay it for each occurrence."
+            ),
+            ArgsError::InvalidCount { option, value } => write!(
+                f,
+                "'{} <N>' must be a non-negative number, but was \"{}\"",
+                option, value
+            ),
+            ArgsError::InvalidJobs { value } => write!(
+                f,
+                "'--jobs <N>' must be a positive number, but was \"{}\"",
+                value
+            ),
+            ArgsError::InvalidFollowInterval { value } => write!(
+                f,
+                "'--follow-interval <ms>' must be a positive number, but was \"{}\"",
+                value
+            ),
+            ArgsError::InvalidCompressionLevel { value } => write!(
+                f,
+                "'--compress-output=<level>' must be a number from 0 to 9, but was \"{}\"",
+                value
+            ),
+            ArgsError::UnknownEncoding { value } => write!(
+                f,
+                "'--encoding <label>' must be a known encoding, but was \"{}\"",
+                value
+            ),
+            ArgsError::InvalidEscape { option, source } => {
+                write!(f, "'{}' is not valid: {}", option, source)
+            }
+            ArgsError::DelimiterNotSingleChar {
+                option,
+                value,
+                compat: Compat::Rut,
+            } => write!(
+                f,
+                "'{}' must be a single character, but was \"{}\"",
+                option, value
+            ),
+            ArgsError::DelimiterNotSingleChar {
+                compat: Compat::Gnu,
+                ..
+            } => write!(f, "the delimiter must be a single character"),
+            #[cfg(feature = "regex")]
+            ArgsError::InvalidRegex {
+                option, pattern, ..
+            } => write!(
+                f,
+                "'{}' must be a valid regular expression, but was \"{}\"",
+                option, pattern
+            ),
+            #[cfg(not(feature = "regex"))]
+            ArgsError::RegexUnavailable { option } => write!(
+                f,
+                "'{}' is not available, since rut was built without regex support",
+                option
+            ),
+            ArgsError::DelimiterIsLineDelimiter { line_delimiter } => write!(
+                f,
+                "'--delimiter <delim>' cannot be the line delimiter (\"{}\"), since lines are split before fields",
+                name(line_delimiter)
+            ),
+            #[cfg(feature = "regex")]
+            ArgsError::RegexNeedsLineDelimiter { line_delimiter } => write!(
+                f,
+                "'--regex-delimiter <regex>' must match more than text containing the line delimiter (\"{}\"), since lines are split before fields",
+                name(line_delimiter)
+            ),
+            ArgsError::AmbiguousOutputDelimiter { line_delimiter } => write!(
+                f,
+                "'--output-delimiter <output-delim>' is the same as the line delimiter (\"{}\"), so fields cannot be told apart from lines. Use --allow-ambiguous-output to allow this.",
+                name(line_delimiter)
+            ),
+            ArgsError::EnvNotUnicode { name } => {
+                write!(f, "environment variable {} must be valid UTF-8", name)
+            }
+            ArgsError::InvalidBool { name, value } => write!(
+                f,
+                "environment variable {} must be one of 1, true, yes, on, 0, false, no or off, but was \"{}\"",
+                name, value
+            ),
+            ArgsError::InvalidDefault { source, origin } => {
+                write!(f, "{} (from {})", source, origin)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ArgsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ArgsError::InvalidRanges { source, .. } => Option::Some(source),
+            ArgsError::InvalidTemplate { source } => Option::Some(source),
+            ArgsError::InvalidEscape { source, .. } => Option::Some(source),
+            #[cfg(feature = "regex")]
+            ArgsError::InvalidRegex { source, .. } => Option::Some(source),
+            ArgsError::InvalidDefault { source, .. } => Option::Some(source.as_ref()),
+            _ => Option::None,
+        }
+    }
+}
 
-    Result::Ok(delimiter)
+/// An unknown or incomplete escape sequence in the value of an option.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum EscapeError {
+    /// A backslash followed by a character which does not start an escape sequence.
+    Unknown(char),
+    /// `\x` followed by the characters, if any, which are not two hexadecimal digits.
+    InvalidHex(String),
+    /// A backslash at the end of the value.
+    Incomplete,
 }
 
-/// Validates and returns the value as a regular expression, or returns an error message if it is not a valid expression.
-fn validate_regex_delimiter(value: &str) -> Result<Regex, String> {
-    Result::Ok(Regex::new(value).map_err(|_| {
-        format!(
-            "'--regex-delimiter <regex>' must be a valid regular expression, but was \"{}\"",
-            &value
-        )
-    })?)
+impl fmt::Display for EscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EscapeError::Unknown(ch) => write!(f, "Invalid escape sequence \"\\{}\".", ch),
+            EscapeError::InvalidHex(hex) => write!(f, "Invalid escape sequence \"\\x{}\".", hex),
+            EscapeError::Incomplete => write!(f, "Incomplete escape sequence \"\\\"."),
+        }
+    }
 }
 
+impl std::error::Error for EscapeError {}
+
 #[cfg(test)]
 mod tests {
+    use super::{ArgsError, Compat, EscapeError};
+    use crate::config::Config;
+    use clap::Parser;
+    use rut::cut::FieldSplitter;
+    use std::path::PathBuf;
+    use std::sync::{Mutex, MutexGuard};
+
     #[test]
     fn valid_args() {
         assert_valid_args(&["rut", "-b1"]);
@@ -301,6 +2190,14 @@ mod tests {
         assert_valid_args(&["rut", "-c1", "--complement", "--complement"]);
         assert_valid_args(&["rut", "-f1", "--complement"]);
         assert_valid_args(&["rut", "-f1", "--complement", "--complement"]);
+        assert_valid_args(&["rut", "-b1", "--complement=8"]);
+        assert_valid_args(&["rut", "-c1", "--complement=0"]);
+        assert_valid_args(&["rut", "-f1", "--complement=3", "--complement"]);
+        assert_valid_args(&["rut", "-f1", "--complement=3", "--complement=3"]);
+
+        assert_valid_args(&["rut", "-b1", "-b2"]);
+        assert_valid_args(&["rut", "-c1", "--characters=2"]);
+        assert_valid_args(&["rut", "-f1-3", "-f7,9", "--complement"]);
 
         assert_valid_args(&["rut", "-f1", "-d,"]);
         assert_valid_args(&["rut", "-f1", "-d=,"]);
@@ -308,11 +2205,14 @@ mod tests {
         assert_valid_args(&["rut", "-f1", "--delimiter=,"]);
         assert_valid_args(&["rut", "-f1", "--delimiter", ","]);
 
-        assert_valid_args(&["rut", "-f1", "-r[a-z]"]);
-        assert_valid_args(&["rut", "-f1", "-r=[a-z],"]);
-        assert_valid_args(&["rut", "-f1", "-r", "[a-z]"]);
-        assert_valid_args(&["rut", "-f1", "--regex-delimiter=[a-z]"]);
-        assert_valid_args(&["rut", "-f1", "--regex-delimiter", "[a-z]"]);
+        #[cfg(feature = "regex")]
+        {
+            assert_valid_args(&["rut", "-f1", "-r[a-z]"]);
+            assert_valid_args(&["rut", "-f1", "-r=[a-z],"]);
+            assert_valid_args(&["rut", "-f1", "-r", "[a-z]"]);
+            assert_valid_args(&["rut", "-f1", "--regex-delimiter=[a-z]"]);
+            assert_valid_args(&["rut", "-f1", "--regex-delimiter", "[a-z]"]);
+        }
 
         assert_valid_args(&["rut", "-f1", "-o#"]);
         assert_valid_args(&["rut", "-f1", "-o=#"]);
@@ -326,11 +2226,14 @@ mod tests {
         assert_valid_args(&["rut", "-f1", "-d_", "--output-delimiter=#"]);
         assert_valid_args(&["rut", "-f1", "-d_", "--output-delimiter", "#"]);
 
-        assert_valid_args(&["rut", "-f1", "-r_+", "-o#"]);
-        assert_valid_args(&["rut", "-f1", "-r_+", "-o=#"]);
-        assert_valid_args(&["rut", "-f1", "-r_+", "-o", "#"]);
-        assert_valid_args(&["rut", "-f1", "-r_+", "--output-delimiter=#"]);
-        assert_valid_args(&["rut", "-f1", "-r_+", "--output-delimiter", "#"]);
+        #[cfg(feature = "regex")]
+        {
+            assert_valid_args(&["rut", "-f1", "-r_+", "-o#"]);
+            assert_valid_args(&["rut", "-f1", "-r_+", "-o=#"]);
+            assert_valid_args(&["rut", "-f1", "-r_+", "-o", "#"]);
+            assert_valid_args(&["rut", "-f1", "-r_+", "--output-delimiter=#"]);
+            assert_valid_args(&["rut", "-f1", "-r_+", "--output-delimiter", "#"]);
+        }
 
         assert_valid_args(&["rut", "-f1", "-s"]);
         assert_valid_args(&["rut", "-f1", "-ss"]);
@@ -348,7 +2251,109 @@ mod tests {
         assert_valid_args(&["rut", "-f1", "--zero-terminated", "-z"]);
         assert_valid_args(&["rut", "-f1", "-z", "-z", "-zz"]);
 
+        assert_valid_args(&["rut", "-b1", "--line-delimiter=\\r\\n"]);
+        assert_valid_args(&["rut", "-f1", "--line-delimiter", ";;"]);
+        assert_valid_args(&["rut", "-c1", "--line-delimiter=-"]);
+        assert_valid_args(&["rut", "-f1", "--output-line-delimiter=\\n"]);
+        assert_valid_args(&["rut", "-f1", "-z", "--output-line-delimiter", ""]);
+
         assert_valid_args(&["rut", "-b1", "-n"]);
+
+        assert_valid_args(&["rut", "-f1", "--byte-offsets"]);
+        #[cfg(feature = "regex")]
+        assert_valid_args(&["rut", "-f1", "-r_+", "--byte-offsets"]);
+
+        assert_valid_args(&["rut", "-b1", "--hex"]);
+        assert_valid_args(&["rut", "-b1", "--hex", "--hex-format=plain"]);
+        assert_valid_args(&["rut", "-b1", "--hex", "--hex-format", "spaced"]);
+        assert_valid_args(&["rut", "-b1", "--hex", "--hex-format=prefixed"]);
+
+        assert_valid_args(&["rut", "-b1", "--base64"]);
+        assert_valid_args(&["rut", "-b1", "--base64=standard"]);
+        assert_valid_args(&["rut", "-b1", "--base64=url"]);
+        assert_valid_args(&["rut", "-b1", "--base64", "file"]);
+
+        assert_valid_args(&["rut", "-f1", "--header-out"]);
+        assert_valid_args(&["rut", "-f1,3", "--header-out=a,b"]);
+        assert_valid_args(&["rut", "-f2-", "--header-out=a,b,c"]);
+        assert_valid_args(&["rut", "-f2", "--complement", "--header-out=a"]);
+
+        assert_valid_args(&["rut", "--format={1}"]);
+        assert_valid_args(&["rut", "--format", "{2}:{1}", "-d,"]);
+        #[cfg(feature = "regex")]
+        assert_valid_args(&["rut", "--format", "{2} {{x}}", "-r[,;]", "-s"]);
+        assert_valid_args(&["rut", "--format={1:-none}", "-z"]);
+
+        assert_valid_args(&["rut", "-b1", "--prefix=["]);
+        assert_valid_args(&["rut", "-c1", "--suffix", "]"]);
+        assert_valid_args(&["rut", "-f1", "--prefix", "'", "--suffix", "'"]);
+        assert_valid_args(&["rut", "-f1", "--prefix=-", "--suffix=\\t\\x00"]);
+        assert_valid_args(&["rut", "--format={1}", "--prefix=", "--suffix="]);
+
+        assert_valid_args(&["rut", "-b1", "--no-decompress"]);
+        assert_valid_args(&["rut", "-f1", "--no-decompress", "file.gz"]);
+
+        assert_valid_args(&["rut", "-c1", "--encoding=latin1"]);
+        assert_valid_args(&["rut", "-f1", "--encoding", "UTF-16LE"]);
+        assert_valid_args(&["rut", "-b1", "--encoding=utf-8"]);
+
+        assert_valid_args(&["rut", "-b1", "--keep-bom"]);
+        assert_valid_args(&["rut", "-f1", "--keep-bom", "--keep-bom"]);
+
+        assert_valid_args(&["rut", "-b1", "--keep-cr"]);
+        assert_valid_args(&["rut", "-f1", "--keep-cr", "-z"]);
+
+        assert_valid_args(&["rut", "-b1", "--files0-from=-"]);
+        assert_valid_args(&["rut", "-f1", "--files0-from", "list"]);
+
+        assert_valid_args(&["rut", "-b1", "--files-from=-"]);
+        assert_valid_args(&["rut", "-f1", "--files-from", "manifest.txt"]);
+
+        assert_valid_args(&["rut", "-b1", "-", "file", "--reuse-stdin"]);
+        assert_valid_args(&["rut", "-b1", "-", "-", "--reuse-stdin"]);
+
+        assert_valid_args(&["rut", "-b1", "-R", "dir"]);
+        assert_valid_args(&["rut", "-f1", "--recursive", "--follow-symlinks", "dir"]);
+        assert_valid_args(&["rut", "-f1", "--glob", "*.csv"]);
+        assert_valid_args(&["rut", "-f1", "--glob", "--glob-allow-empty", "*.csv", "-R"]);
+
+        assert_valid_args(&["rut", "-f1", "-F", "file"]);
+        assert_valid_args(&["rut", "-f1", "--follow", "--follow-interval=10", "file"]);
+        assert_valid_args(&["rut", "-f1", "--line-buffered"]);
+
+        assert_valid_args(&["rut", "-b1", "--skip-lines=0"]);
+        assert_valid_args(&["rut", "-c1", "--skip-lines", "10", "--skip-bytes", "3"]);
+        assert_valid_args(&["rut", "-f1", "--skip-bytes=100"]);
+
+        assert_valid_args(&["rut", "-b1", "--max-lines=0"]);
+        assert_valid_args(&["rut", "-f1", "--max-lines", "100"]);
+        assert_valid_args(&["rut", "-c1", "--max-lines-per-file=5"]);
+
+        assert_valid_args(&["rut", "-b1", "--fail-fast", "a", "b"]);
+
+        assert_valid_args(&["rut", "-c1", "--skip-invalid"]);
+        assert_valid_args(&["rut", "-f1", "--skip-invalid", "--rejects=bad.txt"]);
+
+        assert_valid_args(&["rut", "-c1", "--lossy"]);
+        assert_valid_args(&["rut", "-f1", "-d,", "--lossy"]);
+
+        assert_valid_args(&["rut", "-b1", "--mmap"]);
+        assert_valid_args(&["rut", "-f1", "--mmap", "--mmap", "tests/files/ascii.txt"]);
+        assert_valid_args(&["rut", "-b1-", "--zero-copy"]);
+
+        assert_valid_args(&["rut", "-b1", "--jobs=4"]);
+
+        assert_valid_args(&["rut", "-b1", "--compress-output"]);
+        assert_valid_args(&["rut", "-b1", "--compress-output=0", "--force"]);
+        assert_valid_args(&["rut", "-b1", "--compress-output=9", "-j2"]);
+        assert_valid_args(&["rut", "-f1", "-j", "1", "--max-lines-per-file=2"]);
+
+        assert!(super::Cli::try_parse_from(["rut", "--completions=bash"]).is_ok());
+        assert!(super::Cli::try_parse_from(["rut", "--completions", "zsh"]).is_ok());
+        assert!(super::Cli::try_parse_from(["rut", "--man"]).unwrap().man());
+
+        assert_valid_args(&["rut", "-b1", "--check"]);
+        assert_valid_args(&["rut", "-f1", "--check", "--check"]);
     }
 
     #[test]
@@ -380,6 +2385,25 @@ mod tests {
         assert_invalid_args(&["rut", "-c", "2, 3"]);
         assert_invalid_args(&["rut", "-f", "xyz"]);
 
+        // Ranges which are too large.
+        for mode in &["-b", "-c", "-f"] {
+            let too_large = format!("{}99999999999999999999999999-", mode);
+            assert_invalid_args(&["rut", &too_large]);
+            assert_invalid_args(&["rut", &too_large, "--complement"]);
+            assert_invalid_args(&["rut", &format!("{}1", mode), "--complement=-1"]);
+            assert_invalid_args(&["rut", &format!("{}1", mode), "--complement=x"]);
+            assert_invalid_args(&["rut", &format!("{}1", mode), "--complement=2,3"]);
+            assert_invalid_args(&[
+                "rut",
+                &format!("{}1", mode),
+                "--complement=2",
+                "--complement=3",
+            ]);
+            let largest = format!("{}2-{}", mode, usize::MAX);
+            assert_valid_args(&["rut", &largest]);
+            assert_valid_args(&["rut", &largest, "--complement"]);
+        }
+
         // Invalid character delimiter.
         assert_invalid_args(&["rut", "-f1", "-dfoo"]);
         assert_invalid_args(&["rut", "-f1", "--delimiter=foo"]);
@@ -417,19 +2441,1158 @@ mod tests {
         assert_invalid_args(&["rut", "-c1", "-r_"]);
         assert_invalid_args(&["rut", "-b1", "-o#"]);
 
+        // --byte-offsets with non-field mode.
+        assert_invalid_args(&["rut", "-b1", "--byte-offsets"]);
+        assert_invalid_args(&["rut", "-c1", "--byte-offsets"]);
+
+        // --hex with non-bytes mode, or --hex-format without --hex.
+        assert_invalid_args(&["rut", "-c1", "--hex"]);
+        assert_invalid_args(&["rut", "-f1", "--hex"]);
+        assert_invalid_args(&["rut", "-b1", "--hex-format=plain"]);
+        assert_invalid_args(&["rut", "-b1", "--hex", "--hex-format=upper"]);
+
+        // --base64 with non-bytes mode or with --hex.
+        assert_invalid_args(&["rut", "-c1", "--base64"]);
+        assert_invalid_args(&["rut", "-f1", "--base64"]);
+        assert_invalid_args(&["rut", "-b1", "--base64", "--hex"]);
+        assert_invalid_args(&["rut", "-b1", "--base64=hex"]);
+
+        // --prefix or --suffix with invalid escape sequences.
+        assert_invalid_args(&["rut", "-b1", "--prefix=\\q"]);
+        assert_invalid_args(&["rut", "-b1", "--suffix=\\"]);
+        assert_invalid_args(&["rut", "-b1", "--suffix=\\x4"]);
+        assert_invalid_args(&["rut", "-b1", "--suffix=\\x+1"]);
+
+        // --line-delimiter which is empty, has invalid escapes or is given with -z.
+        assert_invalid_args(&["rut", "-b1", "--line-delimiter="]);
+        assert_invalid_args(&["rut", "-b1", "--line-delimiter=\\q"]);
+        assert_invalid_args(&["rut", "-b1", "--output-line-delimiter=\\x4"]);
+        assert_invalid_args(&["rut", "-b1", "-z", "--line-delimiter=;"]);
+
+        // --files0-from with file arguments.
+        assert_invalid_args(&["rut", "-b1", "--files0-from=list", "file"]);
+        assert_invalid_args(&["rut", "-b1", "--files0-from=-", "-"]);
+        assert_invalid_args(&["rut", "-b1", "--files0-from="]);
+
+        // --files-from with file arguments or --files0-from.
+        assert_invalid_args(&["rut", "-b1", "--files-from=list", "file"]);
+        assert_invalid_args(&["rut", "-b1", "--files-from=list", "--files0-from=list"]);
+        assert_invalid_args(&["rut", "-b1", "--files-from="]);
+
+        // Stdin more than once without --reuse-stdin.
+        assert_invalid_args(&["rut", "-b1", "-", "-"]);
+        assert_invalid_args(&["rut", "-f1", "-", "file", "-"]);
+
+        // --follow-symlinks without -R.
+        assert_invalid_args(&["rut", "-b1", "--follow-symlinks", "dir"]);
+
+        // --glob-allow-empty without --glob, or --glob with a file list.
+        assert_invalid_args(&["rut", "-b1", "--glob-allow-empty", "*.csv"]);
+        assert_invalid_args(&["rut", "-b1", "--glob", "--files-from=list.txt"]);
+        assert_invalid_args(&["rut", "-b1", "--glob", "--files0-from=list.txt"]);
+
+        // --follow-interval without -F, or not a positive number.
+        assert_invalid_args(&["rut", "-b1", "--follow-interval=10"]);
+        assert_invalid_args(&["rut", "-b1", "-F", "--follow-interval=0"]);
+        assert_invalid_args(&["rut", "-b1", "-F", "--follow-interval=x"]);
+        assert_invalid_args(&["rut", "-b1", "-F", "--follow-interval=-1"]);
+
+        // --skip-lines or --skip-bytes which is not a non-negative number.
+        assert_invalid_args(&["rut", "-b1", "--skip-lines=-1"]);
+        assert_invalid_args(&["rut", "-b1", "--skip-lines=x"]);
+        assert_invalid_args(&["rut", "-b1", "--skip-bytes=1.5"]);
+        assert_invalid_args(&["rut", "-b1", "--skip-bytes="]);
+
+        // --max-lines with --max-lines-per-file, or not a non-negative number.
+        assert_invalid_args(&["rut", "-b1", "--max-lines=1", "--max-lines-per-file=1"]);
+        assert_invalid_args(&["rut", "-b1", "--max-lines=-1"]);
+        assert_invalid_args(&["rut", "-b1", "--max-lines-per-file=x"]);
+
+        // --skip-invalid with -b, or --rejects without --skip-invalid.
+        assert_invalid_args(&["rut", "-b1", "--skip-invalid"]);
+        assert_invalid_args(&["rut", "-c1", "--rejects=bad.txt"]);
+
+        // --jobs must be a positive number.
+        assert_invalid_args(&["rut", "-b1", "--jobs=0"]);
+        assert_invalid_args(&["rut", "-b1", "--jobs=-1"]);
+        assert_invalid_args(&["rut", "-b1", "--jobs=x"]);
+        assert_invalid_args(&["rut", "-b1", "--jobs"]);
+
+        // --compress-output must be given a level from 0 to 9, after '='.
+        assert_invalid_args(&["rut", "-b1", "--compress-output=10"]);
+        assert_invalid_args(&["rut", "-b1", "--compress-output=x"]);
+        assert_invalid_args(&["rut", "-b1", "--compress-output="]);
+
+        // --compress-output with --check, or --force without --compress-output.
+        assert_invalid_args(&["rut", "-b1", "--compress-output", "--check"]);
+        assert_invalid_args(&["rut", "-b1", "--force"]);
+
+        // --jobs with options which span input files.
+        assert_invalid_args(&["rut", "-b1", "-j2", "--max-lines=1"]);
+        assert_invalid_args(&["rut", "-b1", "-j2", "--follow"]);
+        assert_invalid_args(&["rut", "-b1", "-j2", "--reuse-stdin"]);
+        assert_invalid_args(&["rut", "-f1", "-j2", "--header-out"]);
+
+        // --lossy with -b or --skip-invalid.
+        assert_invalid_args(&["rut", "-b1", "--lossy"]);
+        assert_invalid_args(&["rut", "-c1", "--lossy", "--skip-invalid"]);
+
+        // Unknown encoding.
+        assert_invalid_args(&["rut", "-c1", "--encoding=latin-9000"]);
+        assert_invalid_args(&["rut", "-c1", "--encoding="]);
+
+        // --header-out with non-field mode, or with the wrong number of names.
+        assert_invalid_args(&["rut", "-b1", "--header-out"]);
+        assert_invalid_args(&["rut", "-c1", "--header-out"]);
+        assert_invalid_args(&["rut", "-f1,3", "--header-out=a"]);
+        assert_invalid_args(&["rut", "-f1-3", "--header-out=a,b,c,d"]);
+
+        // --format with other modes, invalid templates, or conflicting output options.
+        assert_invalid_args(&["rut", "-b1", "--format={1}"]);
+        assert_invalid_args(&["rut", "-c1", "--format={1}"]);
+        assert_invalid_args(&["rut", "-f1", "--format={1}"]);
+        assert_invalid_args(&["rut", "--format=abc"]);
+        assert_invalid_args(&["rut", "--format={0}"]);
+        assert_invalid_args(&["rut", "--format={1"]);
+        assert_invalid_args(&["rut", "--format={1}", "-o,"]);
+        assert_invalid_args(&["rut", "--format={1}", "--complement"]);
+        assert_invalid_args(&["rut", "--format={1}", "--byte-offsets"]);
+
+        // --completions with an unknown shell, or with other arguments.
+        assert_invalid_args(&["rut", "--completions=tcsh"]);
+        assert_invalid_args(&["rut", "--completions=bash", "-b1"]);
+        assert_invalid_args(&["rut", "--completions=bash", "file"]);
+
+        // --man with other arguments.
+        assert_invalid_args(&["rut", "--man", "-b1"]);
+        assert_invalid_args(&["rut", "--man", "file"]);
+        assert_invalid_args(&["rut", "--man", "--completions=bash"]);
+
         // -n with non-bytes mode.
         assert_invalid_args(&["rut", "-c1", "-n"]);
         assert_invalid_args(&["rut", "-f1", "-n"]);
     }
 
+    #[test]
+    fn args_errors() {
+        let error = args_error;
+
+        assert!(matches!(
+            error(&["rut", "-f", "2-1"]),
+            ArgsError::InvalidRanges { spec, fields: true, .. } if spec == "2-1"
+        ));
+        assert!(matches!(
+            error(&["rut", "-b", "1", "-b", "x"]),
+            ArgsError::InvalidRanges { spec, fields: false, .. } if spec == "x"
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "--complement=x"]),
+            ArgsError::InvalidComplement { value } if value == "x"
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "--complement=2", "--complement=3"]),
+            ArgsError::ConflictingComplement {
+                first: 2,
+                second: 3
+            }
+        ));
+        assert!(matches!(
+            error(&["rut", "-f1,3", "--header-out=a"]),
+            ArgsError::HeaderOutCount {
+                fields: 2,
+                names: 1
+            }
+        ));
+        assert!(matches!(
+            error(&["rut", "--format={0}"]),
+            ArgsError::InvalidTemplate { .. }
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "--files-from=list", "file"]),
+            ArgsError::FileListWithFiles {
+                option: "--files-from"
+            }
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "-", "-"]),
+            ArgsError::StdinRepeated
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "--skip-lines=-1"]),
+            ArgsError::InvalidCount { option: "--skip-lines", value } if value == "-1"
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "--jobs=0"]),
+            ArgsError::InvalidJobs { value } if value == "0"
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "-F", "--follow-interval=x"]),
+            ArgsError::InvalidFollowInterval { value } if value == "x"
+        ));
+        assert!(matches!(
+            error(&["rut", "-c1", "--encoding=latin-9000"]),
+            ArgsError::UnknownEncoding { value } if value == "latin-9000"
+        ));
+        assert!(matches!(
+            error(&["rut", "-b1", "--suffix=\\"]),
+            ArgsError::InvalidEscape {
+                option: "--suffix <string>",
+                source: EscapeError::Incomplete,
+            }
+        ));
+        assert!(matches!(
+            error(&["rut", "-f1", "-dfoo"]),
+            ArgsError::DelimiterNotSingleChar { value, compat: Compat::Rut, .. } if value == "foo"
+        ));
+        #[cfg(feature = "regex")]
+        assert!(matches!(
+            error(&["rut", "-f1", "-r(x"]),
+            ArgsError::InvalidRegex { pattern, .. } if pattern == "(x"
+        ));
+        assert!(matches!(
+            error(&["rut", "-f2", "-d", "\n"]),
+            ArgsError::DelimiterIsLineDelimiter { line_delimiter } if line_delimiter == b"\n"
+        ));
+
+        // Clap requires a mode, except with --completions or --man, which are handled before the
+        // arguments are validated.
+        let err = error(&["rut", "--completions=bash"]);
+        assert!(matches!(err, ArgsError::MissingMode));
+        assert!(std::error::Error::source(&err).is_none());
+
+        // The underlying error is the source.
+        let err = error(&["rut", "-b1", "--prefix=\\q"]);
+        let source = std::error::Error::source(&err).unwrap();
+        assert_eq!(source.to_string(), "Invalid escape sequence \"\\q\".");
+    }
+
+    #[test]
+    fn header_out() {
+        use rut::cut::HeaderOut;
+
+        let names = |names: &[&str]| names.iter().map(|name| name.to_string()).collect();
+        assert_header_out(
+            &["rut", "-f1,3,5"],
+            HeaderOut::bounded(names(&["f1", "f3", "f5"])),
+        );
+        assert_header_out(&["rut", "-f2-3"], HeaderOut::bounded(names(&["f2", "f3"])));
+        assert_header_out(
+            &["rut", "-f2-3", "--header-out=x,y"],
+            HeaderOut::bounded(names(&["x", "y"])),
+        );
+        assert_header_out(&["rut", "-f2-"], HeaderOut::unbounded(names(&[])));
+        assert_header_out(
+            &["rut", "-f2-", "--header-out=x"],
+            HeaderOut::unbounded(names(&["x"])),
+        );
+    }
+
+    #[test]
+    fn unescape() {
+        assert_eq!(super::unescape("").unwrap(), b"");
+        assert_eq!(super::unescape("abc").unwrap(), b"abc");
+        assert_eq!(super::unescape("α").unwrap(), "α".as_bytes());
+        assert_eq!(super::unescape(r"\t\n\r\0\\").unwrap(), b"\t\n\r\0\\");
+        assert_eq!(super::unescape(r"\x41\xff\x0a").unwrap(), b"A\xff\n");
+        assert_eq!(super::unescape(r"a\\tb").unwrap(), b"a\\tb");
+
+        assert_eq!(
+            super::unescape(r"\a"),
+            Result::Err(EscapeError::Unknown('a'))
+        );
+        assert_eq!(
+            super::unescape(r"abc\"),
+            Result::Err(EscapeError::Incomplete)
+        );
+        assert_eq!(
+            super::unescape(r"\x"),
+            Result::Err(EscapeError::InvalidHex(String::new()))
+        );
+        assert_eq!(
+            super::unescape(r"\xg0"),
+            Result::Err(EscapeError::InvalidHex(String::from("g0")))
+        );
+    }
+
+    #[test]
+    fn validate_char_delimiter() {
+        let delimiter = |value| {
+            super::validate_char_delimiter(value, "--delimiter <delim>")
+                .map_err(|err| err.to_string())
+        };
+        assert_eq!(delimiter(","), Result::Ok(String::from(",")));
+        assert_eq!(delimiter("α"), Result::Ok(String::from("α")));
+        assert_eq!(delimiter(r"\t"), Result::Ok(String::from("\t")));
+        assert_eq!(delimiter(r"\n"), Result::Ok(String::from("\n")));
+        assert_eq!(delimiter(r"\r"), Result::Ok(String::from("\r")));
+        assert_eq!(delimiter(r"\0"), Result::Ok(String::from("\0")));
+        assert_eq!(delimiter(r"\\"), Result::Ok(String::from("\\")));
+        assert_eq!(delimiter(r"\x2c"), Result::Ok(String::from(",")));
+        assert_eq!(delimiter(r"\x7C"), Result::Ok(String::from("|")));
+
+        // Exactly one character is required after replacing escape sequences.
+        assert!(delimiter("").is_err());
+        assert!(delimiter(r"\t\t").is_err());
+        assert!(delimiter(r"a\t").is_err());
+        assert!(delimiter(r"\xff").is_err());
+
+        // A character may be several Unicode scalar values, if they form one grapheme cluster.
+        let family = "👨\u{200d}👩\u{200d}👦";
+        assert_eq!(delimiter(family), Result::Ok(String::from(family)));
+        assert_eq!(delimiter("e\u{301}"), Result::Ok(String::from("e\u{301}")));
+        assert_eq!(delimiter("🇯🇵"), Result::Ok(String::from("🇯🇵")));
+        assert_eq!(delimiter(r"\r\n"), Result::Ok(String::from("\r\n")));
+        assert!(delimiter("ab").is_err());
+        assert!(delimiter("e\u{301}e").is_err());
+        assert!(delimiter("👨👩").is_err());
+        assert!(delimiter("🇯🇵🇺🇸").is_err());
+
+        // Invalid escape sequences are named.
+        assert_eq!(
+            delimiter("\\"),
+            Result::Err(String::from(
+                "'--delimiter <delim>' is not valid: Incomplete escape sequence \"\\\"."
+            ))
+        );
+        assert_eq!(
+            delimiter(r"\q"),
+            Result::Err(String::from(
+                "'--delimiter <delim>' is not valid: Invalid escape sequence \"\\q\"."
+            ))
+        );
+        assert!(delimiter(r"\x4").is_err());
+
+        assert!(matches!(
+            super::validate_char_delimiter(r"\xff", "--delimiter <delim>"),
+            Result::Err(ArgsError::DelimiterNotSingleChar { value, compat: Compat::Rut, .. }) if value == r"\xff"
+        ));
+        assert!(matches!(
+            super::validate_char_delimiter(r"\q", "--delimiter <delim>"),
+            Result::Err(ArgsError::InvalidEscape {
+                option: "--delimiter <delim>",
+                source: EscapeError::Unknown('q'),
+            })
+        ));
+        assert!(matches!(
+            super::validate_gnu_delimiter(r"\t"),
+            Result::Err(ArgsError::DelimiterNotSingleChar {
+                compat: Compat::Gnu,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn line_delimiter_conflicts() {
+        let error = |args: &[&str]| args_error(args).to_string();
+
+        // A field delimiter which is the line delimiter would never be found.
+        assert_eq!(
+            error(&["rut", "-f2", "-d", "\n"]),
+            "'--delimiter <delim>' cannot be the line delimiter (\"\\n\"), since lines are split before fields"
+        );
+        assert_invalid_args(&["rut", "-f2", r"-d\n"]);
+        assert!(matches!(
+            args_error(&["rut", "-f2", "-z", r"-d\0"]),
+            ArgsError::DelimiterIsLineDelimiter { line_delimiter } if line_delimiter == b"\0"
+        ));
+        assert_invalid_args(&["rut", "-f2", "-z", r"-d\x00"]);
+        #[cfg(feature = "regex")]
+        assert_eq!(
+            error(&["rut", "-f2", "-r", "a\n"]),
+            "'--regex-delimiter <regex>' must match more than text containing the line delimiter (\"\\n\"), since lines are split before fields"
+        );
+        assert_invalid_args(&["rut", "-f2", "-z", "-r", "a\0"]);
+        #[cfg(feature = "regex")]
+        {
+            // Regexes are checked by what they match, not by their source text.
+            assert!(matches!(
+                args_error(&["rut", "-f2", "-r", r"\n"]),
+                ArgsError::RegexNeedsLineDelimiter { line_delimiter } if line_delimiter == b"\n"
+            ));
+            assert_invalid_args(&["rut", "-f2", "-r", r"\n|x\n"]);
+            assert_invalid_args(&["rut", "-f2", "-z", "-r", r"\x00"]);
+            assert_valid_args(&["rut", "-f2", "--line-delimiter=;", "-r", "[;,]"]);
+            assert_valid_args(&["rut", "-f2", "-r", r"\n|,"]);
+            assert_valid_args(&["rut", "-f2", "-r", r"\s+"]);
+        }
+
+        // Output delimiters which are the line delimiter require --allow-ambiguous-output.
+        assert_eq!(
+            error(&["rut", "-f2", "-o", "\n"]),
+            "'--output-delimiter <output-delim>' is the same as the line delimiter (\"\\n\"), so fields cannot be told apart from lines. Use --allow-ambiguous-output to allow this."
+        );
+        assert!(matches!(
+            args_error(&["rut", "-f2", "-z", r"-o\0"]),
+            ArgsError::AmbiguousOutputDelimiter { line_delimiter } if line_delimiter == b"\0"
+        ));
+        assert_invalid_args(&["rut", "-f2", "-r,", "--byte-offsets", r"-o\n"]);
+        assert_valid_args(&["rut", "-f2", r"-o\n", "--allow-ambiguous-output"]);
+        assert_valid_args(&["rut", "-f2", "-z", r"-o\0", "--allow-ambiguous-output"]);
+
+        // Only the active line delimiter conflicts.
+        assert_valid_args(&["rut", "-f2", "-z", r"-d\n"]);
+        #[cfg(feature = "regex")]
+        assert_valid_args(&["rut", "-f2", "-z", "-r", "\n"]);
+        assert_valid_args(&["rut", "-f2", r"-d\0"]);
+        assert_valid_args(&["rut", "-f2", r"-o\0"]);
+        assert_valid_args(&["rut", "-f2", "-o", "\n\n"]);
+        assert_valid_args(&["rut", "-b2", "-z"]);
+    }
+
+    #[test]
+    fn line_delimiter() {
+        let terminators = |args: &[&str]| {
+            let options = parse(args).options;
+            let output_terminator = options.output_terminator().to_vec();
+            (options.line_delimiter, output_terminator)
+        };
+        assert_eq!(
+            terminators(&["rut", "-b1"]),
+            (b"\n".to_vec(), b"\n".to_vec())
+        );
+        assert_eq!(
+            terminators(&["rut", "-b1", "-z"]),
+            (b"\0".to_vec(), b"\0".to_vec())
+        );
+        assert_eq!(
+            terminators(&["rut", "-b1", r"--line-delimiter=\r\n"]),
+            (b"\r\n".to_vec(), b"\r\n".to_vec())
+        );
+        assert_eq!(
+            terminators(&[
+                "rut",
+                "-b1",
+                r"--line-delimiter=\x00\x00",
+                r"--output-line-delimiter=\n"
+            ]),
+            (b"\0\0".to_vec(), b"\n".to_vec())
+        );
+        assert_eq!(
+            terminators(&["rut", "-b1", "-z", "--output-line-delimiter="]),
+            (b"\0".to_vec(), Vec::new())
+        );
+
+        assert!(matches!(
+            args_error(&["rut", "-f1", r"--line-delimiter=\q"]),
+            ArgsError::InvalidEscape {
+                option: "--line-delimiter <string>",
+                source: EscapeError::Unknown('q'),
+            }
+        ));
+        assert!(matches!(
+            args_error(&["rut", "-f1", r"--output-line-delimiter=\"]),
+            ArgsError::InvalidEscape {
+                option: "--output-line-delimiter <string>",
+                source: EscapeError::Incomplete,
+            }
+        ));
+
+        // Delimiters are checked against the whole line delimiter.
+        assert_eq!(
+            args_error(&["rut", "-f2", "--line-delimiter=é", "-dé"]).to_string(),
+            "'--delimiter <delim>' cannot be the line delimiter (\"\\xc3\\xa9\"), since lines are split before fields"
+        );
+        assert_valid_args(&["rut", "-f2", "--line-delimiter=;;", "-d;"]);
+        assert_valid_args(&["rut", "-f2", r"--line-delimiter=\r\n", r"-d\r"]);
+        #[cfg(feature = "regex")]
+        assert!(matches!(
+            args_error(&["rut", "-f2", r"--line-delimiter=\r\n", "-r", "a\r\n"]),
+            ArgsError::RegexNeedsLineDelimiter { line_delimiter } if line_delimiter == b"\r\n"
+        ));
+        assert_valid_args(&["rut", "-f2", "--line-delimiter=;", r"-d\n"]);
+
+        // The output delimiter is checked against the output line delimiter.
+        assert!(matches!(
+            args_error(&["rut", "-f2", "--line-delimiter=;;", "-o;;"]),
+            ArgsError::AmbiguousOutputDelimiter { line_delimiter } if line_delimiter == b";;"
+        ));
+        assert_invalid_args(&["rut", "-f2", "--output-line-delimiter=|", "-o|"]);
+        assert_valid_args(&["rut", "-f2", "--output-line-delimiter=|", r"-o\n"]);
+        assert_valid_args(&[
+            "rut",
+            "-f2",
+            "--line-delimiter=;;",
+            "-o;;",
+            "--output-line-delimiter=;",
+        ]);
+        assert_eq!(
+            args_error(&["rut", "-f2", r"--line-delimiter=\r\n", r"-o\r\n"]).to_string(),
+            "'--output-delimiter <output-delim>' is the same as the line delimiter (\"\\r\\n\"), so fields cannot be told apart from lines. Use --allow-ambiguous-output to allow this."
+        );
+    }
+
+    #[test]
+    #[cfg(not(feature = "regex"))]
+    fn regex_unavailable() {
+        let error = args_error(&["rut", "-f1", "-r", "[a-z]"]);
+        assert!(matches!(error, ArgsError::RegexUnavailable { .. }));
+        assert_eq!(
+            error.to_string(),
+            "'--regex-delimiter <regex>' is not available, since rut was built without regex support"
+        );
+        assert_valid_args(&["rut", "-f1", "-d,"]);
+    }
+
+    #[test]
+    fn output_delimiter() {
+        #[cfg(feature = "regex")]
+        {
+            let assert_output =
+                |args: &[&str], expected: &str| assert_fields_env(&[], args, None, expected);
+            assert_output(&["rut", "-f1", "-r,", "-o", r"\t"], "\t");
+            assert_output(&["rut", "-f1", "-r,", "-o", r"\0"], "\0");
+            assert_output(&["rut", "-f1", "-r,", "-z", "-o", r"\n"], "\n");
+            assert_output(&["rut", "-f1", "-r,", "-o", r"\r\n"], "\r\n");
+            assert_output(&["rut", "-f1", "-r,", "-o", r"a\\b"], "a\\b");
+            assert_output(&["rut", "-f1", "-r,", "-o", r"\x3a\xc3\xa9"], ":é");
+        }
+        assert_fields_env(&[], &["rut", "-f1", "-d,", "-o", r"\t"], Some(','), "\t");
+        assert_fields_env(
+            &[("RUT_OUTPUT_DELIMITER", r"\x7c")],
+            &["rut", "-f1", "-d,"],
+            Some(','),
+            "|",
+        );
+        // Byte escapes need not form valid UTF-8.
+        assert_fields_env(
+            &[],
+            &["rut", "-f1", "-d,", "-o", r"\xff"],
+            Some(','),
+            b"\xff",
+        );
+
+        assert!(parse_env(&[], &["rut", "-f1", "-o", r"\q"]).is_err());
+        assert!(parse_env(&[], &["rut", "-f1", "-o", r"a\"]).is_err());
+        assert!(parse_env(&[("RUT_OUTPUT_DELIMITER", r"\q")], &["rut", "-f1"]).is_err());
+    }
+
+    #[test]
+    fn grapheme_cluster_delimiter() {
+        // The fast path is kept for a single character.
+        assert!(matches!(
+            parse(&["rut", "-f1", "-d,"]).mode,
+            super::cut::Mode::FieldsChar(_, ',', _)
+        ));
+        // A character of several scalar values is also the default output delimiter.
+        for (delimiter, expected) in [("-de\u{301}", "e\u{301}"), ("-d🏳\u{fe0f}", "🏳\u{fe0f}")]
+        {
+            match parse(&["rut", "-f1", delimiter]).mode {
+                super::cut::Mode::FieldsStr(_, actual, options) => {
+                    assert_eq!(actual, expected);
+                    assert_eq!(options.output_delimiter, expected.as_bytes());
+                }
+                _ => panic!("expected fields separated by a string"),
+            }
+        }
+        assert!(matches!(
+            parse(&["rut", "-f1", "-de\u{301}", "-o", "|"]).mode,
+            super::cut::Mode::FieldsStr(_, _, options) if options.output_delimiter == b"|"
+        ));
+
+        assert!(matches!(
+            args_error(&["rut", "-f1", "-dab"]),
+            ArgsError::DelimiterNotSingleChar { .. }
+        ));
+        assert!(matches!(
+            args_error(&["rut", "-f1", "--compat=gnu", "-de\u{301}"]),
+            ArgsError::DelimiterNotSingleChar {
+                compat: Compat::Gnu,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn field_splitters() {
+        assert_fields(&["rut", "-f1"], "a\tb c\t", &["a", "b c", ""]);
+        assert_fields(&["rut", "-f1", "-d,"], "a,b,,c", &["a", "b", "", "c"]);
+        assert_fields(&["rut", "-f1", r"-d\x2c"], "a,b", &["a", "b"]);
+        assert_fields(&["rut", "-f1", "-dγ"], "αγβ", &["α", "β"]);
+        assert_fields(&["rut", "-f1", "-d,"], "abc", &["abc"]);
+        assert_fields(&["rut", "-f1", "-de\u{301}"], "ae\u{301}be", &["a", "be"]);
+        assert_fields(
+            &["rut", "-f1", "-d👨\u{200d}👦"],
+            "a👨b👨\u{200d}👦c",
+            &["a👨b", "c"],
+        );
+        #[cfg(feature = "regex")]
+        {
+            assert_fields(&["rut", "-f1", r"-r\s+"], "a  b\tc", &["a", "b", "c"]);
+            assert_fields(&["rut", "-f1", "-r[,;]"], "a;b,c", &["a", "b", "c"]);
+            assert_fields(&["rut", "-f1", "-r,+"], "abc", &["abc"]);
+        }
+    }
+
+    fn assert_header_out(args: &[&str], expected: rut::cut::HeaderOut) {
+        let mut args = args.to_vec();
+        if !args.iter().any(|arg| arg.starts_with("--header-out")) {
+            args.push("--header-out");
+        }
+        let cli = super::Cli::parse_from(args);
+        match parse_args(cli).ok().unwrap().mode {
+            super::cut::Mode::FieldsChar(_, _, options) => {
+                assert_eq!(
+                    format!("{:?}", options.header.unwrap()),
+                    format!("{:?}", expected)
+                )
+            }
+            _ => panic!("Expected field mode."),
+        }
+    }
+
+    #[test]
+    fn filenames() {
+        assert_filenames(&["rut", "-f1"], &["-"]);
+        assert_filenames(&["rut", "-f1", "a", "-", "b"], &["a", "-", "b"]);
+        // Arguments after "--" are files, even if they look like options.
+        assert_filenames(&["rut", "-f1", "--", "--strange"], &["--strange"]);
+        assert_filenames(
+            &[
+                "rut",
+                "-b1",
+                "--",
+                "-b1.txt",
+                "-",
+                "--complement",
+                "--",
+                "-f1",
+            ],
+            &["-b1.txt", "-", "--complement", "--", "-f1"],
+        );
+        assert_filenames(&["rut", "-f1", "a", "--", "-b"], &["a", "-b"]);
+    }
+
+    #[test]
+    fn args_before_parsing() {
+        use crate::report::ErrorFormat;
+        let format = |args: &[&str]| super::error_format_from_args(args);
+        assert_eq!(format(&["rut", "-b1"]), ErrorFormat::Human);
+        assert_eq!(format(&["rut", "--error-format=json"]), ErrorFormat::Json);
+        assert_eq!(
+            format(&["rut", "--error-format", "json"]),
+            ErrorFormat::Json
+        );
+        assert_eq!(format(&["rut", "--error-format=xml"]), ErrorFormat::Human);
+        assert_eq!(
+            format(&["rut", "--", "--error-format=json"]),
+            ErrorFormat::Human
+        );
+
+        let verbose = |args: &[&str]| super::verbose_from_args(args);
+        assert!(verbose(&["rut", "--version", "-v"]));
+        assert!(verbose(&["rut", "-vv", "--version"]));
+        assert!(verbose(&["rut", "--verbose", "-V"]));
+        assert!(!verbose(&["rut", "--version"]));
+        assert!(!verbose(&["rut", "-dv", "--version"]));
+        assert!(!verbose(&["rut", "--version", "--", "-v"]));
+
+        use super::Compat;
+        let compat = |args: &[&str]| super::compat_from_args(args);
+        assert_eq!(compat(&["rut", "-b1"]), Compat::Rut);
+        assert_eq!(compat(&["rut", "--compat=gnu"]), Compat::Gnu);
+        assert_eq!(compat(&["rut", "--compat", "gnu"]), Compat::Gnu);
+        assert_eq!(compat(&["cut", "-b1"]), Compat::Gnu);
+        assert_eq!(compat(&["/usr/bin/cut", "-b1"]), Compat::Gnu);
+        assert_eq!(compat(&["cut", "--compat=rut"]), Compat::Rut);
+        assert_eq!(compat(&["cutter", "-b1"]), Compat::Rut);
+        assert_eq!(compat(&["rut", "--", "--compat=gnu"]), Compat::Rut);
+    }
+
+    #[test]
+    fn compat_keeps_input() {
+        let args = parse(&["rut", "-b1"]);
+        assert!(!args.options.keep_cr);
+        assert!(!args.input_options.keep_bom);
+        assert!(args.input_options.decompress);
+
+        let args = parse(&["rut", "-b1", "--compat=gnu"]);
+        assert!(args.options.keep_cr);
+        assert!(args.input_options.keep_bom);
+        assert!(!args.input_options.decompress);
+    }
+
+    #[test]
+    fn cli() {
+        use clap::CommandFactory;
+        super::Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn ranges_error() {
+        use rut::range::ParseRangesError;
+
+        let values = [String::from("1"), String::from("3-2")];
+        let err =
+            super::validate_ranges(&values, Option::None, &[], Compat::Rut, false).unwrap_err();
+        match &err {
+            ArgsError::InvalidRanges { spec, source, .. } => {
+                assert_eq!(*source, ParseRangesError::DescendingRange(0..3));
+                assert_eq!(spec, "3-2");
+            }
+            _ => panic!("Expected a parse error."),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Ranges must be ascending.\n  3-2\n  ^^^ at position 1"
+        );
+
+        let err =
+            super::validate_ranges(&values[1..], Option::None, &[], Compat::Gnu, true).unwrap_err();
+        assert_eq!(err.to_string(), "invalid decreasing range");
+        let err =
+            super::validate_ranges(&values, Option::None, &[], Compat::Gnu, true).unwrap_err();
+        assert!(matches!(err, ArgsError::MultipleRangeLists));
+        assert_eq!(err.to_string(), "only one list may be specified");
+    }
+
+    #[test]
+    fn exclude() {
+        use rut::range::Ranges;
+
+        let selected = |args: &[&str]| match parse(args).mode {
+            super::cut::Mode::Bytes(ranges, _)
+            | super::cut::Mode::Characters(ranges)
+            | super::cut::Mode::FieldsChar(ranges, _, _) => ranges,
+            _ => panic!("Expected byte, character, or field mode."),
+        };
+        let ranges = |ranges: &str| ranges.parse::<Ranges>().unwrap();
+
+        // Exclusions are removed from the union of the lists, in every mode.
+        assert_eq!(selected(&["rut", "-f1-10", "-e4,6"]), ranges("1-3,5,7-10"));
+        assert_eq!(
+            selected(&["rut", "-b1-4", "-b8-", "-e2", "-e-1"]),
+            ranges("3-4,8-")
+        );
+        assert_eq!(selected(&["rut", "-c2-", "--exclude=5-"]), ranges("2-4"));
+        assert!(selected(&["rut", "-f2-3", "-e1-"]).is_empty());
+
+        // Without a mode, every field is selected except those excluded.
+        assert_eq!(selected(&["rut", "-e4,6"]), ranges("1-3,5,7-"));
+        assert_eq!(selected(&["rut", "-e", "-2", "-d,"]), ranges("3-"));
+
+        // Exclusions apply after --complement.
+        assert_eq!(
+            selected(&["rut", "-f2", "--complement", "-e4"]),
+            ranges("1,3,5-")
+        );
+        assert_eq!(
+            selected(&["rut", "-b2", "--complement=5", "-e1"]),
+            ranges("3-5")
+        );
+
+        assert_invalid_args(&["rut", "-e0"]);
+        assert_invalid_args(&["rut", "-f1", "-e3-2"]);
+        assert_invalid_args(&["rut", "-f1", "-e", ""]);
+        assert_invalid_args(&["rut", "--format={1}", "-e2"]);
+        assert_invalid_args(&["rut", "-e1", "-b1", "-c1"]);
+        assert_invalid_args(&["rut", "-b1", "-e1", "-s"]);
+    }
+
+    #[test]
+    fn compress_output() {
+        assert_eq!(parse(&["rut", "-b1"]).compress_output, Option::None);
+        assert_eq!(
+            parse(&["rut", "-b1", "--compress-output"]).compress_output,
+            Option::Some(6)
+        );
+        assert_eq!(
+            parse(&["rut", "-b1", "--compress-output=0"]).compress_output,
+            Option::Some(0)
+        );
+
+        // The level must follow '=', so a separate argument is a file.
+        let args = parse(&["rut", "-b1", "--compress-output", "9"]);
+        assert_eq!(args.compress_output, Option::Some(6));
+        assert_eq!(args.filenames, [PathBuf::from("9")]);
+        assert!(!args.force);
+        assert!(parse(&["rut", "-b1", "--compress-output", "--force"]).force);
+    }
+
+    #[test]
+    fn sub_fields() {
+        let sub_fields = |args: &[&str]| match parse(args).mode {
+            super::cut::Mode::FieldsChar(_, _, options)
+            | super::cut::Mode::FieldsStr(_, _, options) => options.sub_fields,
+            #[cfg(feature = "regex")]
+            super::cut::Mode::FieldsRegex(_, _, options) => options.sub_fields,
+            _ => panic!("Expected field mode."),
+        };
+
+        assert!(sub_fields(&["rut", "-f1"]).is_none());
+        let sub =
+            sub_fields(&["rut", "-f3", "-d,", "--sub-delimiter=;", "--sub-fields=2"]).unwrap();
+        assert_eq!(sub.delimiter, ";");
+        assert_eq!(sub.ranges, "2".parse().unwrap());
+        assert_eq!(sub.output_delimiter, b";");
+
+        // Escapes are replaced, and the output delimiter may be given.
+        let args = [
+            "rut",
+            "-e1",
+            r"--sub-delimiter=\t",
+            "--sub-fields=-2",
+            r"--sub-output-delimiter=\x2c",
+        ];
+        let sub = sub_fields(&args).unwrap();
+        assert_eq!(
+            (sub.delimiter.as_str(), sub.output_delimiter.as_slice()),
+            ("\t", &b","[..])
+        );
+        assert_eq!(sub.ranges, "1-2".parse().unwrap());
+        #[cfg(feature = "regex")]
+        assert!(
+            sub_fields(&["rut", "-f1", "-r,+", "--sub-delimiter=:", "--sub-fields=1"]).is_some()
+        );
+
+        assert_eq!(
+            args_error(&["rut", "-f1", "--sub-delimiter=ab", "--sub-fields=1"]).to_string(),
+            "'--sub-delimiter <delim>' must be a single character, but was \"ab\""
+        );
+        assert!(matches!(
+            args_error(&["rut", "-f1", "--sub-delimiter=;", "--sub-fields=0"]),
+            ArgsError::InvalidRanges { .. }
+        ));
+        let args = [
+            "rut",
+            "-f1",
+            "--sub-delimiter=;",
+            "--sub-fields=1",
+            r"--sub-output-delimiter=\xff",
+        ];
+        assert_eq!(sub_fields(&args).unwrap().output_delimiter, b"\xff");
+        assert_invalid_args(&["rut", "-f1", "--sub-delimiter=;"]);
+        assert_invalid_args(&["rut", "-f1", "--sub-fields=1"]);
+        assert_invalid_args(&["rut", "-f1", "--sub-output-delimiter=|"]);
+        assert_invalid_args(&["rut", "-b1", "--sub-delimiter=;", "--sub-fields=1"]);
+        assert_invalid_args(&["rut", "--format={1}", "--sub-delimiter=;", "--sub-fields=1"]);
+        assert_invalid_args(&[
+            "rut",
+            "-f1",
+            "--byte-offsets",
+            "--sub-delimiter=;",
+            "--sub-fields=1",
+        ]);
+        assert_invalid_args(&[
+            "rut",
+            "--between",
+            "a",
+            "b",
+            "--sub-delimiter=;",
+            "--sub-fields=1",
+        ]);
+    }
+
+    #[test]
+    fn between() {
+        let between = |args: &[&str]| match parse(args).mode {
+            super::cut::Mode::Between(start, end, options) => (start, end, options),
+            _ => panic!("Expected --between."),
+        };
+
+        let (start, end, options) = between(&["rut", "--between", "user=", "&"]);
+        assert_eq!((start.as_str(), end.as_str()), ("user=", "&"));
+        assert_eq!(options.output_delimiter, b"\t");
+        assert!(!options.suppress && !options.all_occurrences);
+
+        // Markers are literal, and may start with a hyphen.
+        let args = [
+            "rut",
+            "--between",
+            "-",
+            r"\t",
+            "-s",
+            r"-o\t",
+            "--all-occurrences",
+        ];
+        let (start, end, options) = between(&args);
+        assert_eq!((start.as_str(), end.as_str()), ("-", r"\t"));
+        assert_eq!(options.output_delimiter, b"\t");
+        assert!(options.suppress && options.all_occurrences);
+
+        #[cfg(feature = "regex")]
+        assert!(matches!(
+            parse(&["rut", "--between-regex", "^", "[;&]"]).mode,
+            super::cut::Mode::BetweenRegex(..)
+        ));
+        #[cfg(feature = "regex")]
+        assert!(matches!(
+            args_error(&["rut", "--between-regex", "(", "x"]),
+            ArgsError::InvalidRegex { pattern, .. } if pattern == "("
+        ));
+
+        // The output delimiter only joins the text between pairs of markers with
+        // --all-occurrences.
+        assert_valid_args(&["rut", "--between", "a", "b", "-o", r"\n"]);
+        assert!(matches!(
+            args_error(&[
+                "rut",
+                "--between",
+                "a",
+                "b",
+                "-o",
+                r"\n",
+                "--all-occurrences"
+            ]),
+            ArgsError::AmbiguousOutputDelimiter { .. }
+        ));
+
+        assert_invalid_args(&["rut", "--between", "a"]);
+        assert_invalid_args(&["rut", "--between", "a", ""]);
+        assert_invalid_args(&["rut", "--between", "a", "b", "--between", "c", "d"]);
+        assert_invalid_args(&["rut", "--between", "a", "b", "--between-regex", "c", "d"]);
+        assert_invalid_args(&["rut", "--between", "a", "b", "-f1"]);
+        assert_invalid_args(&["rut", "--between", "a", "b", "-d,"]);
+        assert_invalid_args(&["rut", "--between", "a", "b", "--complement"]);
+        assert_invalid_args(&["rut", "--between", "a", "b", "-e1"]);
+        assert_invalid_args(&["rut", "--between", "a", "b", "--header-out"]);
+        assert_invalid_args(&["rut", "-f1", "--all-occurrences"]);
+        assert_invalid_args(&["rut", "-b1", "--all-occurrences"]);
+    }
+
+    #[test]
+    fn value_parsing() {
+        use rut::cut::{Base64Alphabet, ByteFormat};
+        use rut::range::Ranges;
+
+        let bytes = |args: &[&str]| match parse(args).mode {
+            super::cut::Mode::Bytes(ranges, format) => (ranges, format),
+            _ => panic!("Expected byte mode."),
+        };
+        let ranges = |ranges: &str| ranges.parse::<Ranges>().unwrap();
+
+        // Values may be attached with "=", and may start with a hyphen.
+        assert_eq!(bytes(&["rut", "-b=2"]).0, ranges("2"));
+        assert_eq!(bytes(&["rut", "-b", "-3"]).0, ranges("-3"));
+        assert_eq!(bytes(&["rut", "--bytes", "-3,5"]).0, ranges("-3,5"));
+
+        // Repeated modes select the union of their ranges, and --complement applies to the union.
+        assert_eq!(bytes(&["rut", "-b1-3", "-b7,9"]).0, ranges("1-3,7,9"));
+        assert_eq!(bytes(&["rut", "-b2-4", "--bytes=3-6"]).0, ranges("2-6"));
+        assert_eq!(
+            bytes(&["rut", "-b2", "-b4-", "--complement"]).0,
+            ranges("1,3")
+        );
+        assert_eq!(
+            bytes(&["rut", "-b2", "-b5", "--complement=6"]).0,
+            ranges("1,3-4,6")
+        );
+        // A bound given once applies, even if --complement is also given without one.
+        assert_eq!(
+            bytes(&["rut", "-b2", "--complement", "--complement=3"]).0,
+            ranges("1,3")
+        );
+        assert!(bytes(&["rut", "-b1-", "--complement=3"]).0.is_empty());
+        assert!(parse_args(super::Cli::parse_from(["rut", "-b1", "-b0"])).is_err());
+
+        // Optional values must be attached with "=", so a following argument is a file.
+        let args = ["rut", "-b1", "--base64", "file"];
+        assert_eq!(bytes(&args).1, ByteFormat::Base64(Base64Alphabet::Standard));
+        assert_eq!(parse(&args).filenames, &[PathBuf::from("file")]);
+        assert_eq!(
+            bytes(&["rut", "-b1", "--base64=url"]).1,
+            ByteFormat::Base64(Base64Alphabet::Url)
+        );
+        assert_filenames(&["rut", "-f1", "--header-out", "file"], &["file"]);
+
+        // Flags may be repeated, but options which take a value may not.
+        assert_valid_args(&["rut", "-f1", "-ss", "-s", "--only-delimited"]);
+        assert_invalid_args(&["rut", "-f1", "-d,", "-d,"]);
+        assert_invalid_args(&["rut", "-b1", "--prefix=a", "--prefix=b"]);
+
+        // Files default to stdin, even with "--".
+        assert_filenames(&["rut", "-b1", "--"], &["-"]);
+    }
+
+    #[test]
+    fn env_defaults() {
+        // Environment variables set defaults for field mode.
+        assert_fields_env(&[("RUT_DELIMITER", ",")], &["rut", "-f1"], Some(','), ",");
+        assert_fields_env(
+            &[("RUT_OUTPUT_DELIMITER", "|")],
+            &["rut", "-f1"],
+            Some('\t'),
+            "|",
+        );
+        assert_fields_env(
+            &[("RUT_DELIMITER", ","), ("RUT_OUTPUT_DELIMITER", "|")],
+            &["rut", "-f1"],
+            Some(','),
+            "|",
+        );
+        #[cfg(feature = "regex")]
+        assert_fields_env(
+            &[("RUT_OUTPUT_DELIMITER", "|")],
+            &["rut", "-f1", "-r,"],
+            None,
+            "|",
+        );
+
+        // Options given on the command line take precedence.
+        assert_fields_env(
+            &[("RUT_DELIMITER", ",")],
+            &["rut", "-f1", "-d:"],
+            Some(':'),
+            ":",
+        );
+        assert_fields_env(
+            &[("RUT_DELIMITER", ","), ("RUT_OUTPUT_DELIMITER", "|")],
+            &["rut", "-f1", "-d:", "-o;"],
+            Some(':'),
+            ";",
+        );
+        #[cfg(feature = "regex")]
+        assert_fields_env(
+            &[("RUT_DELIMITER", "ab")],
+            &["rut", "-f1", "-r,"],
+            None,
+            "\t",
+        );
+
+        // Empty variables are ignored.
+        assert_fields_env(&[("RUT_DELIMITER", "")], &["rut", "-f1"], Some('\t'), "\t");
+
+        // Like -o, RUT_OUTPUT_DELIMITER does not apply to --byte-offsets.
+        assert_fields_env(
+            &[("RUT_DELIMITER", ","), ("RUT_OUTPUT_DELIMITER", "|")],
+            &["rut", "-f1", "--byte-offsets"],
+            Some(','),
+            ",",
+        );
+
+        let line_buffered = |vars: &[(&str, &str)], args: &[&str]| {
+            parse_env(vars, args).ok().unwrap().options.line_buffered
+        };
+        assert!(!line_buffered(&[], &["rut", "-b1"]));
+        assert!(line_buffered(
+            &[("RUT_LINE_BUFFERED", "1")],
+            &["rut", "-b1"]
+        ));
+        assert!(line_buffered(
+            &[("RUT_LINE_BUFFERED", "TRUE")],
+            &["rut", "-c1"]
+        ));
+        assert!(!line_buffered(
+            &[("RUT_LINE_BUFFERED", "no")],
+            &["rut", "-b1"]
+        ));
+        assert!(line_buffered(
+            &[("RUT_LINE_BUFFERED", "0")],
+            &["rut", "-b1", "--line-buffered"]
+        ));
+
+        // Variables for options which do not apply are ignored, even if invalid.
+        assert!(parse_env(&[("RUT_DELIMITER", "ab")], &["rut", "-b1"]).is_ok());
+
+        // Invalid values name the environment variable.
+        let err = |vars: &[(&str, &str)], args: &[&str]| parse_env(vars, args).err().unwrap();
+        let delimiter_err = err(&[("RUT_DELIMITER", "ab")], &["rut", "-f1"]);
+        assert!(delimiter_err.to_string().contains("RUT_DELIMITER"));
+        match delimiter_err {
+            ArgsError::InvalidDefault { source, origin } => {
+                assert!(matches!(*source, ArgsError::DelimiterNotSingleChar { .. }));
+                assert_eq!(origin, "environment variable RUT_DELIMITER");
+            }
+            err => panic!("expected an invalid default, but was {:?}", err),
+        }
+        let line_buffered_err = err(&[("RUT_LINE_BUFFERED", "maybe")], &["rut", "-b1"]);
+        assert!(line_buffered_err.to_string().contains("RUT_LINE_BUFFERED"));
+        assert!(matches!(
+            line_buffered_err,
+            ArgsError::InvalidBool { name: "RUT_LINE_BUFFERED", value } if value == "maybe"
+        ));
+    }
+
+    /// Serializes tests which parse arguments, since parsing reads environment variables.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
+
+    fn lock_env() -> MutexGuard<'static, ()> {
+        ENV_LOCK.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn parse_args(cli: super::Cli) -> Result<super::Args, ArgsError> {
+        let _lock = lock_env();
+        super::parse_args(cli, &Config::default())
+    }
+
+    /// Parses the arguments with the environment variables set, removing them afterwards.
+    fn parse_env(vars: &[(&str, &str)], args: &[&str]) -> Result<super::Args, ArgsError> {
+        let cli = super::Cli::parse_from(args);
+        let _lock = lock_env();
+        for (name, value) in vars {
+            std::env::set_var(name, value);
+        }
+        let result = super::parse_args(cli, &Config::default());
+        for (name, _) in vars {
+            std::env::remove_var(name);
+        }
+        result
+    }
+
+    /// Asserts the field delimiter, or `None` for a regex delimiter, and the output delimiter.
+    fn assert_fields_env(
+        vars: &[(&str, &str)],
+        args: &[&str],
+        delimiter: Option<char>,
+        output_delimiter: impl AsRef<[u8]>,
+    ) {
+        match parse_env(vars, args).ok().unwrap().mode {
+            super::cut::Mode::FieldsChar(_, actual, options) => {
+                assert_eq!(Some(actual), delimiter);
+                assert_eq!(options.output_delimiter, output_delimiter.as_ref());
+            }
+            #[cfg(feature = "regex")]
+            super::cut::Mode::FieldsRegex(_, _, options) => {
+                assert_eq!(None, delimiter);
+                assert_eq!(options.output_delimiter, output_delimiter.as_ref());
+            }
+            _ => panic!("expected field mode"),
+        }
+    }
+
+    /// Asserts the fields which the delimiter given by the arguments splits `line` into.
+    fn assert_fields(args: &[&str], line: &str, expected: &[&str]) {
+        fn split<S: FieldSplitter>(delimiter: S, line: &str) -> (Vec<&str>, bool) {
+            (delimiter.split(line).collect(), delimiter.matches(line))
+        }
+        let (fields, matches) = match parse(args).mode {
+            super::cut::Mode::FieldsChar(_, delimiter, _) => split(delimiter, line),
+            super::cut::Mode::FieldsStr(_, delimiter, _) => split(delimiter.as_str(), line),
+            #[cfg(feature = "regex")]
+            super::cut::Mode::FieldsRegex(_, delimiter, _) => {
+                let fields = delimiter.split(line.as_bytes());
+                let fields = fields.map(|field| std::str::from_utf8(field).unwrap());
+                (fields.collect(), delimiter.is_match(line.as_bytes()))
+            }
+            _ => panic!("expected field mode"),
+        };
+        assert_eq!(fields, expected, "{:?}", args);
+        assert_eq!(matches, expected.len() > 1, "{:?}", args);
+    }
+
+    /// Returns the error for arguments which clap accepts, but which are not valid.
+    fn args_error(args: &[&str]) -> ArgsError {
+        parse_args(super::Cli::parse_from(args)).err().unwrap()
+    }
+
+    fn parse(args: &[&str]) -> super::Args {
+        parse_args(super::Cli::parse_from(args)).ok().unwrap()
+    }
+
+    fn assert_filenames(args: &[&str], expected: &[&str]) {
+        let cli = super::Cli::parse_from(args);
+        let expected: Vec<PathBuf> = expected.iter().map(PathBuf::from).collect();
+        assert_eq!(parse_args(cli).ok().unwrap().filenames, expected);
+    }
+
     fn assert_valid_args(args: &[&str]) {
-        let matches = super::get_app().get_matches_from(args);
-        assert!(super::parse_args(&matches).is_ok());
+        let cli = super::Cli::parse_from(args);
+        assert!(parse_args(cli).is_ok());
     }
 
     fn assert_invalid_args(args: &[&str]) {
-        if let Ok(matches) = super::get_app().get_matches_from_safe(args) {
-            assert!(super::parse_args(&matches).is_err())
+        if let Ok(cli) = super::Cli::try_parse_from(args) {
+            assert!(parse_args(cli).is_err())
         }
     }
 }
@@ -0,0 +1,691 @@
+#[cfg(feature = "regex")]
+use crate::cut::regex_needs_line_delimiter;
+use crate::cut::{
+    ByteFormat, CutLines, FieldOptions, InvalidUtf8, Mode, Options, Reject, RunState,
+};
+use crate::range::Ranges;
+use crate::template::Template;
+#[cfg(feature = "regex")]
+use regex::bytes::Regex;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::ControlFlow;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// What to select from each record, and how, ready to cut any number of inputs.
+///
+/// A `Cut` is created with [`Cut::bytes`], [`Cut::characters`] or [`Cut::fields`], which return a
+/// [`CutBuilder`] for setting any other options.
+///
+/// # Examples
+///
+/// The equivalent of `rut -d, -f1,3 -o '\t' -s`:
+///
+/// ```
+/// use rut::{Cut, Delim};
+///
+/// let cut = Cut::fields("1,3".parse()?)
+///     .delimiter(Delim::Char(','))
+///     .output_delimiter("\t")
+///     .suppress(true)
+///     .build()?;
+/// let mut output = Vec::new();
+/// cut.run(&b"a,b,c\nno delimiter\nd,e,f\n"[..], &mut output)?;
+/// assert_eq!(output, b"a\tc\nd\tf\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct Cut {
+    mode: Mode,
+    options: Options,
+    /// Records skipped by every run, until they are taken.
+    rejects: Mutex<Vec<Reject>>,
+}
+
+impl Clone for Cut {
+    fn clone(&self) -> Self {
+        Cut {
+            mode: self.mode.clone(),
+            options: self.options.clone(),
+            rejects: Mutex::new(self.rejects().clone()),
+        }
+    }
+}
+
+impl Cut {
+    /// Returns a builder which selects bytes.
+    pub fn bytes(ranges: Ranges) -> CutBuilder {
+        CutBuilder::new(Selecting::Bytes, ranges)
+    }
+
+    /// Returns a builder which selects characters.
+    pub fn characters(ranges: Ranges) -> CutBuilder {
+        CutBuilder::new(Selecting::Characters, ranges)
+    }
+
+    /// Returns a builder which selects fields, separated by a tab unless another delimiter is set.
+    pub fn fields(ranges: Ranges) -> CutBuilder {
+        CutBuilder::new(Selecting::Fields, ranges)
+    }
+
+    /// Cuts each record of `reader` and writes the selected parts to `writer`.
+    ///
+    /// Each run is independent, e.g. [`CutBuilder::max_lines`] limits the records written by each
+    /// run. Records skipped with [`InvalidUtf8::Skip`] are kept until they are taken with
+    /// [`Cut::take_rejects`].
+    pub fn run<R, W>(&self, mut reader: R, mut writer: W) -> io::Result<()>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        let mut state = RunState::default();
+        let result = self
+            .mode
+            .cut(&mut reader, &mut writer, &self.options, &mut state);
+        self.rejects().append(&mut state.rejects);
+        result
+    }
+
+    /// Cuts each record of `reader` and writes the selected parts to `writer`, as [`Cut::run`]
+    /// does, without blocking. See [`Mode::cut_async`].
+    #[cfg(feature = "tokio")]
+    pub async fn run_async<R, W>(&self, mut reader: R, mut writer: W) -> io::Result<()>
+    where
+        R: tokio::io::AsyncBufRead + Unpin,
+        W: tokio::io::AsyncWrite + Unpin,
+    {
+        let mut state = RunState::default();
+        let result = self
+            .mode
+            .cut_async(&mut reader, &mut writer, &self.options, &mut state)
+            .await;
+        self.rejects().append(&mut state.rejects);
+        result
+    }
+
+    /// Cuts each record of `reader`, and calls `visit` with each output record, instead of writing
+    /// it, until `visit` breaks. See [`Mode::visit`].
+    ///
+    /// Each visit is independent, as with [`Cut::run`].
+    pub fn visit<R, F, B>(&self, mut reader: R, visit: F) -> io::Result<ControlFlow<B>>
+    where
+        R: BufRead,
+        F: FnMut(&[u8]) -> ControlFlow<B>,
+    {
+        let mut state = RunState::default();
+        let result = self
+            .mode
+            .visit(&mut reader, &self.options, &mut state, visit);
+        self.rejects().append(&mut state.rejects);
+        result
+    }
+
+    /// Returns an iterator over the output records of cutting `reader`, instead of writing them.
+    ///
+    /// The iterator is independent of any run, and records it skips with [`InvalidUtf8::Skip`]
+    /// are kept in [`CutLines::state`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::{Cut, Delim};
+    ///
+    /// let cut = Cut::fields("2".parse()?).delimiter(Delim::Char(',')).suppress(true).build()?;
+    /// let records = cut.lines(&b"a,b\nc\nd,e"[..]).collect::<Result<Vec<_>, _>>()?;
+    /// assert_eq!(records, [b"b", b"e"]);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn lines<R: BufRead>(&self, reader: R) -> CutLines<R> {
+        CutLines::new(reader, self.mode.clone(), self.options.clone())
+    }
+
+    /// Returns the records skipped with [`InvalidUtf8::Skip`] since they were last taken.
+    pub fn take_rejects(&self) -> Vec<Reject> {
+        std::mem::take(&mut *self.rejects())
+    }
+
+    /// Returns what is selected from each record.
+    pub fn mode(&self) -> &Mode {
+        &self.mode
+    }
+
+    /// Returns the options shared by all modes.
+    pub fn options(&self) -> &Options {
+        &self.options
+    }
+
+    /// Locks the records skipped by every run. Nothing panics while they are locked, so a poisoned
+    /// lock still holds every record.
+    fn rejects(&self) -> MutexGuard<'_, Vec<Reject>> {
+        self.rejects.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Delimiter which separates fields.
+#[derive(Clone, Debug)]
+pub enum Delim {
+    /// A single character, which is also used to join the selected fields by default.
+    Char(char),
+    /// Matches of a regular expression, in records which do not need to be valid UTF-8. Selected
+    /// fields are joined with a tab by default. Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    Regex(Regex),
+}
+
+/// What a [`CutBuilder`] selects.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Selecting {
+    Bytes,
+    Characters,
+    Fields,
+}
+
+/// Builder for a [`Cut`], created by [`Cut::bytes`], [`Cut::characters`] or [`Cut::fields`].
+///
+/// Options which only apply to some modes, such as [`CutBuilder::delimiter`], may be set in any
+/// mode, but [`CutBuilder::build`] returns an error if they do not apply.
+#[derive(Clone, Debug)]
+#[must_use]
+pub struct CutBuilder {
+    selecting: Selecting,
+    ranges: Ranges,
+    complement: bool,
+    byte_format: Option<ByteFormat>,
+    delimiter: Option<Delim>,
+    output_delimiter: Option<Vec<u8>>,
+    suppress: bool,
+    byte_offsets: bool,
+    template: Option<Template>,
+    options: Options,
+}
+
+impl CutBuilder {
+    fn new(selecting: Selecting, ranges: Ranges) -> CutBuilder {
+        CutBuilder {
+            selecting,
+            ranges,
+            complement: false,
+            byte_format: Option::None,
+            delimiter: Option::None,
+            output_delimiter: Option::None,
+            suppress: false,
+            byte_offsets: false,
+            template: Option::None,
+            options: Options::default(),
+        }
+    }
+
+    /// Selects everything except the ranges.
+    pub fn complement(mut self, complement: bool) -> CutBuilder {
+        self.complement = complement;
+        self
+    }
+
+    /// Sets the format selected bytes are written in. Only applies to bytes.
+    pub fn byte_format(mut self, format: ByteFormat) -> CutBuilder {
+        self.byte_format = Option::Some(format);
+        self
+    }
+
+    /// Sets the delimiter which separates fields. Only applies to fields.
+    pub fn delimiter(mut self, delimiter: Delim) -> CutBuilder {
+        self.delimiter = Option::Some(delimiter);
+        self
+    }
+
+    /// Sets the bytes used to join selected fields, which need not be valid UTF-8. Only applies to
+    /// fields.
+    pub fn output_delimiter(mut self, output_delimiter: impl Into<Vec<u8>>) -> CutBuilder {
+        self.output_delimiter = Option::Some(output_delimiter.into());
+        self
+    }
+
+    /// Sets whether records which do not contain a field delimiter are left out. Only applies to
+    /// fields.
+    pub fn suppress(mut self, suppress: bool) -> CutBuilder {
+        self.suppress = suppress;
+        self
+    }
+
+    /// Sets whether the byte offsets of selected fields are written instead of their contents.
+    /// Only applies to fields.
+    pub fn byte_offsets(mut self, byte_offsets: bool) -> CutBuilder {
+        self.byte_offsets = byte_offsets;
+        self
+    }
+
+    /// Writes fields by substituting them into a template, rather than joining them with the output
+    /// delimiter. The fields written are those referenced by the template, rather than the ranges.
+    /// Only applies to fields.
+    pub fn template(mut self, template: Template) -> CutBuilder {
+        self.template = Option::Some(template);
+        self
+    }
+
+    /// Sets the byte which terminates each input and output record. The default is `b'\n'`.
+    pub fn line_terminator(self, line_terminator: u8) -> CutBuilder {
+        self.line_terminator_bytes([line_terminator])
+    }
+
+    /// Sets the bytes which terminate each input record, and each output record unless
+    /// [`output_line_terminator`](CutBuilder::output_line_terminator) is set, such as `"\r\n"`.
+    pub fn line_terminator_bytes(mut self, line_terminator: impl Into<Vec<u8>>) -> CutBuilder {
+        self.options.line_delimiter = line_terminator.into();
+        self
+    }
+
+    /// Sets the bytes which terminate each output record, instead of the line terminator of the
+    /// input.
+    pub fn output_line_terminator(mut self, line_terminator: impl Into<Vec<u8>>) -> CutBuilder {
+        self.options.output_line_delimiter = Option::Some(line_terminator.into());
+        self
+    }
+
+    /// Sets the bytes written before each output record.
+    pub fn prefix(mut self, prefix: impl Into<Vec<u8>>) -> CutBuilder {
+        self.options.prefix = prefix.into();
+        self
+    }
+
+    /// Sets the bytes written after each output record, before the line terminator.
+    pub fn suffix(mut self, suffix: impl Into<Vec<u8>>) -> CutBuilder {
+        self.options.suffix = suffix.into();
+        self
+    }
+
+    /// Sets whether a carriage return before a newline line terminator is kept as part of the
+    /// record.
+    pub fn keep_cr(mut self, keep_cr: bool) -> CutBuilder {
+        self.options.keep_cr = keep_cr;
+        self
+    }
+
+    /// Sets whether output is flushed after each record.
+    pub fn line_buffered(mut self, line_buffered: bool) -> CutBuilder {
+        self.options.line_buffered = line_buffered;
+        self
+    }
+
+    /// Sets the number of records to skip at the start of the input.
+    pub fn skip_lines(mut self, skip_lines: usize) -> CutBuilder {
+        self.options.skip_lines = skip_lines;
+        self
+    }
+
+    /// Sets the maximum number of records to write, or `None` for no limit.
+    pub fn max_lines(mut self, max_lines: Option<usize>) -> CutBuilder {
+        self.options.max_lines = max_lines;
+        self
+    }
+
+    /// Sets how records which are not valid UTF-8 are handled, when selecting characters or fields.
+    pub fn invalid_utf8(mut self, invalid_utf8: InvalidUtf8) -> CutBuilder {
+        self.options.invalid_utf8 = invalid_utf8;
+        self
+    }
+
+    /// Returns the `Cut`, or an error if the options cannot be used together.
+    pub fn build(self) -> Result<Cut, BuildError> {
+        let ranges = if self.complement {
+            self.ranges.complement()
+        } else {
+            self.ranges
+        };
+        let mode = match self.selecting {
+            Selecting::Bytes | Selecting::Characters => {
+                let field_options = [
+                    ("delimiter", self.delimiter.is_some()),
+                    ("output_delimiter", self.output_delimiter.is_some()),
+                    ("suppress", self.suppress),
+                    ("byte_offsets", self.byte_offsets),
+                    ("template", self.template.is_some()),
+                ];
+                if let Option::Some((option, _)) = field_options.iter().find(|(_, set)| *set) {
+                    return Result::Err(BuildError::FieldsOnly(option));
+                }
+                match self.selecting {
+                    Selecting::Bytes => {
+                        Mode::Bytes(ranges, self.byte_format.unwrap_or(ByteFormat::Raw))
+                    }
+                    _ if self.byte_format.is_some() => {
+                        return Result::Err(BuildError::BytesOnly("byte_format"));
+                    }
+                    _ => Mode::Characters(ranges),
+                }
+            }
+            Selecting::Fields => {
+                if self.byte_format.is_some() {
+                    return Result::Err(BuildError::BytesOnly("byte_format"));
+                }
+                if self.template.is_some() {
+                    if self.byte_offsets {
+                        return Result::Err(BuildError::Conflict("template", "byte_offsets"));
+                    }
+                    if self.output_delimiter.is_some() {
+                        return Result::Err(BuildError::Conflict("template", "output_delimiter"));
+                    }
+                }
+
+                let terminator = &self.options.line_delimiter[..];
+                let delimiter = self.delimiter.unwrap_or(Delim::Char('\t'));
+                let matches_terminator = !terminator.is_empty()
+                    && match &delimiter {
+                        Delim::Char(delimiter) => delimiter
+                            .to_string()
+                            .as_bytes()
+                            .windows(terminator.len())
+                            .any(|window| window == terminator),
+                        #[cfg(feature = "regex")]
+                        Delim::Regex(delimiter) => {
+                            regex_needs_line_delimiter(delimiter, terminator)
+                        }
+                    };
+                if matches_terminator {
+                    return Result::Err(BuildError::DelimiterIsLineTerminator);
+                }
+
+                let field_options = FieldOptions {
+                    output_delimiter: self.output_delimiter.unwrap_or_else(|| match &delimiter {
+                        Delim::Char(delimiter) => delimiter.to_string().into_bytes(),
+                        #[cfg(feature = "regex")]
+                        Delim::Regex(_) => b"\t".to_vec(),
+                    }),
+                    suppress: self.suppress,
+                    byte_offsets: self.byte_offsets,
+                    header: Option::None,
+                    template: self.template,
+                    sub_fields: Option::None,
+                };
+                match delimiter {
+                    Delim::Char(delimiter) => Mode::FieldsChar(ranges, delimiter, field_options),
+                    #[cfg(feature = "regex")]
+                    Delim::Regex(delimiter) => Mode::FieldsRegex(ranges, delimiter, field_options),
+                }
+            }
+        };
+        Result::Ok(Cut {
+            mode,
+            options: self.options,
+            rejects: Mutex::new(Vec::new()),
+        })
+    }
+}
+
+/// Error from building a [`Cut`] with options which cannot be used together. Options are named
+/// after the [`CutBuilder`] methods which set them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// An option which only applies to fields was set when selecting bytes or characters.
+    FieldsOnly(&'static str),
+    /// An option which only applies to bytes was set when selecting characters or fields.
+    BytesOnly(&'static str),
+    /// Two options which cannot be used together were both set.
+    Conflict(&'static str, &'static str),
+    /// The field delimiter contains the line terminator, or a regex delimiter only matches text
+    /// containing it. Records are split before fields, so fields could never be separated by it.
+    DelimiterIsLineTerminator,
+}
+
+impl fmt::Display for BuildError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BuildError::FieldsOnly(option) => {
+                write!(f, "{} can only be used when selecting fields", option)
+            }
+            BuildError::BytesOnly(option) => {
+                write!(f, "{} can only be used when selecting bytes", option)
+            }
+            BuildError::Conflict(first, second) => {
+                write!(f, "{} cannot be used with {}", first, second)
+            }
+            BuildError::DelimiterIsLineTerminator => write!(
+                f,
+                "the field delimiter cannot match the line terminator, since lines are split before fields"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for BuildError {}
+
+#[cfg(test)]
+mod tests {
+    use super::{BuildError, Cut, Delim};
+    #[cfg(feature = "regex")]
+    use crate::cut::Mode;
+    use crate::cut::{Base64Alphabet, ByteFormat, InvalidUtf8};
+    use crate::range::Ranges;
+    #[cfg(feature = "regex")]
+    use regex::bytes::Regex;
+    use std::ops::ControlFlow;
+
+    fn ranges(ranges: &str) -> Ranges {
+        ranges.parse().unwrap()
+    }
+
+    fn run(cut: &Cut, input: &[u8]) -> Vec<u8> {
+        let mut output = Vec::new();
+        cut.run(input, &mut output).unwrap();
+        output
+    }
+
+    #[test]
+    fn visit() {
+        let cut = Cut::fields(ranges("2"))
+            .delimiter(Delim::Char(','))
+            .max_lines(Option::Some(2))
+            .build()
+            .unwrap();
+        let mut visited = Vec::new();
+        let result = cut.visit(&b"a,b\nc,d\ne,f\n"[..], |record| {
+            visited.push(record.to_vec());
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(result.unwrap(), ControlFlow::Continue(()));
+        assert_eq!(visited, [b"b", b"d"]);
+    }
+
+    #[test]
+    fn lines() {
+        let cut = Cut::fields(ranges("2"))
+            .delimiter(Delim::Char(','))
+            .invalid_utf8(InvalidUtf8::Skip)
+            .max_lines(Option::Some(2))
+            .build()
+            .unwrap();
+        let input = b"a,b\n\xff\nc,d\ne,f\n";
+        let records: Vec<Vec<u8>> = cut.lines(&input[..]).map(Result::unwrap).collect();
+        assert_eq!(records, [b"b", b"d"]);
+        assert_eq!(run(&cut, input), b"b\nd\n");
+
+        // Each iterator is independent of runs and other iterators.
+        let mut lines = cut.lines(&input[..]);
+        assert_eq!(lines.by_ref().count(), 2);
+        assert_eq!(lines.state().rejects.len(), 1);
+        assert_eq!(cut.take_rejects().len(), 1);
+    }
+
+    #[test]
+    fn bytes() {
+        let cut = Cut::bytes(ranges("1-2,4")).build().unwrap();
+        assert_eq!(run(&cut, b"abcde\nfghij\n"), b"abd\nfgi\n");
+
+        let cut = Cut::bytes(ranges("1-3"))
+            .byte_format(ByteFormat::Base64(Base64Alphabet::Standard))
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"abcde\n"), b"YWJj\n");
+
+        let cut = Cut::bytes(ranges("2")).complement(true).build().unwrap();
+        assert_eq!(run(&cut, b"abc\n"), b"ac\n");
+
+        assert_eq!(
+            Cut::bytes(ranges("1"))
+                .delimiter(Delim::Char(','))
+                .build()
+                .err(),
+            Option::Some(BuildError::FieldsOnly("delimiter"))
+        );
+        assert_eq!(
+            Cut::bytes(ranges("1")).suppress(true).build().err(),
+            Option::Some(BuildError::FieldsOnly("suppress"))
+        );
+        assert!(Cut::bytes(ranges("1")).suppress(false).build().is_ok());
+    }
+
+    #[test]
+    fn characters() {
+        let cut = Cut::characters(ranges("2-")).build().unwrap();
+        assert_eq!(run(&cut, "αβγ\n".as_bytes()), "βγ\n".as_bytes());
+
+        let cut = Cut::characters(ranges("1"))
+            .invalid_utf8(InvalidUtf8::Skip)
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"a\n\xff\nb\n"), b"a\nb\n");
+        let rejects = cut.take_rejects();
+        assert_eq!(rejects.len(), 1);
+        assert_eq!(rejects[0].record, 2);
+        assert!(cut.take_rejects().is_empty());
+
+        assert_eq!(
+            Cut::characters(ranges("1"))
+                .output_delimiter(":")
+                .build()
+                .err(),
+            Option::Some(BuildError::FieldsOnly("output_delimiter"))
+        );
+        assert_eq!(
+            Cut::characters(ranges("1"))
+                .byte_format(ByteFormat::Raw)
+                .build()
+                .err(),
+            Option::Some(BuildError::BytesOnly("byte_format"))
+        );
+    }
+
+    #[test]
+    fn fields() {
+        // Tab delimited by default.
+        let cut = Cut::fields(ranges("2")).build().unwrap();
+        assert_eq!(run(&cut, b"a\tb\tc\nno delimiter\n"), b"b\nno delimiter\n");
+
+        let cut = Cut::fields(ranges("1,3"))
+            .delimiter(Delim::Char(','))
+            .output_delimiter("\t")
+            .suppress(true)
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"a,b,c\nno delimiter\n"), b"a\tc\n");
+
+        let cut = Cut::fields(ranges("1,3"))
+            .delimiter(Delim::Char(','))
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"a,b,c\n"), b"a,c\n");
+
+        // Fields separated by a regex are joined with a tab by default.
+        #[cfg(feature = "regex")]
+        {
+            let cut = Cut::fields(ranges("1,3"))
+                .delimiter(Delim::Regex(Regex::new(" +").unwrap()))
+                .build()
+                .unwrap();
+            assert!(matches!(cut.mode(), Mode::FieldsRegex(..)));
+            assert_eq!(run(&cut, b"a  b c\n"), b"a\tc\n");
+        }
+
+        let cut = Cut::fields(ranges("2"))
+            .template("<{2}> {1}".parse().unwrap())
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"a\tb\n"), b"<b> a\n");
+
+        let cut = Cut::fields(ranges("2"))
+            .delimiter(Delim::Char(','))
+            .line_terminator(0)
+            .prefix("[")
+            .suffix("]")
+            .skip_lines(1)
+            .max_lines(Option::Some(1))
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"h,i\0a,b\0c,d\0"), b"[b]\0");
+        // The maximum applies to each run.
+        assert_eq!(run(&cut, b"h,i\0e,f\0"), b"[f]\0");
+
+        assert_eq!(
+            Cut::fields(ranges("1"))
+                .byte_format(ByteFormat::Raw)
+                .build()
+                .err(),
+            Option::Some(BuildError::BytesOnly("byte_format"))
+        );
+        assert_eq!(
+            Cut::fields(ranges("1"))
+                .template("{1}".parse().unwrap())
+                .byte_offsets(true)
+                .build()
+                .err(),
+            Option::Some(BuildError::Conflict("template", "byte_offsets"))
+        );
+        assert_eq!(
+            Cut::fields(ranges("1"))
+                .delimiter(Delim::Char('\n'))
+                .build()
+                .err(),
+            Option::Some(BuildError::DelimiterIsLineTerminator)
+        );
+        #[cfg(feature = "regex")]
+        assert_eq!(
+            Cut::fields(ranges("1"))
+                .delimiter(Delim::Regex(Regex::new(r"a\x00|\x00").unwrap()))
+                .line_terminator(0)
+                .build()
+                .err(),
+            Option::Some(BuildError::DelimiterIsLineTerminator)
+        );
+        // A regex which also matches other text may still separate fields.
+        #[cfg(feature = "regex")]
+        assert!(Cut::fields(ranges("1"))
+            .delimiter(Delim::Regex(Regex::new("[;,]").unwrap()))
+            .line_terminator(b';')
+            .build()
+            .is_ok());
+        // The delimiter is only checked against the line terminator which is used.
+        assert!(Cut::fields(ranges("1"))
+            .delimiter(Delim::Char('\n'))
+            .line_terminator(0)
+            .build()
+            .is_ok());
+        // A delimiter which is part of a longer line terminator may still separate fields.
+        assert!(Cut::fields(ranges("1"))
+            .delimiter(Delim::Char(';'))
+            .line_terminator_bytes(";;")
+            .build()
+            .is_ok());
+    }
+
+    #[test]
+    fn line_terminator_bytes() {
+        let run = |cut: &Cut, input: &[u8]| {
+            let mut output = Vec::new();
+            cut.run(input, &mut output).unwrap();
+            output
+        };
+        let cut = Cut::fields(ranges("2"))
+            .delimiter(Delim::Char(','))
+            .line_terminator_bytes("\r\n")
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"a,b\r\nc,d\ne\r\n"), b"b\r\nd\ne\r\n");
+
+        let cut = Cut::bytes(ranges("2-"))
+            .line_terminator_bytes(";;")
+            .output_line_terminator("\n")
+            .build()
+            .unwrap();
+        assert_eq!(run(&cut, b"abc;;d;e;;;f"), b"bc\n;e\nf\n");
+    }
+}
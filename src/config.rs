@@ -0,0 +1,728 @@
+use crate::report::Kind;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Environment variable which sets the path of the config file.
+const ENV_CONFIG: &str = "RUT_CONFIG";
+
+/// Defaults for options, read from a config file. Options given on the command line and
+/// environment variables take precedence.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Config {
+    /// Path of the config file, if one was read.
+    pub(crate) path: Option<String>,
+    pub(crate) delimiter: Option<String>,
+    pub(crate) output_delimiter: Option<String>,
+    pub(crate) line_buffered: Option<bool>,
+}
+
+/// An error or warning about a config file.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct ConfigError {
+    pub(crate) kind: Kind,
+    pub(crate) path: String,
+    /// Line the error is on, if it is about a particular line.
+    pub(crate) line: Option<usize>,
+    pub(crate) message: String,
+}
+
+impl ConfigError {
+    /// Returns the message, prefixed with the line number if there is one.
+    pub(crate) fn detail(&self) -> String {
+        match self.line {
+            Option::Some(line) => format!("line {}: {}", line, self.message),
+            Option::None => self.message.clone(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.path, self.detail())
+    }
+}
+
+/// Returns the path of the config file to read, and whether it must exist. A path given with
+/// --config or RUT_CONFIG must exist, but the default path need not.
+pub(crate) fn config_path(option: Option<&Path>, no_config: bool) -> Option<(PathBuf, bool)> {
+    if no_config {
+        return Option::None;
+    }
+    if let Option::Some(path) = option {
+        return Option::Some((path.to_path_buf(), true));
+    }
+    if let Option::Some(path) = std::env::var_os(ENV_CONFIG).filter(|path| !path.is_empty()) {
+        return Option::Some((PathBuf::from(path), true));
+    }
+    default_config_dir().map(|dir| (dir.join("rut").join("config.toml"), false))
+}
+
+/// Returns the directory which contains user config files: $XDG_CONFIG_HOME or ~/.config, or
+/// %APPDATA% on Windows.
+fn default_config_dir() -> Option<PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|value| !value.is_empty());
+    if cfg!(windows) {
+        var("APPDATA").map(PathBuf::from)
+    } else {
+        var("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))
+    }
+}
+
+/// Reads the config file, returning the config and warnings for unknown keys. A missing file is
+/// treated as empty unless it is required.
+pub(crate) fn read_config(
+    path: &Path,
+    required: bool,
+) -> Result<(Config, Vec<ConfigError>), ConfigError> {
+    let display = path.display().to_string();
+    match fs::read_to_string(path) {
+        Result::Ok(contents) => parse_config(&display, &contents),
+        Result::Err(err) if err.kind() == io::ErrorKind::NotFound && !required => {
+            Result::Ok((Config::default(), Vec::new()))
+        }
+        Result::Err(err) => Result::Err(ConfigError {
+            kind: Kind::of(&err),
+            path: display,
+            line: Option::None,
+            message: err.to_string(),
+        }),
+    }
+}
+
+/// Parses a config file, which is TOML. Only the options' keys at the top level are read. Other
+/// keys and tables are ignored with a warning, whatever their values are.
+fn parse_config(path: &str, contents: &str) -> Result<(Config, Vec<ConfigError>), ConfigError> {
+    let mut config = Config {
+        path: Option::Some(path.to_string()),
+        ..Config::default()
+    };
+    let mut warnings = Vec::new();
+    let mut keys = Vec::new();
+    let error = |line: usize, message: String| ConfigError {
+        kind: Kind::InvalidConfig,
+        path: path.to_string(),
+        line: Option::Some(line),
+        message,
+    };
+    let mut parser = Parser::new(contents);
+    // Keys after a table header belong to the table, so they are not options.
+    let mut in_table = false;
+    loop {
+        parser.skip_blank();
+        let line = parser.line;
+        match parser.peek() {
+            Option::None => break,
+            Option::Some('[') => {
+                let table = parser
+                    .table_header()
+                    .map_err(|message| error(parser.line, message))?;
+                warnings.push(ConfigError {
+                    kind: Kind::UnknownConfigKey,
+                    ..error(line, format!("unknown table \"{}\", ignored", table))
+                });
+                in_table = true;
+                continue;
+            }
+            Option::Some(_) => {}
+        }
+        let (key, value) = parser
+            .key_value()
+            .and_then(|pair| parser.end_of_line().map(|_| pair))
+            .map_err(|message| error(parser.line, message))?;
+        if in_table {
+            continue;
+        }
+        if keys.contains(&key) {
+            return Result::Err(error(line, format!("duplicate key \"{}\"", key)));
+        }
+        let error = |message| error(line, message);
+        match key.as_str() {
+            "delimiter" => config.delimiter = Option::Some(value.string(&key).map_err(error)?),
+            "output_delimiter" => {
+                config.output_delimiter = Option::Some(value.string(&key).map_err(error)?)
+            }
+            "line_buffered" => {
+                config.line_buffered = Option::Some(value.boolean(&key).map_err(error)?)
+            }
+            _ => warnings.push(ConfigError {
+                kind: Kind::UnknownConfigKey,
+                ..error(format!("unknown key \"{}\", ignored", key))
+            }),
+        }
+        keys.push(key);
+    }
+    Result::Ok((config, warnings))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Value {
+    String(String),
+    Boolean(bool),
+    /// A number, date-time, array or inline table, none of which any option takes.
+    Other,
+}
+
+impl Value {
+    fn string(self, key: &str) -> Result<String, String> {
+        match self {
+            Value::String(value) => Result::Ok(value),
+            _ => Result::Err(format!("\"{}\" must be a string", key)),
+        }
+    }
+
+    fn boolean(self, key: &str) -> Result<bool, String> {
+        match self {
+            Value::Boolean(value) => Result::Ok(value),
+            _ => Result::Err(format!("\"{}\" must be true or false", key)),
+        }
+    }
+}
+
+/// Reads the contents of a config file, keeping track of the line of the next character.
+struct Parser<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(contents: &'a str) -> Parser<'a> {
+        Parser {
+            rest: contents,
+            line: 1,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.rest.chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let ch = self.peek()?;
+        self.rest = &self.rest[ch.len_utf8()..];
+        if ch == '\n' {
+            self.line += 1;
+        }
+        Option::Some(ch)
+    }
+
+    /// Skips `prefix` if the rest starts with it. Newlines in `prefix` are not counted as lines.
+    fn eat(&mut self, prefix: &str) -> bool {
+        match self.rest.strip_prefix(prefix) {
+            Option::Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            Option::None => false,
+        }
+    }
+
+    /// Returns the rest of the current line, not including the newline.
+    fn rest_of_line(&self) -> &'a str {
+        let end = self.rest.find('\n').unwrap_or(self.rest.len());
+        self.rest[..end].trim_end_matches('\r')
+    }
+
+    fn skip_spaces(&mut self) {
+        self.rest = self.rest.trim_start_matches([' ', '\t']);
+    }
+
+    fn skip_comment(&mut self) {
+        if self.peek() == Option::Some('#') {
+            self.rest = &self.rest[self.rest_of_line().len()..];
+        }
+    }
+
+    /// Skips whitespace, newlines and comments, e.g. between lines or the values of an array.
+    fn skip_blank(&mut self) {
+        loop {
+            self.skip_spaces();
+            self.skip_comment();
+            match self.peek() {
+                Option::Some('\r') | Option::Some('\n') => {
+                    self.next();
+                }
+                _ => return,
+            }
+        }
+    }
+
+    /// Skips the end of a line, which may have a comment, after a key and value or table header.
+    fn end_of_line(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        self.skip_comment();
+        self.eat("\r");
+        match self.peek() {
+            Option::None => Result::Ok(()),
+            Option::Some('\n') => {
+                self.next();
+                Result::Ok(())
+            }
+            Option::Some(_) => Result::Err(format!(
+                "unexpected \"{}\" after value",
+                self.rest_of_line().trim_end()
+            )),
+        }
+    }
+
+    /// Parses a table header, `[table]` or `[[table]]`, returning the name of the table.
+    fn table_header(&mut self) -> Result<String, String> {
+        let close = if self.eat("[[") {
+            "]]"
+        } else {
+            self.eat("[");
+            "]"
+        };
+        let name = self.key()?;
+        if !self.eat(close) {
+            return Result::Err(format!("expected \"{}\" after the table name", close));
+        }
+        self.end_of_line()?;
+        Result::Ok(name)
+    }
+
+    /// Parses a `key = value` pair.
+    fn key_value(&mut self) -> Result<(String, Value), String> {
+        let line = self.rest_of_line();
+        let invalid_key = || match line.find('=') {
+            Option::Some(equals) => format!("invalid key \"{}\"", line[..equals].trim()),
+            Option::None => String::from("expected \"key = value\""),
+        };
+        let key = self.key().map_err(|_| invalid_key())?;
+        if !self.eat("=") {
+            return Result::Err(invalid_key());
+        }
+        self.skip_spaces();
+        let value = self.value()?;
+        Result::Ok((key, value))
+    }
+
+    /// Parses a key, which may be dotted, e.g. `a.b`, and whose parts may be quoted. Spaces after
+    /// the key are skipped.
+    fn key(&mut self) -> Result<String, String> {
+        let mut parts = Vec::new();
+        loop {
+            self.skip_spaces();
+            let part = match self.peek() {
+                Option::Some('"') => {
+                    self.next();
+                    self.basic_string()?
+                }
+                Option::Some('\'') => {
+                    self.next();
+                    self.literal_string()?
+                }
+                _ => {
+                    let end = self
+                        .rest
+                        .find(|ch: char| !(ch.is_ascii_alphanumeric() || ch == '_' || ch == '-'))
+                        .unwrap_or(self.rest.len());
+                    if end == 0 {
+                        return Result::Err(String::from("expected a key"));
+                    }
+                    let (part, rest) = self.rest.split_at(end);
+                    self.rest = rest;
+                    part.to_string()
+                }
+            };
+            parts.push(part);
+            self.skip_spaces();
+            if !self.eat(".") {
+                return Result::Ok(parts.join("."));
+            }
+        }
+    }
+
+    /// Parses a value. Strings and booleans are returned, and other values are only checked to be
+    /// well formed.
+    fn value(&mut self) -> Result<Value, String> {
+        if self.eat("\"\"\"") {
+            return self.multi_line_string('"').map(Value::String);
+        }
+        if self.eat("'''") {
+            return self.multi_line_string('\'').map(Value::String);
+        }
+        match self.peek() {
+            Option::Some('"') => {
+                self.next();
+                self.basic_string().map(Value::String)
+            }
+            Option::Some('\'') => {
+                self.next();
+                self.literal_string().map(Value::String)
+            }
+            Option::Some('[') => {
+                self.next();
+                self.array().map(|_| Value::Other)
+            }
+            Option::Some('{') => {
+                self.next();
+                self.inline_table().map(|_| Value::Other)
+            }
+            _ => self.scalar(),
+        }
+    }
+
+    /// Parses the rest of a basic string, after the opening quote.
+    fn basic_string(&mut self) -> Result<String, String> {
+        let mut string = String::new();
+        loop {
+            match self.peek() {
+                Option::None | Option::Some('\n') => {
+                    return Result::Err(String::from("unterminated string"))
+                }
+                Option::Some('"') => {
+                    self.next();
+                    return Result::Ok(string);
+                }
+                Option::Some('\\') => {
+                    self.next();
+                    string.push(self.escape()?);
+                }
+                Option::Some(ch) => {
+                    self.next();
+                    string.push(ch);
+                }
+            }
+        }
+    }
+
+    /// Parses the rest of a literal string, after the opening quote.
+    fn literal_string(&mut self) -> Result<String, String> {
+        let line = self.rest_of_line();
+        let end = line
+            .find('\'')
+            .ok_or_else(|| String::from("unterminated string"))?;
+        self.rest = &self.rest[end + 1..];
+        Result::Ok(line[..end].to_string())
+    }
+
+    /// Parses the rest of a multi-line string, after the opening quotes. A newline right after
+    /// them is not part of the string.
+    fn multi_line_string(&mut self, quote: char) -> Result<String, String> {
+        let quotes = quote.to_string().repeat(3);
+        if self.eat("\r\n") || self.eat("\n") {
+            self.line += 1;
+        }
+        let mut string = String::new();
+        loop {
+            if self.rest.starts_with(&quotes) {
+                // Up to two quotes before the closing ones are part of the string.
+                let count = self.rest.len() - self.rest.trim_start_matches(quote).len();
+                let extra = count.min(5) - 3;
+                string.push_str(&quote.to_string().repeat(extra));
+                self.rest = &self.rest[extra + 3..];
+                return Result::Ok(string);
+            }
+            match self.next() {
+                Option::None => return Result::Err(String::from("unterminated string")),
+                Option::Some('\\') if quote == '"' => {
+                    // A backslash at the end of a line removes the whitespace which follows it.
+                    if self.rest_of_line().trim().is_empty() {
+                        while let Option::Some(' ')
+                        | Option::Some('\t')
+                        | Option::Some('\r')
+                        | Option::Some('\n') = self.peek()
+                        {
+                            self.next();
+                        }
+                    } else {
+                        string.push(self.escape()?);
+                    }
+                }
+                Option::Some(ch) => string.push(ch),
+            }
+        }
+    }
+
+    /// Parses an escape sequence in a basic string, after the backslash.
+    fn escape(&mut self) -> Result<char, String> {
+        let escaped = match self.next() {
+            Option::Some('t') => '\t',
+            Option::Some('n') => '\n',
+            Option::Some('r') => '\r',
+            Option::Some('b') => '\u{8}',
+            Option::Some('f') => '\u{c}',
+            Option::Some('"') => '"',
+            Option::Some('\\') => '\\',
+            Option::Some(kind @ 'u') | Option::Some(kind @ 'U') => {
+                let digits = if kind == 'u' { 4 } else { 8 };
+                let hex = self
+                    .rest
+                    .chars()
+                    .take(digits)
+                    .take_while(|ch| *ch != '\n')
+                    .collect::<String>();
+                self.rest = &self.rest[hex.len()..];
+                u32::from_str_radix(&hex, 16)
+                    .ok()
+                    .filter(|_| hex.len() == digits)
+                    .and_then(std::char::from_u32)
+                    .ok_or_else(|| format!("invalid escape \"\\{}{}\"", kind, hex))?
+            }
+            Option::Some(ch) => return Result::Err(format!("invalid escape \"\\{}\"", ch)),
+            Option::None => return Result::Err(String::from("unterminated string")),
+        };
+        Result::Ok(escaped)
+    }
+
+    /// Parses the rest of an array, after the opening bracket. Its values may be on several lines.
+    fn array(&mut self) -> Result<(), String> {
+        loop {
+            self.skip_blank();
+            if self.eat("]") {
+                return Result::Ok(());
+            }
+            if self.peek().is_none() {
+                return Result::Err(String::from("unterminated array"));
+            }
+            self.value()?;
+            self.skip_blank();
+            if !self.eat(",") {
+                return if self.eat("]") {
+                    Result::Ok(())
+                } else {
+                    Result::Err(String::from("expected \",\" or \"]\" after array value"))
+                };
+            }
+        }
+    }
+
+    /// Parses the rest of an inline table, after the opening brace. It must be on one line.
+    fn inline_table(&mut self) -> Result<(), String> {
+        self.skip_spaces();
+        if self.eat("}") {
+            return Result::Ok(());
+        }
+        loop {
+            self.key_value()?;
+            self.skip_spaces();
+            if self.eat("}") {
+                return Result::Ok(());
+            }
+            if !self.eat(",") {
+                return Result::Err(String::from(
+                    "expected \",\" or \"}\" after inline table value",
+                ));
+            }
+            self.skip_spaces();
+        }
+    }
+
+    /// Parses a boolean, or checks that the value is a number or date-time.
+    fn scalar(&mut self) -> Result<Value, String> {
+        let token_len = |text: &str| {
+            text.find(|ch: char| !(ch.is_ascii_alphanumeric() || "+-_.:".contains(ch)))
+                .unwrap_or(text.len())
+        };
+        let mut end = token_len(self.rest);
+        // A date may be followed by a time after a space, e.g. 1979-05-27 07:32:00.
+        if is_date(&self.rest[..end])
+            && self.rest[end..].starts_with(' ')
+            && self.rest[end + 1..].starts_with(|ch: char| ch.is_ascii_digit())
+        {
+            end += 1 + token_len(&self.rest[end + 1..]);
+        }
+        let token = &self.rest[..end];
+        let value = match token {
+            "" => return Result::Err(String::from("expected a value")),
+            "true" => Value::Boolean(true),
+            "false" => Value::Boolean(false),
+            _ if is_number_or_date(token) => Value::Other,
+            _ => return Result::Err(format!("invalid value \"{}\"", token)),
+        };
+        self.rest = &self.rest[end..];
+        Result::Ok(value)
+    }
+}
+
+/// Returns whether a value is a date, e.g. 1979-05-27.
+fn is_date(value: &str) -> bool {
+    value.len() == 10
+        && value.bytes().enumerate().all(|(index, byte)| match index {
+            4 | 7 => byte == b'-',
+            _ => byte.is_ascii_digit(),
+        })
+}
+
+/// Returns whether a value looks like a number, e.g. 1_000, -0.5e3, 0xff or inf, or a date-time.
+/// Such values are only ever ignored, so they are not checked any further.
+fn is_number_or_date(value: &str) -> bool {
+    let unsigned = value.trim_start_matches(['+', '-']);
+    unsigned.len() + 1 >= value.len()
+        && (unsigned.starts_with(|ch: char| ch.is_ascii_digit())
+            || unsigned == "inf"
+            || unsigned == "nan")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Parser, Value};
+    use crate::report::Kind;
+
+    #[test]
+    fn parse_config() {
+        assert_config("", Config::default());
+        assert_config(
+            "# Defaults\n\ndelimiter = ','\noutput_delimiter = \"\\t\" # tab\nline_buffered = true\n",
+            Config {
+                delimiter: Option::Some(String::from(",")),
+                output_delimiter: Option::Some(String::from("\t")),
+                line_buffered: Option::Some(true),
+                ..Config::default()
+            },
+        );
+        assert_config(
+            "  line_buffered=false  ",
+            Config {
+                line_buffered: Option::Some(false),
+                ..Config::default()
+            },
+        );
+
+        // Unknown keys produce warnings.
+        let (config, warnings) = super::parse_config("rut.toml", "color = \"auto\"\n").unwrap();
+        assert_eq!(config.path.as_deref(), Option::Some("rut.toml"));
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, Kind::UnknownConfigKey);
+        assert_eq!(warnings[0].line, Option::Some(1));
+        assert_eq!(
+            warnings[0].to_string(),
+            "rut.toml: line 1: unknown key \"color\", ignored"
+        );
+
+        // Errors include the line number.
+        assert_error(
+            "delimiter = ','\ndelimiter = ';'",
+            "rut.toml: line 2: duplicate key \"delimiter\"",
+        );
+        assert_error(
+            "\n\nline_buffered = \"yes\"",
+            "rut.toml: line 3: \"line_buffered\" must be true or false",
+        );
+        assert_error(
+            "delimiter = true",
+            "rut.toml: line 1: \"delimiter\" must be a string",
+        );
+        assert_error("delimiter", "rut.toml: line 1: expected \"key = value\"");
+        assert_error(
+            "delimiter = 1",
+            "rut.toml: line 1: \"delimiter\" must be a string",
+        );
+        assert_error(
+            "color = [\n  1,\n  2 3,\n]",
+            "rut.toml: line 3: expected \",\" or \"]\" after array value",
+        );
+        assert_error("color = auto", "rut.toml: line 1: invalid value \"auto\"");
+        assert_error(
+            "[rut",
+            "rut.toml: line 1: expected \"]\" after the table name",
+        );
+    }
+
+    #[test]
+    fn parse_config_unknown_values() {
+        // Unknown keys produce warnings whatever their values are, and keys in tables are not
+        // options.
+        let contents = concat!(
+            "level = 1\n",
+            "ratio = -0.5e3\n",
+            "colors = [\n  'red', # first\n  \"green\",\n]\n",
+            "point = { x = 1, y = [2] }\n",
+            "date = 1979-05-27 07:32:00Z\n",
+            "text = \"\"\"\nline 1\nline 2\"\"\"\n",
+            "delimiter = ','\n",
+            "[style]\n",
+            "delimiter = 1\n",
+            "[[themes]]\n",
+            "name.first = 'a'\n",
+        );
+        let (config, warnings) = super::parse_config("rut.toml", contents).unwrap();
+        assert_eq!(config.delimiter.as_deref(), Option::Some(","));
+        let warnings: Vec<_> = warnings.iter().map(|warning| warning.to_string()).collect();
+        assert_eq!(
+            warnings,
+            [
+                "rut.toml: line 1: unknown key \"level\", ignored",
+                "rut.toml: line 2: unknown key \"ratio\", ignored",
+                "rut.toml: line 3: unknown key \"colors\", ignored",
+                "rut.toml: line 7: unknown key \"point\", ignored",
+                "rut.toml: line 8: unknown key \"date\", ignored",
+                "rut.toml: line 9: unknown key \"text\", ignored",
+                "rut.toml: line 13: unknown table \"style\", ignored",
+                "rut.toml: line 15: unknown table \"themes\", ignored",
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_line() {
+        assert_line("a = \"b\"", "a", Value::String(String::from("b")));
+        assert_line(
+            "a-b_1 = 'c\\t'",
+            "a-b_1",
+            Value::String(String::from("c\\t")),
+        );
+        assert_line(
+            "a = \"\\u00e9\\U0001F600\\\"\"",
+            "a",
+            Value::String(String::from("é😀\"")),
+        );
+        assert_line("a = \"#\" # comment", "a", Value::String(String::from("#")));
+        assert_line("a = true", "a", Value::Boolean(true));
+
+        assert_line("a = 1", "a", Value::Other);
+        assert_line("a.'b c' = [1, [2]]", "a.b c", Value::Other);
+        assert_line("a = '''b\n'c'''", "a", Value::String(String::from("b\n'c")));
+        assert_line(
+            "a = \"\"\"\nb \\\n  c\"\"\"\"",
+            "a",
+            Value::String(String::from("b c\"")),
+        );
+
+        assert!(key_value("= 'a'").is_err());
+        assert!(key_value("a b = 'a'").is_err());
+        assert!(key_value("a = \"b").is_err());
+        assert!(key_value("a = 'b").is_err());
+        assert!(key_value("a = \"\\q\"").is_err());
+        assert!(key_value("a = \"\\u12\"").is_err());
+        assert!(key_value("a = b").is_err());
+        assert!(key_value("a = 1 2").is_err());
+        assert!(key_value("a = [1").is_err());
+        assert!(key_value("a = { b = 1").is_err());
+        assert!(key_value("a = '''b").is_err());
+        assert!(key_value("a = 'b' c").is_err());
+    }
+
+    fn assert_config(contents: &str, expected: Config) {
+        let (config, warnings) = super::parse_config("rut.toml", contents).unwrap();
+        let expected = Config {
+            path: Option::Some(String::from("rut.toml")),
+            ..expected
+        };
+        assert_eq!(config, expected);
+        assert!(warnings.is_empty());
+    }
+
+    fn assert_error(contents: &str, expected: &str) {
+        let err = super::parse_config("rut.toml", contents).err().unwrap();
+        assert_eq!(err.kind, Kind::InvalidConfig);
+        assert_eq!(err.to_string(), expected);
+    }
+
+    fn assert_line(line: &str, key: &str, value: Value) {
+        assert_eq!(key_value(line), Result::Ok((key.to_string(), value)));
+    }
+
+    /// Parses a `key = value` pair, which must be all of `line`.
+    fn key_value(line: &str) -> Result<(String, Value), String> {
+        let mut parser = Parser::new(line);
+        let pair = parser.key_value()?;
+        parser.end_of_line()?;
+        assert!(parser.peek().is_none());
+        Result::Ok(pair)
+    }
+}
@@ -1,284 +1,2350 @@
+//! Cutting records read from an input, and the options which control it.
+
 use crate::range::{MergedRange, Ranges};
-use regex::Regex;
-use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use crate::select::SelectIndices;
+use crate::template::Template;
+use memchr::memmem;
+#[cfg(feature = "regex")]
+use regex::bytes::Regex;
+#[cfg(feature = "regex")]
+use regex_syntax::hir::literal::Literals;
+#[cfg(feature = "regex")]
+use regex_syntax::ParserBuilder;
+use std::borrow::Cow;
+use std::io::{self, BufRead, IoSlice, Write};
+use std::iter::FusedIterator;
+use std::mem;
+use std::ops::{ControlFlow, Range};
 use std::result::Result;
 use std::vec::Vec;
 
+#[cfg(feature = "tokio")]
+mod async_io;
+mod between;
+mod split;
+mod terminator;
+
+use between::{BetweenSpans, Marker};
+pub use split::FieldSplitter;
+use terminator::{read_until_terminator, TerminatorSearch};
+
+/// Options which control how selected fields are written.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct FieldOptions {
+    /// Bytes used to join selected fields, which need not be valid UTF-8.
+    pub output_delimiter: Vec<u8>,
+    /// Whether to suppress lines which do not contain a field delimiter.
+    pub suppress: bool,
+    /// Whether to write the byte offsets of selected fields instead of their contents.
+    pub byte_offsets: bool,
+    /// Header row to write before the first record.
+    pub header: Option<HeaderOut>,
+    /// Template used to write fields instead of joining them with the output delimiter.
+    pub template: Option<Template>,
+    /// Sub-fields selected from each selected field, instead of writing the whole field.
+    pub sub_fields: Option<SubFields>,
+}
+
+/// A second level of field selection, which splits each selected field again and selects some of
+/// its sub-fields. A field without the sub-delimiter is written whole.
+///
+/// # Examples
+///
+/// ```
+/// use rut::cut::{FieldOptions, Mode, Options, RunState, SubFields};
+///
+/// let mut field_options = FieldOptions::default();
+/// field_options.output_delimiter = b",".to_vec();
+/// field_options.sub_fields = Option::Some(SubFields::new(";", "2".parse()?));
+/// let mode = Mode::FieldsChar("3".parse()?, ',', field_options);
+/// let (options, mut state) = (Options::default(), RunState::default());
+/// let mut output = Vec::new();
+/// mode.cut(&mut &b"10:11,host,a;b;c\n"[..], &mut output, &options, &mut state)?;
+/// assert_eq!(output, b"b\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct SubFields {
+    /// Delimiter which separates the sub-fields of each field, which must not be empty.
+    pub delimiter: String,
+    /// Sub-fields to select from each field.
+    pub ranges: Ranges,
+    /// Bytes used to join the selected sub-fields of each field.
+    pub output_delimiter: Vec<u8>,
+}
+
+impl SubFields {
+    /// Creates a selection of sub-fields separated by `delimiter`, which joins the selected
+    /// sub-fields with the same delimiter.
+    pub fn new(delimiter: &str, ranges: Ranges) -> SubFields {
+        SubFields {
+            delimiter: delimiter.to_string(),
+            ranges,
+            output_delimiter: delimiter.as_bytes().to_vec(),
+        }
+    }
+}
+
+/// Options which control how the text between markers is written.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct BetweenOptions {
+    /// Bytes used to join the text between each pair of markers, with `all_occurrences`.
+    pub output_delimiter: Vec<u8>,
+    /// Whether to suppress lines which do not contain both markers.
+    pub suppress: bool,
+    /// Whether to write the text between every pair of markers, rather than only the first.
+    pub all_occurrences: bool,
+}
+
+/// A header row naming the selected fields, written once before the first record.
+///
+/// If the selection is bounded, the header consists of exactly the given names. Otherwise, the
+/// number of selected fields is not known until a record is read, so the header is generated from
+/// the first written record: each selected field in that record is named using the given names, in
+/// order, falling back to a positional name (e.g. "f3") once the names run out.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HeaderOut {
+    names: Vec<String>,
+    bounded: bool,
+}
+
+impl HeaderOut {
+    /// Creates a header with exactly the specified names, for a bounded selection.
+    pub fn bounded(names: Vec<String>) -> HeaderOut {
+        HeaderOut {
+            names,
+            bounded: true,
+        }
+    }
+
+    /// Creates a header which is generated from the first record, for an unbounded selection.
+    pub fn unbounded(names: Vec<String>) -> HeaderOut {
+        HeaderOut {
+            names,
+            bounded: false,
+        }
+    }
+
+    /// Returns the positional name of the field at the specified (0-based) index.
+    pub fn positional_name(index: usize) -> String {
+        format!("f{}", index + 1)
+    }
+}
+
+/// Format used to write selected bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ByteFormat {
+    /// Write bytes as-is.
+    Raw,
+    /// Write each byte as two lowercase hexadecimal digits.
+    Hex(HexFormat),
+    /// Write the selected bytes of each record as base64.
+    Base64(Base64Alphabet),
+}
+
+/// Layout of hexadecimal output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HexFormat {
+    /// No separator between bytes, e.g. "0a1b".
+    Plain,
+    /// Bytes separated by a space, e.g. "0a 1b".
+    Spaced,
+    /// Bytes prefixed with "0x" and separated by a space, e.g. "0x0a 0x1b".
+    Prefixed,
+}
+
+/// Alphabet used for base64 output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// Standard alphabet (RFC 4648 section 4) with padding.
+    Standard,
+    /// URL and filename safe alphabet (RFC 4648 section 5) without padding.
+    Url,
+}
+
+/// Options shared by all modes.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub struct Options {
+    /// Bytes which terminate each input record, and each output record unless
+    /// `output_line_delimiter` is set. The default is a newline. If empty, the whole input is a
+    /// single record.
+    pub line_delimiter: Vec<u8>,
+    /// Bytes which terminate each output record, or `None` for the same bytes as `line_delimiter`.
+    pub output_line_delimiter: Option<Vec<u8>>,
+    /// Bytes written before each output record.
+    pub prefix: Vec<u8>,
+    /// Bytes written after each output record, before the line delimiter.
+    pub suffix: Vec<u8>,
+    /// Whether a carriage return before a newline line delimiter is kept as part of the record.
+    pub keep_cr: bool,
+    /// Whether output is flushed after each record.
+    pub line_buffered: bool,
+    /// Number of records to skip at the start of each input.
+    pub skip_lines: usize,
+    /// Maximum number of records to write, or `None` for no limit.
+    pub max_lines: Option<usize>,
+    /// Whether `max_lines` applies to each input separately, rather than to all inputs combined.
+    pub max_lines_per_file: bool,
+    /// How records which are not valid UTF-8 are handled, in character and field modes.
+    pub invalid_utf8: InvalidUtf8,
+}
+
+/// What has happened so far while cutting one or more inputs with the same options.
+///
+/// Cutting several inputs with the same state treats them as one: [`Options::max_lines`] limits
+/// the records written from all of them combined, unless [`Options::max_lines_per_file`] is set,
+/// and a header row is only written before the first record of the first input.
+#[derive(Clone, Debug, Default)]
+#[non_exhaustive]
+pub struct RunState {
+    /// Number of records written, counted towards [`Options::max_lines`].
+    pub written: usize,
+    /// Whether the header row has been written.
+    pub header_written: bool,
+    /// Records which were skipped since they were not valid UTF-8.
+    pub rejects: Vec<Reject>,
+}
+
+/// How records which are not valid UTF-8 are handled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InvalidUtf8 {
+    /// Stop processing the input with an error.
+    Fail,
+    /// Skip the record and continue with the next one.
+    Skip,
+    /// Replace invalid sequences with U+FFFD.
+    Lossy,
+}
+
+/// Error for a record which is not valid UTF-8, when such records are not skipped or replaced.
+#[derive(Debug)]
+pub struct InvalidUtf8Error {
+    /// Number of the record within its input, starting from one.
+    pub line: usize,
+    /// Byte offset of the start of the record within its input.
+    pub offset: u64,
+}
+
+impl std::fmt::Display for InvalidUtf8Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "line {} (byte {}): invalid UTF-8",
+            self.line, self.offset
+        )
+    }
+}
+
+impl std::error::Error for InvalidUtf8Error {}
+
+/// A record which was skipped since it was not valid UTF-8.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Reject {
+    /// Number of the record within its input, starting from one.
+    pub record: usize,
+    /// Byte offset of the start of the record within its input.
+    pub offset: u64,
+    /// Contents of the record, without the line delimiter.
+    pub bytes: Vec<u8>,
+}
+
+/// Position of the most recently read record within its input.
+#[derive(Debug, Default)]
+struct RecordPosition {
+    /// Number of the record, starting from one.
+    number: usize,
+    /// Byte offset of the start of the record.
+    offset: u64,
+    /// Byte offset of the start of the next record.
+    next_offset: u64,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Options {
+            line_delimiter: b"\n".to_vec(),
+            output_line_delimiter: Option::None,
+            prefix: Vec::new(),
+            suffix: Vec::new(),
+            keep_cr: false,
+            line_buffered: false,
+            skip_lines: 0,
+            max_lines: Option::None,
+            max_lines_per_file: false,
+            invalid_utf8: InvalidUtf8::Fail,
+        }
+    }
+}
+
+impl Options {
+    /// Returns the bytes which terminate each output record.
+    pub fn output_terminator(&self) -> &[u8] {
+        self.output_line_delimiter
+            .as_deref()
+            .unwrap_or(&self.line_delimiter)
+    }
+
+    /// Returns whether a carriage return before the line delimiter is removed from each record,
+    /// which is only the case for a newline line delimiter without `keep_cr`.
+    fn strip_cr(&self) -> bool {
+        self.line_delimiter == b"\n" && !self.keep_cr
+    }
+}
+
+impl RunState {
+    /// Returns whether `options.max_lines` records have been written.
+    pub fn limit_reached(&self, options: &Options) -> bool {
+        match options.max_lines {
+            Option::Some(max_lines) => self.written >= max_lines,
+            Option::None => false,
+        }
+    }
+
+    /// Prepares to cut the next input. Returns `false` if no more records should be written, in
+    /// which case the input does not need to be opened.
+    pub fn start_input(&mut self, options: &Options) -> bool {
+        if options.max_lines_per_file {
+            self.written = 0;
+        }
+        !self.limit_reached(options)
+    }
+
+    /// Counts an output record as written, towards `max_lines`.
+    fn record_written(&mut self) {
+        self.written += 1;
+    }
+}
+
+/// What to select from each record of the input, and how to write it.
+///
+/// # Examples
+///
+/// ```
+/// use rut::cut::{Mode, Options, RunState};
+/// use rut::range::Ranges;
+///
+/// let ranges: Ranges = "2-".parse()?;
+/// let (options, mut state) = (Options::default(), RunState::default());
+/// let mut output = Vec::new();
+/// Mode::characters(ranges).cut(&mut "αβγ\n".as_bytes(), &mut output, &options, &mut state)?;
+/// assert_eq!(String::from_utf8(output)?, "βγ\n");
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum Mode {
+    /// Select bytes, and write them in the given format.
+    Bytes(Ranges, ByteFormat),
+    /// Select characters. Records must be valid UTF-8, unless handled by [`Options::invalid_utf8`].
+    Characters(Ranges),
+    /// Select fields separated by a character.
+    FieldsChar(Ranges, char, FieldOptions),
+    /// Select fields separated by a literal string, such as a user-perceived character made of
+    /// several Unicode scalar values, e.g. a letter followed by a combining mark. Prefer
+    /// [`Mode::FieldsChar`] for a single `char`, which is faster to split.
+    FieldsStr(Ranges, String, FieldOptions),
+    /// Select fields separated by matches of a regular expression. Records are split as they are
+    /// read, so they do not need to be valid UTF-8, and [`Options::invalid_utf8`] does not apply.
+    /// Matches of the empty string are not delimiters. Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    FieldsRegex(Ranges, Regex, FieldOptions),
+    /// Select the text between the first occurrence of a start marker and the next occurrence of
+    /// an end marker after it, or nothing if either is missing.
+    Between(String, String, BetweenOptions),
+    /// Select the text between matches of regular expressions, as [`Mode::Between`] does. Records
+    /// are searched as they are read, as with [`Mode::FieldsRegex`], and empty matches are markers.
+    /// Requires the `regex` feature.
+    #[cfg(feature = "regex")]
+    BetweenRegex(Regex, Regex, BetweenOptions),
+}
+
+impl Mode {
+    /// Creates a mode which selects bytes and writes them as they are.
+    pub fn bytes(ranges: Ranges) -> Mode {
+        Mode::Bytes(ranges, ByteFormat::Raw)
+    }
+
+    /// Creates a mode which selects characters.
+    pub fn characters(ranges: Ranges) -> Mode {
+        Mode::Characters(ranges)
+    }
+
+    /// Creates a mode which selects fields separated by `delimiter`, and joins the selected fields
+    /// with the same delimiter.
+    pub fn fields(delimiter: char, ranges: Ranges) -> Mode {
+        let field_options = FieldOptions {
+            output_delimiter: delimiter.to_string().into_bytes(),
+            ..FieldOptions::default()
+        };
+        Mode::FieldsChar(ranges, delimiter, field_options)
+    }
+
+    /// Creates a mode which selects the text between the first occurrence of `start` and the next
+    /// occurrence of `end` after it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::cut::{Mode, Options, RunState};
+    ///
+    /// let (options, mut state) = (Options::default(), RunState::default());
+    /// let mut output = Vec::new();
+    /// let mode = Mode::between("user=", "&");
+    /// mode.cut(&mut &b"id=1&user=amy&x=2\nuser=bob\n"[..], &mut output, &options, &mut state)?;
+    /// assert_eq!(output, b"amy\n\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn between(start: &str, end: &str) -> Mode {
+        Mode::Between(
+            start.to_string(),
+            end.to_string(),
+            BetweenOptions::default(),
+        )
+    }
+
+    /// Cuts each record of `input` and writes the selected parts to `output`.
+    ///
+    /// The output is buffered, and is flushed before returning. Records which are not valid UTF-8
+    /// are reported as an error of kind [`io::ErrorKind::InvalidData`] wrapping an
+    /// [`InvalidUtf8Error`], unless handled otherwise by [`Options::invalid_utf8`]. The records
+    /// written, and any which are skipped, are added to `state`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::cut::{Mode, Options, RunState};
+    ///
+    /// let mut options = Options::default();
+    /// options.line_delimiter = b"\0".to_vec();
+    /// let mut state = RunState::default();
+    /// let mut output = Vec::new();
+    /// let mode = Mode::bytes("1-2".parse()?);
+    /// mode.cut(&mut &b"abc\0def\0"[..], &mut output, &options, &mut state)?;
+    /// assert_eq!(output, b"ab\0de\0");
+    /// assert_eq!(state.written, 2);
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn cut<R, W>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        options: &Options,
+        state: &mut RunState,
+    ) -> io::Result<()>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        self.cut_with(input, output, options, state, &mut Buffers::default())
+    }
+
+    /// Cuts each record of `input` and writes the selected parts to `output`, as [`Mode::cut`]
+    /// does, using `buffers` rather than allocating new ones.
+    ///
+    /// Reusing the same buffers to cut each of several inputs avoids allocating them again for
+    /// each input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::cut::{Buffers, Mode, Options, RunState};
+    ///
+    /// let mode = Mode::fields(',', "2".parse()?);
+    /// let (options, mut state) = (Options::default(), RunState::default());
+    /// let mut buffers = Buffers::default();
+    /// let mut output = Vec::new();
+    /// for input in [&b"a,b\n"[..], &b"c,d\n"[..]] {
+    ///     mode.cut_with(&mut &input[..], &mut output, &options, &mut state, &mut buffers)?;
+    /// }
+    /// assert_eq!(output, b"b\nd\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn cut_with<R, W>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        options: &Options,
+        state: &mut RunState,
+        buffers: &mut Buffers,
+    ) -> io::Result<()>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        match self {
+            Mode::Bytes(ranges, format) => {
+                cut_bytes(input, output, options, state, *format, ranges, buffers)
+            }
+            Mode::Characters(ranges) => {
+                cut_characters(input, output, options, state, ranges, buffers)
+            }
+            Mode::FieldsChar(ranges, delimiter, field_options) => cut_fields(
+                input,
+                output,
+                options,
+                state,
+                *delimiter,
+                field_options,
+                ranges,
+                buffers,
+            ),
+            Mode::FieldsStr(ranges, delimiter, field_options) => cut_fields(
+                input,
+                output,
+                options,
+                state,
+                delimiter.as_str(),
+                field_options,
+                ranges,
+                buffers,
+            ),
+            #[cfg(feature = "regex")]
+            Mode::FieldsRegex(ranges, delimiter, field_options) => cut_fields(
+                input,
+                output,
+                options,
+                state,
+                delimiter,
+                field_options,
+                ranges,
+                buffers,
+            ),
+            Mode::Between(start, end, between_options) => cut_between(
+                input,
+                output,
+                options,
+                state,
+                start.as_str(),
+                end.as_str(),
+                between_options,
+                buffers,
+            ),
+            #[cfg(feature = "regex")]
+            Mode::BetweenRegex(start, end, between_options) => cut_between(
+                input,
+                output,
+                options,
+                state,
+                start,
+                end,
+                between_options,
+                buffers,
+            ),
+        }
+    }
+
+    /// Cuts each record of `input`, and calls `visit` with each output record, instead of writing
+    /// it, until `visit` breaks.
+    ///
+    /// Each output record is as [`Mode::cut`] would write it, including [`Options::prefix`] and
+    /// [`Options::suffix`] but not the line delimiter, as with [`CutLines`]. A header row, if
+    /// there is one, is visited before the first record. Records which are suppressed, or skipped
+    /// since they are not valid UTF-8, are not visited. Returns the value `visit` broke with, if
+    /// it broke before the end of the input or [`Options::max_lines`] records.
+    ///
+    /// # Examples
+    ///
+    /// Finding the first record whose third field is "x", without reading any further:
+    ///
+    /// ```
+    /// use rut::cut::{Mode, Options, RunState};
+    /// use std::ops::ControlFlow;
+    ///
+    /// let mode = Mode::fields(',', "1,3".parse()?);
+    /// let (options, mut state) = (Options::default(), RunState::default());
+    /// let mut input = &b"a,b,c\nd,e,x\nf,g,x\n"[..];
+    /// let found = mode.visit(&mut input, &options, &mut state, |record| {
+    ///     if record.ends_with(b",x") {
+    ///         ControlFlow::Break(record.to_vec())
+    ///     } else {
+    ///         ControlFlow::Continue(())
+    ///     }
+    /// })?;
+    /// assert_eq!(found, ControlFlow::Break(b"d,x".to_vec()));
+    /// assert_eq!(input, b"f,g,x\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub fn visit<R, F, B>(
+        &self,
+        input: &mut R,
+        options: &Options,
+        state: &mut RunState,
+        mut visit: F,
+    ) -> io::Result<ControlFlow<B>>
+    where
+        R: BufRead,
+        F: FnMut(&[u8]) -> ControlFlow<B>,
+    {
+        let mut selector = self.selector(options);
+        RecordCutter::default().visit(input, options, state, &mut *selector, |record| {
+            Result::Ok(visit(record))
+        })
+    }
+
+    /// Returns whether cutting in this mode writes each input unchanged, apart from a line
+    /// delimiter added after the last record if the input does not end with one, so that inputs can
+    /// be copied to the output without being read.
+    ///
+    /// This is only the case when every byte is selected and the options neither skip nor change
+    /// any records. Characters and fields are never copied, since records must be checked to be
+    /// valid UTF-8.
+    pub fn copies_input(&self, options: &Options) -> bool {
+        match self {
+            Mode::Bytes(ranges, ByteFormat::Raw) => {
+                selects_all(ranges)
+                    && can_copy_records(options)
+                    && options.skip_lines == 0
+                    && !options.strip_cr()
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the selector which cuts records which have been read into memory in this mode.
+    fn selector<'a>(&'a self, options: &'a Options) -> Box<dyn Selector + Send + 'a> {
+        match self {
+            Mode::Bytes(ranges, format) => Box::new(ByteSelector {
+                ranges,
+                format: *format,
+            }),
+            Mode::Characters(ranges) => Box::new(CharSelector { ranges, options }),
+            Mode::FieldsChar(ranges, delimiter, field_options) => Box::new(FieldSelector {
+                ranges,
+                delimiter: *delimiter,
+                field_options,
+                options,
+                fields: Vec::new(),
+            }),
+            Mode::FieldsStr(ranges, delimiter, field_options) => Box::new(FieldSelector {
+                ranges,
+                delimiter: delimiter.as_str(),
+                field_options,
+                options,
+                fields: Vec::new(),
+            }),
+            #[cfg(feature = "regex")]
+            Mode::FieldsRegex(ranges, delimiter, field_options) => Box::new(FieldSelector {
+                ranges,
+                delimiter,
+                field_options,
+                options,
+                fields: Vec::new(),
+            }),
+            Mode::Between(start, end, between_options) => Box::new(BetweenSelector {
+                start: start.as_str(),
+                end: end.as_str(),
+                between_options,
+                options,
+            }),
+            #[cfg(feature = "regex")]
+            Mode::BetweenRegex(start, end, between_options) => Box::new(BetweenSelector {
+                start,
+                end,
+                between_options,
+                options,
+            }),
+        }
+    }
+}
+
+/// Iterator over the output records of cutting an input, as an alternative to writing them with
+/// [`Mode::cut`].
+///
+/// Each item is an output record as [`Mode::cut`] would write it, including [`Options::prefix`]
+/// and [`Options::suffix`] but not the line delimiter. A header row, if there is one, is returned
+/// before the first record. Records which are suppressed, or skipped since they are not valid
+/// UTF-8, are not returned, and the iterator ends after an error.
+///
+/// Unlike [`Mode::cut`], each record is read into memory, including in byte mode.
+///
+/// # Examples
+///
+/// ```
+/// use rut::cut::{CutLines, Mode, Options};
+///
+/// let mode = Mode::fields(',', "2".parse()?);
+/// let mut options = Options::default();
+/// options.max_lines = Option::Some(2);
+/// let lines = CutLines::new(&b"a,b\nc,d\ne,f\n"[..], mode, options);
+/// for (record, expected) in lines.zip(["b", "d"]) {
+///     assert_eq!(record?, expected.as_bytes());
+/// }
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct CutLines<R> {
+    reader: R,
+    mode: Mode,
+    options: Options,
+    state: RunState,
+    cutter: RecordCutter,
+    /// Whether the end of the input, the maximum number of records, or an error has been reached.
+    finished: bool,
+}
+
+impl<R: BufRead> CutLines<R> {
+    /// Creates an iterator over the output records of cutting `reader` in the specified mode.
+    pub fn new(reader: R, mode: Mode, options: Options) -> CutLines<R> {
+        CutLines {
+            reader,
+            mode,
+            options,
+            state: RunState::default(),
+            cutter: RecordCutter::default(),
+            finished: false,
+        }
+    }
+
+    /// Returns the state of cutting so far, e.g. to check the records which were skipped with
+    /// [`InvalidUtf8::Skip`].
+    pub fn state(&self) -> &RunState {
+        &self.state
+    }
+}
+
+impl<R: BufRead> Iterator for CutLines<R> {
+    type Item = io::Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return Option::None;
+        }
+        let mut selector = self.mode.selector(&self.options);
+        let next = self.cutter.visit(
+            &mut self.reader,
+            &self.options,
+            &mut self.state,
+            &mut *selector,
+            |record| Result::Ok(ControlFlow::Break(record.to_vec())),
+        );
+        match next {
+            Result::Ok(ControlFlow::Break(record)) => Option::Some(Result::Ok(record)),
+            Result::Ok(ControlFlow::Continue(())) => {
+                self.finished = true;
+                Option::None
+            }
+            Result::Err(error) => {
+                self.finished = true;
+                Option::Some(Result::Err(error))
+            }
+        }
+    }
+}
+
+impl<R: BufRead> FusedIterator for CutLines<R> {}
+
+/// Returns the characters of `line` which are selected by `ranges`.
+///
+/// The result borrows from `line` when the selected characters are contiguous.
+///
+/// # Examples
+///
+/// ```
+/// use rut::cut::cut_line_chars;
+///
+/// assert_eq!(cut_line_chars("αβγδ", &"2-3".parse()?), "βγ");
+/// assert_eq!(cut_line_chars("αβγδ", &"1,4".parse()?), "αδ");
+/// # Ok::<(), rut::range::ParseRangesError>(())
+/// ```
+pub fn cut_line_chars<'a>(line: &'a str, ranges: &Ranges) -> Cow<'a, str> {
+    let mut spans = selected_char_spans(line, ranges);
+    match (spans.next(), spans.next()) {
+        (Option::None, _) => Cow::Borrowed(""),
+        (Option::Some(span), Option::None) => Cow::Borrowed(&line[span]),
+        (Option::Some(first), Option::Some(second)) => {
+            let mut selected = String::from(&line[first]);
+            selected.push_str(&line[second]);
+            selected.extend(spans.map(|span| &line[span]));
+            Cow::Owned(selected)
+        }
+    }
+}
+
+/// Returns the fields of `line`, separated by `delimiter`, which are selected by `ranges`, joined
+/// by `output_delimiter`.
+///
+/// The delimiter is any [`FieldSplitter`], such as a `char` or a `&str`. As when cutting fields
+/// from an input, a line which does not contain the delimiter is returned whole. The result borrows
+/// from `line` when it is the whole line, a single field, or fields which are already separated by
+/// the output delimiter in the line.
+///
+/// # Examples
+///
+/// ```
+/// use rut::cut::cut_line_fields;
+///
+/// assert_eq!(cut_line_fields("a,b,c,d", ',', &"2-3".parse()?, ","), "b,c");
+/// assert_eq!(cut_line_fields("a,b,c,d", ',', &"1,4".parse()?, "\t"), "a\td");
+/// assert_eq!(cut_line_fields("abcd", ',', &"2".parse()?, ","), "abcd");
+/// assert_eq!(cut_line_fields("a, b, c", ", ", &"3,1".parse()?, ","), "a,c");
+/// # Ok::<(), rut::range::ParseRangesError>(())
+/// ```
+pub fn cut_line_fields<'a, S: FieldSplitter>(
+    line: &'a str,
+    delimiter: S,
+    ranges: &Ranges,
+    output_delimiter: &str,
+) -> Cow<'a, str> {
+    if !delimiter.matches(line) {
+        return Cow::Borrowed(line);
+    }
+    let spans: Vec<(usize, usize)> = SelectIndices::new(
+        FieldSpans::new(delimiter.delimiters(line), line.len()),
+        ranges,
+    )
+    .collect();
+
+    let separated_by_output_delimiter = || {
+        spans
+            .windows(2)
+            .all(|pair| &line[pair[0].1..pair[1].0] == output_delimiter)
+    };
+    match spans.as_slice() {
+        [] => Cow::Borrowed(""),
+        [(start, end)] => Cow::Borrowed(&line[*start..*end]),
+        [(start, _), .., (_, end)] if separated_by_output_delimiter() => {
+            Cow::Borrowed(&line[*start..*end])
+        }
+        _ => Cow::Owned(
+            spans
+                .iter()
+                .map(|&(start, end)| &line[start..end])
+                .collect::<Vec<&str>>()
+                .join(output_delimiter),
+        ),
+    }
+}
+
+/// Returns the bytes of `record` which are selected by `ranges`.
+///
+/// The result borrows from `record` when the selected bytes are contiguous.
+///
+/// # Examples
+///
+/// ```
+/// use rut::cut::cut_record_bytes;
+///
+/// assert_eq!(cut_record_bytes(b"abcdef", &"2-3".parse()?), &b"bc"[..]);
+/// assert_eq!(cut_record_bytes(b"abcdef", &"1,5-".parse()?), &b"aef"[..]);
+/// # Ok::<(), rut::range::ParseRangesError>(())
+/// ```
+pub fn cut_record_bytes<'a>(record: &'a [u8], ranges: &Ranges) -> Cow<'a, [u8]> {
+    match single_span(ranges, record.len()) {
+        Option::Some(span) => Cow::Borrowed(&record[span]),
+        Option::None => Cow::Owned(select(record, ranges)),
+    }
+}
+
+/// Returns whether every match of a regex delimiter contains the line delimiter. Records are split
+/// on the line delimiter before fields, so such a regex could never separate fields.
+///
+/// A regex which may also match other text, such as `[;,]` with a `;` line delimiter, is not
+/// rejected by this, even though the matches of the line delimiter itself are never used.
+///
+/// # Examples
+///
+/// ```
+/// use regex::bytes::Regex;
+/// use rut::cut::regex_needs_line_delimiter;
+///
+/// assert!(regex_needs_line_delimiter(&Regex::new(r"\n")?, b"\n"));
+/// assert!(regex_needs_line_delimiter(&Regex::new(r"a\r\n|\r\nb")?, b"\r\n"));
+/// assert!(!regex_needs_line_delimiter(&Regex::new(r"\s+")?, b"\n"));
+/// assert!(!regex_needs_line_delimiter(&Regex::new("[;,]")?, b";"));
+/// # Ok::<(), regex::Error>(())
+/// ```
+#[cfg(feature = "regex")]
+pub fn regex_needs_line_delimiter(regex: &Regex, line_delimiter: &[u8]) -> bool {
+    // A regex matches only a finite set of strings when all of its literal prefixes are complete.
+    let hir = match ParserBuilder::new()
+        .allow_invalid_utf8(true)
+        .build()
+        .parse(regex.as_str())
+    {
+        Result::Ok(hir) => hir,
+        Result::Err(_) => return false,
+    };
+    let prefixes = Literals::prefixes(&hir);
+    prefixes.all_complete()
+        && prefixes
+            .literals()
+            .iter()
+            .all(|literal| memmem::find(literal, line_delimiter).is_some())
+}
+
 /// Selects bytes from the input, based on the specified ranges, and writes it to the output.
-pub(crate) fn cut_bytes<R, W>(
+///
+/// Records are streamed rather than read into memory, so memory use does not depend on the length
+/// of each record.
+fn cut_bytes<R, W>(
     input: &mut R,
     output: &mut W,
-    line_delimiter: u8,
+    options: &Options,
+    state: &mut RunState,
+    format: ByteFormat,
     ranges: &Ranges,
+    buffers: &mut Buffers,
 ) -> io::Result<()>
 where
-    R: Read,
+    R: BufRead,
     W: Write,
 {
-    let mut reader = BufReader::new(input);
-    let mut writer = BufWriter::new(output);
-    let mut buf = Vec::new();
+    let mut writer = StagedWriter::new(output, &mut buffers.staged);
+    let mut position = RecordPosition::default();
+    skip_records(input, options, &mut position)?;
+    let copying = format == ByteFormat::Raw && selects_all(ranges) && can_copy_records(options);
 
-    while reader.read_until(line_delimiter, &mut buf)? > 0 {
-        if buf.ends_with(&[line_delimiter]) {
-            buf.pop();
+    let mut search = TerminatorSearch::new(&options.line_delimiter);
+    let mut selection = ByteSelection::new(ranges, format);
+    let prefix = match format {
+        ByteFormat::Raw => prefix_len(ranges),
+        _ => Option::None,
+    };
+    loop {
+        // Records are copied until one does not end within the reader's buffer, which is streamed
+        // as usual before copying again.
+        if copying {
+            copy_records(input, &mut writer, options, state, false, &mut position)?;
+        }
+        if state.limit_reached(options) || input.fill_buf()?.is_empty() {
+            break;
         }
+        writer.write_all(&options.prefix)?;
+        let truncated = match prefix {
+            Option::Some(len) => truncate_record(input, &mut writer, options, &mut search, len)?,
+            Option::None => false,
+        };
+        if !truncated {
+            stream_record(input, &mut writer, options, &mut search, &mut selection)?;
+        }
+        finish_record(&mut writer, options, state)?;
+    }
+    writer.flush()
+}
 
-        let bytes = select(&buf, ranges.clone());
-        writer.write_all(&bytes)?;
-        writer.write_all(&[line_delimiter])?;
+/// Returns the number of bytes selected from the start of each record, if the ranges select only
+/// those, as in `-b1-80`.
+fn prefix_len(ranges: &Ranges) -> Option<usize> {
+    match ranges.as_slice() {
+        [MergedRange::Closed(0, end)] => Option::Some(end.saturating_add(1)),
+        _ => Option::None,
+    }
+}
 
-        buf.clear();
+/// Writes up to `len` bytes from the start of the next record, if the whole record is in the
+/// reader's buffer, and skips the rest of it. Returns `false`, without consuming anything, if the
+/// record does not end within the buffer, in which case it must be streamed instead.
+fn truncate_record<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    options: &Options,
+    search: &mut TerminatorSearch,
+    len: usize,
+) -> io::Result<bool> {
+    let chunk = reader.fill_buf()?;
+    let end = match search.find(chunk) {
+        Option::Some(end) => end,
+        Option::None => {
+            search.reset();
+            return Result::Ok(false);
+        }
+    };
+    let mut record = &chunk[..end - options.line_delimiter.len()];
+    if options.strip_cr() {
+        record = record.strip_suffix(b"\r").unwrap_or(record);
+    }
+    writer.write_all(&record[..len.min(record.len())])?;
+    reader.consume(end);
+    Result::Ok(true)
+}
+
+/// Reads the next record and writes the selected bytes to the writer as they are read. The line
+/// delimiter is removed as in `read_record`.
+fn stream_record<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    options: &Options,
+    search: &mut TerminatorSearch,
+    selection: &mut ByteSelection,
+) -> io::Result<()> {
+    let terminator = &options.line_delimiter[..];
+    let mut content = RecordContent {
+        strip_cr: options.strip_cr(),
+        pending_cr: false,
+    };
+    loop {
+        let chunk = reader.fill_buf()?;
+        if chunk.is_empty() {
+            break;
+        }
+        // Bytes which may have been the start of the line delimiter are held back until the rest
+        // of it is found, or not.
+        let held = search.matched();
+        let (consumed, end) = match search.find(chunk) {
+            Option::Some(i) => (i, true),
+            Option::None => (chunk.len(), false),
+        };
+        let still_held = if end {
+            terminator.len()
+        } else {
+            search.matched()
+        };
+        // Number of bytes, out of those held back followed by the consumed part of the chunk,
+        // which are part of the record.
+        let len = held + consumed - still_held;
+        if len <= held {
+            content.write(selection, writer, &terminator[..len])?;
+        } else {
+            content.write(selection, writer, &terminator[..held])?;
+            content.write(selection, writer, &chunk[..len - held])?;
+        }
+        reader.consume(consumed);
+        if end {
+            content.pending_cr = false;
+            break;
+        }
+    }
+
+    // The input ended with part of the line delimiter, which is part of the record. The carriage
+    // return is only removed from records which end with the line delimiter.
+    content.write(selection, writer, &terminator[..search.matched()])?;
+    search.reset();
+    if content.pending_cr {
+        selection.write(writer, b"\r")?;
+    }
+    selection.finish(writer)
+}
+
+/// Writes the content of a record which is streamed, removing a carriage return before the line
+/// delimiter if needed.
+struct RecordContent {
+    strip_cr: bool,
+    /// Whether the content written so far ends with a carriage return which has not been written.
+    /// It cannot be selected until the next part is read, since it is removed if the line delimiter
+    /// comes next.
+    pending_cr: bool,
+}
+
+impl RecordContent {
+    fn write<W: Write>(
+        &mut self,
+        selection: &mut ByteSelection,
+        writer: &mut W,
+        content: &[u8],
+    ) -> io::Result<()> {
+        if content.is_empty() {
+            return Result::Ok(());
+        }
+        if self.pending_cr {
+            selection.write(writer, b"\r")?;
+        }
+        self.pending_cr = self.strip_cr && content.ends_with(b"\r");
+        let content = if self.pending_cr {
+            &content[..content.len() - 1]
+        } else {
+            content
+        };
+        selection.write(writer, content)
+    }
+}
+
+/// Selects bytes from a record as it is streamed, and writes them in the specified format.
+struct ByteSelection<'a> {
+    ranges: &'a [MergedRange],
+    format: ByteFormat,
+    /// Index of the first range which may contain the next byte of the record.
+    range: usize,
+    /// Index of the next byte of the record.
+    index: usize,
+    /// Number of bytes of the record which have been selected.
+    selected: usize,
+    /// Selected bytes which have not been written since they do not make up a whole base64 group.
+    partial: Vec<u8>,
+}
+
+impl<'a> ByteSelection<'a> {
+    fn new(ranges: &'a Ranges, format: ByteFormat) -> ByteSelection<'a> {
+        ByteSelection {
+            ranges: &ranges.ranges,
+            format,
+            range: 0,
+            index: 0,
+            selected: 0,
+            partial: Vec::new(),
+        }
+    }
+
+    /// Writes the selected bytes from the next part of the record.
+    fn write<W: Write>(&mut self, writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+        let start = self.index;
+        let end = start + bytes.len();
+        self.index = end;
+
+        // Ranges are sorted and do not overlap, so only ranges from the current one onwards can
+        // contain any of the bytes.
+        while let Option::Some(range) = self.ranges.get(self.range) {
+            let (range_start, range_end) = match *range {
+                MergedRange::Closed(range_start, range_end) => {
+                    (range_start, range_end.saturating_add(1))
+                }
+                MergedRange::ToEnd(range_start) => (range_start, usize::MAX),
+            };
+            if range_start >= end {
+                break;
+            }
+            if range_end > start {
+                let from = range_start.max(start) - start;
+                let to = range_end.min(end) - start;
+                self.emit(writer, &bytes[from..to])?;
+            }
+            // The range may continue into the next part of the record.
+            if range_end > end {
+                break;
+            }
+            self.range += 1;
+        }
+        Result::Ok(())
+    }
+
+    fn emit<W: Write>(&mut self, writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+        let first = self.selected == 0;
+        self.selected += bytes.len();
+        match self.format {
+            ByteFormat::Raw => writer.write_all(bytes),
+            ByteFormat::Hex(hex_format) => write_hex(writer, bytes, hex_format, first),
+            ByteFormat::Base64(alphabet) => {
+                let mut bytes = bytes;
+                if !self.partial.is_empty() {
+                    let n = (3 - self.partial.len()).min(bytes.len());
+                    self.partial.extend_from_slice(&bytes[..n]);
+                    bytes = &bytes[n..];
+                    if self.partial.len() < 3 {
+                        return Result::Ok(());
+                    }
+                    write_base64(writer, &self.partial, alphabet)?;
+                    self.partial.clear();
+                }
+                let whole = bytes.len() - bytes.len() % 3;
+                write_base64(writer, &bytes[..whole], alphabet)?;
+                self.partial.extend_from_slice(&bytes[whole..]);
+                Result::Ok(())
+            }
+        }
+    }
+
+    /// Writes any remaining output for the record, and prepares to select from the next record.
+    fn finish<W: Write>(&mut self, writer: &mut W) -> io::Result<()> {
+        if let ByteFormat::Base64(alphabet) = self.format {
+            write_base64(writer, &self.partial, alphabet)?;
+            self.partial.clear();
+        }
+        self.range = 0;
+        self.index = 0;
+        self.selected = 0;
+        Result::Ok(())
     }
-    Result::Ok(())
 }
 
 /// Selects characters from the input, based on the specified ranges, and writes it to the output.
-pub(crate) fn cut_characters<R, W>(
+fn cut_characters<R, W>(
     input: &mut R,
     output: &mut W,
-    line_delimiter: u8,
+    options: &Options,
+    state: &mut RunState,
     ranges: &Ranges,
+    buffers: &mut Buffers,
 ) -> io::Result<()>
 where
-    R: Read,
+    R: BufRead,
     W: Write,
 {
-    let mut reader = BufReader::new(input);
-    let mut writer = BufWriter::new(output);
-    let mut buf = Vec::new();
-
-    while reader.read_until(line_delimiter, &mut buf)? > 0 {
-        if buf.ends_with(&[line_delimiter]) {
-            buf.pop();
-        }
-
-        let line = string_from_utf8(buf.to_owned())?;
-        let selected = select(&line.chars().collect::<Vec<char>>(), ranges.clone());
-        let str = selected.iter().collect::<String>();
-        writer.write_all(&str.as_bytes())?;
-        writer.write_all(&[line_delimiter])?;
-
-        buf.clear();
-    }
-    Result::Ok(())
+    let mut selector = CharSelector { ranges, options };
+    cut_records(input, output, options, state, &mut selector, buffers)
 }
 
-/// Splits and selects fields separated by a delimiter character. Rejoins fields using the delimiter
+/// Splits and selects fields separated by a delimiter. Rejoins fields using the output delimiter
 /// then writes the selected fields to the output.
-pub(crate) fn cut_fields_with_char<R, W>(
+#[allow(clippy::too_many_arguments)]
+fn cut_fields<S, R, W>(
     input: &mut R,
     output: &mut W,
-    line_delimiter: u8,
-    field_delimiter: char,
-    output_delimiter: &str,
-    suppress: bool,
+    options: &Options,
+    state: &mut RunState,
+    field_delimiter: S,
+    field_options: &FieldOptions,
     ranges: &Ranges,
+    buffers: &mut Buffers,
 ) -> io::Result<()>
 where
-    R: Read,
+    for<'a> FieldSelector<'a, S>: Selector,
+    R: BufRead,
     W: Write,
 {
-    let mut reader = BufReader::new(input);
-    let mut writer = BufWriter::new(output);
-    let mut buf = Vec::new();
-
-    while reader.read_until(line_delimiter, &mut buf)? > 0 {
-        if buf.ends_with(&[line_delimiter]) {
-            buf.pop();
-        }
-        let line = string_from_utf8(buf.to_owned())?;
-
-        if line.contains(field_delimiter) {
-            let fields = line.split(field_delimiter).map(|s| s.to_string());
-            let bytes: Vec<u8> = RangeFilterIterator::new(fields, ranges.clone())
-                .collect::<Vec<String>>()
-                .join(output_delimiter)
-                .bytes()
-                .collect();
-            writer.write_all(&bytes)?;
-            writer.write_all(&[line_delimiter])?;
-        } else if !suppress {
-            writer.write_all(line.as_bytes())?;
-            writer.write_all(&[line_delimiter])?;
-        }
-        buf.clear();
-    }
-
-    Result::Ok(())
+    let mut selector = FieldSelector {
+        ranges,
+        delimiter: field_delimiter,
+        field_options,
+        options,
+        fields: mem::take(&mut buffers.fields),
+    };
+    let result = cut_records(input, output, options, state, &mut selector, buffers);
+    buffers.fields = selector.fields;
+    result
 }
 
-/// Splits and selects fields separated by regex delimiter. Rejoins fields using a specified
-/// "joiner" string then writes the selected fields to the output.
-pub(crate) fn cut_fields_with_regex<R, W>(
+/// Selects the text between a start and an end marker from each record, and writes it to the
+/// output.
+#[allow(clippy::too_many_arguments)]
+fn cut_between<M, R, W>(
     input: &mut R,
     output: &mut W,
-    line_delimiter: u8,
-    field_delimiter: &Regex,
-    output_delimiter: &str,
-    suppress: bool,
-    ranges: &Ranges,
+    options: &Options,
+    state: &mut RunState,
+    start: M,
+    end: M,
+    between_options: &BetweenOptions,
+    buffers: &mut Buffers,
 ) -> io::Result<()>
 where
-    R: Read,
+    for<'a> BetweenSelector<'a, M>: Selector,
+    R: BufRead,
     W: Write,
 {
-    let mut reader = BufReader::new(input);
-    let mut writer = BufWriter::new(output);
-    let mut buf = Vec::new();
+    let mut selector = BetweenSelector {
+        start,
+        end,
+        between_options,
+        options,
+    };
+    cut_records(input, output, options, state, &mut selector, buffers)
+}
 
-    while reader.read_until(line_delimiter, &mut buf)? > 0 {
-        if buf.ends_with(&[line_delimiter]) {
-            buf.pop();
+/// Reads each record of the input into memory, cuts it with `selector`, and writes the output.
+fn cut_records<R, W, L>(
+    input: &mut R,
+    output: &mut W,
+    options: &Options,
+    state: &mut RunState,
+    selector: &mut L,
+    buffers: &mut Buffers,
+) -> io::Result<()>
+where
+    R: BufRead,
+    W: Write,
+    L: Selector + ?Sized,
+{
+    let mut staged = mem::take(&mut buffers.staged);
+    let mut cutter = RecordCutter {
+        buf: mem::take(&mut buffers.record),
+        output: mem::take(&mut buffers.output),
+        terminated: true,
+        ..RecordCutter::default()
+    };
+    let mut writer = StagedWriter::new(output, &mut staged);
+    let copying = selector.copies_records() && can_copy_records(options);
+    let result = loop {
+        // Records are copied until one does not end within the reader's buffer, or is not valid
+        // UTF-8, which is cut as usual before copying again.
+        if copying {
+            if let Result::Err(error) = cutter.copy_records(input, &mut writer, options, state) {
+                break Result::Err(error);
+            }
+        }
+        // Each record is written with its line delimiter at once.
+        let write = |record: &[u8]| {
+            writer.write_all(record)?;
+            if options.line_buffered {
+                writer.flush()?;
+            }
+            Result::Ok(if copying {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            })
+        };
+        match cutter.visit(input, options, state, selector, write) {
+            Result::Ok(ControlFlow::Break(())) => continue,
+            Result::Ok(ControlFlow::Continue(())) => break writer.flush(),
+            Result::Err(error) => break Result::Err(error),
         }
-        let line = string_from_utf8(buf.to_owned())?;
+    };
+    drop(writer);
 
-        if field_delimiter.is_match(&line) {
-            let fields = field_delimiter.split(&line).map(|s| s.to_string());
-            let bytes: Vec<u8> = RangeFilterIterator::new(fields, ranges.clone())
-                .collect::<Vec<String>>()
-                .join(&output_delimiter.to_string())
-                .bytes()
-                .collect();
-            writer.write_all(&bytes)?;
-            writer.write_all(&[line_delimiter])?;
-        } else if !suppress {
-            writer.write_all(line.as_bytes())?;
-            writer.write_all(&[line_delimiter])?;
+    buffers.record = cutter.buf;
+    buffers.output = cutter.output;
+    buffers.staged = staged;
+    result
+}
+
+/// Buffers used to cut an input, which can be reused to cut other inputs with [`Mode::cut_with`]
+/// instead of being allocated for each one.
+#[derive(Clone, Debug, Default)]
+pub struct Buffers {
+    /// Buffer which each record is read into.
+    record: Vec<u8>,
+    /// Buffer which each output record is written to.
+    output: Vec<u8>,
+    /// Byte spans of the fields of a record which are written with a template.
+    fields: Vec<(usize, usize)>,
+    /// Output which has not been written to the output of the input being cut yet.
+    staged: Vec<u8>,
+}
+
+/// Capacity of the buffer which output is staged in before it is written. Larger buffers made
+/// little difference in benchmarks, and this is the default capacity of a pipe on Linux.
+pub(crate) const STAGED_CAPACITY: usize = 64 * 1024;
+
+/// Length from which a part of the output is written along with the staged output, with a vectored
+/// write, rather than copied into the staging buffer first.
+const VECTORED_LEN: usize = 16 * 1024;
+
+/// Writes all of `bufs`, as `write_all` does for a single buffer. Writers which do not support
+/// vectored writes write one buffer at a time.
+fn write_all_vectored<W: Write>(writer: &mut W, mut bufs: &mut [IoSlice]) -> io::Result<()> {
+    IoSlice::advance_slices(&mut bufs, 0);
+    while !bufs.is_empty() {
+        match writer.write_vectored(bufs) {
+            Result::Ok(0) => {
+                return Result::Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole buffer",
+                ))
+            }
+            Result::Ok(n) => IoSlice::advance_slices(&mut bufs, n),
+            Result::Err(error) if error.kind() == io::ErrorKind::Interrupted => {}
+            Result::Err(error) => return Result::Err(error),
         }
-        buf.clear();
     }
-
     Result::Ok(())
 }
 
-fn string_from_utf8(bytes: Vec<u8>) -> io::Result<String> {
-    String::from_utf8(bytes)
-        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Input was not valid UTF-8"))
+/// Buffers output in a borrowed buffer, as a `BufWriter` does in one which it allocates, so that
+/// the buffer can be reused. Staged output is written when the buffer is full, when flushed, and
+/// when dropped. Large parts of the output are not copied, but written with the staged output.
+struct StagedWriter<'a, W: Write> {
+    inner: W,
+    staged: &'a mut Vec<u8>,
 }
 
-struct RangeFilterIterator<I> {
-    inner: I,
-    /// Index of next element of `inner`.
-    next_index: usize,
-    ranges: Box<dyn Iterator<Item = MergedRange>>,
-    current_range: Option<MergedRange>,
+impl<'a, W: Write> StagedWriter<'a, W> {
+    fn new(inner: W, staged: &'a mut Vec<u8>) -> StagedWriter<'a, W> {
+        staged.clear();
+        staged.reserve(STAGED_CAPACITY);
+        StagedWriter { inner, staged }
+    }
+
+    /// Writes the staged output to the inner writer.
+    fn write_staged(&mut self) -> io::Result<()> {
+        let result = self.inner.write_all(self.staged);
+        self.staged.clear();
+        result
+    }
 }
 
-impl<'a, I: Iterator<Item = T>, T> RangeFilterIterator<I> {
-    fn new<Into: IntoIterator<Item = T, IntoIter = I>>(into_inner: Into, ranges: Ranges) -> Self {
-        let mut ranges = Box::new(ranges.into_iter());
-        let current_range = ranges.next();
-        RangeFilterIterator {
-            inner: into_inner.into_iter(),
-            next_index: 0,
-            ranges,
-            current_range,
+impl<W: Write> Write for StagedWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() >= VECTORED_LEN {
+            let mut bufs = [IoSlice::new(self.staged), IoSlice::new(buf)];
+            let result = write_all_vectored(&mut self.inner, &mut bufs);
+            self.staged.clear();
+            return result.map(|()| buf.len());
+        }
+        if self.staged.len() + buf.len() > STAGED_CAPACITY {
+            self.write_staged()?;
         }
+        self.staged.extend_from_slice(buf);
+        Result::Ok(buf.len())
     }
 
-    fn advance_inner(&mut self, until: usize) {
-        while self.next_index < until {
-            self.next_index += 1;
-            self.inner.next();
-        }
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_staged()?;
+        self.inner.flush()
     }
 }
 
-impl<'a, I: Iterator<Item = T>, T> Iterator for RangeFilterIterator<I> {
-    type Item = T;
-    fn next(&mut self) -> Option<Self::Item> {
-        match self.current_range {
-            Some(MergedRange::Closed(start, end)) => {
-                self.advance_inner(start);
-
-                // Return inner.next() if we are still withing the current range.
-                if self.next_index <= end {
-                    self.next_index += 1;
-                    self.inner.next()
-                }
-                // Update current range then try again.
-                else {
-                    self.current_range = self.ranges.next();
-                    self.next()
-                }
-            }
-            Some(MergedRange::ToEnd(start)) => {
-                // Since ranges are sorted, there can be nothing after a ToEnd range.
-                // Advance inner if necessary, then return all remaining elements.
-                self.advance_inner(start);
-                self.inner.next()
-            }
-            None => Option::None,
-        }
+impl<W: Write> Drop for StagedWriter<'_, W> {
+    fn drop(&mut self) {
+        // As with a `BufWriter`, errors are ignored when dropped rather than flushed.
+        let _ = self.write_staged();
     }
 }
 
-fn select<T: Clone>(input: &[T], ranges: Ranges) -> Vec<T> {
-    let mut result = Vec::new();
+/// Reads records from an input and cuts them, keeping its place between calls so that cutting can
+/// stop and resume. Writing with [`Mode::cut`], other than streamed bytes, iterating with
+/// [`CutLines`] and visiting with [`Mode::visit`] are all built on this.
+#[derive(Debug, Default)]
+struct RecordCutter {
+    /// Buffer which each record is read into.
+    buf: Vec<u8>,
+    /// Buffer which each output record is written to.
+    output: Vec<u8>,
+    position: RecordPosition,
+    /// Whether the records specified by `skip_lines` have been skipped.
+    started: bool,
+    /// Whether `output` is a record which was not visited, since visiting the header row before it
+    /// stopped.
+    pending: bool,
+    /// Whether output records, and the header row, are visited with the output line delimiter at
+    /// the end, so that each can be written at once.
+    terminated: bool,
+}
+
+impl RecordCutter {
+    /// Skips records as `visit` does, if they have not been skipped yet, then copies records as
+    /// `copy_records` does, so that `visit` cuts the next record.
+    fn copy_records<R: BufRead, W: Write>(
+        &mut self,
+        reader: &mut R,
+        writer: &mut W,
+        options: &Options,
+        state: &mut RunState,
+    ) -> io::Result<()> {
+        if !self.started {
+            self.started = true;
+            skip_records(reader, options, &mut self.position)?;
+        }
+        copy_records(reader, writer, options, state, true, &mut self.position)
+    }
+
+    /// Cuts each record with `selector`, and calls `visit` with each output record, including the
+    /// header row, until the end of the input, until `max_lines` records have been visited, or
+    /// until `visit` breaks, in which case the next call resumes from the next output record.
+    fn visit<R, L, F, B>(
+        &mut self,
+        reader: &mut R,
+        options: &Options,
+        state: &mut RunState,
+        selector: &mut L,
+        mut visit: F,
+    ) -> io::Result<ControlFlow<B>>
+    where
+        R: BufRead,
+        L: Selector + ?Sized,
+        F: FnMut(&[u8]) -> io::Result<ControlFlow<B>>,
+    {
+        if self.pending {
+            self.pending = false;
+            state.record_written();
+            if let ControlFlow::Break(value) = visit(&self.output)? {
+                return Result::Ok(ControlFlow::Break(value));
+            }
+        }
+        if !self.started {
+            self.started = true;
+            skip_records(reader, options, &mut self.position)?;
+        }
 
-    for range in ranges.into_iter() {
-        match range {
-            MergedRange::Closed(start, end) => {
-                // Range is outside size of input. Since ranges are sorted, all following ranges
-                // will also be outside of input. Skip and break from loop.
-                if start >= input.len() {
-                    break;
+        let mut search = TerminatorSearch::new(&options.line_delimiter);
+        while !state.limit_reached(options)
+            && read_record(
+                reader,
+                &mut search,
+                &mut self.buf,
+                options,
+                &mut self.position,
+            )?
+        {
+            let disposition = select_record(
+                selector,
+                &self.buf,
+                &self.position,
+                options,
+                state,
+                &mut self.output,
+            )?;
+            let mut header = match disposition {
+                RecordDisposition::Write { header } => header,
+                RecordDisposition::Skip => continue,
+            };
+            if self.terminated {
+                let terminator = options.output_terminator();
+                self.output.extend_from_slice(terminator);
+                if let Option::Some(header) = &mut header {
+                    header.extend_from_slice(terminator);
                 }
-                // Otherwise include elements in range, up to the last element.
-                let end = std::cmp::min(end + 1, input.len());
-                result.extend_from_slice(&input[start..end])
             }
-            MergedRange::ToEnd(start) => {
-                // Range is outside size of input. Since ranges are sorted, all following ranges
-                // will also be outside of input. Skip and break from loop.
-                if start >= input.len() {
-                    break;
+            if let Option::Some(header) = header {
+                if let ControlFlow::Break(value) = visit(&header)? {
+                    self.pending = true;
+                    return Result::Ok(ControlFlow::Break(value));
                 }
-                // Otherwise include the remaining elements.
-                result.extend_from_slice(&input[start..])
+            }
+            state.record_written();
+            if let ControlFlow::Break(value) = visit(&self.output)? {
+                return Result::Ok(ControlFlow::Break(value));
             }
         }
+        Result::Ok(ControlFlow::Continue(()))
     }
+}
 
-    result
+/// Cuts a record which has been read into memory with `selector`, and writes the output record,
+/// with the prefix and suffix but not the line delimiter, to `output`.
+fn select_record<L: Selector + ?Sized>(
+    selector: &mut L,
+    record: &[u8],
+    position: &RecordPosition,
+    options: &Options,
+    state: &mut RunState,
+    output: &mut Vec<u8>,
+) -> io::Result<RecordDisposition> {
+    output.clear();
+    output.extend_from_slice(&options.prefix);
+    let disposition = selector.process_record(record, position, state, output)?;
+    output.extend_from_slice(&options.suffix);
+    Result::Ok(disposition)
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::range::Ranges;
-    use regex::Regex;
+/// Selects the parts of each record which are written, for one mode. Reading records, writing
+/// them, and the options which apply to every mode are handled once, by [`RecordCutter`].
+trait Selector {
+    /// Returns whether every record is selected unchanged, as long as it is valid UTF-8, so that
+    /// records can be copied rather than cut.
+    fn copies_records(&self) -> bool {
+        false
+    }
 
-    #[test]
-    fn cut_bytes() {
-        // One line.
-        let input = &[1, 2, 3, 4, 5, 6, 7, 8];
-        assert_cut_bytes(
-            &mut input.clone(),
-            b'\n',
-            "1-",
-            &[1, 2, 3, 4, 5, 6, 7, 8, b'\n'],
-        );
-        assert_cut_bytes(&mut input.clone(), b'\n', "2-5", &[2, 3, 4, 5, b'\n']);
-        assert_cut_bytes(
-            &mut input.clone(),
-            b'\n',
-            "-3,6-",
-            &[1, 2, 3, 6, 7, 8, b'\n'],
-        );
+    /// Appends the parts of `record`, without its line delimiter, which are selected to `output`,
+    /// and returns whether they are written. Records which are skipped, and the header row, are
+    /// recorded in `state`.
+    fn process_record(
+        &mut self,
+        record: &[u8],
+        position: &RecordPosition,
+        state: &mut RunState,
+        output: &mut Vec<u8>,
+    ) -> io::Result<RecordDisposition>;
+}
+
+/// Whether the output for a record is written, returned by [`Selector::process_record`].
+enum RecordDisposition {
+    /// The output record is written, after the header row if there is one, without its line
+    /// delimiter.
+    Write { header: Option<Vec<u8>> },
+    /// Nothing is written, since the record is suppressed, or skipped since it is not valid UTF-8.
+    Skip,
+}
+
+/// Selects bytes from records which have been read into memory.
+struct ByteSelector<'a> {
+    ranges: &'a Ranges,
+    format: ByteFormat,
+}
+
+impl Selector for ByteSelector<'_> {
+    fn process_record(
+        &mut self,
+        record: &[u8],
+        _: &RecordPosition,
+        _: &mut RunState,
+        output: &mut Vec<u8>,
+    ) -> io::Result<RecordDisposition> {
+        let mut selection = ByteSelection::new(self.ranges, self.format);
+        selection.write(output, record)?;
+        selection.finish(output)?;
+        Result::Ok(RecordDisposition::Write {
+            header: Option::None,
+        })
+    }
+}
+
+/// Selects characters from each record.
+struct CharSelector<'a> {
+    ranges: &'a Ranges,
+    options: &'a Options,
+}
+
+impl Selector for CharSelector<'_> {
+    fn copies_records(&self) -> bool {
+        selects_all(self.ranges)
+    }
+
+    fn process_record(
+        &mut self,
+        record: &[u8],
+        position: &RecordPosition,
+        state: &mut RunState,
+        output: &mut Vec<u8>,
+    ) -> io::Result<RecordDisposition> {
+        // Invalid UTF-8 is never an error when lossy, so only the characters which are selected
+        // need to be decoded. Otherwise the whole record is validated.
+        let head = match self.options.invalid_utf8 {
+            InvalidUtf8::Lossy => decode_head(record, self.ranges),
+            InvalidUtf8::Fail | InvalidUtf8::Skip => Option::None,
+        };
+        let line = match head {
+            Option::Some(head) => Cow::Borrowed(head),
+            Option::None => match decode_record(record, self.options, position, state)? {
+                Option::Some(line) => line,
+                Option::None => return Result::Ok(RecordDisposition::Skip),
+            },
+        };
+        for span in selected_char_spans(&line, self.ranges) {
+            output.extend_from_slice(&line.as_bytes()[span]);
+        }
+        Result::Ok(RecordDisposition::Write {
+            header: Option::None,
+        })
+    }
+}
+
+/// Selects fields, separated by any kind of delimiter, from each record.
+struct FieldSelector<'a, S> {
+    ranges: &'a Ranges,
+    delimiter: S,
+    field_options: &'a FieldOptions,
+    options: &'a Options,
+    /// Byte spans of the fields of each record which are written with a template.
+    fields: Vec<(usize, usize)>,
+}
+
+impl<S: FieldSplitter> Selector for FieldSelector<'_, S> {
+    fn copies_records(&self) -> bool {
+        let field_options = self.field_options;
+        // Every mode which uses a `FieldSplitter` splits on a literal delimiter, so fields are
+        // rejoined unchanged if the output delimiter is exactly one delimiter.
+        let rejoined = match std::str::from_utf8(&field_options.output_delimiter) {
+            Result::Ok(output_delimiter) => self
+                .delimiter
+                .delimiters(output_delimiter)
+                .eq([(0, output_delimiter.len())]),
+            Result::Err(_) => false,
+        };
+        selects_all(self.ranges)
+            && rejoined
+            && !field_options.suppress
+            && !field_options.byte_offsets
+            && field_options.template.is_none()
+            && field_options.header.is_none()
+            && field_options.sub_fields.is_none()
+    }
+
+    fn process_record(
+        &mut self,
+        record: &[u8],
+        position: &RecordPosition,
+        state: &mut RunState,
+        output: &mut Vec<u8>,
+    ) -> io::Result<RecordDisposition> {
+        let line = match decode_record(record, self.options, position, state)? {
+            Option::Some(line) => line,
+            Option::None => return Result::Ok(RecordDisposition::Skip),
+        };
+        let delimiter = &self.delimiter;
+        let (ranges, field_options) = (self.ranges, self.field_options);
+        select_fields(
+            line.as_bytes(),
+            || delimiter.delimiters(&line),
+            ranges,
+            field_options,
+            &mut self.fields,
+            &mut state.header_written,
+            output,
+        )
+    }
+}
+
+/// Fields separated by a regular expression are split in the record as it was read, which does not
+/// need to be valid UTF-8.
+#[cfg(feature = "regex")]
+impl Selector for FieldSelector<'_, &Regex> {
+    fn process_record(
+        &mut self,
+        record: &[u8],
+        _: &RecordPosition,
+        state: &mut RunState,
+        output: &mut Vec<u8>,
+    ) -> io::Result<RecordDisposition> {
+        let delimiter = self.delimiter;
+        select_fields(
+            record,
+            || {
+                delimiter
+                    .find_iter(record)
+                    .filter(|m| m.start() < m.end())
+                    .map(|m| (m.start(), m.end()))
+            },
+            self.ranges,
+            self.field_options,
+            &mut self.fields,
+            &mut state.header_written,
+            output,
+        )
+    }
+}
+
+/// Selects the text between a start and an end marker from each record.
+struct BetweenSelector<'a, M> {
+    start: M,
+    end: M,
+    between_options: &'a BetweenOptions,
+    options: &'a Options,
+}
+
+impl Selector for BetweenSelector<'_, &str> {
+    fn process_record(
+        &mut self,
+        record: &[u8],
+        position: &RecordPosition,
+        state: &mut RunState,
+        output: &mut Vec<u8>,
+    ) -> io::Result<RecordDisposition> {
+        let line = match decode_record(record, self.options, position, state)? {
+            Option::Some(line) => line,
+            Option::None => return Result::Ok(RecordDisposition::Skip),
+        };
+        let spans = BetweenSpans::new(line.as_bytes(), self.start, self.end);
+        Result::Ok(select_between(
+            line.as_bytes(),
+            spans,
+            self.between_options,
+            output,
+        ))
+    }
+}
+
+/// Markers which are regular expressions are searched for in the record as it was read, which does
+/// not need to be valid UTF-8.
+#[cfg(feature = "regex")]
+impl Selector for BetweenSelector<'_, &Regex> {
+    fn process_record(
+        &mut self,
+        record: &[u8],
+        _: &RecordPosition,
+        _: &mut RunState,
+        output: &mut Vec<u8>,
+    ) -> io::Result<RecordDisposition> {
+        let spans = BetweenSpans::new(record, self.start, self.end);
+        Result::Ok(select_between(record, spans, self.between_options, output))
+    }
+}
+
+/// Appends the text of `line` between the first pair of markers, or between each pair with
+/// `all_occurrences`, to `output`.
+fn select_between<M: Marker>(
+    line: &[u8],
+    mut spans: BetweenSpans<M>,
+    between_options: &BetweenOptions,
+    output: &mut Vec<u8>,
+) -> RecordDisposition {
+    match spans.next() {
+        Option::Some((start, end)) => {
+            output.extend_from_slice(&line[start..end]);
+            if between_options.all_occurrences {
+                for (start, end) in spans {
+                    output.extend_from_slice(&between_options.output_delimiter);
+                    output.extend_from_slice(&line[start..end]);
+                }
+            }
+        }
+        // A line without both markers is written empty, unless it is suppressed.
+        Option::None if between_options.suppress => return RecordDisposition::Skip,
+        Option::None => {}
+    }
+    RecordDisposition::Write {
+        header: Option::None,
+    }
+}
+
+/// Appends the fields of `line` which are selected to `output`. `delimiters` returns the byte
+/// spans of the delimiters in the line, `fields` is a buffer for the spans of the fields, and
+/// `header_written` is whether the header row has been returned.
+fn select_fields<F, I>(
+    line: &[u8],
+    delimiters: F,
+    ranges: &Ranges,
+    field_options: &FieldOptions,
+    fields: &mut Vec<(usize, usize)>,
+    header_written: &mut bool,
+    output: &mut Vec<u8>,
+) -> io::Result<RecordDisposition>
+where
+    F: Fn() -> I,
+    I: Iterator<Item = (usize, usize)>,
+{
+    // The line is only scanned once, as it is split, unless the header row needs the number of
+    // fields.
+    let mut spans = delimiters().peekable();
+    if spans.peek().is_none() {
+        // A line without a delimiter is treated as a single field, unless it is suppressed.
+        if field_options.suppress {
+            return Result::Ok(RecordDisposition::Skip);
+        }
+        write_unsplit_line(output, line, field_options)?;
+        return Result::Ok(RecordDisposition::Write {
+            header: header_row(|| 1, field_options, ranges, header_written),
+        });
+    }
+
+    let spans = FieldSpans::new(spans, line.len());
+    if let Some(template) = &field_options.template {
+        fields.clear();
+        fields.extend(spans.take(template.field_count()));
+        template.write(output, line, fields)?;
+    } else {
+        let output_delimiter = &field_options.output_delimiter;
+        if field_options.byte_offsets {
+            write_byte_offsets(output, spans, output_delimiter, ranges)?;
+        } else if let Option::Some(sub_fields) = &field_options.sub_fields {
+            write_sub_fields(output, line, spans, output_delimiter, ranges, sub_fields)?;
+        } else {
+            write_fields(output, line, spans, output_delimiter, ranges)?;
+        }
+    }
+    let field_count = || delimiters().count() + 1;
+    Result::Ok(RecordDisposition::Write {
+        header: header_row(field_count, field_options, ranges, header_written),
+    })
+}
+
+/// Returns whether `ranges` selects every byte, character or field.
+fn selects_all(ranges: &Ranges) -> bool {
+    ranges.as_slice() == [MergedRange::ToEnd(0)]
+}
+
+/// Returns whether records which are selected unchanged can be copied with `copy_records`, since
+/// the options do not add to them or need each one to be written separately.
+fn can_copy_records(options: &Options) -> bool {
+    options.line_delimiter.len() == 1
+        && options.output_terminator() == options.line_delimiter
+        && options.prefix.is_empty()
+        && options.suffix.is_empty()
+        && options.max_lines.is_none()
+        && !options.line_buffered
+}
+
+/// Copies whole records from the reader's buffer to the writer, as they are written when every
+/// part of them is selected, without reading them into memory or cutting them. A carriage return
+/// is still removed before each line delimiter if needed.
+///
+/// Stops at the end of the input, at a record which does not end within the reader's buffer, or,
+/// if `utf8` is set, at a record which is not valid UTF-8. The rest of the input is then cut as
+/// usual, from `position`.
+fn copy_records<R: BufRead, W: Write>(
+    reader: &mut R,
+    writer: &mut W,
+    options: &Options,
+    state: &mut RunState,
+    utf8: bool,
+    position: &mut RecordPosition,
+) -> io::Result<()> {
+    let terminator = options.line_delimiter[0];
+    loop {
+        let chunk = reader.fill_buf()?;
+        // A line delimiter is a single byte, so it is never part of a multi-byte character, and
+        // the records up to it are valid UTF-8 if each record is.
+        let valid = match utf8.then(|| std::str::from_utf8(chunk)) {
+            Option::Some(Result::Err(err)) => &chunk[..err.valid_up_to()],
+            _ => chunk,
+        };
+        let records = match memchr::memrchr(terminator, valid) {
+            Option::Some(index) => &valid[..index + 1],
+            Option::None => return Result::Ok(()),
+        };
+
+        if options.strip_cr() {
+            let mut start = 0;
+            for index in memmem::find_iter(records, b"\r\n") {
+                writer.write_all(&records[start..index])?;
+                start = index + 1;
+            }
+            writer.write_all(&records[start..])?;
+        } else {
+            writer.write_all(records)?;
+        }
+
+        let count = memchr::memchr_iter(terminator, records).count();
+        let last = memchr::memrchr(terminator, &records[..records.len() - 1]).map_or(0, |i| i + 1);
+        position.number += count;
+        position.offset = position.next_offset + last as u64;
+        position.next_offset += records.len() as u64;
+        state.written += count;
+        let len = records.len();
+        reader.consume(len);
+    }
+}
+
+/// Reads and discards the number of records specified by `skip_lines`, or until the end of the input.
+/// Skipped records are not read into memory.
+fn skip_records<R: BufRead>(
+    reader: &mut R,
+    options: &Options,
+    position: &mut RecordPosition,
+) -> io::Result<()> {
+    let mut search = TerminatorSearch::new(&options.line_delimiter);
+    for _ in 0..options.skip_lines {
+        let mut n = 0;
+        loop {
+            let chunk = reader.fill_buf()?;
+            if chunk.is_empty() {
+                break;
+            }
+            let (consumed, end) = match search.find(chunk) {
+                Option::Some(i) => (i, true),
+                Option::None => (chunk.len(), false),
+            };
+            reader.consume(consumed);
+            n += consumed as u64;
+            if end {
+                break;
+            }
+        }
+        if n == 0 {
+            break;
+        }
+        advance_position(n, position);
+    }
+    Result::Ok(())
+}
+
+/// Reads the next record into the buffer, replacing its previous contents. The line delimiter is
+/// removed, along with a preceding carriage return when records are delimited by newlines, unless
+/// `keep_cr` is set. Returns `false` if the end of the input has been reached.
+fn read_record<R: BufRead>(
+    reader: &mut R,
+    search: &mut TerminatorSearch,
+    buf: &mut Vec<u8>,
+    options: &Options,
+    position: &mut RecordPosition,
+) -> io::Result<bool> {
+    buf.clear();
+    let n = read_until_terminator(reader, search, buf)?;
+    Result::Ok(finish_read(buf, n, options, position))
+}
+
+/// Updates the position after `n` bytes were read into the buffer as the next record, and removes
+/// the line delimiter as in `read_record`. Returns `false` if nothing was read.
+fn finish_read(
+    buf: &mut Vec<u8>,
+    n: usize,
+    options: &Options,
+    position: &mut RecordPosition,
+) -> bool {
+    if n == 0 {
+        return false;
+    }
+    advance_position(n as u64, position);
+
+    if !options.line_delimiter.is_empty() && buf.ends_with(&options.line_delimiter) {
+        buf.truncate(buf.len() - options.line_delimiter.len());
+        if options.strip_cr() && buf.ends_with(b"\r") {
+            buf.pop();
+        }
+    }
+    true
+}
+
+/// Updates the position after a record of `n` bytes, including the line delimiter, was read or
+/// skipped.
+fn advance_position(n: u64, position: &mut RecordPosition) {
+    position.number += 1;
+    position.offset = position.next_offset;
+    position.next_offset += n;
+}
+
+/// Writes the suffix and line delimiter which end an output record which is streamed, and counts
+/// the record as written.
+fn finish_record<W: Write>(
+    writer: &mut W,
+    options: &Options,
+    state: &mut RunState,
+) -> io::Result<()> {
+    writer.write_all(&options.suffix)?;
+    state.record_written();
+    writer.write_all(options.output_terminator())?;
+    if options.line_buffered {
+        writer.flush()?;
+    }
+    Result::Ok(())
+}
+
+/// Writes bytes as lowercase hexadecimal digits. `first` is whether the bytes start the output
+/// record, in which case no separator is written before the first byte.
+fn write_hex<W: Write>(
+    writer: &mut W,
+    bytes: &[u8],
+    format: HexFormat,
+    first: bool,
+) -> io::Result<()> {
+    for (i, byte) in bytes.iter().enumerate() {
+        match format {
+            HexFormat::Plain => write!(writer, "{:02x}", byte)?,
+            HexFormat::Spaced if first && i == 0 => write!(writer, "{:02x}", byte)?,
+            HexFormat::Spaced => write!(writer, " {:02x}", byte)?,
+            HexFormat::Prefixed if first && i == 0 => write!(writer, "0x{:02x}", byte)?,
+            HexFormat::Prefixed => write!(writer, " 0x{:02x}", byte)?,
+        }
+    }
+    Result::Ok(())
+}
+
+/// Writes bytes encoded as base64.
+fn write_base64<W: Write>(
+    writer: &mut W,
+    bytes: &[u8],
+    alphabet: Base64Alphabet,
+) -> io::Result<()> {
+    let (symbols, pad) = match alphabet {
+        Base64Alphabet::Standard => (
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+            true,
+        ),
+        Base64Alphabet::Url => (
+            b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+            false,
+        ),
+    };
+
+    let mut encoded = Vec::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = match *chunk {
+            [a] => u32::from(a) << 16,
+            [a, b] => u32::from(a) << 16 | u32::from(b) << 8,
+            [a, b, c] => u32::from(a) << 16 | u32::from(b) << 8 | u32::from(c),
+            _ => unreachable!(),
+        };
+        // Each input byte contributes to one more output symbol than the number of bytes.
+        for i in 0..=chunk.len() {
+            encoded.push(symbols[(group >> (18 - 6 * i) & 0x3f) as usize]);
+        }
+        if pad {
+            encoded.resize(encoded.len() + 3 - chunk.len(), b'=');
+        }
+    }
+    writer.write_all(&encoded)
+}
+
+/// Returns the header row, if there is one and it has not already been returned, as recorded by
+/// `written`. `field_count` returns the number of fields in the record about to be written, and is
+/// only called if the header row names every selected field.
+fn header_row<F>(
+    field_count: F,
+    options: &FieldOptions,
+    ranges: &Ranges,
+    written: &mut bool,
+) -> Option<Vec<u8>>
+where
+    F: FnOnce() -> usize,
+{
+    let header = match &options.header {
+        Option::Some(header) if !*written => header,
+        _ => return Option::None,
+    };
+    *written = true;
+
+    let names = if header.bounded {
+        header.names.clone()
+    } else {
+        SelectIndices::new(0..field_count(), ranges)
+            .enumerate()
+            .map(|(i, index)| {
+                header
+                    .names
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| HeaderOut::positional_name(index))
+            })
+            .collect()
+    };
+    let names: Vec<&[u8]> = names.iter().map(String::as_bytes).collect();
+    Option::Some(names.join(&options.output_delimiter[..]))
+}
+
+/// Writes a line which does not contain a field delimiter. The line is treated as a single field.
+fn write_unsplit_line<W: Write>(
+    writer: &mut W,
+    line: &[u8],
+    options: &FieldOptions,
+) -> io::Result<()> {
+    if let Some(template) = &options.template {
+        template.write(writer, line, &[(0, line.len())])
+    } else if options.byte_offsets {
+        write!(writer, "0-{}", line.len())
+    } else {
+        writer.write_all(line)
+    }
+}
+
+/// Writes the fields of `line` which are selected, given the byte spans of every field, separated
+/// by the output delimiter. Nothing is written if no fields are selected.
+///
+/// Selected fields which are already separated by the output delimiter in the line are written
+/// together, as a single slice of the line.
+fn write_fields<W, I>(
+    writer: &mut W,
+    line: &[u8],
+    spans: I,
+    output_delimiter: &[u8],
+    ranges: &Ranges,
+) -> io::Result<()>
+where
+    W: Write,
+    I: Iterator<Item = (usize, usize)>,
+{
+    let mut selected = SelectIndices::new(spans, ranges);
+    let (mut start, mut end) = match selected.next() {
+        Option::Some(span) => span,
+        Option::None => return Result::Ok(()),
+    };
+    for (next_start, next_end) in selected {
+        if &line[end..next_start] != output_delimiter {
+            writer.write_all(&line[start..end])?;
+            writer.write_all(output_delimiter)?;
+            start = next_start;
+        }
+        end = next_end;
+    }
+    writer.write_all(&line[start..end])
+}
+
+/// Writes the sub-fields selected from each of the fields of `line` which are selected, separated
+/// by the sub-field output delimiter within each field and by the output delimiter between fields.
+fn write_sub_fields<W, I>(
+    writer: &mut W,
+    line: &[u8],
+    spans: I,
+    output_delimiter: &[u8],
+    ranges: &Ranges,
+    sub_fields: &SubFields,
+) -> io::Result<()>
+where
+    W: Write,
+    I: Iterator<Item = (usize, usize)>,
+{
+    let sub_delimiter = sub_fields.delimiter.as_bytes();
+    for (index, (start, end)) in SelectIndices::new(spans, ranges).enumerate() {
+        if index > 0 {
+            writer.write_all(output_delimiter)?;
+        }
+        let field = &line[start..end];
+        let mut delimiters = memmem::find_iter(field, sub_delimiter)
+            .map(|offset| (offset, offset + sub_delimiter.len()))
+            .peekable();
+        // A field without the sub-delimiter is written whole, as a line without the delimiter is.
+        if delimiters.peek().is_none() {
+            writer.write_all(field)?;
+        } else {
+            let sub_spans = FieldSpans::new(delimiters, field.len());
+            write_fields(
+                writer,
+                field,
+                sub_spans,
+                &sub_fields.output_delimiter,
+                &sub_fields.ranges,
+            )?;
+        }
+    }
+    Result::Ok(())
+}
+
+/// Writes the byte offsets of the selected fields, formatted as "start-end" (0-based, end exclusive)
+/// and separated by the output delimiter.
+fn write_byte_offsets<W, I>(
+    writer: &mut W,
+    spans: I,
+    output_delimiter: &[u8],
+    ranges: &Ranges,
+) -> io::Result<()>
+where
+    W: Write,
+    I: Iterator<Item = (usize, usize)>,
+{
+    for (i, (start, end)) in SelectIndices::new(spans, ranges).enumerate() {
+        if i > 0 {
+            writer.write_all(output_delimiter)?;
+        }
+        write!(writer, "{}-{}", start, end)?;
+    }
+    Result::Ok(())
+}
+
+/// Iterator over the byte spans of fields in a line, given the byte spans of the delimiters which
+/// separate them.
+struct FieldSpans<I> {
+    delimiters: I,
+    /// Start of the next field, or `None` if all fields have been returned.
+    next_start: Option<usize>,
+    len: usize,
+}
+
+impl<I: Iterator<Item = (usize, usize)>> FieldSpans<I> {
+    fn new(delimiters: I, len: usize) -> Self {
+        FieldSpans {
+            delimiters,
+            next_start: Option::Some(0),
+            len,
+        }
+    }
+}
+
+impl<I: Iterator<Item = (usize, usize)>> Iterator for FieldSpans<I> {
+    type Item = (usize, usize);
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.next_start?;
+        match self.delimiters.next() {
+            Option::Some((delimiter_start, delimiter_end)) => {
+                self.next_start = Option::Some(delimiter_end);
+                Option::Some((start, delimiter_start))
+            }
+            Option::None => {
+                self.next_start = Option::None;
+                Option::Some((start, self.len))
+            }
+        }
+    }
+}
+
+/// Decodes only the start of a record as UTF-8, as far as the last character selected by `ranges`,
+/// if the selection is bounded and that much of the record is valid. The rest of the record is not
+/// decoded, so it is not checked for invalid UTF-8, which is only right when it would be replaced.
+fn decode_head<'a>(record: &'a [u8], ranges: &Ranges) -> Option<&'a str> {
+    let last = ranges.max_index()?;
+    // Each character is at most four bytes, so the last character needed ends within this many
+    // bytes, if the record is long enough.
+    let head = &record[..record.len().min(last.saturating_add(1).saturating_mul(4))];
+    let valid = match std::str::from_utf8(head) {
+        Result::Ok(valid) => valid,
+        Result::Err(err) => std::str::from_utf8(&head[..err.valid_up_to()]).ok()?,
+    };
+    match valid.char_indices().nth(last) {
+        Option::Some((start, c)) => Option::Some(&valid[..start + c.len_utf8()]),
+        // The whole record is shorter than the selection.
+        Option::None if valid.len() == record.len() => Option::Some(valid),
+        Option::None => Option::None,
+    }
+}
+
+/// Decodes a record as UTF-8, handling invalid records as specified by `invalid_utf8`. Returns `None`
+/// if the record is skipped, in which case it is added to the rejected records in `state`.
+fn decode_record<'a>(
+    bytes: &'a [u8],
+    options: &Options,
+    position: &RecordPosition,
+    state: &mut RunState,
+) -> io::Result<Option<Cow<'a, str>>> {
+    match (std::str::from_utf8(bytes), options.invalid_utf8) {
+        (Result::Ok(line), _) => Result::Ok(Option::Some(Cow::Borrowed(line))),
+        (Result::Err(_), InvalidUtf8::Fail) => Result::Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            InvalidUtf8Error {
+                line: position.number,
+                offset: position.offset,
+            },
+        )),
+        (Result::Err(_), InvalidUtf8::Skip) => {
+            state.rejects.push(Reject {
+                record: position.number,
+                offset: position.offset,
+                bytes: bytes.to_owned(),
+            });
+            Result::Ok(Option::None)
+        }
+        (Result::Err(_), InvalidUtf8::Lossy) => {
+            Result::Ok(Option::Some(String::from_utf8_lossy(bytes)))
+        }
+    }
+}
+
+/// Returns the elements of `input` which are selected by `ranges`.
+fn select<T: Clone>(input: &[T], ranges: &Ranges) -> Vec<T> {
+    let mut result = Vec::new();
+    for span in selected_spans(ranges, input.len()) {
+        result.extend_from_slice(&input[span]);
+    }
+    result
+}
+
+/// Returns the spans of the elements selected by `ranges` from `len` elements.
+fn selected_spans(ranges: &Ranges, len: usize) -> impl Iterator<Item = Range<usize>> + '_ {
+    ranges
+        .iter()
+        // Ranges are sorted, so once a range starts outside of the input, all following ranges
+        // will also be outside of it.
+        .take_while(move |range| range.start() < len)
+        .map(move |range| range.to_slice_range(len))
+}
+
+/// Returns the byte offsets in `line` of the characters selected by `ranges`, with one span for
+/// each range. Like [`selected_spans`], but the characters are counted as the spans are returned,
+/// and only as far as the last range needs, rather than first counting every character.
+fn selected_char_spans<'a>(
+    line: &'a str,
+    ranges: &'a Ranges,
+) -> impl Iterator<Item = Range<usize>> + 'a {
+    // Byte offsets of the start of each character, and then of the end of the line.
+    let mut boundaries = line
+        .char_indices()
+        .map(|(offset, _)| offset)
+        .chain(std::iter::once(line.len()));
+    // Index of the character which the next boundary starts.
+    let mut next = 0;
+    // Ranges are sorted and not adjacent, so each boundary is after the previous one.
+    let mut boundary = move |index: usize| {
+        let offset = boundaries.nth(index - next)?;
+        next = index + 1;
+        Option::Some(offset)
+    };
+    ranges.iter().map_while(move |range| {
+        // Once a range starts at the end of the line, all following ranges are outside of it.
+        let start = boundary(range.start()).filter(|&start| start < line.len())?;
+        let end = match range.end() {
+            Option::Some(end) => boundary(end.saturating_add(1)).unwrap_or(line.len()),
+            Option::None => line.len(),
+        };
+        Option::Some(start..end)
+    })
+}
+
+/// Returns the span of the elements selected by `ranges` from `len` elements, if they are
+/// contiguous, or `None` if they are not.
+fn single_span(ranges: &Ranges, len: usize) -> Option<Range<usize>> {
+    let mut spans = selected_spans(ranges, len);
+    match (spans.next(), spans.next()) {
+        (Option::None, _) => Option::Some(0..0),
+        (Option::Some(span), Option::None) => Option::Some(span),
+        (Option::Some(_), Option::Some(_)) => Option::None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Base64Alphabet, BetweenOptions, Buffers, ByteFormat, CutLines, FieldOptions, HeaderOut,
+        HexFormat, InvalidUtf8, Mode, Options, Reject, RunState, SubFields,
+    };
+    use crate::range::Ranges;
+    use proptest::prelude::*;
+    #[cfg(feature = "regex")]
+    use regex::bytes::Regex;
+    use std::borrow::Cow;
+    use std::cell::Cell;
+    use std::io::{self, BufReader, IoSlice, Write};
+    use std::ops::ControlFlow;
+
+    #[test]
+    fn cut_bytes() {
+        // One line.
+        let input = &[1, 2, 3, 4, 5, 6, 7, 8];
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
-            "1,2,4,8,16-",
-            &[1, 2, 4, 8, b'\n'],
+            "1-",
+            &[1, 2, 3, 4, 5, 6, 7, 8, b'\n'],
         );
+        assert_cut_bytes(&input.clone(), b'\n', "2-5", &[2, 3, 4, 5, b'\n']);
+        assert_cut_bytes(&input.clone(), b'\n', "-3,6-", &[1, 2, 3, 6, 7, 8, b'\n']);
+        assert_cut_bytes(&input.clone(), b'\n', "1,2,4,8,16-", &[1, 2, 4, 8, b'\n']);
 
         // Multiple lines.
         let input = &[
             1, 2, 3, 4, 5, 6, 7, 8, b'\n', 11, 12, 13, 14, 15, 16, 17, 18,
         ];
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "1-",
             &[
@@ -286,13 +2352,13 @@ mod tests {
             ],
         );
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "2-4,7-",
             &[2, 3, 4, 7, 8, b'\n', 12, 13, 14, 17, 18, b'\n'],
         );
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "4-8",
             &[4, 5, 6, 7, 8, b'\n', 14, 15, 16, 17, 18, b'\n'],
@@ -314,19 +2380,19 @@ mod tests {
         // Different sized lines.
         let input = &[1, 2, 3, 4, 5, 6, 7, 8, b'\n', 11, 12, 13, 14, 15, 16];
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "1-",
             &[1, 2, 3, 4, 5, 6, 7, 8, b'\n', 11, 12, 13, 14, 15, 16, b'\n'],
         );
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "5-",
             &[5, 6, 7, 8, b'\n', 15, 16, b'\n'],
         );
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "2-4,7-9",
             &[2, 3, 4, 7, 8, b'\n', 12, 13, 14, b'\n'],
@@ -337,7 +2403,7 @@ mod tests {
             1, b'\n', 11, 12, b'\n', 21, 22, 23, b'\n', 31, 32, 33, 34, b'\n', 41, 42, 43, 44, 45,
         ];
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "1-",
             &[
@@ -346,7 +2412,7 @@ mod tests {
             ],
         );
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "3,5-",
             &[b'\n', b'\n', 23, b'\n', 33, b'\n', 43, 45, b'\n'],
@@ -355,18 +2421,18 @@ mod tests {
         // Non-UTF-8.
         let input = &[255, 254, 253, b'\n', 252, 251, 250];
         assert_cut_bytes(
-            &mut input.clone(),
+            &input.clone(),
             b'\n',
             "1-",
             &[255, 254, 253, b'\n', 252, 251, 250, b'\n'],
         );
-        assert_cut_bytes(&mut input.clone(), b'\n', "2", &[254, b'\n', 251, b'\n']);
+        assert_cut_bytes(&input.clone(), b'\n', "2", &[254, b'\n', 251, b'\n']);
     }
 
     #[test]
     fn cut_bytes_trailing_newline() {
         assert_cut_bytes(&[], b'\n', "1-", &[]);
-        assert_cut_bytes(&[b'\n'], b'\n', "1-", &[b'\n']);
+        assert_cut_bytes(b"\n", b'\n', "1-", b"\n");
 
         assert_cut_bytes(&[1, 2, 3, 4], b'\n', "1-", &[1, 2, 3, 4, b'\n']);
         assert_cut_bytes(&[1, 2, 3, 4, b'\n'], b'\n', "1-", &[1, 2, 3, 4, b'\n']);
@@ -415,13 +2481,279 @@ mod tests {
         assert_cut_bytes(input, 5, "2,4", &[2, 4, 5, 2, 4, 5]);
     }
 
-    fn assert_cut_bytes(mut input: &[u8], line_delimiter: u8, ranges: &str, expected: &[u8]) {
-        let mut output = Vec::new();
-        let ranges: Ranges = ranges.parse().unwrap();
-        super::cut_bytes(&mut input, &mut output, line_delimiter, &ranges).unwrap();
+    fn assert_cut_bytes(input: &[u8], line_delimiter: u8, ranges: &str, expected: &[u8]) {
+        let output = cut_bytes_streamed(
+            input,
+            &delimited_by(line_delimiter),
+            ByteFormat::Raw,
+            ranges,
+        );
         assert_eq!(output, expected);
     }
 
+    /// Cuts bytes from the input, checking that the output is the same however the input is split
+    /// into chunks as it is read.
+    fn cut_bytes_streamed(
+        input: &[u8],
+        options: &Options,
+        format: ByteFormat,
+        ranges: &str,
+    ) -> Vec<u8> {
+        let ranges: Ranges = ranges.parse().unwrap();
+        let mut output = Vec::new();
+        super::cut_bytes(
+            &mut &input[..],
+            &mut output,
+            options,
+            &mut RunState::default(),
+            format,
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+
+        for capacity in 1..=4 {
+            let mut chunked = Vec::new();
+            super::cut_bytes(
+                &mut BufReader::with_capacity(capacity, input),
+                &mut chunked,
+                options,
+                &mut RunState::default(),
+                format,
+                &ranges,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(chunked, output, "capacity {}", capacity);
+        }
+        output
+    }
+
+    #[test]
+    fn cut_bytes_streamed_crlf() {
+        let keep_cr = Options {
+            keep_cr: true,
+            ..Options::default()
+        };
+        let input = b"ab\r\n\r\r\n\rc\r\rd\r";
+        assert_eq!(
+            cut_bytes_streamed(input, &Options::default(), ByteFormat::Raw, "1-"),
+            b"ab\n\r\n\rc\r\rd\r\n"
+        );
+        assert_eq!(
+            cut_bytes_streamed(input, &Options::default(), ByteFormat::Raw, "2-3"),
+            b"b\n\nc\r\n"
+        );
+        assert_eq!(
+            cut_bytes_streamed(input, &keep_cr, ByteFormat::Raw, "1-"),
+            b"ab\r\n\r\r\n\rc\r\rd\r\n"
+        );
+    }
+
+    #[test]
+    fn cut_bytes_streamed_multi_byte_delimiter() {
+        let crlf = Options {
+            line_delimiter: b"\r\n".to_vec(),
+            ..Options::default()
+        };
+        // The delimiter is split between reads for some capacities, and a lone CR is kept.
+        let input = b"ab\r\n\r\r\n\rc\r\rd\r";
+        assert_eq!(
+            cut_bytes_streamed(input, &crlf, ByteFormat::Raw, "1-"),
+            b"ab\r\n\r\r\n\rc\r\rd\r\r\n"
+        );
+        assert_eq!(
+            cut_bytes_streamed(input, &crlf, ByteFormat::Raw, "2-3"),
+            b"b\r\n\r\nc\r\r\n"
+        );
+        let skip = Options {
+            skip_lines: 2,
+            output_line_delimiter: Option::Some(b";".to_vec()),
+            ..crlf
+        };
+        assert_eq!(
+            cut_bytes_streamed(input, &skip, ByteFormat::Raw, "1-"),
+            b"\rc\r\rd\r;"
+        );
+
+        // A partial match is written once the next byte shows it is not the delimiter.
+        let options = Options {
+            line_delimiter: b"<eol>".to_vec(),
+            ..Options::default()
+        };
+        assert_eq!(
+            cut_bytes_streamed(b"a<eo<eol><<eol>b<eo", &options, ByteFormat::Raw, "2-"),
+            b"<eo<eol><eol><eo<eol>"
+        );
+    }
+
+    #[test]
+    fn copies_input() {
+        let keep_cr = Options {
+            keep_cr: true,
+            ..Options::default()
+        };
+        let mode = |ranges: &str| Mode::bytes(ranges.parse().unwrap());
+        assert!(mode("1-").copies_input(&keep_cr));
+        assert!(!mode("1-").copies_input(&Options::default()));
+        assert!(!mode("2-").copies_input(&keep_cr));
+        assert!(!Mode::Bytes(
+            "1-".parse().unwrap(),
+            ByteFormat::Base64(Base64Alphabet::Standard)
+        )
+        .copies_input(&keep_cr));
+        assert!(!Mode::characters("1-".parse().unwrap()).copies_input(&keep_cr));
+        assert!(!Mode::fields(',', "1-".parse().unwrap()).copies_input(&keep_cr));
+
+        let nul = Options {
+            line_delimiter: b"\0".to_vec(),
+            ..Options::default()
+        };
+        assert!(mode("1-").copies_input(&nul));
+        for options in [
+            Options {
+                skip_lines: 1,
+                ..nul.clone()
+            },
+            Options {
+                max_lines: Option::Some(1),
+                ..nul.clone()
+            },
+            Options {
+                prefix: b">".to_vec(),
+                ..nul.clone()
+            },
+            Options {
+                output_line_delimiter: Option::Some(b"\n".to_vec()),
+                ..nul.clone()
+            },
+        ] {
+            assert!(!mode("1-").copies_input(&options), "{:?}", options);
+        }
+    }
+
+    #[test]
+    fn cut_bytes_hex() {
+        let input = &[0, 1, 0x7f, 0xab, 0xff, b'\n', b'a', b'b', b'c'];
+        assert_cut_bytes_hex(input, "1-", HexFormat::Plain, "00017fabff\n616263\n");
+        assert_cut_bytes_hex(input, "2,4", HexFormat::Plain, "01ab\n62\n");
+        assert_cut_bytes_hex(input, "1-", HexFormat::Spaced, "00 01 7f ab ff\n61 62 63\n");
+        assert_cut_bytes_hex(input, "3", HexFormat::Spaced, "7f\n63\n");
+        assert_cut_bytes_hex(input, "4-", HexFormat::Prefixed, "0xab 0xff\n\n");
+        assert_cut_bytes_hex(input, "1,3", HexFormat::Prefixed, "0x00 0x7f\n0x61 0x63\n");
+        assert_cut_bytes_hex(&[], "1-", HexFormat::Plain, "");
+        assert_cut_bytes_hex(b"\n", "1-", HexFormat::Spaced, "\n");
+    }
+
+    fn assert_cut_bytes_hex(input: &[u8], ranges: &str, format: HexFormat, expected: &str) {
+        let output =
+            cut_bytes_streamed(input, &delimited_by(b'\n'), ByteFormat::Hex(format), ranges);
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn cut_bytes_base64() {
+        use Base64Alphabet::{Standard, Url};
+
+        // RFC 4648 test vectors.
+        assert_cut_bytes_base64(
+            b"f\nfo\nfoo\nfoob",
+            "1-",
+            Standard,
+            "Zg==\nZm8=\nZm9v\nZm9vYg==\n",
+        );
+        assert_cut_bytes_base64(b"fooba\nfoobar", "1-", Standard, "Zm9vYmE=\nZm9vYmFy\n");
+        assert_cut_bytes_base64(b"f\nfo\nfoo\nfoob", "1-", Url, "Zg\nZm8\nZm9v\nZm9vYg\n");
+
+        // Alphabets differ for the last two symbols.
+        assert_cut_bytes_base64(&[0xfb, 0xff, 0xbf], "1-", Standard, "+/+/\n");
+        assert_cut_bytes_base64(&[0xfb, 0xff, 0xbf], "1-", Url, "-_-_\n");
+
+        // Empty selections.
+        assert_cut_bytes_base64(b"abc\ndef", "4-", Standard, "\n\n");
+        assert_cut_bytes_base64(b"\n\n", "1-", Url, "\n\n");
+        assert_cut_bytes_base64(&[], "1-", Standard, "");
+    }
+
+    #[test]
+    fn cut_bytes_base64_round_trip() {
+        let input: Vec<u8> = (0..=255).filter(|b| *b != b'\n').collect();
+        for ranges in &["1-", "2-", "3-", "1,5,9-20", "100-200"] {
+            for alphabet in &[Base64Alphabet::Standard, Base64Alphabet::Url] {
+                let mut output = Vec::new();
+                super::cut_bytes(
+                    &mut &input[..],
+                    &mut output,
+                    &delimited_by(b'\n'),
+                    &mut RunState::default(),
+                    ByteFormat::Base64(*alphabet),
+                    &ranges.parse().unwrap(),
+                    &mut Buffers::default(),
+                )
+                .unwrap();
+                assert_eq!(output.pop(), Some(b'\n'));
+
+                let mut expected = Vec::new();
+                super::cut_bytes(
+                    &mut &input[..],
+                    &mut expected,
+                    &delimited_by(b'\n'),
+                    &mut RunState::default(),
+                    ByteFormat::Raw,
+                    &ranges.parse().unwrap(),
+                    &mut Buffers::default(),
+                )
+                .unwrap();
+                expected.pop();
+
+                assert_eq!(decode_base64(&output), expected);
+            }
+        }
+    }
+
+    fn assert_cut_bytes_base64(
+        input: &[u8],
+        ranges: &str,
+        alphabet: Base64Alphabet,
+        expected: &str,
+    ) {
+        let output = cut_bytes_streamed(
+            input,
+            &delimited_by(b'\n'),
+            ByteFormat::Base64(alphabet),
+            ranges,
+        );
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    // Decodes base64 in either alphabet, with or without padding.
+    fn decode_base64(encoded: &[u8]) -> Vec<u8> {
+        let values: Vec<u32> = encoded
+            .iter()
+            .filter(|b| **b != b'=')
+            .map(|b| match b {
+                b'A'..=b'Z' => u32::from(b - b'A'),
+                b'a'..=b'z' => u32::from(b - b'a') + 26,
+                b'0'..=b'9' => u32::from(b - b'0') + 52,
+                b'+' | b'-' => 62,
+                b'/' | b'_' => 63,
+                _ => panic!("Invalid base64 symbol {}", b),
+            })
+            .collect();
+
+        let mut decoded = Vec::new();
+        for chunk in values.chunks(4) {
+            let group = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |group, (i, value)| group | value << (18 - 6 * i));
+            for i in 0..chunk.len() - 1 {
+                decoded.push((group >> (16 - 8 * i)) as u8);
+            }
+        }
+        decoded
+    }
+
     #[test]
     fn cut_characters() {
         // One line.
@@ -528,10 +2860,96 @@ mod tests {
         assert_cut_chars("abcd\0abcd", 0, "3", "c\0c\0");
     }
 
-    fn assert_cut_chars(input: &str, line_delimiter: u8, ranges: &str, expected: &str) {
-        let mut output = Vec::new();
-        let ranges: Ranges = ranges.parse().unwrap();
-        super::cut_characters(&mut input.as_bytes(), &mut output, line_delimiter, &ranges).unwrap();
+    #[test]
+    fn cut_characters_invalid_after_selection() {
+        let cut = |input: &[u8], ranges: &str, invalid_utf8: InvalidUtf8| {
+            let options = Options {
+                invalid_utf8,
+                ..Options::default()
+            };
+            let mut state = RunState::default();
+            let mut output = Vec::new();
+            let result = super::cut_characters(
+                &mut &input[..],
+                &mut output,
+                &options,
+                &mut state,
+                &ranges.parse().unwrap(),
+                &mut Buffers::default(),
+            );
+            result.map(|()| (String::from_utf8(output).unwrap(), state.rejects))
+        };
+        let selected = |output: &str| Option::Some((output.to_string(), Vec::new()));
+
+        // Invalid UTF-8 after the last selected character is still an error, or skipped.
+        let input = &["abγ".as_bytes(), b"\xff\n", "δ😀😀".as_bytes(), b"\xfe\n"].concat();
+        for ranges in ["1-3", "2"] {
+            assert_eq!(
+                cut(input, ranges, InvalidUtf8::Fail)
+                    .unwrap_err()
+                    .to_string(),
+                "line 1 (byte 0): invalid UTF-8"
+            );
+            let (output, rejects) = cut(input, ranges, InvalidUtf8::Skip).unwrap();
+            assert_eq!((output.as_str(), rejects.len()), ("", 2));
+        }
+        assert!(cut(b"a\xce\xb1\xff\n", "2", InvalidUtf8::Fail).is_err());
+
+        // When lossy, it is not decoded, since it would not change the selected characters.
+        assert_eq!(
+            cut(input, "1-3", InvalidUtf8::Lossy).ok(),
+            selected("abγ\nδ😀😀\n")
+        );
+        assert_eq!(
+            cut(input, "2", InvalidUtf8::Lossy).ok(),
+            selected("b\n😀\n")
+        );
+        assert_eq!(
+            cut(b"a\xce\xb1\xff\n", "2", InvalidUtf8::Lossy).unwrap().0,
+            "\u{3b1}\n"
+        );
+
+        // Selected characters which are invalid are replaced, as when the selection is not bounded.
+        for ranges in ["1-4", "3-"] {
+            assert_eq!(
+                cut(input, ranges, InvalidUtf8::Fail)
+                    .unwrap_err()
+                    .to_string(),
+                "line 1 (byte 0): invalid UTF-8"
+            );
+            assert_eq!(
+                cut(input, ranges, InvalidUtf8::Lossy).unwrap().0,
+                if ranges == "3-" {
+                    "γ\u{fffd}\n😀\u{fffd}\n"
+                } else {
+                    "abγ\u{fffd}\nδ😀😀\u{fffd}\n"
+                }
+            );
+            assert_eq!(cut(input, ranges, InvalidUtf8::Skip).unwrap().1.len(), 2);
+        }
+        // A selection past the end of a short record decodes all of it.
+        assert_eq!(
+            cut(b"a\xff\n", "1-9", InvalidUtf8::Lossy).unwrap().0,
+            "a\u{fffd}\n"
+        );
+        assert_eq!(
+            cut("aβ\n".as_bytes(), "1-9", InvalidUtf8::Fail).ok(),
+            selected("aβ\n")
+        );
+    }
+
+    fn assert_cut_chars(input: &str, line_delimiter: u8, ranges: &str, expected: &str) {
+        let mut output = Vec::new();
+        let ranges: Ranges = ranges.parse().unwrap();
+        super::cut_characters(
+            &mut input.as_bytes(),
+            &mut output,
+            &delimited_by(line_delimiter),
+            &mut RunState::default(),
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
         let actual = String::from_utf8(output).unwrap();
         assert_eq!(actual, expected);
     }
@@ -861,6 +3279,220 @@ mod tests {
         assert_cut_fields_with_char("a b c\0d e f", "1,3", 0, ' ', " ", false, "a c\0d f\0");
     }
 
+    /// Ranges of fields, the fields they select, and the fields they do not select.
+    type FieldCase = (&'static str, &'static str, &'static str);
+
+    /// Fields of lines with leading, trailing and adjacent delimiters, as GNU cut selects them with
+    /// `-d, -f<ranges>`, and with `--complement`. Each delimiter ends a field, so a line which ends
+    /// with a delimiter has an empty last field, and a line with `n` delimiters has `n + 1` fields.
+    const EMPTY_FIELDS: &[(&str, &[FieldCase])] = &[
+        (
+            "a,b,c,",
+            &[
+                ("1", "a", "b,c,"),
+                ("2", "b", "a,c,"),
+                ("4", "", "a,b,c"),
+                ("2-", "b,c,", "a"),
+                ("-2", "a,b", "c,"),
+                ("1,3", "a,c", "b,"),
+            ],
+        ),
+        (
+            ",a,b",
+            &[
+                ("1", "", "a,b"),
+                ("2", "a", ",b"),
+                ("4", "", ",a,b"),
+                ("2-", "a,b", ""),
+                ("-2", ",a", "b"),
+                ("1,3", ",b", "a"),
+            ],
+        ),
+        (
+            "a,,b",
+            &[
+                ("1", "a", ",b"),
+                ("2", "", "a,b"),
+                ("4", "", "a,,b"),
+                ("2-", ",b", "a"),
+                ("-2", "a,", "b"),
+                ("1,3", "a,b", ""),
+            ],
+        ),
+        (
+            ",,",
+            &[
+                ("1", "", ","),
+                ("2", "", ","),
+                ("4", "", ",,"),
+                ("2-", ",", ""),
+                ("-2", ",", ""),
+                ("1,3", ",", ""),
+            ],
+        ),
+        (
+            ",",
+            &[
+                ("1", "", ""),
+                ("2", "", ""),
+                ("4", "", ","),
+                ("2-", "", ""),
+                ("-2", ",", ""),
+                ("1,3", "", ""),
+            ],
+        ),
+    ];
+
+    #[test]
+    fn cut_fields_empty_fields() {
+        #[cfg(feature = "regex")]
+        let (regex, str_regex) = (Regex::new(",").unwrap(), regex::Regex::new(",").unwrap());
+        for &(line, cases) in EMPTY_FIELDS {
+            for &(ranges, selected, complement) in cases {
+                let complemented = ranges.parse::<Ranges>().unwrap().complement();
+                for (ranges, expected) in [
+                    (ranges.parse::<Ranges>().unwrap(), selected),
+                    (complemented, complement),
+                ] {
+                    let message = format!("{:?} {:?}", line, ranges);
+                    let input = format!("{}\n", line);
+                    let expected_line = format!("{}\n", expected);
+                    // Suppressing lines without a delimiter makes no difference, as each line has
+                    // one.
+                    for suppress in [false, true] {
+                        let options = field_options(",", suppress);
+                        let mut output = Vec::new();
+                        super::cut_fields(
+                            &mut input.as_bytes(),
+                            &mut output,
+                            &Options::default(),
+                            &mut RunState::default(),
+                            ',',
+                            &options,
+                            &ranges,
+                            &mut Buffers::default(),
+                        )
+                        .unwrap();
+                        assert_eq!(
+                            String::from_utf8(output).unwrap(),
+                            expected_line,
+                            "{}",
+                            message
+                        );
+
+                        #[cfg(feature = "regex")]
+                        {
+                            let mut output = Vec::new();
+                            super::cut_fields(
+                                &mut input.as_bytes(),
+                                &mut output,
+                                &Options::default(),
+                                &mut RunState::default(),
+                                &regex,
+                                &options,
+                                &ranges,
+                                &mut Buffers::default(),
+                            )
+                            .unwrap();
+                            assert_eq!(
+                                String::from_utf8(output).unwrap(),
+                                expected_line,
+                                "{}",
+                                message
+                            );
+                        }
+                    }
+
+                    assert_eq!(
+                        super::cut_line_fields(line, ',', &ranges, ","),
+                        expected,
+                        "{}",
+                        message
+                    );
+                    #[cfg(feature = "regex")]
+                    assert_eq!(
+                        super::cut_line_fields(line, &str_regex, &ranges, ","),
+                        expected,
+                        "{}",
+                        message
+                    );
+                }
+            }
+        }
+
+        // A line without a delimiter is a single field, unless it is suppressed.
+        assert_cut_fields_with_char("a", "2", b'\n', ',', ",", false, "a\n");
+        assert_cut_fields_with_char("a", "2", b'\n', ',', ",", true, "");
+    }
+
+    /// A match of a regular expression is a single delimiter however long it is, so adjacent
+    /// delimiters are only empty fields if they are separate matches, and a match at the end of a
+    /// line, such as trailing whitespace, is followed by an empty last field.
+    #[cfg(feature = "regex")]
+    #[test]
+    fn cut_fields_empty_fields_regex() {
+        assert_cut_fields_with_regex("a  b ", "1-", b'\n', r"\s+", ",", false, "a,b,\n");
+        assert_cut_fields_with_regex("a  b ", "3", b'\n', r"\s+", ",", false, "\n");
+        assert_cut_fields_with_regex(" a  b", "1-", b'\n', r"\s+", ",", false, ",a,b\n");
+        assert_cut_fields_with_regex("a  b", "1-", b'\n', r"\s", ",", false, "a,,b\n");
+        assert_cut_fields_with_regex(",,a,,", "1-", b'\n', ",+", ";", false, ";a;\n");
+        assert_cut_fields_with_regex(",,", "2", b'\n', ",+", ";", true, "\n");
+    }
+
+    /// Matches of the empty string are not delimiters, so they neither split fields nor count as
+    /// delimiters for -s.
+    #[cfg(feature = "regex")]
+    #[test]
+    fn cut_fields_regex_empty_matches() {
+        let cases: &[(&str, &str, &str, bool)] = &[
+            // Pattern, input, fields joined by ",", and whether the input has a delimiter.
+            ("x*", "axxbxc\n", "a,b,c\n", true),
+            ("x*", "xab\n", ",ab\n", true),
+            ("x*", "abc\n", "abc\n", false),
+            ("x*", "\n", "\n", false),
+            ("", "abc\n", "abc\n", false),
+            ("", "\n", "\n", false),
+            (r"\b", "a b\n", "a b\n", false),
+            (r" |\b", "a b\n", "a,b\n", true),
+        ];
+        for &(pattern, input, fields, delimited) in cases {
+            let message = format!("{:?} {:?}", pattern, input);
+            let cut = |suppress| {
+                let mut output = Vec::new();
+                super::cut_fields(
+                    &mut input.as_bytes(),
+                    &mut output,
+                    &Options::default(),
+                    &mut RunState::default(),
+                    &Regex::new(pattern).unwrap(),
+                    &field_options(",", suppress),
+                    &"1-".parse().unwrap(),
+                    &mut Buffers::default(),
+                )
+                .unwrap();
+                String::from_utf8(output).unwrap()
+            };
+            assert_eq!(cut(false), fields, "{}", message);
+            let expected = if delimited { fields } else { "" };
+            assert_eq!(cut(true), expected, "{}", message);
+
+            let line = input.trim_end_matches('\n');
+            let str_regex = regex::Regex::new(pattern).unwrap();
+            let ranges = "1-".parse().unwrap();
+            let expected = fields.trim_end_matches('\n');
+            assert_eq!(
+                super::cut_line_fields(line, &str_regex, &ranges, ","),
+                expected,
+                "{}",
+                message
+            );
+        }
+
+        // Field numbers count only non-empty matches.
+        assert_cut_fields_with_regex("axxbxc", "2", b'\n', "x*", ",", false, "b\n");
+        assert_cut_fields_with_regex("axxbxc", "3", b'\n', "x*", ",", true, "c\n");
+    }
+
     fn assert_cut_fields_with_char(
         input: &str,
         ranges: &str,
@@ -872,20 +3504,23 @@ mod tests {
     ) {
         let mut output = Vec::new();
         let ranges: Ranges = ranges.parse().unwrap();
-        super::cut_fields_with_char(
+        let options = field_options(output_delimiter, suppress);
+        super::cut_fields(
             &mut input.as_bytes(),
             &mut output,
-            line_delimiter,
+            &delimited_by(line_delimiter),
+            &mut RunState::default(),
             field_delimiter,
-            output_delimiter,
-            suppress,
+            &options,
             &ranges,
+            &mut Buffers::default(),
         )
         .unwrap();
         let actual = String::from_utf8(output).unwrap();
         assert_eq!(actual, expected);
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn cut_fields_with_regex() {
         // Empty.
@@ -1028,6 +3663,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn cut_fields_with_regex_suppress() {
         // Single line. No suppress.
@@ -1157,6 +3793,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn cut_fields_with_regex_delimiter() {
         // Single character delimiter
@@ -1254,6 +3891,7 @@ mod tests {
         );
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn cut_fields_with_regex_trailing_newline() {
         assert_cut_fields_with_regex("", "1-", b'\n', r"\s+", "\t", false, "");
@@ -1286,6 +3924,7 @@ mod tests {
         assert_cut_fields_with_regex("a b c\nd e f\n", "2", b'\n', r"\s+", "\t", false, "b\ne\n");
     }
 
+    #[cfg(feature = "regex")]
     #[test]
     fn cut_fields_with_regex_line_delimiter() {
         assert_cut_fields_with_regex(
@@ -1325,6 +3964,253 @@ mod tests {
         assert_cut_fields_with_regex("a1b2c\0a1b2c", "1,3", 0, r"\d", " ", false, "a c\0a c\0");
     }
 
+    #[test]
+    fn select_fields_stops_after_last_field() {
+        let line = b"a,b,c,d,e";
+        let consumed = Cell::new(0);
+        let delimiters = || {
+            memchr::memchr_iter(b',', line).map(|index| {
+                consumed.set(consumed.get() + 1);
+                (index, index + 1)
+            })
+        };
+        // Number of delimiters which are found for each selection, out of the four in the line.
+        for (ranges, expected, delimiters_found) in [
+            ("1", "a", 1),
+            ("2", "b", 2),
+            ("1,3", "a,c", 3),
+            ("4-", "d,e", 4),
+            ("9", "", 4),
+        ] {
+            consumed.set(0);
+            let mut output = Vec::new();
+            super::select_fields(
+                line,
+                delimiters,
+                &ranges.parse().unwrap(),
+                &field_options(",", false),
+                &mut Vec::new(),
+                &mut false,
+                &mut output,
+            )
+            .unwrap();
+            assert_eq!(output, expected.as_bytes(), "{}", ranges);
+            assert_eq!(consumed.get(), delimiters_found, "{}", ranges);
+        }
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn cut_fields_with_regex_invalid_utf8() {
+        let cut = |input: &[u8], field_delimiter: &str, ranges: &str, suppress: bool| {
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut &input[..],
+                &mut output,
+                &Options::default(),
+                &mut RunState::default(),
+                &Regex::new(field_delimiter).unwrap(),
+                &field_options("|", suppress),
+                &ranges.parse().unwrap(),
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            output
+        };
+
+        let input = b"a\xff b\xfe\n\xff\xff  c\n\xfe\n";
+        assert_eq!(cut(input, r"\s+", "2", false), b"b\xfe\nc\n\xfe\n");
+        assert_eq!(
+            cut(input, r"\s+", "1-", false),
+            b"a\xff|b\xfe\n\xff\xff|c\n\xfe\n"
+        );
+        assert_eq!(cut(input, r"\s+", "1", true), b"a\xff\n\xff\xff\n");
+        // Fields after the last one selected are not split.
+        assert_eq!(cut(b"a,b,\xff,\xfe\xfe\n", ",", "2", false), b"b\n");
+        // Unicode classes still match characters, between bytes which are not UTF-8.
+        assert_eq!(
+            cut(
+                b"a\xce\xb1\xce\xb2b\xff\xce\xb3c\n",
+                r"\p{Greek}+",
+                "2-",
+                false
+            ),
+            b"b\xff|c\n"
+        );
+        // Invalid bytes can be matched as part of the delimiter.
+        assert_eq!(cut(b"a\xff\xffb\n", r"(?-u)\xff+", "2", false), b"b\n");
+    }
+
+    #[test]
+    fn cut_sub_fields() {
+        let cut = |ranges: &str, sub_fields: SubFields, input: &str| {
+            let field_options = FieldOptions {
+                output_delimiter: b",".to_vec(),
+                sub_fields: Option::Some(sub_fields),
+                ..FieldOptions::default()
+            };
+            let mode = Mode::FieldsChar(ranges.parse().unwrap(), ',', field_options);
+            let mut output = Vec::new();
+            mode.cut(
+                &mut input.as_bytes(),
+                &mut output,
+                &Options::default(),
+                &mut RunState::default(),
+            )
+            .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let sub =
+            |delimiter: &str, ranges: &str| SubFields::new(delimiter, ranges.parse().unwrap());
+
+        let input = "10:11:12,host,key=a;b;c\n";
+        assert_eq!(cut("3", sub(";", "2"), input), "b\n");
+        assert_eq!(cut("3", sub(";", "2-"), input), "b;c\n");
+        assert_eq!(cut("1,3", sub(":", "1,3"), input), "10:12,key=a;b;c\n");
+        // Fields without the sub-delimiter are written whole, even if no sub-field is selected.
+        assert_eq!(cut("2-", sub(";", "3"), input), "host,c\n");
+        assert_eq!(cut("2", sub(";", "3"), input), "host\n");
+        // Selected fields are still delimited if no sub-field is selected from them.
+        assert_eq!(cut("1,3", sub(";", "5"), "a;b,x,c;d\n"), ",\n");
+        // Lines without the delimiter are written whole.
+        assert_eq!(cut("1", sub(";", "2"), "a;b\n"), "a;b\n");
+
+        let mut sub_fields = sub("→", "1,3");
+        sub_fields.output_delimiter = b" ".to_vec();
+        assert_eq!(cut("2", sub_fields, "x,α→β→γ→δ\n"), "α γ\n");
+    }
+
+    #[test]
+    fn cut_between() {
+        let cut = |mode: &Mode, input: &[u8]| {
+            let mut output = Vec::new();
+            mode.cut(
+                &mut &input[..],
+                &mut output,
+                &Options::default(),
+                &mut RunState::default(),
+            )
+            .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        let between = |start: &str, end: &str, suppress: bool, all_occurrences: bool| {
+            let options = BetweenOptions {
+                output_delimiter: b",".to_vec(),
+                suppress,
+                all_occurrences,
+            };
+            Mode::Between(start.to_string(), end.to_string(), options)
+        };
+
+        let input = b"a=1&user=amy&user=bob&\nuser=cy\nx=1\nuser=&\n";
+        assert_eq!(
+            cut(&between("user=", "&", false, false), input),
+            "amy\n\n\n\n"
+        );
+        assert_eq!(cut(&between("user=", "&", true, false), input), "amy\n\n");
+        assert_eq!(
+            cut(&between("user=", "&", false, true), input),
+            "amy,bob\n\n\n\n"
+        );
+        assert_eq!(
+            cut(&between("user=", "&", true, true), input),
+            "amy,bob\n\n"
+        );
+
+        // Overlapping and multi-byte markers.
+        assert_eq!(cut(&between("aa", "a", false, true), b"aaaa\n"), "\n");
+        assert_eq!(
+            cut(&between("«", "»", false, true), "«α» «βγ»\n".as_bytes()),
+            "α,βγ\n"
+        );
+
+        // Records must be valid UTF-8 for literal markers.
+        let mode = Mode::between("<", ">");
+        let mut output = Vec::new();
+        let result = mode.cut(
+            &mut &b"<\xff>\n"[..],
+            &mut output,
+            &Options::default(),
+            &mut RunState::default(),
+        );
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn cut_between_regex() {
+        let options = BetweenOptions {
+            output_delimiter: b" ".to_vec(),
+            all_occurrences: true,
+            ..BetweenOptions::default()
+        };
+        let start = Regex::new("[0-9]+:").unwrap();
+        let end = Regex::new(";|$").unwrap();
+        let mode = Mode::BetweenRegex(start, end, options);
+        let mut output = Vec::new();
+        mode.cut(
+            &mut &b"1:a;22:b\nc\n3:\xff;\n"[..],
+            &mut output,
+            &Options::default(),
+            &mut RunState::default(),
+        )
+        .unwrap();
+        assert_eq!(output, b"a b\n\n\xff\n");
+    }
+
+    /// `^` and `$` match at the start and end of each record, which never includes its line
+    /// delimiter. A match at the start is preceded by an empty first field, and a match at the end
+    /// is followed by an empty last field. Only records with a match are delimited for -s.
+    #[cfg(feature = "regex")]
+    #[test]
+    fn cut_fields_regex_anchors() {
+        // Leading anchor.
+        let leading = r"^\s+";
+        assert_cut_fields_with_regex(
+            "  a b\nc d",
+            "1-",
+            b'\n',
+            leading,
+            ",",
+            false,
+            ",a b\nc d\n",
+        );
+        assert_cut_fields_with_regex("  a b\nc d", "2", b'\n', leading, ",", false, "a b\nc d\n");
+        assert_cut_fields_with_regex("  a b\nc d", "1", b'\n', leading, ",", true, "\n");
+        assert_cut_fields_with_regex("  a b\nc d", "2", b'\n', leading, ",", true, "a b\n");
+        // Only the start of the record is matched, not the start of each field.
+        assert_cut_fields_with_regex("a  b", "1-", b'\n', leading, ",", false, "a  b\n");
+
+        // Trailing anchor.
+        let trailing = ";$";
+        assert_cut_fields_with_regex(
+            "a;b;\na;b",
+            "1-",
+            b'\n',
+            trailing,
+            ",",
+            false,
+            "a;b,\na;b\n",
+        );
+        assert_cut_fields_with_regex("a;b;\na;b", "2", b'\n', trailing, ",", false, "\na;b\n");
+        assert_cut_fields_with_regex("a;b;\na;b", "1", b'\n', trailing, ",", true, "a;b\n");
+        // A carriage return before the newline is not part of the record.
+        assert_cut_fields_with_regex("a;b;\r\n", "1", b'\n', trailing, ",", false, "a;b\n");
+
+        // Both anchors.
+        let both = r"^\s+|\s+$";
+        assert_cut_fields_with_regex(" a b \na b", "1-", b'\n', both, ",", false, ",a b,\na b\n");
+        assert_cut_fields_with_regex(" a b \na b", "2", b'\n', both, ",", true, "a b\n");
+        assert_cut_fields_with_regex("a b ", "1-", b'\n', both, ",", true, "a b,\n");
+        // A record of only whitespace is one match, so it is two empty fields.
+        assert_cut_fields_with_regex("   ", "1-", b'\n', both, ",", true, ",\n");
+
+        // Records only contain a newline if it is not the line delimiter, in which case multi-line
+        // mode lets the anchors match either side of it.
+        assert_cut_fields_with_regex("a\n;b;\0", "1-", b'\0', "(?m);$", ",", false, "a\n;b,\0");
+        assert_cut_fields_with_regex("a;\nb;\0", "1-", b'\0', "(?m);$", ",", false, "a,\nb,\0");
+    }
+
     fn assert_cut_fields_with_regex(
         input: &str,
         ranges: &str,
@@ -1336,14 +4222,16 @@ mod tests {
     ) {
         let mut output = Vec::new();
         let ranges: Ranges = ranges.parse().unwrap();
-        super::cut_fields_with_regex(
+        let options = field_options(output_delimiter, suppress);
+        super::cut_fields(
             &mut input.as_bytes(),
             &mut output,
-            line_delimiter,
+            &delimited_by(line_delimiter),
+            &mut RunState::default(),
             &Regex::new(field_delimiter).unwrap(),
-            &output_delimiter.to_string(),
-            suppress,
+            &options,
             &ranges,
+            &mut Buffers::default(),
         )
         .unwrap();
         let actual = String::from_utf8(output).unwrap();
@@ -1351,53 +4239,1629 @@ mod tests {
     }
 
     #[test]
-    fn cut_empty_ranges() {
-        let empty_ranges = "1-".parse::<Ranges>().unwrap().complement();
+    fn cut_fields_byte_offsets() {
+        assert_byte_offsets_with_char("a b c", "1-", ' ', false, "0-1 2-3 4-5\n");
+        assert_byte_offsets_with_char("a b c", "2", ' ', false, "2-3\n");
+        assert_byte_offsets_with_char("ab  cd", "1-", ' ', false, "0-2 3-3 4-6\n");
+        assert_byte_offsets_with_char(" a ", "1-", ' ', false, "0-0 1-2 3-3\n");
+        assert_byte_offsets_with_char("abc\nd e", "1-", ' ', false, "0-3\n0-1 2-3\n");
+        assert_byte_offsets_with_char("abc\nd e", "1-", ' ', true, "0-1 2-3\n");
+
+        // Multi-byte characters.
+        assert_byte_offsets_with_char("αβ γδ εζ", "1-", ' ', false, "0-4 5-9 10-14\n");
+        assert_byte_offsets_with_char("αβ→γδ→εζ", "2-", '→', false, "7-11 14-18\n");
+        assert_byte_offsets_with_char("😀😁", "1", ' ', false, "0-8\n");
+
+        #[cfg(feature = "regex")]
+        {
+            assert_byte_offsets_with_regex("a b\tc", "1-", r"\s+", false, "0-1 2-3 4-5\n");
+            assert_byte_offsets_with_regex("a   b c", "1,3", r"\s+", false, "0-1 6-7\n");
+            assert_byte_offsets_with_regex("αβ  γδ εζ", "2-", r"\s+", false, "6-10 11-15\n");
+            assert_byte_offsets_with_regex("αβγ", "1", r"\s+", false, "0-6\n");
+            assert_byte_offsets_with_regex("αβγ", "1", r"\s+", true, "");
+        }
+    }
+
+    fn assert_byte_offsets_with_char(
+        input: &str,
+        ranges: &str,
+        field_delimiter: char,
+        suppress: bool,
+        expected: &str,
+    ) {
+        let mut output = Vec::new();
+        let options = FieldOptions {
+            byte_offsets: true,
+            ..field_options(" ", suppress)
+        };
+        super::cut_fields(
+            &mut input.as_bytes(),
+            &mut output,
+            &delimited_by(b'\n'),
+            &mut RunState::default(),
+            field_delimiter,
+            &options,
+            &ranges.parse().unwrap(),
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[cfg(feature = "regex")]
+    fn assert_byte_offsets_with_regex(
+        input: &str,
+        ranges: &str,
+        field_delimiter: &str,
+        suppress: bool,
+        expected: &str,
+    ) {
+        let mut output = Vec::new();
+        let options = FieldOptions {
+            byte_offsets: true,
+            ..field_options(" ", suppress)
+        };
+        super::cut_fields(
+            &mut input.as_bytes(),
+            &mut output,
+            &delimited_by(b'\n'),
+            &mut RunState::default(),
+            &Regex::new(field_delimiter).unwrap(),
+            &options,
+            &ranges.parse().unwrap(),
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    #[test]
+    fn cut_fields_header_out() {
+        let bounded = || HeaderOut::bounded(vec!["x".to_string(), "y".to_string()]);
+        let unbounded = |names: &[&str]| {
+            HeaderOut::unbounded(names.iter().map(|name| name.to_string()).collect())
+        };
+
+        assert_header_out("a,b,c\nd,e,f", "1,3", bounded(), false, "x,y\na,c\nd,f\n");
+        assert_header_out("a,b\nd,e,f", "2-3", bounded(), false, "x,y\nb\ne,f\n");
+        assert_header_out("abc\nd,e,f", "1,3", bounded(), false, "x,y\nabc\nd,f\n");
+        assert_header_out("abc\nd,e,f", "1,3", bounded(), true, "x,y\nd,f\n");
+
+        assert_header_out("a,b,c\nd", "2-", unbounded(&[]), false, "f2,f3\nb,c\nd\n");
+        assert_header_out(
+            "a,b,c,d",
+            "1,3-",
+            unbounded(&["x"]),
+            false,
+            "x,f3,f4\na,c,d\n",
+        );
+        assert_header_out(
+            "a,b,c,d",
+            "1-",
+            unbounded(&["w", "x", "y", "z"]),
+            false,
+            "w,x,y,z\na,b,c,d\n",
+        );
+        assert_header_out("abc\nd,e,f", "2-", unbounded(&[]), true, "f2,f3\ne,f\n");
+        assert_header_out(
+            "abc\nd,e,f",
+            "1-",
+            unbounded(&[]),
+            false,
+            "f1\nabc\nd,e,f\n",
+        );
+
+        // No header without records.
+        assert_header_out("", "1,3", bounded(), false, "");
+        assert_header_out("abc", "1,3", bounded(), true, "");
+    }
+
+    fn assert_header_out(
+        input: &str,
+        ranges: &str,
+        header: HeaderOut,
+        suppress: bool,
+        expected: &str,
+    ) {
+        let ranges: Ranges = ranges.parse().unwrap();
+        let options = FieldOptions {
+            header: Option::Some(header),
+            ..field_options(",", suppress)
+        };
+
+        let mut state = RunState::default();
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut input.as_bytes(),
+            &mut output,
+            &delimited_by(b'\n'),
+            &mut state,
+            ',',
+            &options,
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        #[cfg(feature = "regex")]
+        {
+            // Header is only written once, even when the state is reused.
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut input.as_bytes(),
+                &mut output,
+                &delimited_by(b'\n'),
+                &mut state,
+                &Regex::new(",").unwrap(),
+                &options,
+                &ranges,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            let records = expected.split_once('\n').map_or("", |(_, records)| records);
+            assert_eq!(String::from_utf8(output).unwrap(), records);
+        }
+    }
+
+    #[test]
+    fn cut_fields_template() {
+        let input = "a,b,c\nd,e\nf\n";
+        assert_template(input, "{2}:{1}", b'\n', false, "b:a\ne:d\n:f\n");
+        assert_template(input, "{3:-?}", b'\n', false, "c\n?\n?\n");
+        assert_template(input, "{3:-?}", b'\n', true, "c\n?\n");
+        assert_template(input, "{{{1}}}", b'\n', false, "{a}\n{d}\n{f}\n");
+        assert_template("α,βγ\0δ", "{2}→{1}", 0, false, "βγ→α\0→δ\0");
+    }
+
+    fn assert_template(
+        input: &str,
+        template: &str,
+        line_delimiter: u8,
+        suppress: bool,
+        expected: &str,
+    ) {
+        let template: crate::template::Template = template.parse().unwrap();
+        let ranges = template.ranges();
+        let options = FieldOptions {
+            template: Option::Some(template),
+            ..field_options("", suppress)
+        };
+
         let mut output = Vec::new();
+        super::cut_fields(
+            &mut input.as_bytes(),
+            &mut output,
+            &delimited_by(line_delimiter),
+            &mut RunState::default(),
+            ',',
+            &options,
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+        #[cfg(feature = "regex")]
+        {
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut input.as_bytes(),
+                &mut output,
+                &delimited_by(line_delimiter),
+                &mut RunState::default(),
+                &Regex::new(",").unwrap(),
+                &options,
+                &ranges,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), expected);
+        }
+    }
 
+    #[test]
+    fn cut_prefix_suffix() {
+        let options = Options {
+            prefix: b"<".to_vec(),
+            suffix: b">".to_vec(),
+            ..Options::default()
+        };
+        let ranges = "2-".parse::<Ranges>().unwrap();
+
+        let mut output = Vec::new();
         super::cut_bytes(
-            &mut "abc\ndef".as_bytes(),
+            &mut "abc\nd\n".as_bytes(),
             &mut output,
-            b'\n',
-            &empty_ranges,
+            &options,
+            &mut RunState::default(),
+            ByteFormat::Raw,
+            &ranges,
+            &mut Buffers::default(),
         )
         .unwrap();
-        assert_eq!(output, vec![b'\n', b'\n']);
+        assert_eq!(String::from_utf8(output).unwrap(), "<bc>\n<>\n");
 
-        output.clear();
+        let mut output = Vec::new();
         super::cut_characters(
-            &mut "abc\ndef".as_bytes(),
+            &mut "αβγ\nδ".as_bytes(),
             &mut output,
-            b'\n',
-            &empty_ranges,
+            &options,
+            &mut RunState::default(),
+            &ranges,
+            &mut Buffers::default(),
         )
         .unwrap();
-        assert_eq!(output, vec![b'\n', b'\n']);
+        assert_eq!(String::from_utf8(output).unwrap(), "<βγ>\n<>\n");
 
-        output.clear();
-        super::cut_fields_with_char(
-            &mut "a b c\nd e f".as_bytes(),
+        // Suppressed lines are not written, so they are not surrounded by the prefix and suffix.
+        let input = "a,b,c\nd\ne,f\n";
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut input.as_bytes(),
             &mut output,
-            b'\n',
-            ' ',
-            " ",
-            false,
-            &empty_ranges,
+            &options,
+            &mut RunState::default(),
+            ',',
+            &field_options(",", true),
+            &ranges,
+            &mut Buffers::default(),
         )
         .unwrap();
-        assert_eq!(output, vec![b'\n', b'\n']);
+        assert_eq!(String::from_utf8(output).unwrap(), "<b,c>\n<f>\n");
 
-        output.clear();
-        super::cut_fields_with_regex(
-            &mut "a b c\nd e f".as_bytes(),
+        #[cfg(feature = "regex")]
+        {
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut input.as_bytes(),
+                &mut output,
+                &options,
+                &mut RunState::default(),
+                &Regex::new(",").unwrap(),
+                &field_options(",", false),
+                &ranges,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), "<b,c>\n<d>\n<f>\n");
+        }
+    }
+
+    #[test]
+    fn cut_crlf() {
+        let input = "ab,c\r\nd\re,f\r\n\r\ng,h\r";
+        let keep_cr = Options {
+            keep_cr: true,
+            ..Options::default()
+        };
+        let all = "1-".parse::<Ranges>().unwrap();
+        let last = "2".parse::<Ranges>().unwrap();
+
+        let mut output = Vec::new();
+        super::cut_bytes(
+            &mut input.as_bytes(),
             &mut output,
-            b'\n',
-            &Regex::new(&r"\s+").unwrap(),
-            " ",
-            false,
-            &empty_ranges,
+            &Options::default(),
+            &mut RunState::default(),
+            ByteFormat::Raw,
+            &all,
+            &mut Buffers::default(),
         )
         .unwrap();
-        assert_eq!(output, vec![b'\n', b'\n']);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "ab,c\nd\re,f\n\ng,h\r\n"
+        );
+
+        let mut output = Vec::new();
+        super::cut_bytes(
+            &mut input.as_bytes(),
+            &mut output,
+            &keep_cr,
+            &mut RunState::default(),
+            ByteFormat::Raw,
+            &all,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), format!("{}\n", input));
+
+        let mut output = Vec::new();
+        super::cut_characters(
+            &mut input.as_bytes(),
+            &mut output,
+            &Options::default(),
+            &mut RunState::default(),
+            &all,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "ab,c\nd\re,f\n\ng,h\r\n"
+        );
+
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut input.as_bytes(),
+            &mut output,
+            &Options::default(),
+            &mut RunState::default(),
+            ',',
+            &field_options(",", false),
+            &last,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "c\nf\n\nh\r\n");
+
+        #[cfg(feature = "regex")]
+        {
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut input.as_bytes(),
+                &mut output,
+                &keep_cr,
+                &mut RunState::default(),
+                &Regex::new(",").unwrap(),
+                &field_options(",", false),
+                &last,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), "c\r\nf\r\n\r\nh\r\n");
+        }
+
+        // Carriage returns are not removed from zero terminated records.
+        let mut output = Vec::new();
+        super::cut_bytes(
+            &mut "a\r\0b\r\n\0".as_bytes(),
+            &mut output,
+            &delimited_by(0),
+            &mut RunState::default(),
+            ByteFormat::Raw,
+            &all,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a\r\0b\r\n\0");
+    }
+
+    #[test]
+    fn cut_skip_lines() {
+        let input = "ab,c\nd,ef\ng\n";
+        let skip = |skip_lines| Options {
+            skip_lines,
+            ..Options::default()
+        };
+        let all = "1-".parse::<Ranges>().unwrap();
+
+        for (skip_lines, expected) in &[(0, "ab,c\nd,ef\ng\n"), (1, "d,ef\ng\n"), (3, ""), (10, "")]
+        {
+            let mut output = Vec::new();
+            super::cut_bytes(
+                &mut input.as_bytes(),
+                &mut output,
+                &skip(*skip_lines),
+                &mut RunState::default(),
+                ByteFormat::Raw,
+                &all,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), *expected);
+
+            let mut output = Vec::new();
+            super::cut_characters(
+                &mut input.as_bytes(),
+                &mut output,
+                &skip(*skip_lines),
+                &mut RunState::default(),
+                &all,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), *expected);
+        }
+
+        // Skipped lines are not split, so they are neither suppressed nor counted for the header.
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut input.as_bytes(),
+            &mut output,
+            &skip(1),
+            &mut RunState::default(),
+            ',',
+            &field_options(",", true),
+            &"2".parse::<Ranges>().unwrap(),
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "ef\n");
+
+        #[cfg(feature = "regex")]
+        {
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut "x\0a,b\0c,d\0".as_bytes(),
+                &mut output,
+                &Options {
+                    skip_lines: 2,
+                    ..delimited_by(0)
+                },
+                &mut RunState::default(),
+                &Regex::new(",").unwrap(),
+                &field_options(",", false),
+                &"1".parse::<Ranges>().unwrap(),
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), "c\0");
+        }
+    }
+
+    #[test]
+    fn cut_max_lines() {
+        let max = |max_lines| Options {
+            max_lines: Option::Some(max_lines),
+            ..Options::default()
+        };
+        let ranges = "2".parse::<Ranges>().unwrap();
+
+        // Input never ends, so reading must stop once enough records are written.
+        let mut output = Vec::new();
+        super::cut_bytes(
+            &mut BufReader::new(InfiniteLines(b"ab,c\n")),
+            &mut output,
+            &max(3),
+            &mut RunState::default(),
+            ByteFormat::Raw,
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "b\nb\nb\n");
+
+        let mut output = Vec::new();
+        super::cut_characters(
+            &mut BufReader::new(InfiniteLines("αβ\n".as_bytes())),
+            &mut output,
+            &max(2),
+            &mut RunState::default(),
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "β\nβ\n");
+
+        #[cfg(feature = "regex")]
+        {
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut BufReader::new(InfiniteLines(b"a,b\n")),
+                &mut output,
+                &max(1),
+                &mut RunState::default(),
+                &Regex::new(",").unwrap(),
+                &field_options(",", false),
+                &ranges,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(String::from_utf8(output).unwrap(), "b\n");
+        }
+
+        // Suppressed lines are not counted.
+        let options = max(2);
+        let mut state = RunState::default();
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut "a\nb,1\nc\nd,2\ne,3\n".as_bytes(),
+            &mut output,
+            &options,
+            &mut state,
+            ',',
+            &field_options(",", true),
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "1\n2\n");
+
+        // The limit applies across inputs unless it is per file.
+        assert!(!state.start_input(&options));
+        let options = Options {
+            max_lines_per_file: true,
+            ..options
+        };
+        assert!(state.start_input(&options));
+        assert_eq!(state.written, 0);
+    }
+
+    /// Reader which repeats the same line forever.
+    struct InfiniteLines(&'static [u8]);
+
+    impl std::io::Read for InfiniteLines {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            for (i, byte) in buf.iter_mut().enumerate() {
+                *byte = self.0[i % self.0.len()];
+            }
+            Result::Ok(buf.len() - buf.len() % self.0.len())
+        }
+    }
+
+    #[test]
+    fn cut_skip_invalid() {
+        let input = b"ab,c\nd\xff,e\n\xfe\nf,g\n";
+        let ranges = "2".parse::<Ranges>().unwrap();
+        let expected_rejects = vec![
+            Reject {
+                record: 2,
+                offset: 5,
+                bytes: b"d\xff,e".to_vec(),
+            },
+            Reject {
+                record: 3,
+                offset: 10,
+                bytes: b"\xfe".to_vec(),
+            },
+        ];
+        let skip_invalid = || Options {
+            invalid_utf8: InvalidUtf8::Skip,
+            ..Options::default()
+        };
+
+        let options = skip_invalid();
+        let mut state = RunState::default();
+        let mut output = Vec::new();
+        super::cut_characters(
+            &mut &input[..],
+            &mut output,
+            &options,
+            &mut state,
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "b\n,\n");
+        assert_eq!(state.rejects, expected_rejects);
+
+        let options = skip_invalid();
+        let mut state = RunState::default();
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut &input[..],
+            &mut output,
+            &options,
+            &mut state,
+            ',',
+            &field_options(",", false),
+            &ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "c\ng\n");
+        assert_eq!(state.rejects, expected_rejects);
+
+        // Fields separated by a regex are split without decoding the record, so nothing is
+        // skipped.
+        #[cfg(feature = "regex")]
+        {
+            let options = skip_invalid();
+            let mut state = RunState::default();
+            let mut output = Vec::new();
+            super::cut_fields(
+                &mut &input[..],
+                &mut output,
+                &options,
+                &mut state,
+                &Regex::new(",").unwrap(),
+                &field_options(",", false),
+                &ranges,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(output, b"c\ne\n\xfe\ng\n");
+            assert!(state.rejects.is_empty());
+        }
+
+        // Invalid records are an error by default.
+        let mut output = Vec::new();
+        let result = super::cut_characters(
+            &mut &input[..],
+            &mut output,
+            &Options::default(),
+            &mut RunState::default(),
+            &ranges,
+            &mut Buffers::default(),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "line 2 (byte 5): invalid UTF-8"
+        );
+
+        // Skipped lines are counted.
+        let options = Options {
+            skip_lines: 1,
+            keep_cr: false,
+            ..Options::default()
+        };
+        let result = super::cut_fields(
+            &mut &b"a\r\nb\r\n\xff\r\n"[..],
+            &mut Vec::new(),
+            &options,
+            &mut RunState::default(),
+            ',',
+            &field_options(",", false),
+            &ranges,
+            &mut Buffers::default(),
+        );
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "line 3 (byte 6): invalid UTF-8"
+        );
+    }
+
+    #[test]
+    fn decode_record_borrows_valid_records() {
+        let position = super::RecordPosition::default();
+        for invalid_utf8 in [InvalidUtf8::Fail, InvalidUtf8::Skip, InvalidUtf8::Lossy] {
+            let options = Options {
+                invalid_utf8,
+                ..Options::default()
+            };
+            let decoded = super::decode_record(
+                "aβc".as_bytes(),
+                &options,
+                &position,
+                &mut RunState::default(),
+            );
+            assert!(matches!(
+                decoded.unwrap(),
+                Option::Some(Cow::Borrowed("aβc"))
+            ));
+        }
+        let options = Options {
+            invalid_utf8: InvalidUtf8::Lossy,
+            ..Options::default()
+        };
+        let mut state = RunState::default();
+        let decoded = super::decode_record(b"a\xffc", &options, &position, &mut state).unwrap();
+        assert!(matches!(decoded, Option::Some(Cow::Owned(line)) if line == "a\u{fffd}c"));
+    }
+
+    #[test]
+    fn cut_lossy() {
+        let input = b"\xffab\nc\xce,\xb1d\n";
+        let options = Options {
+            invalid_utf8: InvalidUtf8::Lossy,
+            ..Options::default()
+        };
+
+        let mut output = Vec::new();
+        super::cut_characters(
+            &mut &input[..],
+            &mut output,
+            &options,
+            &mut RunState::default(),
+            &"1-3".parse::<Ranges>().unwrap(),
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "\u{fffd}ab\nc\u{fffd},\n"
+        );
+
+        // Fields separated by a regex are split without decoding the record, so invalid bytes are
+        // not replaced.
+        #[cfg(feature = "regex")]
+        {
+            for delimiter in &[",", "\u{fffd}"] {
+                let mut output = Vec::new();
+                super::cut_fields(
+                    &mut &input[..],
+                    &mut output,
+                    &options,
+                    &mut RunState::default(),
+                    &Regex::new(delimiter).unwrap(),
+                    &field_options("|", false),
+                    &"2".parse::<Ranges>().unwrap(),
+                    &mut Buffers::default(),
+                )
+                .unwrap();
+                let expected: &[u8] = if *delimiter == "," {
+                    b"\xffab\n\xb1d\n"
+                } else {
+                    input
+                };
+                assert_eq!(output, expected);
+            }
+        }
+
+        let mut state = RunState::default();
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut &input[..],
+            &mut output,
+            &options,
+            &mut state,
+            ',',
+            &field_options(",", true),
+            &"1".parse::<Ranges>().unwrap(),
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "c\u{fffd}\n");
+        assert!(state.rejects.is_empty());
+    }
+
+    #[test]
+    fn cut_empty_ranges() {
+        let empty_ranges = "1-".parse::<Ranges>().unwrap().complement();
+        let mut output = Vec::new();
+
+        super::cut_bytes(
+            &mut "abc\ndef".as_bytes(),
+            &mut output,
+            &delimited_by(b'\n'),
+            &mut RunState::default(),
+            ByteFormat::Raw,
+            &empty_ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(output, vec![b'\n', b'\n']);
+
+        output.clear();
+        super::cut_characters(
+            &mut "abc\ndef".as_bytes(),
+            &mut output,
+            &delimited_by(b'\n'),
+            &mut RunState::default(),
+            &empty_ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(output, vec![b'\n', b'\n']);
+
+        output.clear();
+        super::cut_fields(
+            &mut "a b c\nd e f".as_bytes(),
+            &mut output,
+            &delimited_by(b'\n'),
+            &mut RunState::default(),
+            ' ',
+            &field_options(" ", false),
+            &empty_ranges,
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(output, vec![b'\n', b'\n']);
+
+        #[cfg(feature = "regex")]
+        {
+            output.clear();
+            super::cut_fields(
+                &mut "a b c\nd e f".as_bytes(),
+                &mut output,
+                &delimited_by(b'\n'),
+                &mut RunState::default(),
+                &Regex::new(r"\s+").unwrap(),
+                &field_options(" ", false),
+                &empty_ranges,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            assert_eq!(output, vec![b'\n', b'\n']);
+        }
+    }
+
+    #[test]
+    fn cut_line_chars() {
+        let cut = |line: &'static str, ranges: &str| {
+            super::cut_line_chars(line, &ranges.parse().unwrap())
+        };
+        assert_eq!(cut("αβγδ", "1-"), "αβγδ");
+        assert_eq!(cut("αβγδ", "2-3"), "βγ");
+        assert_eq!(cut("αβγδ", "-1,3-"), "αγδ");
+        assert_eq!(cut("αβγδ", "5-"), "");
+        assert_eq!(cut("", "1"), "");
+
+        assert!(matches!(cut("αβγδ", "1-"), Cow::Borrowed("αβγδ")));
+        assert!(matches!(cut("αβγδ", "3-9"), Cow::Borrowed("γδ")));
+        assert!(matches!(cut("αβγδ", "1,3"), Cow::Owned(_)));
+        assert!(matches!(cut("αβγδ", "3,5-"), Cow::Borrowed("γ")));
+        assert_eq!(cut("αβγδ", "2,4-4"), "βδ");
+        assert_eq!(cut("aβ😀d", "3-4,9-"), "😀d");
+        assert_eq!(
+            cut("αβγδ", &format!("3-{}", usize::MAX)),
+            Cow::Borrowed("γδ")
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn cut_line_chars_selects_each_character(
+            line in "[aβ😀中]{0,12}",
+            ranges in "[1-9]{1,2}(-[1-9]{0,2})?(,[1-9]{1,2}(-[1-9]{0,2})?){0,3}",
+        ) {
+            let ranges = match ranges.parse::<Ranges>() {
+                Result::Ok(ranges) => ranges,
+                // e.g. a descending range.
+                Result::Err(_) => return Result::Ok(()),
+            };
+            let expected: String = line
+                .chars()
+                .enumerate()
+                .filter(|&(index, _)| ranges.contains(index))
+                .map(|(_, ch)| ch)
+                .collect();
+            prop_assert_eq!(super::cut_line_chars(&line, &ranges), expected);
+        }
+
+        #[test]
+        fn cut_fields_selects_each_field(
+            lines in proptest::collection::vec("[aé,]{0,8}", 0..4),
+            ranges in "[1-9](-[1-9]?)?(,[1-9](-[1-9]?)?){0,2}",
+            suppress: bool,
+        ) {
+            let ranges = match ranges.parse::<Ranges>() {
+                Result::Ok(ranges) => ranges,
+                Result::Err(_) => return Result::Ok(()),
+            };
+            let mut expected = String::new();
+            for line in &lines {
+                if !line.contains(',') {
+                    // A line without a delimiter is passed through, unless it is suppressed.
+                    if !suppress {
+                        expected += line;
+                        expected += "\n";
+                    }
+                    continue;
+                }
+                let fields: Vec<&str> = line
+                    .split(',')
+                    .enumerate()
+                    .filter(|&(index, _)| ranges.contains(index))
+                    .map(|(_, field)| field)
+                    .collect();
+                expected += &fields.join(":");
+                expected += "\n";
+            }
+
+            let field_options = FieldOptions {
+                output_delimiter: b":".to_vec(),
+                suppress,
+                ..FieldOptions::default()
+            };
+            let mode = Mode::FieldsChar(ranges, ',', field_options);
+            let input: String = lines.iter().map(|line| format!("{}\n", line)).collect();
+            let mut output = Vec::new();
+            mode.cut(&mut input.as_bytes(), &mut output, &Options::default(), &mut RunState::default()).unwrap();
+            prop_assert_eq!(String::from_utf8(output).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn cut_line_fields() {
+        let cut = |line: &'static str, ranges: &str, output_delimiter: &str| {
+            super::cut_line_fields(line, ',', &ranges.parse().unwrap(), output_delimiter)
+        };
+        assert_eq!(cut("a,b,c,d", "1-", ","), "a,b,c,d");
+        assert_eq!(cut("a,b,c,d", "1-", ":"), "a:b:c:d");
+        assert_eq!(cut("a,b,c,d", "-2,4", ","), "a,b,d");
+        assert_eq!(cut("a,,c", "2", ","), "");
+        assert_eq!(cut("a,b", "3-", ","), "");
+        // Lines without the delimiter are not split.
+        assert_eq!(cut("abcd", "2", ","), "abcd");
+
+        assert!(matches!(
+            cut("a,b,c,d", "1-", ","),
+            Cow::Borrowed("a,b,c,d")
+        ));
+        assert!(matches!(cut("a,b,c,d", "2-3", ","), Cow::Borrowed("b,c")));
+        assert!(matches!(cut("a,b,c,d", "2", ":"), Cow::Borrowed("b")));
+        assert!(matches!(cut("a,b,c,d", "2-3", ":"), Cow::Owned(_)));
+        assert!(matches!(cut("a,b,c,d", "1,3", ","), Cow::Owned(_)));
+        // Fields whose delimiters in the line are the same as the output delimiter are borrowed.
+        assert!(matches!(cut("a,,c,d", "1,3", ",,"), Cow::Borrowed("a,,c")));
+
+        let ranges: Ranges = "2-".parse().unwrap();
+        assert_eq!(
+            super::cut_line_fields("a::b::c", "::", &ranges, "::"),
+            "b::c"
+        );
+        assert_eq!(super::cut_line_fields("a::b:c", "::", &ranges, "\t"), "b:c");
+        #[cfg(feature = "regex")]
+        {
+            let delimiter = regex::Regex::new(" +").unwrap();
+            assert_eq!(
+                super::cut_line_fields("a b  c", &delimiter, &ranges, ","),
+                "b,c"
+            );
+            assert!(matches!(
+                super::cut_line_fields("a b  c d", &delimiter, &ranges, "  "),
+                Cow::Owned(_)
+            ));
+        }
+    }
+
+    #[test]
+    fn cut_fields_with_str() {
+        let mut output = Vec::new();
+        super::cut_fields(
+            &mut "a::b::c\nd:e\n::f\n".as_bytes(),
+            &mut output,
+            &Options::default(),
+            &mut RunState::default(),
+            "::",
+            &field_options("|", false),
+            &"2-".parse().unwrap(),
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "b|c\nd:e\nf\n");
+    }
+
+    #[test]
+    fn cut_fields_with_grapheme_cluster() {
+        let cut = |delimiter: &str, ranges: &str, input: &str| {
+            let mode = Mode::FieldsStr(
+                ranges.parse().unwrap(),
+                delimiter.to_string(),
+                field_options(delimiter, false),
+            );
+            let mut output = Vec::new();
+            mode.cut(
+                &mut input.as_bytes(),
+                &mut output,
+                &Options::default(),
+                &mut RunState::default(),
+            )
+            .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+
+        // A family emoji is a man, a woman and a boy joined by zero width joiners.
+        let family = "👨\u{200d}👩\u{200d}👦";
+        let line = format!("a{}b{}c\n", family, family);
+        assert_eq!(cut(family, "2", &line), "b\n");
+        assert_eq!(cut(family, "1,3", &line), format!("a{}c\n", family));
+        // Each of its characters on its own is not a delimiter.
+        assert_eq!(cut(family, "1", "a👨b👩c\n"), "a👨b👩c\n");
+
+        // "é" as an "e" followed by a combining acute accent.
+        let accented = "e\u{301}";
+        assert_eq!(cut(accented, "2-", "ae\u{301}be\u{301}c\n"), "be\u{301}c\n");
+        assert_eq!(cut(accented, "2", "abe\u{301}\n"), "\n");
+        assert_eq!(cut(accented, "1", "a e b \u{301} é\n"), "a e b \u{301} é\n");
+    }
+
+    #[cfg(feature = "regex")]
+    #[test]
+    fn regex_fields_agree_with_split() {
+        // Fields separated by a regex were joined from `Regex::split` before the delimiters were
+        // unified, and must still be the same, apart from empty matches, which are no longer
+        // delimiters.
+        let delimiters = [" +", ",", "[,;]*", "x?", r"\b", "β+"];
+        let lines = [
+            "",
+            "a b  c",
+            "a,b;;c",
+            ",,",
+            "αβγβδ",
+            "no match",
+            " lead trail ",
+        ];
+        let ranges = ["1", "2-", "-2,4", "1,3-", "5-"];
+        for delimiter in &delimiters {
+            let regex = regex::Regex::new(delimiter).unwrap();
+            for line in &lines {
+                for ranges in &ranges {
+                    let parsed: Ranges = ranges.parse().unwrap();
+                    // `Regex::split` also splits at empty matches, so the fields either side of
+                    // each one are joined back together.
+                    let mut fields: Vec<String> = Vec::new();
+                    let mut joined = false;
+                    for (field, m) in regex.split(line).zip(
+                        regex
+                            .find_iter(line)
+                            .map(|m| Option::Some(m.as_str()))
+                            .chain([Option::None]),
+                    ) {
+                        match fields.last_mut() {
+                            Option::Some(last) if joined => last.push_str(field),
+                            _ => fields.push(field.to_string()),
+                        }
+                        joined = m == Option::Some("");
+                    }
+                    let expected = if fields.len() > 1 {
+                        crate::select::SelectIndices::new(
+                            fields.iter().map(String::as_str),
+                            &parsed,
+                        )
+                        .collect::<Vec<&str>>()
+                        .join("|")
+                    } else {
+                        line.to_string()
+                    };
+                    let actual = super::cut_line_fields(line, &regex, &parsed, "|");
+                    assert_eq!(actual, expected, "{:?} {:?} {:?}", delimiter, line, ranges);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn cut_record_bytes() {
+        let cut = |record: &'static [u8], ranges: &str| {
+            super::cut_record_bytes(record, &ranges.parse().unwrap())
+        };
+        assert_eq!(cut(b"abcdef", "1-"), &b"abcdef"[..]);
+        assert_eq!(cut(b"abcdef", "-2,4,6-"), &b"abdf"[..]);
+        assert_eq!(cut(b"abcdef", "7-"), &b""[..]);
+
+        assert!(matches!(cut(b"abcdef", "1-"), Cow::Borrowed(b"abcdef")));
+        assert!(matches!(cut(b"abcdef", "2-3"), Cow::Borrowed(b"bc")));
+        assert!(matches!(cut(b"abcdef", "1,3"), Cow::Owned(_)));
+    }
+
+    #[test]
+    fn cut_record_bytes_agrees_with_streaming() {
+        let record = b"0123456789abcdef";
+        for ranges in ["1", "1-", "3-5", "-2,4,6-", "2,4,6,8,10-12,30-", "17-"] {
+            let parsed: Ranges = ranges.parse().unwrap();
+            let mut output = Vec::new();
+            super::cut_bytes(
+                &mut BufReader::with_capacity(3, &record[..]),
+                &mut output,
+                &Options::default(),
+                &mut RunState::default(),
+                ByteFormat::Raw,
+                &parsed,
+                &mut Buffers::default(),
+            )
+            .unwrap();
+            let mut expected = super::cut_record_bytes(record, &parsed).into_owned();
+            expected.push(b'\n');
+            assert_eq!(output, expected, "{}", ranges);
+        }
+    }
+
+    #[test]
+    fn cut_lines_agrees_with_cut() {
+        let ranges = |ranges: &str| ranges.parse::<Ranges>().unwrap();
+        let fields =
+            |field_options: FieldOptions| Mode::FieldsChar(ranges("2-"), ',', field_options);
+        let modes = [
+            Mode::bytes(ranges("2-3")),
+            Mode::Bytes(ranges("1,3-"), ByteFormat::Hex(HexFormat::Spaced)),
+            Mode::Bytes(ranges("2-"), ByteFormat::Base64(Base64Alphabet::Standard)),
+            Mode::characters(ranges("-2,4-")),
+            Mode::fields(',', ranges("1,3")),
+            fields(field_options(":", true)),
+            fields(FieldOptions {
+                header: Option::Some(HeaderOut::unbounded(vec!["first".to_string()])),
+                ..field_options(",", false)
+            }),
+            fields(FieldOptions {
+                byte_offsets: true,
+                ..field_options(" ", false)
+            }),
+            fields(FieldOptions {
+                template: Option::Some("{2}-{1}".parse().unwrap()),
+                ..field_options(",", false)
+            }),
+        ];
+        #[cfg(feature = "regex")]
+        let modes = [
+            &modes[..],
+            &[Mode::FieldsRegex(
+                ranges("1,3"),
+                Regex::new(",+").unwrap(),
+                field_options("|", true),
+            )],
+        ]
+        .concat();
+        let options = [
+            Options::default(),
+            Options {
+                prefix: b"<".to_vec(),
+                suffix: b">".to_vec(),
+                ..Options::default()
+            },
+            Options {
+                skip_lines: 1,
+                max_lines: Option::Some(2),
+                ..Options::default()
+            },
+            Options {
+                keep_cr: true,
+                invalid_utf8: InvalidUtf8::Skip,
+                ..Options::default()
+            },
+            Options {
+                invalid_utf8: InvalidUtf8::Lossy,
+                ..Options::default()
+            },
+            delimited_by(b'\0'),
+            Options {
+                line_delimiter: b"\r\n".to_vec(),
+                output_line_delimiter: Option::Some(b";".to_vec()),
+                skip_lines: 1,
+                ..Options::default()
+            },
+        ];
+        let inputs: [&[u8]; 8] = [
+            b"",
+            b"\n\n",
+            b"a,b,c\nd,e,f\n",
+            b"a,b,c\nno delimiter\n\nd,,e,f",
+            b"a,b\r\nc,d\r\n",
+            b"x,\xffy\nz,w\n",
+            b"a,b\0c\0d,e",
+            "α,β,γ\nδ".as_bytes(),
+        ];
+
+        for mode in &modes {
+            for options in &options {
+                for input in &inputs {
+                    assert_cut_lines_agrees(mode, options, input);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn multi_byte_delimiter_split_between_reads() {
+        let ranges = |ranges: &str| ranges.parse::<Ranges>().unwrap();
+        let modes = [
+            Mode::bytes(ranges("3-")),
+            Mode::characters(ranges("2-")),
+            Mode::fields(',', ranges("2")),
+        ];
+        let options = Options {
+            line_delimiter: b"\r\n".to_vec(),
+            ..Options::default()
+        };
+        let input = "a,b\r\nγ,δ\r\r\n\r\n,\r,\r".as_bytes();
+        for mode in &modes {
+            let mut expected = Vec::new();
+            mode.cut(
+                &mut &input[..],
+                &mut expected,
+                &options,
+                &mut RunState::default(),
+            )
+            .unwrap();
+            for capacity in 1..=4 {
+                let mut output = Vec::new();
+                let mut reader = BufReader::with_capacity(capacity, input);
+                mode.cut(&mut reader, &mut output, &options, &mut RunState::default())
+                    .unwrap();
+                assert_eq!(output, expected, "{:?} capacity {}", mode, capacity);
+            }
+        }
+
+        let cut = |mode: &Mode| {
+            let mut output = Vec::new();
+            mode.cut(
+                &mut BufReader::with_capacity(1, input),
+                &mut output,
+                &options,
+                &mut RunState::default(),
+            )
+            .unwrap();
+            String::from_utf8(output).unwrap()
+        };
+        assert_eq!(cut(&modes[0]), "b\r\n,δ\r\r\n\r\n,\r\r\n");
+        assert_eq!(cut(&modes[1]), ",b\r\n,δ\r\r\n\r\n\r,\r\r\n");
+        assert_eq!(cut(&modes[2]), "b\r\nδ\r\r\n\r\n\r\r\n");
+    }
+
+    #[test]
+    fn byte_prefix_agrees_with_streamed_records() {
+        let options = [
+            Options::default(),
+            Options {
+                keep_cr: true,
+                ..Options::default()
+            },
+            Options {
+                line_delimiter: b"\r\n".to_vec(),
+                ..Options::default()
+            },
+            delimited_by(b'\0'),
+        ];
+        let input = b"abc\r\nab\r\na\r\n\r\n\nabcdef\0ghi\r\nlast\r";
+        for ranges in ["1", "1-2", "1-3", "1-4", "1-100"] {
+            let mode = Mode::bytes(ranges.parse().unwrap());
+            for options in &options {
+                // Records are only truncated in place when they end within the reader's buffer.
+                let mut expected = Vec::new();
+                let mut reader = BufReader::with_capacity(1, &input[..]);
+                mode.cut(
+                    &mut reader,
+                    &mut expected,
+                    options,
+                    &mut RunState::default(),
+                )
+                .unwrap();
+                for capacity in [4, 8192] {
+                    let mut output = Vec::new();
+                    let mut reader = BufReader::with_capacity(capacity, &input[..]);
+                    mode.cut(&mut reader, &mut output, options, &mut RunState::default())
+                        .unwrap();
+                    assert_eq!(output, expected, "{} {:?} {}", ranges, options, capacity);
+                }
+            }
+        }
+
+        let mut output = Vec::new();
+        Mode::bytes("1-3".parse().unwrap())
+            .cut(
+                &mut &input[..],
+                &mut output,
+                &Options::default(),
+                &mut RunState::default(),
+            )
+            .unwrap();
+        assert_eq!(output, b"abc\nab\na\n\n\nabc\nlas\n");
+    }
+
+    #[test]
+    fn copied_records_agree_with_cut_records() {
+        let ranges = |ranges: &str| ranges.parse::<Ranges>().unwrap();
+        let modes = [
+            Mode::bytes(ranges("1-")),
+            Mode::characters(ranges("1-")),
+            Mode::fields(',', ranges("1-")),
+            Mode::FieldsChar(ranges("1-"), '\t', field_options("\t", false)),
+        ];
+        let options = [
+            Options::default(),
+            Options {
+                keep_cr: true,
+                skip_lines: 1,
+                ..Options::default()
+            },
+            delimited_by(b'\0'),
+            Options {
+                invalid_utf8: InvalidUtf8::Lossy,
+                ..Options::default()
+            },
+            Options {
+                invalid_utf8: InvalidUtf8::Skip,
+                ..Options::default()
+            },
+        ];
+        let directory = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/files");
+        let mut inputs = vec![b"a,b\r\n\xff\nc\td\r".to_vec(), b"\n\r\n".to_vec()];
+        for entry in std::fs::read_dir(directory).unwrap() {
+            inputs.push(std::fs::read(entry.unwrap().path()).unwrap());
+        }
+
+        // Records are only copied unchanged when not line buffered.
+        let cut = |mode: &Mode, options: &Options, input: &[u8], capacity: usize| {
+            let mut state = RunState::default();
+            let mut output = Vec::new();
+            let mut reader = BufReader::with_capacity(capacity, input);
+            let result = mode.cut(&mut reader, &mut output, options, &mut state);
+            (
+                output,
+                result.map_err(|error| error.to_string()),
+                state.written,
+                state.rejects,
+            )
+        };
+        for mode in &modes {
+            for options in &options {
+                let buffered = Options {
+                    line_buffered: true,
+                    ..options.clone()
+                };
+                for input in &inputs {
+                    let expected = cut(mode, &buffered, input, 8192);
+                    for capacity in [1, 7, 8192] {
+                        assert_eq!(
+                            cut(mode, options, input, capacity),
+                            expected,
+                            "{:?} {:?} capacity {}",
+                            mode,
+                            options,
+                            capacity
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    /// Checks that the records returned by `CutLines` are those written by `Mode::cut`, each
+    /// followed by the line delimiter, and that both end with the same error, if any.
+    fn assert_cut_lines_agrees(mode: &Mode, options: &Options, input: &[u8]) {
+        let lines = CutLines::new(input, mode.clone(), options.clone());
+
+        let mut written = Vec::new();
+        let cut_result = mode.cut(
+            &mut &input[..],
+            &mut written,
+            options,
+            &mut RunState::default(),
+        );
+
+        let mut returned = Vec::new();
+        let mut lines_error = Option::None;
+        for record in lines {
+            match record {
+                Result::Ok(record) => {
+                    returned.extend_from_slice(&record);
+                    returned.extend_from_slice(options.output_terminator());
+                }
+                Result::Err(error) => lines_error = Option::Some(error.kind()),
+            }
+        }
+
+        let context = format!(
+            "{:?} {:?} {:?}",
+            mode,
+            options,
+            String::from_utf8_lossy(input)
+        );
+        assert_eq!(returned, written, "{}", context);
+        assert_eq!(
+            lines_error,
+            cut_result.err().map(|error| error.kind()),
+            "{}",
+            context
+        );
+    }
+
+    /// Writer which writes at most `limit` bytes at a time, and only supports vectored writes if
+    /// `vectored` is set.
+    struct ShortWriter {
+        written: Vec<u8>,
+        limit: usize,
+        vectored: bool,
+    }
+
+    impl Write for ShortWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.limit);
+            self.written.extend_from_slice(&buf[..n]);
+            Result::Ok(n)
+        }
+
+        fn write_vectored(&mut self, bufs: &[IoSlice]) -> io::Result<usize> {
+            if !self.vectored {
+                // As the default implementation does.
+                let buf = bufs.iter().find(|buf| !buf.is_empty());
+                return self.write(buf.map_or(&[][..], |buf| buf));
+            }
+            let mut n = 0;
+            for buf in bufs {
+                let len = buf.len().min(self.limit - n);
+                self.written.extend_from_slice(&buf[..len]);
+                n += len;
+            }
+            Result::Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Result::Ok(())
+        }
+    }
+
+    #[test]
+    fn staged_writer_writes_large_parts_with_staged_output() {
+        // Parts which are staged, written along with the staged output, and larger than the
+        // staging buffer.
+        let parts: Vec<Vec<u8>> = [10, super::VECTORED_LEN, 3, super::STAGED_CAPACITY * 2, 7]
+            .iter()
+            .enumerate()
+            .map(|(i, &len)| vec![b'a' + i as u8; len])
+            .collect();
+        let expected = parts.concat();
+        for vectored in [true, false] {
+            for limit in [1000, usize::MAX] {
+                let mut inner = ShortWriter {
+                    written: Vec::new(),
+                    limit,
+                    vectored,
+                };
+                let mut staged = Vec::new();
+                let mut writer = super::StagedWriter::new(&mut inner, &mut staged);
+                for part in &parts {
+                    writer.write_all(part).unwrap();
+                }
+                writer.flush().unwrap();
+                drop(writer);
+                assert!(inner.written == expected, "{} {}", vectored, limit);
+            }
+        }
+    }
+
+    #[test]
+    fn visit_stops_early() {
+        let mode = Mode::fields(',', "3".parse().unwrap());
+        let mut input = &b"a,b,c\nd,e,x\nf,g,x\n"[..];
+        let mut visited = 0;
+        let result = mode.visit(
+            &mut input,
+            &Options::default(),
+            &mut RunState::default(),
+            |record| {
+                visited += 1;
+                if record == b"x" {
+                    ControlFlow::Break(visited)
+                } else {
+                    ControlFlow::Continue(())
+                }
+            },
+        );
+        assert_eq!(result.unwrap(), ControlFlow::Break(2));
+        // Nothing after the record which broke is read.
+        assert_eq!(input, b"f,g,x\n");
+
+        // Breaking on the header row stops before the record it was returned with.
+        let field_options = FieldOptions {
+            header: Option::Some(HeaderOut::bounded(vec!["c".to_string()])),
+            ..field_options(",", false)
+        };
+        let mode = Mode::FieldsChar("3".parse().unwrap(), ',', field_options);
+        let (options, mut state) = (Options::default(), RunState::default());
+        let result = mode.visit(&mut &b"a,b,c\n"[..], &options, &mut state, |record| {
+            ControlFlow::Break(record.to_vec())
+        });
+        assert_eq!(result.unwrap(), ControlFlow::Break(b"c".to_vec()));
+        assert_eq!(state.written, 0);
+    }
+
+    #[test]
+    fn visit_suppressed() {
+        let input = &b"a,b\nno delimiter\n\nc,d"[..];
+        let visit = |suppress: bool| {
+            let mode = Mode::FieldsChar("2".parse().unwrap(), ',', field_options(",", suppress));
+            let options = Options {
+                prefix: b"<".to_vec(),
+                ..Options::default()
+            };
+            let mut state = RunState::default();
+            let mut visited = Vec::new();
+            let result = mode.visit(&mut &input[..], &options, &mut state, |record| {
+                visited.push(String::from_utf8(record.to_vec()).unwrap());
+                ControlFlow::<()>::Continue(())
+            });
+            assert_eq!(result.unwrap(), ControlFlow::Continue(()));
+            assert_eq!(state.written, visited.len());
+            visited
+        };
+        assert_eq!(visit(false), ["<b", "<no delimiter", "<", "<d"]);
+        // Suppressed lines are not visited at all, rather than visited as empty records.
+        assert_eq!(visit(true), ["<b", "<d"]);
+    }
+
+    #[test]
+    fn record_cutter_selector() {
+        // Selector which writes every other record, reversed, after writing to the output.
+        struct EveryOther(bool);
+        impl super::Selector for EveryOther {
+            fn process_record(
+                &mut self,
+                record: &[u8],
+                _: &super::RecordPosition,
+                _: &mut RunState,
+                output: &mut Vec<u8>,
+            ) -> std::io::Result<super::RecordDisposition> {
+                output.extend(record.iter().rev());
+                self.0 = !self.0;
+                Result::Ok(if self.0 {
+                    super::RecordDisposition::Write {
+                        header: Option::None,
+                    }
+                } else {
+                    super::RecordDisposition::Skip
+                })
+            }
+        }
+
+        let options = Options {
+            prefix: b"<".to_vec(),
+            suffix: b">".to_vec(),
+            ..Options::default()
+        };
+        let mut state = RunState::default();
+        let mut output = Vec::new();
+        super::cut_records(
+            &mut &b"ab\ncd\nef\ngh"[..],
+            &mut output,
+            &options,
+            &mut state,
+            &mut EveryOther(false),
+            &mut Buffers::default(),
+        )
+        .unwrap();
+        // The output of skipped records is discarded, and the prefix, suffix and line delimiter
+        // are written by the driver rather than the selector.
+        assert_eq!(String::from_utf8(output).unwrap(), "<ba>\n<fe>\n");
+        assert_eq!(state.written, 2);
+    }
+
+    #[test]
+    fn visit_max_lines() {
+        let mode = Mode::characters("1".parse().unwrap());
+        let options = Options {
+            max_lines: Option::Some(2),
+            ..Options::default()
+        };
+        let mut input = &b"ab\ncd\nef\n"[..];
+        let mut visited = Vec::new();
+        let result = mode.visit(&mut input, &options, &mut RunState::default(), |record| {
+            visited.push(record.to_vec());
+            ControlFlow::<()>::Continue(())
+        });
+        assert_eq!(result.unwrap(), ControlFlow::Continue(()));
+        assert_eq!(visited, [b"a", b"c"]);
+        assert_eq!(input, b"ef\n");
+    }
+
+    #[test]
+    fn cut_lines() {
+        let mode = Mode::Bytes("1,3".parse().unwrap(), ByteFormat::Raw);
+        let mut lines = CutLines::new(&b"abc\nxy\n\nz"[..], mode, Options::default());
+        assert_eq!(lines.next().unwrap().unwrap(), b"ac");
+        assert_eq!(lines.next().unwrap().unwrap(), b"x");
+        assert_eq!(lines.next().unwrap().unwrap(), b"");
+        // The last record does not need to be terminated.
+        assert_eq!(lines.next().unwrap().unwrap(), b"z");
+        assert!(lines.next().is_none());
+        assert!(lines.next().is_none());
+
+        // Header rows are returned as a separate record.
+        let field_options = FieldOptions {
+            header: Option::Some(HeaderOut::bounded(vec!["b".to_string()])),
+            ..field_options(",", true)
+        };
+        let mode = Mode::FieldsChar("2".parse().unwrap(), ',', field_options);
+        let lines = CutLines::new(&b"none\n1,2\n3,4\n"[..], mode, Options::default());
+        let records: Vec<Vec<u8>> = lines.map(Result::unwrap).collect();
+        assert_eq!(records, [&b"b"[..], b"2", b"4"]);
+
+        // The iterator ends after an error.
+        let mode = Mode::characters("1".parse().unwrap());
+        let mut lines = CutLines::new(&b"ab\n\xff\ncd\n"[..], mode, Options::default());
+        assert_eq!(lines.next().unwrap().unwrap(), b"a");
+        assert_eq!(
+            lines.next().unwrap().unwrap_err().kind(),
+            std::io::ErrorKind::InvalidData
+        );
+        assert!(lines.next().is_none());
+
+        // Skipped records are kept in the state.
+        let options = Options {
+            invalid_utf8: InvalidUtf8::Skip,
+            ..Options::default()
+        };
+        let mode = Mode::characters("1".parse().unwrap());
+        let mut lines = CutLines::new(&b"ab\n\xff\ncd\n"[..], mode, options);
+        assert_eq!(lines.by_ref().count(), 2);
+        assert_eq!(lines.state().rejects[0].bytes, b"\xff");
+    }
+
+    fn field_options(output_delimiter: &str, suppress: bool) -> FieldOptions {
+        FieldOptions {
+            output_delimiter: output_delimiter.as_bytes().to_vec(),
+            suppress,
+            ..FieldOptions::default()
+        }
+    }
+
+    fn delimited_by(line_delimiter: u8) -> Options {
+        Options {
+            line_delimiter: vec![line_delimiter],
+            ..Options::default()
+        }
     }
 }
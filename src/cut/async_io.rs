@@ -0,0 +1,344 @@
+//! Cutting records read from an asynchronous input, with tokio.
+
+use super::{
+    advance_position, finish_read, select_record, Mode, Options, RecordDisposition, RecordPosition,
+    RunState, TerminatorSearch,
+};
+use std::io;
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufWriter};
+
+impl Mode {
+    /// Cuts each record of `input` and writes the selected parts to `output`, as [`Mode::cut`]
+    /// does, without blocking.
+    ///
+    /// Unlike [`Mode::cut`], each record is read into memory, including in byte mode. The output is
+    /// buffered, and is flushed before returning. If the future is dropped before it completes,
+    /// records which were cut may not have been written, unless [`Options::line_buffered`] is set.
+    ///
+    /// The future is [`Send`] as long as the input and output are, so it can be spawned on a
+    /// multi-threaded runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::cut::{Mode, Options, RunState};
+    ///
+    /// # tokio::runtime::Builder::new_current_thread().build()?.block_on(async {
+    /// let mode = Mode::fields(',', "2".parse()?);
+    /// let (options, mut state) = (Options::default(), RunState::default());
+    /// let mut output = Vec::new();
+    /// mode.cut_async(&mut &b"a,b\nc,d\n"[..], &mut output, &options, &mut state)
+    ///     .await?;
+    /// assert_eq!(output, b"b\nd\n");
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// # })?;
+    /// # Ok::<(), Box<dyn std::error::Error>>(())
+    /// ```
+    pub async fn cut_async<R, W>(
+        &self,
+        input: &mut R,
+        output: &mut W,
+        options: &Options,
+        state: &mut RunState,
+    ) -> io::Result<()>
+    where
+        R: AsyncBufRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let mut writer = BufWriter::with_capacity(super::STAGED_CAPACITY, output);
+        let mut buf = Vec::new();
+        let mut record = Vec::new();
+        let mut selector = self.selector(options);
+        let mut position = RecordPosition::default();
+        skip_records(input, options, &mut position).await?;
+
+        let mut search = TerminatorSearch::new(&options.line_delimiter);
+        while !state.limit_reached(options)
+            && read_record(input, &mut search, &mut buf, options, &mut position).await?
+        {
+            let disposition =
+                select_record(&mut *selector, &buf, &position, options, state, &mut record)?;
+            let header = match disposition {
+                RecordDisposition::Write { header } => header,
+                RecordDisposition::Skip => continue,
+            };
+            if let Option::Some(header) = header {
+                writer.write_all(&header).await?;
+                writer.write_all(options.output_terminator()).await?;
+            }
+            writer.write_all(&record).await?;
+            writer.write_all(options.output_terminator()).await?;
+            state.record_written();
+            if options.line_buffered {
+                writer.flush().await?;
+            }
+        }
+        writer.flush().await
+    }
+}
+
+/// Reads and discards the number of records specified by `skip_lines`, as `super::skip_records`
+/// does.
+async fn skip_records<R>(
+    reader: &mut R,
+    options: &Options,
+    position: &mut RecordPosition,
+) -> io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut search = TerminatorSearch::new(&options.line_delimiter);
+    for _ in 0..options.skip_lines {
+        let mut n = 0;
+        loop {
+            let chunk = reader.fill_buf().await?;
+            if chunk.is_empty() {
+                break;
+            }
+            let (consumed, end) = match search.find(chunk) {
+                Option::Some(i) => (i, true),
+                Option::None => (chunk.len(), false),
+            };
+            reader.consume(consumed);
+            n += consumed as u64;
+            if end {
+                break;
+            }
+        }
+        if n == 0 {
+            break;
+        }
+        advance_position(n, position);
+    }
+    Result::Ok(())
+}
+
+/// Reads the next record into the buffer, as `super::read_record` does.
+async fn read_record<R>(
+    reader: &mut R,
+    search: &mut TerminatorSearch<'_>,
+    buf: &mut Vec<u8>,
+    options: &Options,
+    position: &mut RecordPosition,
+) -> io::Result<bool>
+where
+    R: AsyncBufRead + Unpin,
+{
+    buf.clear();
+    let n = read_until_terminator(reader, search, buf).await?;
+    Result::Ok(finish_read(buf, n, options, position))
+}
+
+/// Reads bytes into the buffer until the terminator or the end of the input is reached, as
+/// `super::terminator::read_until_terminator` does.
+async fn read_until_terminator<R>(
+    reader: &mut R,
+    search: &mut TerminatorSearch<'_>,
+    buf: &mut Vec<u8>,
+) -> io::Result<usize>
+where
+    R: AsyncBufRead + Unpin,
+{
+    let mut read = 0;
+    loop {
+        let chunk = reader.fill_buf().await?;
+        if chunk.is_empty() {
+            search.reset();
+            return Result::Ok(read);
+        }
+        let (consumed, end) = match search.find(chunk) {
+            Option::Some(index) => (index, true),
+            Option::None => (chunk.len(), false),
+        };
+        buf.extend_from_slice(&chunk[..consumed]);
+        reader.consume(consumed);
+        read += consumed;
+        if end {
+            return Result::Ok(read);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cut::{FieldOptions, HeaderOut, InvalidUtf8, Mode, Options, RunState};
+    use crate::range::Ranges;
+    #[cfg(feature = "regex")]
+    use regex::bytes::Regex;
+    use tokio::io::{self, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+
+    fn ranges(ranges: &str) -> Ranges {
+        ranges.parse().unwrap()
+    }
+
+    #[tokio::test]
+    async fn agrees_with_cut() {
+        let modes = [
+            Mode::bytes(ranges("2-3")),
+            Mode::characters(ranges("-2,4-")),
+            Mode::fields(',', ranges("1,3")),
+            Mode::FieldsChar(
+                ranges("2-"),
+                ',',
+                FieldOptions {
+                    output_delimiter: b":".to_vec(),
+                    suppress: true,
+                    header: Option::Some(HeaderOut::unbounded(Vec::new())),
+                    ..FieldOptions::default()
+                },
+            ),
+        ];
+        #[cfg(feature = "regex")]
+        let modes = [
+            &modes[..],
+            &[Mode::FieldsRegex(
+                ranges("1,3"),
+                Regex::new(",+").unwrap(),
+                FieldOptions::default(),
+            )],
+        ]
+        .concat();
+        let options = [
+            Options::default(),
+            Options {
+                prefix: b"<".to_vec(),
+                suffix: b">".to_vec(),
+                skip_lines: 1,
+                max_lines: Option::Some(2),
+                ..Options::default()
+            },
+            Options {
+                invalid_utf8: InvalidUtf8::Skip,
+                line_buffered: true,
+                ..Options::default()
+            },
+        ];
+        let inputs: [&[u8]; 5] = [
+            b"",
+            b"a,b,c\nd,e,f\n",
+            b"a,b,c\nno delimiter\n\nd,,e,f",
+            b"a,b\r\nc,d\r\n",
+            b"x,\xffy\nz,w\n",
+        ];
+
+        for mode in &modes {
+            for options in &options {
+                for input in &inputs {
+                    let mut sync_state = RunState::default();
+                    let mut expected = Vec::new();
+                    let sync_result =
+                        mode.cut(&mut &input[..], &mut expected, options, &mut sync_state);
+
+                    let mut state = RunState::default();
+                    let mut output = Vec::new();
+                    let result = mode
+                        .cut_async(&mut &input[..], &mut output, options, &mut state)
+                        .await;
+
+                    let context = format!("{:?} {:?} {:?}", mode, options, input);
+                    assert_eq!(output, expected, "{}", context);
+                    assert_eq!(
+                        result.map_err(|error| error.kind()),
+                        sync_result.map_err(|error| error.kind()),
+                        "{}",
+                        context
+                    );
+                    assert_eq!(state.written, sync_state.written, "{}", context);
+                    assert_eq!(state.rejects, sync_state.rejects, "{}", context);
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn large_records() {
+        // Records much larger than the buffers of the streams are read and written in parts.
+        let record: Vec<u8> = (0..1_000_000).map(|i| b'a' + (i % 26) as u8).collect();
+        let mut input = record.clone();
+        input.push(b'\n');
+        input.extend_from_slice(&record);
+
+        let (mut input_writer, input_reader) = io::duplex(64);
+        let (mut output_writer, mut output_reader) = io::duplex(64);
+        let mode = Mode::bytes(ranges("2-"));
+        let (options, mut state) = (Options::default(), RunState::default());
+
+        let write_input = async {
+            input_writer.write_all(&input).await.unwrap();
+            input_writer.shutdown().await.unwrap();
+        };
+        let cut = async {
+            let mut input_reader = BufReader::new(input_reader);
+            mode.cut_async(&mut input_reader, &mut output_writer, &options, &mut state)
+                .await
+                .unwrap();
+            output_writer.shutdown().await.unwrap();
+        };
+        let read_output = async {
+            let mut output = Vec::new();
+            output_reader.read_to_end(&mut output).await.unwrap();
+            output
+        };
+        let ((), (), output) = tokio::join!(write_input, cut, read_output);
+
+        let mut expected = record[1..].to_vec();
+        expected.push(b'\n');
+        expected.extend_from_slice(&record[1..]);
+        expected.push(b'\n');
+        assert_eq!(output.len(), expected.len());
+        assert!(output == expected);
+    }
+
+    #[tokio::test]
+    async fn cancelled_mid_stream() {
+        let (mut input_writer, input_reader) = io::duplex(64);
+        let (mut output_writer, output_reader) = io::duplex(64);
+        let mut output_reader = BufReader::new(output_reader);
+        let mode = Mode::fields(',', ranges("2"));
+        let options = Options {
+            line_buffered: true,
+            ..Options::default()
+        };
+        let mut state = RunState::default();
+
+        {
+            let mut input_reader = BufReader::new(input_reader);
+            let cut = mode.cut_async(&mut input_reader, &mut output_writer, &options, &mut state);
+            let first_record = async {
+                // The second record is not complete, and the input is not closed.
+                input_writer.write_all(b"a,b\nc,").await.unwrap();
+                let mut line = Vec::new();
+                output_reader.read_until(b'\n', &mut line).await.unwrap();
+                line
+            };
+            tokio::select! {
+                result = cut => panic!("cut completed before the input ended: {:?}", result),
+                line = first_record => assert_eq!(line, b"b\n"),
+            }
+        }
+        assert_eq!(state.written, 1);
+
+        // Nothing more is written once the future is dropped.
+        drop(output_writer);
+        let mut rest = Vec::new();
+        output_reader.read_to_end(&mut rest).await.unwrap();
+        assert_eq!(rest, b"");
+    }
+
+    #[test]
+    fn future_is_send() {
+        fn assert_send<T: Send>(_: T) {}
+
+        let mode = Mode::FieldsChar(
+            ranges("2"),
+            ',',
+            FieldOptions {
+                header: Option::Some(HeaderOut::bounded(vec!["x".to_string()])),
+                ..FieldOptions::default()
+            },
+        );
+        let (options, mut state) = (Options::default(), RunState::default());
+        let mut output = Vec::new();
+        assert_send(mode.cut_async(&mut &b"a,b\n"[..], &mut output, &options, &mut state));
+    }
+}
@@ -0,0 +1,152 @@
+//! Finding the text between a start marker and the next end marker after it.
+
+use memchr::memmem;
+#[cfg(feature = "regex")]
+use regex::bytes::Regex;
+
+/// Marker which starts or ends the text selected by [`super::Mode::Between`], or by
+/// [`super::Mode::BetweenRegex`] with the `regex` feature.
+pub(super) trait Marker {
+    /// Returns the byte span of the first occurrence of the marker in `haystack` which starts at
+    /// or after `from`, which is at most the length of `haystack`.
+    fn find_at(&self, haystack: &[u8], from: usize) -> Option<(usize, usize)>;
+}
+
+impl Marker for str {
+    fn find_at(&self, haystack: &[u8], from: usize) -> Option<(usize, usize)> {
+        memmem::find(&haystack[from..], self.as_bytes())
+            .map(|index| (from + index, from + index + self.len()))
+    }
+}
+
+/// Anchors such as `^` match relative to the whole haystack, not to `from`.
+#[cfg(feature = "regex")]
+impl Marker for Regex {
+    fn find_at(&self, haystack: &[u8], from: usize) -> Option<(usize, usize)> {
+        Regex::find_at(self, haystack, from).map(|m| (m.start(), m.end()))
+    }
+}
+
+impl<T: Marker + ?Sized> Marker for &T {
+    fn find_at(&self, haystack: &[u8], from: usize) -> Option<(usize, usize)> {
+        T::find_at(self, haystack, from)
+    }
+}
+
+/// Iterator over the byte spans of the text between each occurrence of a start marker and the
+/// next occurrence of an end marker after it. The search for the next start marker continues after
+/// the end marker, so neither marker is part of more than one span.
+pub(super) struct BetweenSpans<'a, M> {
+    haystack: &'a [u8],
+    start: M,
+    end: M,
+    /// Offset which the search for the next start marker starts from, or `None` once either
+    /// marker is not found.
+    from: Option<usize>,
+}
+
+impl<'a, M: Marker> BetweenSpans<'a, M> {
+    pub(super) fn new(haystack: &'a [u8], start: M, end: M) -> BetweenSpans<'a, M> {
+        BetweenSpans {
+            haystack,
+            start,
+            end,
+            from: Option::Some(0),
+        }
+    }
+}
+
+impl<M: Marker> Iterator for BetweenSpans<'_, M> {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        let from = self.from.take()?;
+        let (_, start) = self.start.find_at(self.haystack, from)?;
+        let (end, after) = self.end.find_at(self.haystack, start)?;
+        // Markers which match the empty string could match at the same offset again, so the search
+        // moves past it.
+        self.from = if after > from {
+            Option::Some(after)
+        } else if from < self.haystack.len() {
+            Option::Some(from + 1)
+        } else {
+            Option::None
+        };
+        Option::Some((start, end))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BetweenSpans, Marker};
+    #[cfg(feature = "regex")]
+    use regex::bytes::Regex;
+
+    /// Asserts the text between each pair of markers in each line.
+    fn assert_between<M: Marker + Copy>(start: M, end: M, cases: &[(&str, &[&str])]) {
+        for (line, expected) in cases {
+            let spans: Vec<&str> = BetweenSpans::new(line.as_bytes(), start, end)
+                .map(|(start, end)| &line[start..end])
+                .collect();
+            assert_eq!(spans, *expected, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn literal_markers() {
+        assert_between(
+            "user=",
+            "&",
+            &[
+                ("a=1&user=bob&c=3", &["bob"]),
+                ("user=bob&user=amy&", &["bob", "amy"]),
+                ("user=&", &[""]),
+                // A missing start or end marker.
+                ("a=1&c=3", &[]),
+                ("a=1&user=bob", &[]),
+                ("user=bob&user=amy", &["bob"]),
+                ("", &[]),
+            ],
+        );
+    }
+
+    #[test]
+    fn overlapping_markers() {
+        // The end marker is searched for after the start marker, so they do not overlap.
+        assert_between("aa", "a", &[("aaa", &[""]), ("aaaa", &[""]), ("aab", &[])]);
+        assert_between(
+            "ab",
+            "ba",
+            &[("aba", &[]), ("abba", &[""]), ("abxba", &["x"])],
+        );
+        // The same marker starts and ends each span.
+        assert_between(
+            "\"",
+            "\"",
+            &[("a \"x\" b \"y\" c", &["x", "y"]), ("\"", &[])],
+        );
+    }
+
+    #[test]
+    fn multi_byte_markers() {
+        assert_between("«", "»", &[("a «β» c «δ»", &["β", "δ"]), ("«»", &[""])]);
+        assert_between("→", "😀", &[("x→yz😀", &["yz"]), ("😀→", &[])]);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_markers() {
+        let start = Regex::new("id=[0-9]+:").unwrap();
+        let end = Regex::new("[;,]").unwrap();
+        assert_between(&start, &end, &[("id=12:a;id=3:b,id=x:c;", &["a", "b"])]);
+
+        // Empty matches are markers, and are not found twice.
+        let start = Regex::new("^").unwrap();
+        let end = Regex::new(" ").unwrap();
+        assert_between(&start, &end, &[("ab cd ef", &["ab"]), ("ab", &[])]);
+        let empty = Regex::new("").unwrap();
+        assert_between(&empty, &empty, &[("ab", &["", "", ""]), ("", &[""])]);
+        let end = Regex::new("$").unwrap();
+        assert_between(&Regex::new("=").unwrap(), &end, &[("a=b=c", &["b=c"])]);
+    }
+}
@@ -0,0 +1,188 @@
+//! Splitting a line into fields, shared by every kind of field delimiter.
+
+#[cfg(feature = "regex")]
+use super::FieldSpans;
+#[cfg(feature = "regex")]
+use regex::Regex;
+
+/// Delimiter which splits a line into fields.
+///
+/// Fields are cut the same way whatever the delimiter is, so a new kind of delimiter only needs to
+/// implement this trait. It is implemented for a [`char`], a literal [`str`], and, with the `regex`
+/// feature, a `regex::Regex`, as well as references to any of them.
+///
+/// # Examples
+///
+/// ```
+/// use rut::cut::{cut_line_fields, FieldSplitter};
+///
+/// // Called through the trait, since `str` has inherent methods with the same names.
+/// assert!(FieldSplitter::matches("::", "a::b"));
+/// assert_eq!(FieldSplitter::split("::", "a::b").collect::<Vec<&str>>(), ["a", "b"]);
+/// assert_eq!(cut_line_fields("a::b::c", "::", &"2-".parse()?, "\t"), "b\tc");
+/// # Ok::<(), rut::range::ParseRangesError>(())
+/// ```
+pub trait FieldSplitter {
+    /// Returns the fields of `line`. A line without any delimiters is a single field.
+    fn split<'a>(&self, line: &'a str) -> impl Iterator<Item = &'a str>;
+
+    /// Returns the byte spans, from a start up to but not including an end offset, of each
+    /// delimiter in `line`, in the order that they appear.
+    fn delimiters(&self, line: &str) -> impl Iterator<Item = (usize, usize)>;
+
+    /// Returns whether `line` contains a delimiter.
+    fn matches(&self, line: &str) -> bool;
+}
+
+impl FieldSplitter for char {
+    fn split<'a>(&self, line: &'a str) -> impl Iterator<Item = &'a str> {
+        line.split(*self)
+    }
+
+    fn delimiters(&self, line: &str) -> impl Iterator<Item = (usize, usize)> {
+        // In UTF-8, the first byte of a character is never part of another character, so the
+        // delimiter can only start where `memchr` finds its first byte.
+        let mut encoded = [0; 4];
+        let len = self.encode_utf8(&mut encoded).len();
+        let bytes = line.as_bytes();
+        memchr::memchr_iter(encoded[0], bytes)
+            .filter(move |&index| bytes[index..].starts_with(&encoded[..len]))
+            .map(move |index| (index, index + len))
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        self.delimiters(line).next().is_some()
+    }
+}
+
+impl FieldSplitter for str {
+    fn split<'a>(&self, line: &'a str) -> impl Iterator<Item = &'a str> {
+        line.split(self)
+    }
+
+    fn delimiters(&self, line: &str) -> impl Iterator<Item = (usize, usize)> {
+        line.match_indices(self)
+            .map(|(index, delimiter)| (index, index + delimiter.len()))
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        line.contains(self)
+    }
+}
+
+/// Matches of the empty string are not delimiters, so a pattern such as `x*` splits only at runs
+/// of "x", and a line with only empty matches is a single field.
+#[cfg(feature = "regex")]
+impl FieldSplitter for Regex {
+    fn split<'a>(&self, line: &'a str) -> impl Iterator<Item = &'a str> {
+        FieldSpans::new(self.delimiters(line), line.len())
+            .map(move |(start, end)| &line[start..end])
+    }
+
+    fn delimiters(&self, line: &str) -> impl Iterator<Item = (usize, usize)> {
+        self.find_iter(line)
+            .filter(|m| m.start() < m.end())
+            .map(|m| (m.start(), m.end()))
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        self.delimiters(line).next().is_some()
+    }
+}
+
+impl<T: FieldSplitter + ?Sized> FieldSplitter for &T {
+    fn split<'a>(&self, line: &'a str) -> impl Iterator<Item = &'a str> {
+        T::split(self, line)
+    }
+
+    fn delimiters(&self, line: &str) -> impl Iterator<Item = (usize, usize)> {
+        T::delimiters(self, line)
+    }
+
+    fn matches(&self, line: &str) -> bool {
+        T::matches(self, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FieldSplitter;
+    #[cfg(feature = "regex")]
+    use regex::Regex;
+
+    /// Asserts the fields of each line, and that they agree with the delimiter spans.
+    fn assert_splits<S: FieldSplitter>(splitter: S, cases: &[(&str, &[&str])]) {
+        for (line, expected) in cases {
+            let fields: Vec<&str> = splitter.split(line).collect();
+            assert_eq!(fields, *expected, "{:?}", line);
+            assert_eq!(splitter.matches(line), expected.len() > 1, "{:?}", line);
+
+            let mut start = 0;
+            let mut from_spans = Vec::new();
+            for (delimiter_start, delimiter_end) in splitter.delimiters(line) {
+                from_spans.push(&line[start..delimiter_start]);
+                start = delimiter_end;
+            }
+            from_spans.push(&line[start..]);
+            assert_eq!(from_spans, fields, "{:?}", line);
+        }
+    }
+
+    #[test]
+    fn char_splitter() {
+        assert_splits(
+            ',',
+            &[
+                ("", &[""]),
+                ("abc", &["abc"]),
+                ("a,b,,c", &["a", "b", "", "c"]),
+                (",a,", &["", "a", ""]),
+            ],
+        );
+        assert_splits('γ', &[("αγβγ", &["α", "β", ""]), ("αβ", &["αβ"])]);
+    }
+
+    #[test]
+    fn str_splitter() {
+        assert_splits(
+            "::",
+            &[
+                ("a::b:c", &["a", "b:c"]),
+                ("a:::b", &["a", ":b"]),
+                ("::", &["", ""]),
+                ("a", &["a"]),
+            ],
+        );
+        assert_splits("βγ", &[("αβγδ", &["α", "δ"])]);
+    }
+
+    #[test]
+    #[cfg(feature = "regex")]
+    fn regex_splitter() {
+        assert_splits(
+            Regex::new(" +").unwrap(),
+            &[
+                ("a  b c", &["a", "b", "c"]),
+                (" a ", &["", "a", ""]),
+                ("abc", &["abc"]),
+            ],
+        );
+        let regex = Regex::new("[βδ]").unwrap();
+        assert_splits::<&Regex>(&regex, &[("αβγδε", &["α", "γ", "ε"])]);
+
+        // Empty matches are skipped.
+        assert_splits(
+            Regex::new("x*").unwrap(),
+            &[("axxbxc", &["a", "b", "c"]), ("abc", &["abc"]), ("", &[""])],
+        );
+        assert_splits(Regex::new("").unwrap(), &[("abc", &["abc"]), ("", &[""])]);
+        assert_splits(Regex::new(r"\b").unwrap(), &[("a b", &["a b"])]);
+    }
+
+    #[test]
+    fn references() {
+        let delimiter = String::from(",");
+        assert_splits(delimiter.as_str(), &[("a,b", &["a", "b"])]);
+        assert_splits::<&&char>(&&',', &[("a,b", &["a", "b"])]);
+    }
+}
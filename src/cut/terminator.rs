@@ -0,0 +1,225 @@
+//! Finding the end of each record, for line delimiters of any length.
+
+use memchr::memmem;
+use std::io::{self, BufRead};
+
+/// Search for a line delimiter in input which is read in chunks, which may split the delimiter.
+/// Every mode finds the end of each record with one of these, created once for each input.
+///
+/// A single byte is found with `memchr`, and a longer delimiter with `memmem`. The search
+/// remembers how much of the delimiter the previous chunks ended with, so it needs no buffer of its
+/// own. An empty delimiter is never found, so the whole input is a single record.
+#[derive(Clone, Debug)]
+pub(super) struct TerminatorSearch<'a> {
+    terminator: &'a [u8],
+    /// Finds the whole terminator within a chunk, if it is longer than one byte.
+    finder: Option<memmem::Finder<'a>>,
+    /// Number of bytes at the end of the chunks searched so far which match the start of the
+    /// terminator.
+    matched: usize,
+}
+
+impl<'a> TerminatorSearch<'a> {
+    pub(super) fn new(terminator: &'a [u8]) -> TerminatorSearch<'a> {
+        TerminatorSearch {
+            terminator,
+            finder: Option::Some(terminator)
+                .filter(|terminator| terminator.len() > 1)
+                .map(memmem::Finder::new),
+            matched: 0,
+        }
+    }
+
+    /// Returns the number of bytes at the end of the chunks searched so far which may be the start
+    /// of the terminator, depending on the next chunk. They are `terminator[..matched]`.
+    pub(super) fn matched(&self) -> usize {
+        self.matched
+    }
+
+    /// Forgets any start of the terminator at the end of the chunks searched so far, once the input
+    /// has ended with it, so that the search starts again if there is more input later.
+    pub(super) fn reset(&mut self) {
+        self.matched = 0;
+    }
+
+    /// Searches the next chunk, and returns the index just after the end of the terminator, if it
+    /// ends in this chunk. The search starts again after the terminator is found.
+    pub(super) fn find(&mut self, chunk: &[u8]) -> Option<usize> {
+        let finder = match &self.finder {
+            Option::Some(finder) => finder,
+            Option::None => {
+                let byte = *self.terminator.first()?;
+                return memchr::memchr(byte, chunk).map(|index| index + 1);
+            }
+        };
+
+        // A terminator which was split between chunks is finished one byte at a time.
+        let mut index = 0;
+        while self.matched > 0 {
+            self.matched = self.advance(*chunk.get(index)?);
+            index += 1;
+            if self.matched == self.terminator.len() {
+                self.matched = 0;
+                return Option::Some(index);
+            }
+        }
+
+        // Otherwise the terminator starts within the rest of the chunk, if anywhere, so it either
+        // ends in this chunk or the chunk ends with the start of it.
+        let rest = &chunk[index..];
+        match finder.find(rest) {
+            Option::Some(start) => Option::Some(index + start + self.terminator.len()),
+            Option::None => {
+                self.matched = (1..self.terminator.len())
+                    .rev()
+                    .find(|&len| rest.ends_with(&self.terminator[..len]))
+                    .unwrap_or(0);
+                Option::None
+            }
+        }
+    }
+
+    /// Returns the length of the longest start of the terminator which the bytes matched so far,
+    /// followed by `byte`, end with.
+    fn advance(&self, byte: u8) -> usize {
+        let (terminator, matched) = (self.terminator, self.matched);
+        if terminator[matched] == byte {
+            return matched + 1;
+        }
+        // A shorter start of the terminator may still match, e.g. "aab" after reading "aaa".
+        (1..=matched)
+            .rev()
+            .find(|&len| {
+                terminator[len - 1] == byte
+                    && terminator[..len - 1] == terminator[matched + 1 - len..matched]
+            })
+            .unwrap_or(0)
+    }
+}
+
+/// Reads bytes into the buffer until the terminator or the end of the input is reached, like
+/// [`BufRead::read_until`] does for a single byte. Returns the number of bytes read, including the
+/// terminator.
+pub(super) fn read_until_terminator<R: BufRead + ?Sized>(
+    reader: &mut R,
+    search: &mut TerminatorSearch,
+    buf: &mut Vec<u8>,
+) -> io::Result<usize> {
+    let mut read = 0;
+    loop {
+        let chunk = match reader.fill_buf() {
+            Result::Ok(chunk) => chunk,
+            Result::Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Result::Err(err) => return Result::Err(err),
+        };
+        if chunk.is_empty() {
+            search.reset();
+            return Result::Ok(read);
+        }
+        let (consumed, end) = match search.find(chunk) {
+            Option::Some(index) => (index, true),
+            Option::None => (chunk.len(), false),
+        };
+        buf.extend_from_slice(&chunk[..consumed]);
+        reader.consume(consumed);
+        read += consumed;
+        if end {
+            return Result::Ok(read);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{read_until_terminator, TerminatorSearch};
+    use proptest::prelude::*;
+    use std::io::BufReader;
+
+    /// Returns the end of each terminator found in `input`, searched in chunks of `chunk_size`.
+    fn find_all(terminator: &[u8], input: &[u8], chunk_size: usize) -> Vec<usize> {
+        let mut search = TerminatorSearch::new(terminator);
+        let mut ends = Vec::new();
+        for (chunk_index, chunk) in input.chunks(chunk_size).enumerate() {
+            let mut start = 0;
+            while let Option::Some(end) = search.find(&chunk[start..]) {
+                start += end;
+                ends.push(chunk_index * chunk_size + start);
+            }
+        }
+        ends
+    }
+
+    #[test]
+    fn find() {
+        for chunk_size in 1..8 {
+            assert_eq!(find_all(b"\n", b"ab\ncd\n\n", chunk_size), [3, 6, 7]);
+            assert_eq!(
+                find_all(b"\r\n", b"a\r\nb\rc\r\n\r\n", chunk_size),
+                [3, 8, 10]
+            );
+            assert_eq!(find_all(b";;", b"a;;;b;c;;", chunk_size), [3, 9]);
+            // A partial match which fails may still end with the start of the terminator.
+            assert_eq!(find_all(b"aab", b"aaab", chunk_size), [4]);
+            assert_eq!(find_all(b"abab", b"abababab", chunk_size), [4, 8]);
+            assert_eq!(find_all(b"aba", b"ababa", chunk_size), [3]);
+            assert_eq!(find_all(b"abc", b"ab", chunk_size), Vec::<usize>::new());
+            assert_eq!(find_all(b"", b"abc", chunk_size), Vec::<usize>::new());
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn find_agrees_with_naive_search(
+            terminator in proptest::collection::vec(0..3u8, 1..5),
+            input in proptest::collection::vec(0..3u8, 0..64),
+            chunk_size in 1..16usize,
+        ) {
+            let mut expected = Vec::new();
+            let mut start = 0;
+            while let Option::Some(index) = input[start..]
+                .windows(terminator.len())
+                .position(|window| window == &terminator[..])
+            {
+                start += index + terminator.len();
+                expected.push(start);
+            }
+            prop_assert_eq!(find_all(&terminator, &input, chunk_size), expected);
+        }
+    }
+
+    #[test]
+    fn partial_match() {
+        let mut search = TerminatorSearch::new(b"<eol>");
+        assert_eq!(search.find(b"abc<e"), Option::None);
+        assert_eq!(search.matched(), 2);
+        assert_eq!(search.find(b"x<"), Option::None);
+        assert_eq!(search.matched(), 1);
+        assert_eq!(search.find(b"eol>def"), Option::Some(4));
+        assert_eq!(search.matched(), 0);
+    }
+
+    #[test]
+    fn read_until() {
+        let input = b"ab\r\ncd\re\r\n\r\nf\r";
+        for capacity in 1..8 {
+            let mut reader = BufReader::with_capacity(capacity, &input[..]);
+            let mut records = Vec::new();
+            let mut search = TerminatorSearch::new(b"\r\n");
+            loop {
+                let mut buf = Vec::new();
+                let n = read_until_terminator(&mut reader, &mut search, &mut buf).unwrap();
+                if n == 0 {
+                    break;
+                }
+                assert_eq!(n, buf.len());
+                records.push(buf);
+            }
+            assert_eq!(
+                records,
+                [&b"ab\r\n"[..], b"cd\re\r\n", b"\r\n", b"f\r"],
+                "{}",
+                capacity
+            );
+        }
+    }
+}
@@ -0,0 +1,670 @@
+use crate::args::ArgsError;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+/// A file which lists the input files to process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileList {
+    /// Path of the list. "-" is treated as stdin.
+    pub(crate) path: PathBuf,
+    pub(crate) format: ListFormat,
+}
+
+/// How the names in a file list are separated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ListFormat {
+    /// Names are terminated by a zero byte, e.g. the output of `find -print0`.
+    Nul,
+    /// One name per line. Trailing whitespace is removed, and blank lines and lines starting with
+    /// `#` are skipped.
+    Lines,
+}
+
+/// Reads the names of the input files from a file list.
+pub(crate) fn read_file_list(list: &FileList) -> io::Result<Vec<PathBuf>> {
+    let reader: Box<dyn Read> = if list.path == Path::new("-") {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(&list.path)?)
+    };
+
+    let filenames = match list.format {
+        ListFormat::Nul => parse_nul_separated(BufReader::new(reader))?,
+        ListFormat::Lines => parse_lines(BufReader::new(reader))?,
+    };
+
+    // Stdin cannot be read as input once it has been read as the list.
+    if list.path == Path::new("-") && filenames.iter().any(|filename| filename == Path::new("-")) {
+        return Result::Err(invalid_data(
+            "'-' (stdin) cannot be used as input when the file list is read from stdin",
+        ));
+    }
+    Result::Ok(filenames)
+}
+
+/// Parses names terminated (or separated) by zero bytes. Names must not be empty.
+fn parse_nul_separated<R: BufRead>(reader: R) -> io::Result<Vec<PathBuf>> {
+    let mut filenames = Vec::new();
+    for name in reader.split(0) {
+        let name = name?;
+        if name.is_empty() {
+            return Result::Err(invalid_data("file list contains an empty file name"));
+        }
+        filenames.push(path_from_bytes(name)?);
+    }
+    Result::Ok(filenames)
+}
+
+/// Parses names listed one per line, skipping blank lines and comments.
+fn parse_lines<R: BufRead>(reader: R) -> io::Result<Vec<PathBuf>> {
+    let mut filenames = Vec::new();
+    for line in reader.split(b'\n') {
+        let line = line?;
+        // Trailing whitespace is removed from names which are valid UTF-8 as from any other text,
+        // but only ASCII whitespace from names which are not.
+        let name = match std::str::from_utf8(&line) {
+            Result::Ok(name) => name.trim_end().as_bytes(),
+            Result::Err(_) => line.trim_ascii_end(),
+        };
+        if !name.is_empty() && !name.starts_with(b"#") {
+            filenames.push(path_from_bytes(name.to_vec())?);
+        }
+    }
+    Result::Ok(filenames)
+}
+
+/// Converts a name read from a file list to a path. On Unix, names are bytes, so any name other
+/// than an empty one is valid. Elsewhere, names must be valid UTF-8.
+fn path_from_bytes(name: Vec<u8>) -> io::Result<PathBuf> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        Result::Ok(PathBuf::from(std::ffi::OsString::from_vec(name)))
+    }
+    #[cfg(not(unix))]
+    {
+        String::from_utf8(name)
+            .map(PathBuf::from)
+            .map_err(|_| invalid_data("file list contains a file name which is not valid UTF-8"))
+    }
+}
+
+/// Returns an error if "-" (stdin) appears more than once in the file names, since stdin can only
+/// be read once unless it is buffered.
+pub(crate) fn validate_stdin_once(filenames: &[PathBuf]) -> Result<(), ArgsError> {
+    let stdin = Path::new("-");
+    if filenames
+        .iter()
+        .filter(|filename| *filename == stdin)
+        .count()
+        > 1
+    {
+        return Result::Err(ArgsError::StdinRepeated);
+    }
+    Result::Ok(())
+}
+
+/// Replaces directories in the file names with the regular files they contain, recursively. Files
+/// within each directory are ordered by name. Other names, including "-" and names which do not
+/// exist, are kept as is so that they are handled like any other input file.
+///
+/// Symbolic links named in `filenames` are always followed, but links found while walking a
+/// directory are only followed if `follow_symlinks` is set; otherwise they are skipped. Returns
+/// the expanded file names along with the errors for any entries which could not be read.
+pub(crate) fn expand_directories(
+    filenames: Vec<PathBuf>,
+    follow_symlinks: bool,
+) -> (Vec<PathBuf>, Vec<(PathBuf, io::Error)>) {
+    let mut walker = Walker {
+        follow_symlinks,
+        visited: HashSet::new(),
+        filenames: Vec::new(),
+        errors: Vec::new(),
+    };
+    for filename in filenames {
+        match fs::metadata(&filename) {
+            Result::Ok(metadata) if metadata.is_dir() => walker.walk(&filename),
+            _ => walker.filenames.push(filename),
+        }
+    }
+    (walker.filenames, walker.errors)
+}
+
+struct Walker {
+    follow_symlinks: bool,
+    /// Canonical paths of the directories which have been walked, to avoid symbolic link cycles.
+    visited: HashSet<PathBuf>,
+    filenames: Vec<PathBuf>,
+    errors: Vec<(PathBuf, io::Error)>,
+}
+
+impl Walker {
+    fn walk(&mut self, dir: &Path) {
+        if let Result::Err(err) = self.try_walk(dir) {
+            self.errors.push((dir.to_path_buf(), err));
+        }
+    }
+
+    fn try_walk(&mut self, dir: &Path) -> io::Result<()> {
+        if !self.visited.insert(fs::canonicalize(dir)?) {
+            return Result::Ok(());
+        }
+
+        let mut entries = fs::read_dir(dir)?
+            .map(|entry| entry.map(|entry| entry.path()))
+            .collect::<io::Result<Vec<PathBuf>>>()?;
+        entries.sort();
+
+        for path in entries {
+            let metadata = match self.entry_metadata(&path) {
+                Result::Ok(Option::Some(metadata)) => metadata,
+                Result::Ok(Option::None) => continue,
+                Result::Err(err) => {
+                    self.errors.push((path, err));
+                    continue;
+                }
+            };
+
+            if metadata.is_dir() {
+                self.walk(&path);
+            } else if metadata.is_file() {
+                self.filenames.push(path);
+            }
+        }
+        Result::Ok(())
+    }
+
+    /// Returns the metadata of a directory entry, or `None` if it is a symbolic link which should
+    /// not be followed.
+    fn entry_metadata(&self, path: &Path) -> io::Result<Option<fs::Metadata>> {
+        let metadata = fs::symlink_metadata(path)?;
+        if !metadata.file_type().is_symlink() {
+            Result::Ok(Option::Some(metadata))
+        } else if self.follow_symlinks {
+            fs::metadata(path).map(Option::Some)
+        } else {
+            Result::Ok(Option::None)
+        }
+    }
+}
+
+/// Characters which make a file name a glob pattern.
+const WILDCARDS: [char; 3] = ['*', '?', '['];
+
+/// Replaces each file name which contains `*`, `?` or `[` with the paths which match it as a glob
+/// pattern, in order by name. `*` matches any part of a name, `?` matches any one character, and
+/// `[...]` matches any one of the enclosed characters or ranges, or any other character if it
+/// starts with `!`. Wildcards do not match `/`, but may be used in any component of the path.
+/// Other names, including "-", are kept as is.
+///
+/// Returns the expanded file names along with an error for each pattern which matches nothing,
+/// unless `allow_empty` is set, in which case the pattern is dropped.
+pub(crate) fn expand_globs(
+    filenames: Vec<PathBuf>,
+    allow_empty: bool,
+) -> (Vec<PathBuf>, Vec<(PathBuf, io::Error)>) {
+    let mut expanded = Vec::new();
+    let mut errors = Vec::new();
+    for filename in filenames {
+        if filename == Path::new("-") || !filename.to_string_lossy().contains(WILDCARDS) {
+            expanded.push(filename);
+            continue;
+        }
+        let matches = glob(&filename);
+        if matches.is_empty() && !allow_empty {
+            let err = io::Error::new(io::ErrorKind::NotFound, "no files match the pattern");
+            errors.push((filename, err));
+        }
+        expanded.extend(matches);
+    }
+    (expanded, errors)
+}
+
+/// Returns the paths which match a glob pattern, sorted. Directories which cannot be read are
+/// skipped, and names which are not valid UTF-8 are matched with U+FFFD in place of each invalid
+/// sequence.
+fn glob(pattern: &Path) -> Vec<PathBuf> {
+    let mut paths = vec![PathBuf::new()];
+    for component in pattern.components() {
+        let component = component.as_os_str();
+        let pattern = match component.to_str() {
+            Option::Some(pattern) if pattern.contains(WILDCARDS) => {
+                pattern.chars().collect::<Vec<char>>()
+            }
+            _ => {
+                for path in &mut paths {
+                    path.push(component);
+                }
+                continue;
+            }
+        };
+
+        let mut matches = Vec::new();
+        for dir in &paths {
+            // Relative patterns are matched in the current directory, without a leading "./".
+            let read = if dir.as_os_str().is_empty() {
+                fs::read_dir(".")
+            } else {
+                fs::read_dir(dir)
+            };
+            let entries = match read {
+                Result::Ok(entries) => entries,
+                Result::Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name();
+                let chars = name.to_string_lossy().chars().collect::<Vec<char>>();
+                if glob_matches(&pattern, &chars) {
+                    matches.push(dir.join(name));
+                }
+            }
+        }
+        paths = matches;
+    }
+
+    let mut filenames: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|path| fs::symlink_metadata(path).is_ok())
+        // A file named "-" must not be read as stdin.
+        .map(|path| {
+            if path == Path::new("-") {
+                Path::new(".").join("-")
+            } else {
+                path
+            }
+        })
+        .collect();
+    filenames.sort();
+    filenames
+}
+
+/// Returns whether a name matches a glob pattern. A `[` without a closing `]` matches itself.
+fn glob_matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.first() {
+        Option::None => name.is_empty(),
+        // Each way of splitting the name is tried, from matching nothing to matching the rest.
+        Option::Some('*') => (0..=name.len()).any(|i| glob_matches(&pattern[1..], &name[i..])),
+        Option::Some('?') => !name.is_empty() && glob_matches(&pattern[1..], &name[1..]),
+        Option::Some('[') => match (name.first(), match_class(&pattern[1..], name.first())) {
+            (Option::Some(_), Option::Some((true, len))) => {
+                glob_matches(&pattern[len + 1..], &name[1..])
+            }
+            (_, Option::Some((false, _))) | (Option::None, _) => false,
+            (Option::Some(ch), Option::None) => {
+                *ch == '[' && glob_matches(&pattern[1..], &name[1..])
+            }
+        },
+        Option::Some(ch) => {
+            name.first() == Option::Some(ch) && glob_matches(&pattern[1..], &name[1..])
+        }
+    }
+}
+
+/// Matches a character against a class, given the pattern after its opening `[`. Returns whether
+/// it matches and the length of the class including its closing `]`, or `None` if the class is
+/// not closed. A `]` right after the opening `[` or `!` is part of the class.
+fn match_class(class: &[char], ch: Option<&char>) -> Option<(bool, usize)> {
+    let negated = class.first() == Option::Some(&'!');
+    let start = if negated { 1 } else { 0 };
+    let mut matched = false;
+    let mut i = start;
+    loop {
+        match class.get(i) {
+            Option::None => return Option::None,
+            Option::Some(']') if i > start => break,
+            Option::Some(&low) => {
+                let (high, len) = match (class.get(i + 1), class.get(i + 2)) {
+                    (Option::Some('-'), Option::Some(&high)) if high != ']' => (high, 3),
+                    _ => (low, 1),
+                };
+                if let Option::Some(&ch) = ch {
+                    matched |= low <= ch && ch <= high;
+                }
+                i += len;
+            }
+        }
+    }
+    Option::Some((matched != negated, i + 1))
+}
+
+/// Returns the first of the input files which is the same file as `output`, which would be
+/// truncated before it was read if `output` were created. Files are the same if they have the same
+/// device and inode on Unix, or the same canonical path elsewhere, so links to the output are found
+/// too. Stdin, and names which do not exist, are never the same as the output.
+pub(crate) fn same_file_as<'a>(output: &Path, filenames: &'a [PathBuf]) -> Option<&'a Path> {
+    let output = FileId::of(output)?;
+    filenames
+        .iter()
+        .filter(|filename| *filename != Path::new("-"))
+        .find(|filename| FileId::of(filename).as_ref() == Option::Some(&output))
+        .map(PathBuf::as_path)
+}
+
+/// Identity of a file, whatever name it is opened by.
+#[derive(Debug, PartialEq, Eq)]
+enum FileId {
+    #[cfg(unix)]
+    Inode { dev: u64, ino: u64 },
+    #[cfg(not(unix))]
+    Path(PathBuf),
+}
+
+impl FileId {
+    /// Returns the identity of the file at `path`, or `None` if it does not exist.
+    #[cfg(unix)]
+    fn of(path: &Path) -> Option<FileId> {
+        use std::os::unix::fs::MetadataExt;
+        let metadata = fs::metadata(path).ok()?;
+        Option::Some(FileId::Inode {
+            dev: metadata.dev(),
+            ino: metadata.ino(),
+        })
+    }
+
+    /// Returns the identity of the file at `path`, or `None` if it does not exist.
+    #[cfg(not(unix))]
+    fn of(path: &Path) -> Option<FileId> {
+        fs::canonicalize(path).ok().map(FileId::Path)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FileList, ListFormat};
+    use std::path::PathBuf;
+
+    fn paths(names: &[&str]) -> Vec<PathBuf> {
+        names.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn parse_nul_separated() {
+        assert_nul_separated(b"", &[]);
+        assert_nul_separated(b"a", &["a"]);
+        assert_nul_separated(b"a\0", &["a"]);
+        assert_nul_separated(b"a\0b c\0d\ne\0", &["a", "b c", "d\ne"]);
+        assert_nul_separated(b"-\0\xce\xb1.txt", &["-", "α.txt"]);
+
+        assert!(super::parse_nul_separated(&b"a\0\0b"[..]).is_err());
+        assert!(super::parse_nul_separated(&b"\0"[..]).is_err());
+        #[cfg(not(unix))]
+        assert!(super::parse_nul_separated(&b"a\0\xff\0"[..]).is_err());
+    }
+
+    #[test]
+    fn parse_lines() {
+        assert_lines("", &[]);
+        assert_lines("a", &["a"]);
+        assert_lines("a\nb c\n", &["a", "b c"]);
+        assert_lines("a \t\r\n  b\n", &["a", "  b"]);
+        assert_lines("\n\n  \na\n\t\n", &["a"]);
+        assert_lines("# comment\na\n#b\n c#d\n", &["a", " c#d"]);
+        assert_lines("-\nα.txt\n", &["-", "α.txt"]);
+
+        #[cfg(not(unix))]
+        assert!(super::parse_lines(&b"a\n\xff\n"[..]).is_err());
+    }
+
+    /// Names are bytes on Unix, so names which are not valid UTF-8 are kept as they are.
+    #[test]
+    #[cfg(unix)]
+    fn parse_names_not_utf8() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+        let path = |name: &[u8]| PathBuf::from(OsStr::from_bytes(name));
+
+        assert_eq!(
+            super::parse_nul_separated(&b"a\0\xff \0"[..]).unwrap(),
+            vec![path(b"a"), path(b"\xff ")]
+        );
+        assert_eq!(
+            super::parse_lines(&b"a\n\xff \t\n#\xfe\n"[..]).unwrap(),
+            vec![path(b"a"), path(b"\xff")]
+        );
+    }
+
+    #[test]
+    fn read_file_list() {
+        let list = FileList {
+            path: PathBuf::from("tests/files/manifest.txt"),
+            format: ListFormat::Lines,
+        };
+        assert_eq!(
+            super::read_file_list(&list).unwrap(),
+            paths(&["tests/files/ascii.txt", "tests/files/utf8.txt", "-"])
+        );
+
+        let list = FileList {
+            path: PathBuf::from("tests/files/does-not-exist.txt"),
+            format: ListFormat::Nul,
+        };
+        assert!(super::read_file_list(&list).is_err());
+    }
+
+    #[test]
+    fn same_file_as() {
+        let dir = std::env::temp_dir().join(format!("rut-same-file-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = |name: &str| dir.join(name);
+        std::fs::write(path("input.txt"), "a\n").unwrap();
+        std::fs::write(path("other.txt"), "b\n").unwrap();
+        let filenames = vec![
+            PathBuf::from("-"),
+            path("other.txt"),
+            path("missing.txt"),
+            // The same file, by another name.
+            dir.join("..")
+                .join(dir.file_name().unwrap())
+                .join("input.txt"),
+        ];
+
+        assert_eq!(
+            super::same_file_as(&path("input.txt"), &filenames),
+            Option::Some(filenames[3].as_path())
+        );
+        assert_eq!(
+            super::same_file_as(&path("rejects.txt"), &filenames),
+            Option::None
+        );
+        // A name which does not exist yet is never an input, even if it is also named as one.
+        assert_eq!(
+            super::same_file_as(&path("missing.txt"), &filenames),
+            Option::None
+        );
+        assert_eq!(
+            super::same_file_as(&path("other.txt"), &filenames[..1]),
+            Option::None
+        );
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn validate_stdin_once() {
+        let filenames = paths;
+        assert!(super::validate_stdin_once(&filenames(&[])).is_ok());
+        assert!(super::validate_stdin_once(&filenames(&["-"])).is_ok());
+        assert!(super::validate_stdin_once(&filenames(&["a", "-", "b"])).is_ok());
+        assert!(super::validate_stdin_once(&filenames(&["-", "a", "-"])).is_err());
+        assert!(super::validate_stdin_once(&filenames(&["-", "-"])).is_err());
+    }
+
+    #[test]
+    fn expand_directories() {
+        let root = TempDir::new("expand_directories");
+        root.file("b.txt");
+        root.file("a/z.txt");
+        root.file("a/b/y.txt");
+        root.file("a/c.txt");
+        root.dir("empty");
+        let path = |name: &str| root.path(name);
+
+        let (filenames, errors) = super::expand_directories(
+            vec![PathBuf::from("-"), path(""), path("b.txt"), path("missing")],
+            false,
+        );
+        assert!(errors.is_empty());
+        assert_eq!(
+            filenames,
+            vec![
+                PathBuf::from("-"),
+                path("a/b/y.txt"),
+                path("a/c.txt"),
+                path("a/z.txt"),
+                path("b.txt"),
+                path("b.txt"),
+                path("missing"),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn expand_directories_symlinks() {
+        use std::os::unix::fs::symlink;
+
+        let root = TempDir::new("expand_directories_symlinks");
+        root.file("dir/a.txt");
+        symlink(root.path("dir/a.txt"), root.path("dir/b.txt")).unwrap();
+        symlink(root.path("dir"), root.path("dir/loop")).unwrap();
+        symlink(root.path("does-not-exist"), root.path("dir/broken")).unwrap();
+        let path = |name: &str| root.path(name);
+
+        let (filenames, errors) = super::expand_directories(vec![path("dir")], false);
+        assert_eq!(filenames, vec![path("dir/a.txt")]);
+        assert!(errors.is_empty());
+
+        let (filenames, errors) = super::expand_directories(vec![path("dir")], true);
+        assert_eq!(filenames, vec![path("dir/a.txt"), path("dir/b.txt")]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, path("dir/broken"));
+
+        // Symbolic links given as arguments are always followed.
+        let (filenames, _) = super::expand_directories(vec![path("dir/loop")], false);
+        assert_eq!(filenames, vec![path("dir/loop/a.txt")]);
+    }
+
+    #[test]
+    fn glob_matches() {
+        let matches = |pattern: &str, name: &str| {
+            let chars = |s: &str| s.chars().collect::<Vec<char>>();
+            super::glob_matches(&chars(pattern), &chars(name))
+        };
+        assert!(matches("*.csv", "a.csv"));
+        assert!(matches("*.csv", ".csv"));
+        assert!(matches("a*b*c", "abbbc"));
+        assert!(!matches("*.csv", "a.csv.gz"));
+        assert!(matches("?.txt", "α.txt"));
+        assert!(!matches("?.txt", "ab.txt"));
+        assert!(!matches("?", ""));
+
+        assert!(matches("[ab].txt", "b.txt"));
+        assert!(!matches("[ab].txt", "c.txt"));
+        assert!(matches("[a-c][!0-9]", "bx"));
+        assert!(!matches("[a-c][!0-9]", "b5"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[!]]", "a"));
+        assert!(matches("[a-]", "-"));
+        assert!(matches("[*]", "*"));
+        assert!(!matches("[*]", "a"));
+        // An unclosed class matches itself.
+        assert!(matches("a[b", "a[b"));
+        assert!(!matches("a[b", "ab"));
+    }
+
+    #[test]
+    fn expand_globs() {
+        let root = TempDir::new("expand_globs");
+        root.file("b.csv");
+        root.file("a.csv");
+        root.file("c.txt");
+        root.file("x/d.csv");
+        root.file("y/e.csv");
+        root.file("y/f.txt");
+        let path = |name: &str| root.path(name);
+
+        let (filenames, errors) = super::expand_globs(
+            vec![
+                PathBuf::from("-"),
+                path("*.csv"),
+                path("c.txt"),
+                path("*/*.csv"),
+                path("missing"),
+            ],
+            false,
+        );
+        assert!(errors.is_empty());
+        assert_eq!(
+            filenames,
+            vec![
+                PathBuf::from("-"),
+                path("a.csv"),
+                path("b.csv"),
+                path("c.txt"),
+                path("x/d.csv"),
+                path("y/e.csv"),
+                path("missing"),
+            ]
+        );
+
+        let patterns = vec![path("*.json"), path("?.txt")];
+        let (filenames, errors) = super::expand_globs(patterns.clone(), false);
+        assert_eq!(filenames, vec![path("c.txt")]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, path("*.json"));
+        let (filenames, errors) = super::expand_globs(patterns, true);
+        assert_eq!(filenames, vec![path("c.txt")]);
+        assert!(errors.is_empty());
+    }
+
+    /// Directory which is removed when dropped.
+    struct TempDir {
+        root: PathBuf,
+    }
+
+    impl TempDir {
+        fn new(name: &str) -> TempDir {
+            let root = std::env::temp_dir().join(format!("rut-{}-{}", name, std::process::id()));
+            let _ = std::fs::remove_dir_all(&root);
+            std::fs::create_dir_all(&root).unwrap();
+            TempDir { root }
+        }
+
+        fn path(&self, name: &str) -> PathBuf {
+            self.root.join(name)
+        }
+
+        fn dir(&self, name: &str) {
+            std::fs::create_dir_all(self.root.join(name)).unwrap();
+        }
+
+        fn file(&self, name: &str) {
+            let path = self.root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, name).unwrap();
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.root);
+        }
+    }
+
+    fn assert_lines(input: &str, expected: &[&str]) {
+        assert_eq!(
+            super::parse_lines(input.as_bytes()).unwrap(),
+            paths(expected)
+        );
+    }
+
+    fn assert_nul_separated(input: &[u8], expected: &[&str]) {
+        assert_eq!(super::parse_nul_separated(input).unwrap(), paths(expected));
+    }
+}
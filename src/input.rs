@@ -0,0 +1,697 @@
+use encoding_rs::{CoderResult, Decoder, Encoding};
+use flate2::read::MultiGzDecoder;
+use memmap2::Mmap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Cursor, Read};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+/// Capacity of the buffer which each input is read through. Records which fit in the buffer can
+/// be cut in place, and larger buffers made little difference in benchmarks.
+const BUFFER_CAPACITY: usize = 64 * 1024;
+
+/// Options which control how input files are read.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct InputOptions {
+    /// Whether gzip compressed input is detected and decompressed.
+    pub(crate) decompress: bool,
+    /// Encoding of the input, or `None` to read the input as is.
+    pub(crate) encoding: Option<&'static Encoding>,
+    /// Whether a UTF-8 byte order mark at the start of the input is kept.
+    pub(crate) keep_bom: bool,
+    /// Whether stdin is buffered so that it can be read more than once.
+    pub(crate) reuse_stdin: bool,
+    /// Number of bytes to skip at the start of each input, after decompressing and decoding it.
+    pub(crate) skip_bytes: u64,
+    /// How often to check for more input once the end of the last file has been reached, or `None`
+    /// to stop at the end of the file.
+    pub(crate) follow: Option<Duration>,
+    /// Whether regular files are memory mapped rather than read through a buffer.
+    pub(crate) mmap: bool,
+    /// Whether regular files are copied to stdout by the kernel, when cutting would not change
+    /// them.
+    pub(crate) zero_copy: bool,
+}
+
+impl Default for InputOptions {
+    fn default() -> Self {
+        InputOptions {
+            decompress: true,
+            encoding: Option::None,
+            keep_bom: false,
+            reuse_stdin: false,
+            skip_bytes: 0,
+            follow: Option::None,
+            mmap: false,
+            zero_copy: false,
+        }
+    }
+}
+
+/// Opens input files using the same options for each file.
+pub(crate) struct Inputs {
+    options: InputOptions,
+    /// Contents of stdin, once it has been read, if `reuse_stdin` is set.
+    stdin: Option<Rc<[u8]>>,
+    /// How the last input was opened, e.g. "decompressed", for --verbose.
+    details: Vec<String>,
+}
+
+impl Inputs {
+    pub(crate) fn new(options: InputOptions) -> Inputs {
+        Inputs {
+            options,
+            stdin: Option::None,
+            details: Vec::new(),
+        }
+    }
+
+    /// Returns how the last input was opened, such as whether it was memory mapped or
+    /// decompressed.
+    pub(crate) fn details(&self) -> &[String] {
+        &self.details
+    }
+
+    /// Opens a file for reading. "-" is treated as stdin. If `reuse_stdin` is set, stdin is read in
+    /// full the first time it is opened and the same contents are read each time after that. If
+    /// `mmap` is set, regular files are memory mapped where possible.
+    pub(crate) fn open(&mut self, filename: &Path) -> io::Result<Box<dyn BufRead>> {
+        if filename != Path::new("-") {
+            return self.read_file(open_file(filename)?);
+        }
+        self.details.clear();
+        let reader: Box<dyn Read> = if self.options.reuse_stdin {
+            Box::new(Cursor::new(self.buffered_stdin()?))
+        } else {
+            Box::new(io::stdin())
+        };
+        self.buffer(reader)
+    }
+
+    /// Reads a file which has already been opened with [`open_file`], as `open` does.
+    pub(crate) fn read_file(&mut self, file: File) -> io::Result<Box<dyn BufRead>> {
+        self.details.clear();
+        if self.options.mmap {
+            if let Option::Some(map) = map_file(&file, &self.options)? {
+                self.details.push(String::from("memory mapped"));
+                return Result::Ok(map);
+            }
+        }
+        self.buffer(Box::new(file))
+    }
+
+    fn buffer(&mut self, reader: Box<dyn Read>) -> io::Result<Box<dyn BufRead>> {
+        Result::Ok(Box::new(BufReader::with_capacity(
+            BUFFER_CAPACITY,
+            wrap(reader, &self.options, &mut self.details)?,
+        )))
+    }
+
+    /// Opens a file for reading and keeps reading it as it grows, as specified by `follow`. "-" is
+    /// opened as usual, since reading stdin already waits for more input.
+    pub(crate) fn open_following(
+        &mut self,
+        filename: &Path,
+        interval: Duration,
+    ) -> io::Result<Box<dyn BufRead>> {
+        if filename == Path::new("-") {
+            return self.open(filename);
+        }
+        self.details.clear();
+        let reader = FollowReader::open(filename, interval)?;
+        self.details.push(String::from("following"));
+        self.buffer(Box::new(reader))
+    }
+
+    fn buffered_stdin(&mut self) -> io::Result<Rc<[u8]>> {
+        if let Option::Some(stdin) = &self.stdin {
+            return Result::Ok(Rc::clone(stdin));
+        }
+        let mut buf = Vec::new();
+        io::stdin().read_to_end(&mut buf)?;
+        let stdin: Rc<[u8]> = Rc::from(buf);
+        self.stdin = Option::Some(Rc::clone(&stdin));
+        Result::Ok(stdin)
+    }
+}
+
+/// Number of opened files which can wait to be read, in addition to the one being opened.
+const PREFETCH: usize = 1;
+
+/// Opens files in a background thread, ahead of the file being read, so that the time taken to
+/// open each file, e.g. on network storage, overlaps with cutting the one before it.
+pub(crate) struct Prefetch {
+    receiver: Receiver<Option<io::Result<File>>>,
+}
+
+impl Prefetch {
+    /// Starts opening each of `filenames` with [`open_file`], in order. Only regular files are
+    /// opened, since opening e.g. a named pipe may wait for a writer, and not the last file if
+    /// `follow_last` is set, since it is opened to be followed instead.
+    pub(crate) fn new(filenames: Vec<PathBuf>, follow_last: bool) -> Prefetch {
+        // The file after the one being read is opened, and waits to be read, while the one after it
+        // is opened.
+        let (sender, receiver) = mpsc::sync_channel(PREFETCH);
+        let last = filenames.len().saturating_sub(1);
+        thread::spawn(move || {
+            for (i, filename) in filenames.iter().enumerate() {
+                let opened = filename != Path::new("-")
+                    && !(follow_last && i == last)
+                    && fs::metadata(filename).is_ok_and(|metadata| metadata.is_file());
+                // Stops once the files are no longer needed.
+                if sender.send(opened.then(|| open_file(filename))).is_err() {
+                    break;
+                }
+            }
+        });
+        Prefetch { receiver }
+    }
+
+    /// Returns the next file, or `None` if it was not opened in advance, in which case it is
+    /// opened as usual. Must be called once for each file, in order.
+    pub(crate) fn next(&mut self) -> Option<io::Result<File>> {
+        self.receiver.recv().ok().flatten()
+    }
+}
+
+/// Opens a file, returning an error if it is a directory rather than failing when it is read.
+pub(crate) fn open_file(filename: &Path) -> io::Result<File> {
+    let file = File::open(filename)?;
+    if file.metadata()?.is_dir() {
+        return Result::Err(io::Error::new(
+            io::ErrorKind::IsADirectory,
+            "Is a directory",
+        ));
+    }
+    Result::Ok(file)
+}
+
+/// Memory maps a file so that records can be read directly from the mapped bytes. Returns `None`
+/// if the file should be read as usual instead: if it is not a regular file (e.g. a pipe or a
+/// device), if it is empty, or if it needs to be decompressed or decoded.
+///
+/// The byte order mark and `skip_bytes` are handled by starting the returned reader part way into
+/// the map, so the output is the same as reading the file through `wrap`.
+fn map_file(file: &File, options: &InputOptions) -> io::Result<Option<Box<dyn BufRead>>> {
+    let metadata = file.metadata()?;
+    // Zero length maps are not supported on every platform.
+    if !metadata.is_file() || metadata.len() == 0 || options.encoding.is_some() {
+        return Result::Ok(Option::None);
+    }
+
+    // SAFETY: the map is only read. If the file is truncated by another process while it is
+    // mapped, reading the truncated part may fail with SIGBUS; this is the documented trade-off of
+    // --mmap, which should not be used for files that may be modified while they are read.
+    let map = unsafe { Mmap::map(file)? };
+    let start = match content_start(&map, map.len() as u64, options) {
+        Option::Some(start) => start,
+        Option::None => return Result::Ok(Option::None),
+    };
+
+    let mut reader = Cursor::new(map);
+    reader.set_position(start);
+    Result::Ok(Option::Some(Box::new(reader)))
+}
+
+/// Number of bytes at the start of a file which `content_start` needs, if the file is that long.
+pub(crate) const HEAD_LEN: usize = UTF8_BOM.len();
+
+/// Returns the offset at which reading a file of `len` bytes through `wrap` would start, after the
+/// byte order mark and `skip_bytes`, given its first `HEAD_LEN` bytes. Returns `None` if the file
+/// needs to be decompressed, so it cannot be read from an offset.
+pub(crate) fn content_start(head: &[u8], len: u64, options: &InputOptions) -> Option<u64> {
+    if options.decompress && head.starts_with(&GZIP_MAGIC) {
+        return Option::None;
+    }
+
+    let mut start = 0;
+    if !options.keep_bom && head.starts_with(&UTF8_BOM) {
+        start += UTF8_BOM.len() as u64;
+    }
+    Option::Some((start + options.skip_bytes).min(len))
+}
+
+/// Reader which waits for more data at the end of a file, rather than returning end of file. The
+/// file is reopened from the start if it is truncated, or if it is replaced (e.g. by log rotation).
+struct FollowReader {
+    path: PathBuf,
+    file: File,
+    /// Number of bytes read from the current file.
+    position: u64,
+    identity: Option<u64>,
+    interval: Duration,
+}
+
+impl FollowReader {
+    fn open(filename: &Path, interval: Duration) -> io::Result<FollowReader> {
+        let file = open_file(filename)?;
+        let identity = file_identity(&file.metadata()?);
+        Result::Ok(FollowReader {
+            path: filename.to_path_buf(),
+            file,
+            position: 0,
+            identity,
+            interval,
+        })
+    }
+
+    /// Returns whether the file at the path has been truncated or replaced by a different file. A
+    /// missing file (e.g. in the middle of a rotation) is not considered replaced until it exists.
+    fn replaced(&self) -> bool {
+        match std::fs::metadata(&self.path) {
+            Result::Ok(metadata) => {
+                file_identity(&metadata) != self.identity || metadata.len() < self.position
+            }
+            Result::Err(_) => false,
+        }
+    }
+}
+
+impl Read for FollowReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.file.read(buf)?;
+            if n > 0 || buf.is_empty() {
+                self.position += n as u64;
+                return Result::Ok(n);
+            }
+
+            if self.replaced() {
+                self.file = File::open(&self.path)?;
+                self.identity = file_identity(&self.file.metadata()?);
+                self.position = 0;
+            } else {
+                std::thread::sleep(self.interval);
+            }
+        }
+    }
+}
+
+/// Returns a value which identifies the file on disk, if supported by the platform.
+#[cfg(unix)]
+fn file_identity(metadata: &std::fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Option::Some(metadata.ino())
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &std::fs::Metadata) -> Option<u64> {
+    Option::None
+}
+
+/// Wraps the reader to decompress and decode it, as specified by the options. Adds how it was
+/// wrapped to `details`.
+fn wrap(
+    mut reader: Box<dyn Read>,
+    options: &InputOptions,
+    details: &mut Vec<String>,
+) -> io::Result<Box<dyn Read>> {
+    if options.decompress {
+        reader = maybe_decompress(reader, details)?;
+    }
+    match options.encoding {
+        Option::Some(encoding) => {
+            details.push(format!("decoded from {}", encoding.name()));
+            reader = Box::new(DecodingReader::new(reader, encoding))
+        }
+        Option::None if !options.keep_bom => reader = strip_bom(reader)?,
+        Option::None => {}
+    }
+    if options.skip_bytes > 0 {
+        io::copy(&mut (&mut reader).take(options.skip_bytes), &mut io::sink())?;
+    }
+    Result::Ok(reader)
+}
+
+/// Magic bytes at the start of a gzip stream.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Wraps the reader in a gzip decoder if it starts with the gzip magic bytes. The bytes read while
+/// checking are passed on to the returned reader.
+fn maybe_decompress<R: Read + 'static>(
+    mut reader: R,
+    details: &mut Vec<String>,
+) -> io::Result<Box<dyn Read>> {
+    let mut magic = Vec::with_capacity(GZIP_MAGIC.len());
+    (&mut reader)
+        .take(GZIP_MAGIC.len() as u64)
+        .read_to_end(&mut magic)?;
+
+    let reader = Cursor::new(magic.clone()).chain(reader);
+    if magic == GZIP_MAGIC {
+        details.push(String::from("gzip decompressed"));
+        Result::Ok(Box::new(MultiGzDecoder::new(reader)))
+    } else {
+        Result::Ok(Box::new(reader))
+    }
+}
+
+/// UTF-8 encoded byte order mark.
+const UTF8_BOM: [u8; 3] = [0xef, 0xbb, 0xbf];
+
+/// Removes a UTF-8 byte order mark from the start of the reader, if there is one. Any other bytes
+/// read while checking are passed on to the returned reader.
+fn strip_bom<R: Read + 'static>(mut reader: R) -> io::Result<Box<dyn Read>> {
+    let mut bom = Vec::with_capacity(UTF8_BOM.len());
+    (&mut reader)
+        .take(UTF8_BOM.len() as u64)
+        .read_to_end(&mut bom)?;
+
+    if bom == UTF8_BOM {
+        Result::Ok(Box::new(reader))
+    } else {
+        Result::Ok(Box::new(Cursor::new(bom).chain(reader)))
+    }
+}
+
+/// Size of the buffer used to read encoded input.
+const DECODE_BUFFER_SIZE: usize = 8 * 1024;
+
+/// Reader which decodes input in the specified encoding and produces UTF-8. Malformed input is
+/// replaced with U+FFFD. A byte order mark matching the encoding is removed from the start of the
+/// input.
+///
+/// Since the output is UTF-8, records can be split on the line delimiter after decoding, even for
+/// encodings such as UTF-16 where the line delimiter is not a single byte.
+struct DecodingReader<R> {
+    inner: R,
+    decoder: Decoder,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    position: usize,
+    finished: bool,
+}
+
+impl<R: Read> DecodingReader<R> {
+    fn new(inner: R, encoding: &'static Encoding) -> DecodingReader<R> {
+        DecodingReader {
+            inner,
+            decoder: encoding.new_decoder_with_bom_removal(),
+            input: vec![0; DECODE_BUFFER_SIZE],
+            output: Vec::new(),
+            position: 0,
+            finished: false,
+        }
+    }
+
+    /// Reads and decodes input until some output is available or the end of the input is reached.
+    fn fill_output(&mut self) -> io::Result<()> {
+        self.output.clear();
+        self.position = 0;
+        while self.output.is_empty() && !self.finished {
+            let n = self.inner.read(&mut self.input)?;
+            let last = n == 0;
+
+            // Decoding must finish in a single call since the output buffer is large enough for
+            // any input.
+            let max_length = self
+                .decoder
+                .max_utf8_buffer_length(n)
+                .expect("Decode buffer size should not overflow.");
+            self.output.resize(max_length, 0);
+            let (result, read, written, _) =
+                self.decoder
+                    .decode_to_utf8(&self.input[..n], &mut self.output, last);
+            debug_assert_eq!(result, CoderResult::InputEmpty);
+            debug_assert_eq!(read, n);
+
+            self.output.truncate(written);
+            self.finished = last;
+        }
+        Result::Ok(())
+    }
+}
+
+impl<R: Read> Read for DecodingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.output.len() {
+            self.fill_output()?;
+        }
+        let available = &self.output[self.position..];
+        let n = available.len().min(buf.len());
+        buf[..n].copy_from_slice(&available[..n]);
+        self.position += n;
+        Result::Ok(n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InputOptions, Inputs};
+    use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, WINDOWS_1252};
+    use std::io::{Cursor, Read};
+    use std::path::Path;
+
+    #[test]
+    fn open() {
+        let decompress = InputOptions::default();
+        let raw = InputOptions {
+            decompress: false,
+            ..InputOptions::default()
+        };
+        assert!(Inputs::new(raw).open(Path::new("-")).is_ok());
+        assert!(Inputs::new(decompress)
+            .open(Path::new("tests/files/ascii.txt"))
+            .is_ok());
+        assert!(Inputs::new(raw)
+            .open(Path::new("tests/files/ascii.txt.gz"))
+            .is_ok());
+        assert!(Inputs::new(decompress)
+            .open(Path::new("tests/files/does-not-exist.txt"))
+            .is_err());
+        assert!(Inputs::new(decompress)
+            .open(Path::new("tests/files"))
+            .is_err());
+    }
+
+    #[test]
+    fn details() {
+        let details = |options: InputOptions, filename: &str| {
+            let mut inputs = Inputs::new(options);
+            inputs.open(Path::new(filename)).unwrap();
+            inputs.details().to_vec()
+        };
+        let mmap = InputOptions {
+            mmap: true,
+            ..InputOptions::default()
+        };
+        let latin1 = InputOptions {
+            encoding: Option::Some(encoding_rs::WINDOWS_1252),
+            ..InputOptions::default()
+        };
+        assert!(details(InputOptions::default(), "tests/files/ascii.txt").is_empty());
+        assert_eq!(
+            details(InputOptions::default(), "tests/files/ascii.txt.gz"),
+            &["gzip decompressed"]
+        );
+        assert_eq!(details(mmap, "tests/files/ascii.txt"), &["memory mapped"]);
+        assert_eq!(
+            details(mmap, "tests/files/ascii.txt.gz"),
+            &["gzip decompressed"]
+        );
+        assert_eq!(
+            details(latin1, "tests/files/latin1.txt"),
+            &["decoded from windows-1252"]
+        );
+    }
+
+    #[test]
+    fn map_file() {
+        let map = |filename: &str, options: &InputOptions| {
+            let file = std::fs::File::open(filename).unwrap();
+            super::map_file(&file, options).unwrap().map(|mut reader| {
+                let mut output = Vec::new();
+                reader.read_to_end(&mut output).unwrap();
+                output
+            })
+        };
+        let options = InputOptions::default();
+        assert_eq!(
+            map("tests/files/ascii.txt", &options).unwrap(),
+            std::fs::read("tests/files/ascii.txt").unwrap()
+        );
+        assert_eq!(
+            map("tests/files/utf8-bom.txt", &options).unwrap(),
+            &std::fs::read("tests/files/utf8-bom.txt").unwrap()[3..]
+        );
+        let skip = InputOptions {
+            skip_bytes: 2,
+            keep_bom: true,
+            ..options
+        };
+        assert_eq!(
+            map("tests/files/utf8-bom.txt", &skip).unwrap(),
+            &std::fs::read("tests/files/utf8-bom.txt").unwrap()[2..]
+        );
+
+        // Inputs which must be decompressed or decoded are read as usual.
+        assert!(map("tests/files/ascii.txt.gz", &options).is_none());
+        let latin1 = InputOptions {
+            encoding: Option::Some(WINDOWS_1252),
+            ..options
+        };
+        assert!(map("tests/files/latin1.txt", &latin1).is_none());
+        let raw = InputOptions {
+            decompress: false,
+            ..options
+        };
+        assert!(map("tests/files/ascii.txt.gz", &raw).is_some());
+    }
+
+    #[test]
+    fn skip_bytes() {
+        let skip = |skip_bytes| InputOptions {
+            skip_bytes,
+            ..InputOptions::default()
+        };
+        assert_wrapped(b"abcdef", &skip(0), b"abcdef");
+        assert_wrapped(b"abcdef", &skip(2), b"cdef");
+        assert_wrapped(b"abcdef", &skip(6), b"");
+        assert_wrapped(b"abcdef", &skip(100), b"");
+
+        // Bytes are skipped after removing the byte order mark and decoding.
+        assert_wrapped(b"\xef\xbb\xbfabc", &skip(1), b"bc");
+        let utf16 = InputOptions {
+            encoding: Option::Some(UTF_16LE),
+            ..skip(2)
+        };
+        assert_wrapped(b"\xb1\x03b\x00c\x00", &utf16, b"bc");
+    }
+
+    #[test]
+    fn maybe_decompress() {
+        assert_decompressed(b"", b"");
+        assert_decompressed(b"\x1f", b"\x1f");
+        assert_decompressed(b"abc", b"abc");
+        assert_decompressed(b"\x1f\x8a", b"\x1f\x8a");
+
+        let compressed = std::fs::read("tests/files/ascii.txt.gz").unwrap();
+        let uncompressed = std::fs::read("tests/files/ascii.txt").unwrap();
+        assert_decompressed(&compressed, &uncompressed);
+
+        // Concatenated gzip members are decompressed as a single stream.
+        let concatenated = [&compressed[..], &compressed[..]].concat();
+        assert_decompressed(
+            &concatenated,
+            &[&uncompressed[..], &uncompressed[..]].concat(),
+        );
+
+        // Truncated gzip stream.
+        let truncated = compressed[..compressed.len() / 2].to_vec();
+        let mut output = Vec::new();
+        let result = super::maybe_decompress(Cursor::new(truncated), &mut Vec::new())
+            .and_then(|mut reader| reader.read_to_end(&mut output));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn strip_bom() {
+        assert_bom_stripped(b"", b"");
+        assert_bom_stripped(b"\xef\xbb\xbf", b"");
+        assert_bom_stripped(b"\xef\xbb\xbfabc", b"abc");
+        assert_bom_stripped(b"abc", b"abc");
+        assert_bom_stripped(b"\xef\xbb", b"\xef\xbb");
+        assert_bom_stripped(b"\xef\xbb\xbe", b"\xef\xbb\xbe");
+
+        // Only the first byte order mark is removed.
+        assert_bom_stripped(b"\xef\xbb\xbfa\n\xef\xbb\xbfb\n", b"a\n\xef\xbb\xbfb\n");
+        assert_bom_stripped(b"\xef\xbb\xbf\xef\xbb\xbf", b"\xef\xbb\xbf");
+    }
+
+    #[test]
+    fn decode() {
+        assert_decoded(b"", WINDOWS_1252, "");
+        assert_decoded(b"caf\xe9\n\xa9", WINDOWS_1252, "café\n©");
+        assert_decoded(b"\x80", WINDOWS_1252, "€");
+        assert_decoded(b"a\x00\xb1\x03\n\x00", UTF_16LE, "aα\n");
+        assert_decoded(b"\x00a\x03\xb1\x00\n", UTF_16BE, "aα\n");
+        assert_decoded(b"\x3d\xd8\x00\xde", UTF_16LE, "😀");
+
+        // Byte order mark is removed.
+        assert_decoded(b"\xff\xfea\x00", UTF_16LE, "a");
+        assert_decoded(b"\xfe\xff\x00a", UTF_16BE, "a");
+
+        // Malformed input.
+        assert_decoded(b"a\x00b", UTF_16LE, "a\u{fffd}");
+        assert_decoded(b"\x00\xd8a\x00", UTF_16LE, "\u{fffd}a");
+
+        // Input larger than the decode buffer, with a character split across reads.
+        let input = [0xb1, 0x03].repeat(super::DECODE_BUFFER_SIZE);
+        let expected = "α".repeat(super::DECODE_BUFFER_SIZE);
+        assert_decoded(&input[1..input.len() - 1], UTF_16BE, &expected[2..]);
+        assert_decoded(&input, UTF_16LE, &expected);
+    }
+
+    #[test]
+    fn follow() {
+        let dir = std::env::temp_dir().join(format!("rut-follow-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("follow.txt");
+        std::fs::write(&path, "abc\n").unwrap();
+
+        let interval = std::time::Duration::from_millis(1);
+        let mut reader = super::FollowReader::open(&path, interval).unwrap();
+        assert_read(&mut reader, "abc\n");
+
+        // Appended data.
+        append(&path, "de");
+        assert_read(&mut reader, "de");
+
+        // Truncated file.
+        std::fs::write(&path, "f\n").unwrap();
+        assert_read(&mut reader, "f\n");
+
+        // Replaced file.
+        std::fs::rename(&path, dir.join("follow.txt.1")).unwrap();
+        std::fs::write(&path, "ghijklmn\n").unwrap();
+        if cfg!(unix) {
+            assert_read(&mut reader, "ghijklmn\n");
+        }
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    fn append(path: &std::path::Path, contents: &str) {
+        use std::io::Write;
+        let mut file = std::fs::OpenOptions::new().append(true).open(path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+    }
+
+    fn assert_read<R: Read>(reader: &mut R, expected: &str) {
+        let mut buf = vec![0; expected.len()];
+        reader.read_exact(&mut buf).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), expected);
+    }
+
+    fn assert_wrapped(input: &'static [u8], options: &InputOptions, expected: &[u8]) {
+        let mut reader = super::wrap(Box::new(input), options, &mut Vec::new()).unwrap();
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, expected);
+    }
+
+    fn assert_decompressed(input: &[u8], expected: &[u8]) {
+        let mut reader =
+            super::maybe_decompress(Cursor::new(input.to_vec()), &mut Vec::new()).unwrap();
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, expected);
+    }
+
+    fn assert_bom_stripped(input: &[u8], expected: &[u8]) {
+        let mut reader = super::strip_bom(Cursor::new(input.to_vec())).unwrap();
+        let mut output = Vec::new();
+        reader.read_to_end(&mut output).unwrap();
+        assert_eq!(output, expected);
+    }
+
+    fn assert_decoded(input: &[u8], encoding: &'static Encoding, expected: &str) {
+        let mut reader = super::DecodingReader::new(input, encoding);
+        let mut output = String::new();
+        reader.read_to_string(&mut output).unwrap();
+        assert_eq!(output, expected);
+    }
+}
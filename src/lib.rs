@@ -0,0 +1,40 @@
+//! Select bytes, characters, or fields from each line of the input, like `cut`.
+//!
+//! This is the library used by the `rut` command line tool. A [`Cut`] describes what to select
+//! from each record, using [`Ranges`](range::Ranges) parsed from the same syntax as the command
+//! line, e.g. `"1,3-5,7-"`, and is created with a [`CutBuilder`].
+//!
+//! Input is read from any [`BufRead`](std::io::BufRead) and output is written to any
+//! [`Write`](std::io::Write), so input files need to be opened, and decompressed or decoded if
+//! needed, by the caller.
+//!
+//! # Examples
+//!
+//! ```
+//! use rut::{Cut, Delim};
+//!
+//! let cut = Cut::fields("1,3".parse()?)
+//!     .delimiter(Delim::Char(','))
+//!     .build()?;
+//! let mut output = Vec::new();
+//! cut.run(&b"a,b,c\nd,e,f\n"[..], &mut output)?;
+//! assert_eq!(output, b"a,c\nd,f\n");
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`Cut::lines`] returns the output records as an iterator instead, and [`Cut::visit`] calls a
+//! closure with each of them until it breaks, for processing each record without a writer.
+//!
+//! The [`cut`] module has the lower level [`Mode`](cut::Mode) and [`Options`](cut::Options) which
+//! a `Cut` is made of, as used by the command line tool, and functions which cut a single line or
+//! record without a reader or writer, such as [`cut_line_fields`](cut::cut_line_fields).
+
+#![warn(missing_docs)]
+
+mod builder;
+pub mod cut;
+pub mod range;
+pub mod select;
+pub mod template;
+
+pub use builder::{BuildError, Cut, CutBuilder, Delim};
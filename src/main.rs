@@ -1,101 +1,827 @@
 mod args;
-mod cut;
-mod range;
+mod config;
+mod files;
+mod input;
+mod report;
+mod zero_copy;
 
-use args::{Args, ModeArgs};
+use args::Args;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use input::{InputOptions, Inputs, Prefetch};
+use report::{Kind, Level, Reporter};
+use rut::cut;
+use std::collections::VecDeque;
 use std::fs::File;
-use std::io::Read;
+use std::io::{self, BufRead, BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
 use std::result::Result;
+use std::time::{Duration, Instant};
 
 fn main() {
-    let matches = args::get_matches();
+    let cli = match args::get_cli() {
+        Result::Ok(cli) => cli,
+        Result::Err(err) if err.use_stderr() => {
+            // Clap's message starts with "error: ", which is replaced by the usual prefix. It
+            // already includes the usage.
+            let compat = args::compat_from_args(std::env::args_os());
+            let reporter = Reporter::new(args::error_format_from_args(std::env::args_os()), compat);
+            let message = err.render().to_string();
+            let message = message.strip_prefix("error: ").unwrap_or(&message);
+            let message = match compat {
+                args::Compat::Rut => message.trim_end().to_string(),
+                // Only the error itself is written, as the hints and usage are rut's.
+                args::Compat::Gnu => args::gnu_message(&err)
+                    .unwrap_or_else(|| message.lines().next().unwrap_or_default().to_string()),
+            };
+            reporter.argument_error(&message, Option::None);
+            std::process::exit(1);
+        }
+        Result::Err(err)
+            if err.kind() == clap::error::ErrorKind::DisplayVersion
+                && args::verbose_from_args(std::env::args_os()) =>
+        {
+            let _ = writeln!(io::stdout(), "{}", args::long_version());
+            std::process::exit(0);
+        }
+        // Help and version are written to stdout.
+        Result::Err(err) => {
+            let _ = err.print();
+            std::process::exit(0);
+        }
+    };
+    let mut reporter = Reporter::new(cli.error_format(), cli.compat());
+    if let Option::Some(shell) = cli.completions() {
+        if let Result::Err(err) = args::write_completions(shell, &mut io::stdout()) {
+            if !output_closed(&err) {
+                reporter.error(Kind::of(&err), Option::None, err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if cli.man() {
+        if let Result::Err(err) = args::write_man(&mut io::stdout()) {
+            if !output_closed(&err) {
+                reporter.error(Kind::of(&err), Option::None, err);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
+    // GNU cut has no config file.
+    let config = match cli
+        .config_path()
+        .filter(|_| cli.compat() == args::Compat::Rut)
+    {
+        Option::Some((path, required)) => match config::read_config(&path, required) {
+            Result::Ok((config, warnings)) => {
+                for warning in &warnings {
+                    reporter.config(Level::Warning, warning);
+                }
+                config
+            }
+            Result::Err(err) => {
+                reporter.config(Level::Error, &err);
+                std::process::exit(1);
+            }
+        },
+        Option::None => config::Config::default(),
+    };
+
+    match args::parse_args(cli, &config) {
+        Result::Ok(mut args) => {
+            if let Option::Some(file_list) = &args.file_list {
+                match files::read_file_list(file_list) {
+                    Result::Ok(filenames) => args.filenames = filenames,
+                    Result::Err(err) => {
+                        let path = file_list.path.display().to_string();
+                        reporter.error(Kind::of(&err), Option::Some(&path), err);
+                        std::process::exit(1);
+                    }
+                }
+                if !args.input_options.reuse_stdin {
+                    if let Result::Err(err) = files::validate_stdin_once(&args.filenames) {
+                        reporter.error(Kind::InvalidArgument, Option::None, err);
+                        std::process::exit(1);
+                    }
+                }
+            }
+            if args.compress_output.is_some() && !args.force && io::stdout().is_terminal() {
+                let message =
+                    "--compress-output would write to a terminal. Use --force to write it anyway.";
+                reporter.error(Kind::InvalidArgument, Option::None, message);
+                std::process::exit(1);
+            }
+
+            reporter.quiet = args.quiet;
+            reporter.verbosity = args.verbosity;
+            if let Option::Some(path) = &config.path {
+                let message = format!("read config file {}", path);
+                reporter.info(2, Kind::ReadConfig, Option::None, message);
+            }
+            // Names which could not be expanded by --glob or --recursive count as failed inputs.
+            let mut failures = Failures::new(0);
+            if args.glob {
+                let filenames = std::mem::take(&mut args.filenames);
+                let (filenames, errors) = files::expand_globs(filenames, args.glob_allow_empty);
+                for (pattern, err) in &errors {
+                    reporter.file_error(pattern, err);
+                }
+                args.filenames = filenames;
+                failures = Failures {
+                    failed: errors.len(),
+                    inputs: errors.len(),
+                    output: false,
+                };
+                if failures.failed > 0 && args.fail_fast {
+                    failures.inputs += args.filenames.len();
+                    reporter.failures(failures.failed, failures.inputs);
+                    std::process::exit(1);
+                }
+            }
+            if args.recursive {
+                let filenames = std::mem::take(&mut args.filenames);
+                let (filenames, errors) =
+                    files::expand_directories(filenames, args.follow_symlinks);
+                for (filename, err) in &errors {
+                    reporter.file_error(filename, err);
+                }
+                args.filenames = filenames;
+                failures.failed += errors.len();
+                failures.inputs += errors.len();
+                if failures.failed > 0 && args.fail_fast {
+                    failures.inputs += args.filenames.len();
+                    reporter.failures(failures.failed, failures.inputs);
+                    std::process::exit(1);
+                }
+            }
 
-    match args::parse_args(&matches) {
-        Result::Ok(args) => {
-            if cut(args).is_err() {
+            let result = if args.check {
+                check(&args.filenames, args.fail_fast, reporter)
+            } else {
+                cut(args, reporter)
+            };
+            // Output may not end with a newline, e.g. with -z, so it must be flushed explicitly,
+            // unless writing it has already failed, which would only report the same error again.
+            failures.add(result);
+            if !failures.output {
+                if let Result::Err(err) = io::stdout().flush() {
+                    failures.output_error(&reporter, &err);
+                }
+            }
+            if failures.failed > 0 {
+                reporter.failures(failures.failed, failures.inputs);
+            }
+            if failures.failed > 0 || failures.output {
                 std::process::exit(1);
             }
         }
         Result::Err(err) => {
-            if !err.is_empty() {
-                eprintln!("error: {}\n", err);
-            }
-            eprintln!("{}", matches.usage());
+            // Invalid arguments always exit with 1, like cut. A missing mode means that clap's
+            // configuration is wrong, rather than the arguments, so the usage would not help.
+            let usage = match err {
+                args::ArgsError::MissingMode => Option::None,
+                _ => Option::Some(args::usage()),
+            };
+            reporter.argument_error(&err.to_string(), usage.as_deref());
             std::process::exit(1);
         }
     }
 }
 
-fn cut(args: Args) -> Result<(), ()> {
+/// Returns whether an error means that stdout was closed before everything was written, e.g. by
+/// `head`. Nothing reads the rest of the output, so rut stops without reporting an error, as if it
+/// had been stopped by SIGPIPE, which Rust programs ignore and which does not exist on Windows.
+fn output_closed(err: &io::Error) -> bool {
+    err.kind() == io::ErrorKind::BrokenPipe
+}
+
+/// Number of inputs which could not be processed, out of the total number of inputs.
+#[derive(Debug, PartialEq, Eq)]
+struct Failures {
+    failed: usize,
+    inputs: usize,
+    /// Whether stdout or the rejects file could not be written, which is not counted as a failed
+    /// input.
+    output: bool,
+}
+
+impl Failures {
+    /// Creates a count of no failures out of the specified number of inputs.
+    fn new(inputs: usize) -> Failures {
+        Failures {
+            failed: 0,
+            inputs,
+            output: false,
+        }
+    }
+
+    /// Adds the counts from processing a list of inputs.
+    fn add(&mut self, result: Result<usize, Failures>) {
+        match result {
+            Result::Ok(inputs) => self.inputs += inputs,
+            Result::Err(failures) => {
+                self.failed += failures.failed;
+                self.inputs += failures.inputs;
+                self.output |= failures.output;
+            }
+        }
+    }
+
+    /// Reports an error writing stdout, which stops rut, unless stdout was closed.
+    fn output_error(&mut self, reporter: &Reporter, err: &io::Error) {
+        if !output_closed(err) {
+            reporter.write_error(err);
+            self.output = true;
+        }
+    }
+
+    /// Returns the number of inputs if none failed, or the failures otherwise.
+    fn into_result(self) -> Result<usize, Failures> {
+        if self.failed == 0 && !self.output {
+            Result::Ok(self.inputs)
+        } else {
+            Result::Err(self)
+        }
+    }
+}
+
+/// Cuts each input file. Returns the number of inputs, or the number which failed if any did.
+fn cut(args: Args, reporter: Reporter) -> Result<usize, Failures> {
     let filenames = args.filenames;
-    let line_delimiter = args.line_delimiter;
-
-    let mut stdout = std::io::stdout();
-
-    match args.mode_args {
-        ModeArgs::Bytes(ranges) => for_each_file(filenames, |mut file| {
-            cut::cut_bytes(&mut file, &mut stdout, line_delimiter, &ranges)
-        }),
-        ModeArgs::Characters(ranges) => for_each_file(filenames, |mut file| {
-            cut::cut_characters(&mut file, &mut stdout, line_delimiter, &ranges)
-        }),
-        ModeArgs::FieldsChar(ranges, field_delimiter, output_delimiter, suppress) => {
-            for_each_file(filenames, |mut file| {
-                cut::cut_fields_with_char(
-                    &mut file,
-                    &mut stdout,
-                    line_delimiter,
-                    field_delimiter,
-                    &output_delimiter,
-                    suppress,
-                    &ranges,
-                )
-            })
+    // Creating the rejects file truncates it, so it must not also be read as an input.
+    if let Option::Some(path) = &args.rejects {
+        if let Option::Some(input) = files::same_file_as(path, &filenames) {
+            let message = format!(
+                "--rejects would overwrite the input file '{}'",
+                input.display()
+            );
+            let path = path.display().to_string();
+            reporter.error(Kind::InvalidArgument, Option::Some(&path), message);
+            std::process::exit(1);
         }
-        ModeArgs::FieldsRegex(ranges, delimiter, joiner, suppress) => {
-            for_each_file(filenames, |mut file| {
-                cut::cut_fields_with_regex(
-                    &mut file,
-                    &mut stdout,
-                    line_delimiter,
-                    &delimiter,
-                    &joiner,
-                    suppress,
-                    &ranges,
-                )
-            })
+    }
+    let rejects = match args.rejects {
+        Option::Some(path) => match File::create(&path) {
+            Result::Ok(file) => Option::Some(RejectsFile {
+                path,
+                writer: BufWriter::new(file),
+            }),
+            Result::Err(err) => {
+                let path = path.display().to_string();
+                reporter.error(Kind::of(&err), Option::Some(&path), err);
+                std::process::exit(1);
+            }
+        },
+        Option::None => Option::None,
+    };
+    let options = args.options;
+    // Line counts for --verbose are only known by reading each file.
+    let zero_copy =
+        args.input_options.zero_copy && args.mode.copies_input(&options) && args.verbosity == 0;
+    let mut run = Run {
+        input_options: args.input_options,
+        zero_copy,
+        fail_fast: args.fail_fast,
+        jobs: args.jobs,
+        rejects,
+        reporter,
+    };
+
+    let mode = args.mode;
+    let mut stdout = Stdout::new(args.compress_output);
+    // The same buffers are reused for each file, unless files are cut concurrently.
+    let mut buffers = cut::Buffers::default();
+    let result = for_each_file(
+        filenames,
+        &options,
+        &mut run,
+        &mut stdout,
+        move |mut file, mut output, options, state| {
+            mode.cut_with(&mut file, &mut output, options, state, &mut buffers)
+        },
+    );
+
+    // Errors flushing the rejects file when it is dropped would be lost.
+    let mut failures = Failures::new(0);
+    failures.add(result);
+    // Compressed output is only complete once the gzip trailer is written, which is still done if
+    // an input failed, so that the output of the others can be decompressed. It is not attempted
+    // once writing stdout has failed.
+    if !failures.output {
+        if let Result::Err(err) = stdout.finish() {
+            failures.output_error(&run.reporter, &err);
         }
     }
+    if let Option::Some(file) = &mut run.rejects {
+        if let Result::Err(err) = file.writer.flush() {
+            let path = file.path.display().to_string();
+            run.reporter.error(Kind::of(&err), Option::Some(&path), err);
+            failures.output = true;
+        }
+    }
+    failures.into_result()
+}
+
+/// Settings for processing input files, other than those used to cut each file.
+struct Run {
+    input_options: InputOptions,
+    /// Whether regular files are copied to stdout by the kernel rather than cut, since cutting
+    /// would not change them.
+    zero_copy: bool,
+    /// Whether to stop at the first file which cannot be opened or read.
+    fail_fast: bool,
+    /// Maximum number of files to process at the same time.
+    jobs: usize,
+    rejects: Option<RejectsFile>,
+    reporter: Reporter,
 }
 
-fn for_each_file<F>(filenames: Vec<String>, mut f: F) -> Result<(), ()>
+/// Cuts each file by calling `f` with the opened file, the output to write to, the options, and
+/// the state of the run. Files are processed concurrently if `run.jobs` is more than one, in which
+/// case each file is cut using a separate clone of `f` and its own state.
+fn for_each_file<F>(
+    filenames: Vec<PathBuf>,
+    options: &cut::Options,
+    run: &mut Run,
+    stdout: &mut Stdout,
+    mut f: F,
+) -> Result<usize, Failures>
 where
-    F: FnMut(Box<dyn Read>) -> std::io::Result<()>,
+    F: FnMut(&mut dyn BufRead, &mut dyn Write, &cut::Options, &mut cut::RunState) -> io::Result<()>
+        + Clone
+        + Send,
 {
-    let mut error = false;
-    for filename in filenames {
-        let file: Box<dyn Read> = if filename == "-" {
-            Box::new(std::io::stdin())
-        } else {
-            match File::open(&filename) {
-                Result::Ok(file) => Box::new(file),
-                Result::Err(err) => {
-                    error = true;
-                    eprintln!("{}: {}", &filename, err);
-                    continue;
+    if run.jobs > 1 {
+        return for_each_file_parallel(filenames, options, run, stdout, f);
+    }
+
+    let mut inputs = Inputs::new(run.input_options);
+    let follow = run.input_options.follow;
+    let mut prefetch = Prefetch::new(filenames.clone(), follow.is_some());
+    let mut stdout = TrackedWriter::new(stdout);
+    let mut failures = Failures::new(filenames.len());
+    let last = filenames.len().saturating_sub(1);
+    let mut state = cut::RunState::default();
+    for (i, filename) in filenames.into_iter().enumerate() {
+        if !state.start_input(options) {
+            break;
+        }
+        let start = Instant::now();
+        let written = state.written;
+
+        // Only the last file can be followed, since it is never finished.
+        let file = match (prefetch.next(), follow) {
+            (_, Option::Some(interval)) if i == last => inputs.open_following(&filename, interval),
+            (Option::Some(Result::Ok(file)), _) if run.zero_copy => {
+                let terminator = &options.line_delimiter;
+                match zero_copy::copy_file(&file, &run.input_options, terminator, &mut stdout) {
+                    Result::Ok(true) => continue,
+                    Result::Ok(false) => inputs.read_file(file),
+                    Result::Err(err) => Result::Err(err),
+                }
+            }
+            (Option::Some(file), _) => file.and_then(|file| inputs.read_file(file)),
+            (Option::None, _) => inputs.open(&filename),
+        };
+        let mut file = match file {
+            Result::Ok(file) => file,
+            // Only copying a file to stdout with --zero-copy writes anything before it is cut.
+            Result::Err(err) if stdout.failed => {
+                failures.output_error(&run.reporter, &err);
+                break;
+            }
+            Result::Err(err) => {
+                failures.failed += 1;
+                run.reporter.file_error(&filename, &err);
+                if run.fail_fast {
+                    break;
                 }
+                continue;
             }
         };
 
-        if let std::io::Result::Err(err) = f(file) {
+        run.reporter.opened(&filename, inputs.details());
+
+        let result = f(&mut file, &mut stdout, options, &mut state);
+        // An error writing stdout is not about this file, and stops rut, since the output of this
+        // file or any other cannot be written.
+        let (result, output_error) = match result {
+            Result::Err(err) if stdout.failed => (Result::Ok(()), Option::Some(err)),
+            result => (result, Option::None),
+        };
+        run.reporter
+            .finished(&filename, state.written - written, start.elapsed());
+        let mut error = report_rejects(&filename, options, &mut state, run);
+        if let io::Result::Err(err) = &result {
             error = true;
-            eprintln!("{}: {}", &filename, err);
+            run.reporter.file_error(&filename, err);
+        }
+        if error {
+            failures.failed += 1;
+        }
+        if let Option::Some(err) = output_error {
+            failures.output_error(&run.reporter, &err);
+            break;
+        }
+        if result.is_err() && run.fail_fast {
+            break;
+        }
+    }
+
+    failures.into_result()
+}
+
+/// Writer which remembers whether writing to it failed, so that errors writing the output can be
+/// told apart from errors reading the input, which are returned the same way while cutting.
+struct TrackedWriter<W> {
+    inner: W,
+    failed: bool,
+}
+
+impl<W: Write> TrackedWriter<W> {
+    fn new(inner: W) -> TrackedWriter<W> {
+        TrackedWriter {
+            inner,
+            failed: false,
+        }
+    }
+
+    fn track<T>(&mut self, result: io::Result<T>) -> io::Result<T> {
+        if let Result::Err(err) = &result {
+            // Interrupted writes are retried.
+            self.failed |= err.kind() != io::ErrorKind::Interrupted;
         }
+        result
+    }
+}
+
+impl<W: Write> Write for TrackedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let result = self.inner.write(buf);
+        self.track(result)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        let result = self.inner.write_vectored(bufs);
+        self.track(result)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let result = self.inner.flush();
+        self.track(result)
+    }
+}
+
+impl<W: zero_copy::Output> zero_copy::Output for TrackedWriter<W> {
+    #[cfg(target_os = "linux")]
+    fn fd(&self) -> Option<std::os::unix::io::BorrowedFd<'_>> {
+        self.inner.fd()
+    }
+}
+
+/// Stdout, which the output is written to either unchanged or compressed with gzip.
+enum Stdout {
+    Plain(io::Stdout),
+    Gzip(GzEncoder<io::Stdout>),
+}
+
+impl Stdout {
+    /// Creates the output, compressed at the specified level if there is one.
+    fn new(compression: Option<u32>) -> Stdout {
+        match compression {
+            Option::Some(level) => {
+                Stdout::Gzip(GzEncoder::new(io::stdout(), Compression::new(level)))
+            }
+            Option::None => Stdout::Plain(io::stdout()),
+        }
+    }
+
+    /// Writes the rest of the compressed output, including the gzip trailer, and flushes it.
+    fn finish(&mut self) -> io::Result<()> {
+        match self {
+            Stdout::Plain(stdout) => stdout.flush(),
+            Stdout::Gzip(encoder) => encoder.try_finish().and_then(|_| encoder.get_mut().flush()),
+        }
+    }
+}
+
+impl Write for Stdout {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Stdout::Plain(stdout) => stdout.write(buf),
+            Stdout::Gzip(encoder) => encoder.write(buf),
+        }
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice<'_>]) -> io::Result<usize> {
+        match self {
+            Stdout::Plain(stdout) => stdout.write_vectored(bufs),
+            Stdout::Gzip(encoder) => encoder.write_vectored(bufs),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Stdout::Plain(stdout) => stdout.flush(),
+            Stdout::Gzip(encoder) => encoder.flush(),
+        }
+    }
+}
+
+/// Files are only copied by the kernel to uncompressed output.
+impl zero_copy::Output for Stdout {
+    #[cfg(target_os = "linux")]
+    fn fd(&self) -> Option<std::os::unix::io::BorrowedFd<'_>> {
+        match self {
+            Stdout::Plain(stdout) => stdout.fd(),
+            Stdout::Gzip(_) => Option::None,
+        }
+    }
+}
+
+/// Output of a file which was cut by a separate thread.
+struct Task {
+    filename: PathBuf,
+    output: Vec<u8>,
+    /// State of cutting the file, which holds the lines skipped by --skip-invalid and the number of
+    /// lines written.
+    state: cut::RunState,
+    /// How the file was opened, or `None` if it could not be opened.
+    details: Option<Vec<String>>,
+    elapsed: Duration,
+    result: io::Result<()>,
+}
+
+/// Cuts up to `run.jobs` files at the same time. The output of each file is buffered and written
+/// in the order the files were given, along with any errors, so the output is the same as cutting
+/// the files one at a time. At most `run.jobs` files are buffered at once, including files whose
+/// output is waiting to be written.
+fn for_each_file_parallel<F>(
+    filenames: Vec<PathBuf>,
+    options: &cut::Options,
+    run: &mut Run,
+    stdout: &mut Stdout,
+    f: F,
+) -> Result<usize, Failures>
+where
+    F: FnMut(&mut dyn BufRead, &mut dyn Write, &cut::Options, &mut cut::RunState) -> io::Result<()>
+        + Clone
+        + Send,
+{
+    let input_options = run.input_options;
+    let mut failures = Failures::new(filenames.len());
+    std::thread::scope(|scope| {
+        let mut filenames = filenames.into_iter();
+        let mut pending = VecDeque::with_capacity(run.jobs);
+        loop {
+            while pending.len() < run.jobs {
+                let filename = match filenames.next() {
+                    Option::Some(filename) => filename,
+                    Option::None => break,
+                };
+                let mut f = f.clone();
+                pending.push_back(scope.spawn(move || {
+                    let mut state = cut::RunState::default();
+                    let start = Instant::now();
+                    let mut output = Vec::new();
+                    let mut inputs = Inputs::new(input_options);
+                    let mut details = Option::None;
+                    let result = inputs.open(&filename).and_then(|mut file| {
+                        details = Option::Some(inputs.details().to_vec());
+                        f(&mut file, &mut output, options, &mut state)
+                    });
+                    Task {
+                        filename,
+                        output,
+                        state,
+                        details,
+                        elapsed: start.elapsed(),
+                        result,
+                    }
+                }));
+            }
+
+            let mut task = match pending.pop_front() {
+                Option::Some(handle) => match handle.join() {
+                    Result::Ok(task) => task,
+                    Result::Err(panic) => std::panic::resume_unwind(panic),
+                },
+                Option::None => break,
+            };
+            if let Option::Some(details) = &task.details {
+                run.reporter.opened(&task.filename, details);
+                run.reporter
+                    .finished(&task.filename, task.state.written, task.elapsed);
+            }
+            if let Result::Err(err) = stdout.write_all(&task.output) {
+                // Files which are still being cut are finished, but their output is discarded.
+                failures.output_error(&run.reporter, &err);
+                break;
+            }
+            let result = task.result;
+            let mut error = report_rejects(&task.filename, options, &mut task.state, run);
+            if let io::Result::Err(err) = &result {
+                error = true;
+                run.reporter.file_error(&task.filename, err);
+            }
+            if error {
+                failures.failed += 1;
+            }
+            if result.is_err() && run.fail_fast {
+                // Files which are still being cut are finished, but their output is discarded.
+                break;
+            }
+        }
+    });
+
+    failures.into_result()
+}
+
+/// File which lines skipped by --skip-invalid are copied to.
+struct RejectsFile {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+/// Reports the lines of the last input which were skipped by --skip-invalid, and copies them to
+/// the rejects file if there is one. Returns whether any lines were skipped, or the rejects file
+/// could not be written.
+fn report_rejects(
+    filename: &Path,
+    options: &cut::Options,
+    state: &mut cut::RunState,
+    run: &mut Run,
+) -> bool {
+    let skipped = std::mem::take(&mut state.rejects);
+    for reject in &skipped {
+        run.reporter.skipped(filename, reject);
+        if let Option::Some(file) = &mut run.rejects {
+            let result = file
+                .writer
+                .write_all(&reject.bytes)
+                .and_then(|_| file.writer.write_all(&options.line_delimiter));
+            if let Result::Err(err) = result {
+                let path = file.path.display().to_string();
+                run.reporter.error(Kind::of(&err), Option::Some(&path), err);
+                run.rejects = Option::None;
+                return true;
+            }
+        }
+    }
+    !skipped.is_empty()
+}
+
+/// Checks that every file can be opened, without reading from any of them. Errors are reported to
+/// stderr and processing continues with the remaining files, unless `fail_fast` is set.
+fn check(filenames: &[PathBuf], fail_fast: bool, reporter: Reporter) -> Result<usize, Failures> {
+    let mut failures = Failures::new(filenames.len());
+    for filename in filenames {
+        if let Result::Err(err) = check_file(filename) {
+            failures.failed += 1;
+            reporter.file_error(filename, &err);
+            if fail_fast {
+                break;
+            }
+        }
+    }
+
+    failures.into_result()
+}
+
+/// Opens a file, returning an error if it is not usable as input. "-" (stdin) is always
+/// considered usable.
+fn check_file(filename: &Path) -> io::Result<()> {
+    if filename == Path::new("-") {
+        return Result::Ok(());
+    }
+    input::open_file(filename)?;
+    Result::Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Failures, TrackedWriter};
+    use std::io::{self, Write};
+    use std::path::Path;
+
+    #[test]
+    fn failures() {
+        assert_eq!(Failures::new(3).into_result(), Result::Ok(3));
+
+        let mut failures = Failures::new(3);
+        failures.failed += 1;
+        assert_eq!(
+            failures.into_result(),
+            Result::Err(Failures {
+                failed: 1,
+                inputs: 3,
+                output: false,
+            })
+        );
+
+        let mut failures = Failures {
+            failed: 1,
+            inputs: 1,
+            output: false,
+        };
+        failures.add(Result::Ok(2));
+        assert_eq!(
+            failures,
+            Failures {
+                failed: 1,
+                inputs: 3,
+                output: false,
+            }
+        );
+        failures.add(Result::Err(Failures {
+            failed: 2,
+            inputs: 4,
+            output: false,
+        }));
+        assert_eq!(
+            failures,
+            Failures {
+                failed: 3,
+                inputs: 7,
+                output: false,
+            }
+        );
+
+        // Failing to write the output fails without any failed inputs.
+        let mut failures = Failures::new(2);
+        failures.add(Result::Err(Failures {
+            failed: 0,
+            inputs: 1,
+            output: true,
+        }));
+        assert_eq!(
+            failures.into_result(),
+            Result::Err(Failures {
+                failed: 0,
+                inputs: 3,
+                output: true,
+            })
+        );
+    }
+
+    /// Writer which fails with "No space left on device" once `capacity` bytes have been written.
+    struct FullWriter {
+        written: Vec<u8>,
+        capacity: usize,
+    }
+
+    impl Write for FullWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.capacity - self.written.len());
+            if n == 0 && !buf.is_empty() {
+                // ENOSPC on Linux and macOS.
+                return Result::Err(io::Error::from_raw_os_error(28));
+            }
+            self.written.extend_from_slice(&buf[..n]);
+            Result::Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Result::Ok(())
+        }
+    }
+
+    #[test]
+    fn tracked_writer() {
+        let full = FullWriter {
+            written: Vec::new(),
+            capacity: 5,
+        };
+        let mut writer = TrackedWriter::new(full);
+        writer.write_all(b"abc").unwrap();
+        assert!(!writer.failed);
+
+        let mut input = io::Cursor::new(b"defgh".to_vec());
+        let err = io::copy(&mut input, &mut writer).unwrap_err();
+        assert_eq!(err.raw_os_error(), Option::Some(28));
+        assert!(writer.failed);
+        assert_eq!(writer.inner.written, b"abcde");
+
+        // Errors which are not from writing, e.g. reading the input, are not tracked.
+        let mut writer = TrackedWriter::new(Vec::new());
+        let mut input = io::BufReader::new(&b"\xff\n"[..]);
+        let mode = rut::cut::Mode::characters("1".parse().unwrap());
+        let options = rut::cut::Options::default();
+        let mut state = rut::cut::RunState::default();
+        assert!(mode
+            .cut(&mut input, &mut writer, &options, &mut state)
+            .is_err());
+        assert!(!writer.failed);
     }
 
-    if error {
-        Result::Err(())
-    } else {
-        Result::Ok(())
+    #[test]
+    fn check_file() {
+        assert!(super::check_file(Path::new("-")).is_ok());
+        assert!(super::check_file(Path::new("tests/files/ascii.txt")).is_ok());
+        assert!(super::check_file(Path::new("tests/files/does-not-exist.txt")).is_err());
+        assert!(super::check_file(Path::new("tests/files")).is_err());
     }
 }
@@ -0,0 +1,173 @@
+use std::fmt;
+
+use super::{CutRange, IncreasingRange, Ranges};
+
+/// Builder for [`Ranges`], created by [`Ranges::builder`], as an alternative to parsing a string.
+///
+/// Positions are numbered from one, as in the parsed string, so `unit(3)` selects the same as
+/// `"3"`. Invalid ranges are reported by [`RangesBuilder::build`].
+///
+/// # Examples
+///
+/// ```
+/// use rut::range::Ranges;
+///
+/// let ranges = Ranges::builder().unit(3).closed(5, 9).to_end(20).build()?;
+/// assert_eq!(ranges, "3,5-9,20-".parse()?);
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Debug, Default)]
+#[must_use]
+pub struct RangesBuilder {
+    ranges: Vec<CutRange>,
+    /// First invalid range which was added.
+    error: Option<RangeError>,
+}
+
+impl RangesBuilder {
+    /// Adds a single position.
+    pub fn unit(self, position: usize) -> RangesBuilder {
+        self.add(|| Result::Ok(CutRange::Unit(zero_based(position)?)))
+    }
+
+    /// Adds the positions from `start` to `end`, inclusive, as in `"start-end"`.
+    pub fn closed(self, start: usize, end: usize) -> RangesBuilder {
+        self.add(|| {
+            let range = IncreasingRange::new(zero_based(start)?, zero_based(end)?)
+                .map_err(|_| RangeError::DescendingRange { start, end })?;
+            Result::Ok(CutRange::Closed(range))
+        })
+    }
+
+    /// Adds the positions from the first to `end`, inclusive, as in `"-end"`.
+    pub fn from_start(self, end: usize) -> RangesBuilder {
+        self.add(|| Result::Ok(CutRange::FromStart(zero_based(end)?)))
+    }
+
+    /// Adds the positions from `start` to the end of the input, as in `"start-"`.
+    pub fn to_end(self, start: usize) -> RangesBuilder {
+        self.add(|| Result::Ok(CutRange::ToEnd(zero_based(start)?)))
+    }
+
+    /// Returns the ranges, or the first range which was not valid.
+    pub fn build(self) -> Result<Ranges, RangeError> {
+        match self.error {
+            Option::Some(error) => Result::Err(error),
+            Option::None => Result::Ok(Ranges::from_ranges(&self.ranges)),
+        }
+    }
+
+    fn add<F>(mut self, range: F) -> RangesBuilder
+    where
+        F: FnOnce() -> Result<CutRange, RangeError>,
+    {
+        if self.error.is_none() {
+            match range() {
+                Result::Ok(range) => self.ranges.push(range),
+                Result::Err(error) => self.error = Option::Some(error),
+            }
+        }
+        self
+    }
+}
+
+/// Converts a position numbered from one to an index numbered from zero.
+fn zero_based(position: usize) -> Result<usize, RangeError> {
+    position.checked_sub(1).ok_or(RangeError::NumberedFromZero)
+}
+
+/// Error for a range which is not valid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum RangeError {
+    /// A position of zero, given to [`RangesBuilder`], whose positions are numbered from one.
+    NumberedFromZero,
+    /// A range whose end is before its start.
+    DescendingRange {
+        /// Start of the range.
+        start: usize,
+        /// End of the range.
+        end: usize,
+    },
+}
+
+impl fmt::Display for RangeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RangeError::NumberedFromZero => write!(f, "Ranges are numbered from one."),
+            RangeError::DescendingRange { start, end } => {
+                write!(f, "Ranges must be ascending, but found {}-{}.", start, end)
+            }
+        }
+    }
+}
+
+impl std::error::Error for RangeError {}
+
+#[cfg(test)]
+mod tests {
+    use super::RangeError;
+    use crate::range::Ranges;
+
+    #[test]
+    fn same_as_parsed() {
+        let build = |builder: super::RangesBuilder| builder.build().unwrap();
+        let assert_parsed = |ranges: Ranges, parsed: &str| {
+            assert_eq!(ranges, parsed.parse::<Ranges>().unwrap(), "{}", parsed);
+        };
+
+        assert_parsed(build(Ranges::builder().unit(1)), "1");
+        assert_parsed(build(Ranges::builder().unit(3).unit(1)), "3,1");
+        assert_parsed(build(Ranges::builder().closed(2, 4)), "2-4");
+        assert_parsed(build(Ranges::builder().closed(4, 4)), "4-4");
+        assert_parsed(build(Ranges::builder().from_start(5)), "-5");
+        assert_parsed(build(Ranges::builder().to_end(7)), "7-");
+        assert_parsed(
+            build(Ranges::builder().unit(3).closed(5, 9).to_end(20)),
+            "3,5-9,20-",
+        );
+        assert_parsed(
+            build(Ranges::builder().closed(5, 9).unit(6).unit(10).to_end(8)),
+            "5-9,6,10,8-",
+        );
+        assert_parsed(
+            build(Ranges::builder().from_start(2).unit(4).to_end(3)),
+            "-2 4 3-",
+        );
+        assert_parsed(
+            build(Ranges::builder().unit(usize::MAX).closed(1, usize::MAX)),
+            &format!("{0},1-{0}", usize::MAX),
+        );
+        // No ranges select nothing.
+        assert!(build(Ranges::builder()).is_empty());
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Ranges::builder().unit(0).build(),
+            Result::Err(RangeError::NumberedFromZero)
+        );
+        assert_eq!(
+            Ranges::builder().closed(0, 3).build(),
+            Result::Err(RangeError::NumberedFromZero)
+        );
+        assert_eq!(
+            Ranges::builder().from_start(0).build(),
+            Result::Err(RangeError::NumberedFromZero)
+        );
+        assert_eq!(
+            Ranges::builder().to_end(0).build(),
+            Result::Err(RangeError::NumberedFromZero)
+        );
+        assert_eq!(
+            Ranges::builder().unit(1).closed(5, 3).build(),
+            Result::Err(RangeError::DescendingRange { start: 5, end: 3 })
+        );
+        // The first error is reported.
+        assert_eq!(
+            Ranges::builder().closed(5, 3).unit(0).build(),
+            Result::Err(RangeError::DescendingRange { start: 5, end: 3 })
+        );
+    }
+}
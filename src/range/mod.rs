@@ -1,12 +1,22 @@
+//! Ranges of bytes, characters, or fields to select, and parsing them from strings such as
+//! `"1,3-5,7-"`.
+
+mod builder;
 mod parse;
-use parse::ParseRangesError;
+pub use builder::{RangeError, RangesBuilder};
+pub use parse::{LexError, ParseRangesError, Token};
 use std::cmp::Ordering;
-use std::convert::From;
+use std::convert::{From, TryFrom};
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Range, RangeFrom, RangeInclusive};
 use std::str::FromStr;
 use std::vec::Vec;
 
-/// A range of bytes, characters, or fields to select from the input.
-pub(crate) enum CutRange {
+/// A range of bytes, characters, or fields to select from the input. Elements are numbered from
+/// zero, unlike in the string parsed as [`Ranges`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CutRange {
     /// A single element.
     Unit(usize),
     /// A closed range of elements.
@@ -17,23 +27,59 @@ pub(crate) enum CutRange {
     ToEnd(usize),
 }
 
-/// An increasing range.
-pub(crate) struct IncreasingRange {
-    pub(crate) start: usize,
-    pub(crate) end: usize,
+/// An increasing range, from the start to the end, inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IncreasingRange {
+    start: usize,
+    end: usize,
 }
 
 impl IncreasingRange {
-    /// Creates a new `IncreasingRange` with the specified start and end.
-    fn new(start: usize, end: usize) -> IncreasingRange {
-        assert!(start <= end);
-        IncreasingRange { start, end }
+    /// Creates a new `IncreasingRange` with the specified start and end, or returns an error if
+    /// `start` is greater than `end`.
+    pub fn new(start: usize, end: usize) -> Result<IncreasingRange, RangeError> {
+        if start <= end {
+            Result::Ok(IncreasingRange { start, end })
+        } else {
+            Result::Err(RangeError::DescendingRange { start, end })
+        }
+    }
+
+    /// Returns the start of the range.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Returns the end of the range, which is included.
+    pub fn end(&self) -> usize {
+        self.end
     }
 }
 
 /// A set of simplified and merged `CutRange`s. See [`MergedRange`].
+///
+/// Ranges are usually parsed from a string such as `"1,3-5,7-"`, in which elements are numbered
+/// from one. Iterating over the ranges returns each [`MergedRange`] in order, with elements
+/// numbered from zero.
+///
+/// # Examples
+///
+/// ```
+/// use rut::range::{MergedRange, Ranges};
+///
+/// let ranges: Ranges = "7-,1,3-5,4".parse()?;
+/// assert_eq!(
+///     ranges.into_iter().collect::<Vec<_>>(),
+///     [
+///         MergedRange::Closed(0, 0),
+///         MergedRange::Closed(2, 4),
+///         MergedRange::ToEnd(6)
+///     ]
+/// );
+/// # Ok::<(), rut::range::ParseRangesError>(())
+/// ```
 #[derive(Debug, Clone)]
-pub(crate) struct Ranges {
+pub struct Ranges {
     pub(crate) ranges: Vec<MergedRange>,
 }
 
@@ -45,13 +91,100 @@ impl PartialEq for Ranges {
 
 impl Eq for Ranges {}
 
-/// Simplified view of one or more merged `CutRange`s.
+/// Simplified view of one or more merged `CutRange`s. Elements are numbered from zero.
 #[derive(Copy, Clone, Debug)]
-pub(crate) enum MergedRange {
+pub enum MergedRange {
+    /// Elements from the start to the end, inclusive.
     Closed(usize, usize),
+    /// All elements from the start to the end of the input.
     ToEnd(usize),
 }
 
+impl MergedRange {
+    /// Returns the first element of the range.
+    pub fn start(&self) -> usize {
+        match *self {
+            MergedRange::Closed(start, _) | MergedRange::ToEnd(start) => start,
+        }
+    }
+
+    /// Returns the last element of the range, or `None` if it continues to the end of the input.
+    pub fn end(&self) -> Option<usize> {
+        match *self {
+            MergedRange::Closed(_, end) => Option::Some(end),
+            MergedRange::ToEnd(_) => Option::None,
+        }
+    }
+
+    /// Returns whether the element at `index` is in the range.
+    pub fn contains(&self, index: usize) -> bool {
+        index >= self.start() && self.end().is_none_or(|end| index <= end)
+    }
+
+    /// Returns the indices of the elements in the range from a slice of `len` elements, which can
+    /// be used to index the slice. The range is empty, at the end of the slice, if it starts after
+    /// the end of the slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::range::MergedRange;
+    ///
+    /// let input = [1, 2, 3, 4];
+    /// assert_eq!(input[MergedRange::Closed(1, 2).to_slice_range(input.len())], [2, 3]);
+    /// assert_eq!(input[MergedRange::ToEnd(2).to_slice_range(input.len())], [3, 4]);
+    /// assert_eq!(MergedRange::Closed(2, 9).to_slice_range(4), 2..4);
+    /// assert_eq!(MergedRange::ToEnd(6).to_slice_range(4), 4..4);
+    /// ```
+    pub fn to_slice_range(&self, len: usize) -> Range<usize> {
+        let start = self.start().min(len);
+        let end = self.end().map_or(len, |end| end.saturating_add(1).min(len));
+        start..end.max(start)
+    }
+}
+
+impl TryFrom<RangeInclusive<usize>> for MergedRange {
+    type Error = RangeError;
+
+    /// Converts to a closed range, or returns an error if the start is greater than the end.
+    fn try_from(range: RangeInclusive<usize>) -> Result<Self, Self::Error> {
+        let (start, end) = range.into_inner();
+        IncreasingRange::new(start, end).map(|_| MergedRange::Closed(start, end))
+    }
+}
+
+impl From<RangeFrom<usize>> for MergedRange {
+    fn from(range: RangeFrom<usize>) -> Self {
+        MergedRange::ToEnd(range.start)
+    }
+}
+
+impl TryFrom<MergedRange> for RangeInclusive<usize> {
+    type Error = MergedRange;
+
+    /// Converts a closed range, or returns the range unchanged if it continues to the end of the
+    /// input.
+    fn try_from(range: MergedRange) -> Result<Self, Self::Error> {
+        match range {
+            MergedRange::Closed(start, end) => Result::Ok(start..=end),
+            MergedRange::ToEnd(_) => Result::Err(range),
+        }
+    }
+}
+
+impl TryFrom<MergedRange> for RangeFrom<usize> {
+    type Error = MergedRange;
+
+    /// Converts a range which continues to the end of the input, or returns the range unchanged if
+    /// it is closed.
+    fn try_from(range: MergedRange) -> Result<Self, Self::Error> {
+        match range {
+            MergedRange::ToEnd(start) => Result::Ok(start..),
+            MergedRange::Closed(..) => Result::Err(range),
+        }
+    }
+}
+
 impl From<&CutRange> for MergedRange {
     fn from(range: &CutRange) -> Self {
         match *range {
@@ -96,8 +229,116 @@ impl PartialOrd for MergedRange {
 
 /// A collection of ranges.
 impl Ranges {
+    /// Returns a builder which adds ranges one at a time, numbered from one.
+    pub fn builder() -> RangesBuilder {
+        RangesBuilder::default()
+    }
+
+    /// Creates a new `Ranges` from `MergedRange`s, which may be in any order and may overlap. Returns
+    /// an error if a closed range ends before it starts.
+    pub fn from_merged<I: IntoIterator<Item = MergedRange>>(
+        ranges: I,
+    ) -> Result<Ranges, RangeError> {
+        let ranges = ranges
+            .into_iter()
+            .map(|range| match range {
+                MergedRange::Closed(start, end) => {
+                    IncreasingRange::new(start, end).map(CutRange::Closed)
+                }
+                MergedRange::ToEnd(start) => Result::Ok(CutRange::ToEnd(start)),
+            })
+            .collect::<Result<Vec<CutRange>, RangeError>>()?;
+        Result::Ok(Ranges::from_ranges(&ranges))
+    }
+
+    /// Returns the merged ranges, sorted and without overlaps.
+    pub fn as_slice(&self) -> &[MergedRange] {
+        &self.ranges
+    }
+
+    /// Returns an iterator over the merged ranges, sorted and without overlaps.
+    pub fn iter(&self) -> std::slice::Iter<'_, MergedRange> {
+        self.ranges.iter()
+    }
+
+    /// Returns the number of merged ranges. Ranges which overlap or touch are counted once.
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Returns whether nothing is selected.
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns whether the element at `index`, numbered from zero, is selected.
+    pub fn contains(&self, index: usize) -> bool {
+        // Ranges are sorted and do not overlap, so only the last range which starts at or before
+        // the index can contain it.
+        match self.ranges.partition_point(|range| range.start() <= index) {
+            0 => false,
+            n => self.ranges[n - 1].contains(index),
+        }
+    }
+
+    /// Returns the largest selected index, numbered from zero, or `None` if nothing is selected or
+    /// the selection continues to the end of the input.
+    pub fn max_index(&self) -> Option<usize> {
+        // Ranges are sorted and do not overlap, so only the last range can end after the others.
+        self.ranges.last().and_then(MergedRange::end)
+    }
+
+    /// Returns whether the selection continues to the end of the input, however long it is.
+    pub fn is_unbounded(&self) -> bool {
+        matches!(self.ranges.last(), Option::Some(MergedRange::ToEnd(_)))
+    }
+
+    /// Returns the number of elements selected from an input of `n` elements, which is the number
+    /// of indices returned by [`Ranges::indices`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::range::Ranges;
+    ///
+    /// let ranges: Ranges = "2,4-5,8-".parse()?;
+    /// assert_eq!(ranges.len_within(10), 6);
+    /// assert_eq!(ranges.len_within(4), 2);
+    /// assert_eq!(ranges.max_index(), Option::None);
+    /// assert!(ranges.is_unbounded());
+    /// # Ok::<(), rut::range::ParseRangesError>(())
+    /// ```
+    pub fn len_within(&self, n: usize) -> usize {
+        self.ranges
+            .iter()
+            .take_while(|range| range.start() < n)
+            .map(|range| range.to_slice_range(n).len())
+            .sum()
+    }
+
+    /// Returns the selected indices, numbered from zero, which are less than `bound`, in order.
+    /// Ranges which continue to the end of the input stop at `bound`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::range::Ranges;
+    ///
+    /// let ranges: Ranges = "2,4-5,8-".parse()?;
+    /// assert_eq!(ranges.indices(10).collect::<Vec<_>>(), [1, 3, 4, 7, 8, 9]);
+    /// # Ok::<(), rut::range::ParseRangesError>(())
+    /// ```
+    pub fn indices(&self, bound: usize) -> impl Iterator<Item = usize> + '_ {
+        self.ranges.iter().flat_map(move |range| {
+            let end = range
+                .end()
+                .map_or(bound, |end| end.saturating_add(1).min(bound));
+            range.start().min(end)..end
+        })
+    }
+
     /// Create a new `Ranges` from `CutRange`s.
-    pub(crate) fn from_ranges(ranges: &[CutRange]) -> Ranges {
+    pub fn from_ranges(ranges: &[CutRange]) -> Ranges {
         if ranges.is_empty() {
             return Ranges { ranges: Vec::new() };
         }
@@ -126,7 +367,7 @@ impl Ranges {
                     // Current Closed overlaps or touches next ToEnd, which we merge into ToEnd(s1).
                     // Sort order ensure that all following values can also be merged (see comment
                     // for previous match arm).
-                    if *s2 <= e1 + 1 {
+                    if *s2 <= e1.saturating_add(1) {
                         merge_chain = ToEnd(s1);
                         break;
                     }
@@ -141,7 +382,7 @@ impl Ranges {
                     // assert s1 <= s2
 
                     // Ranges overlap or touch. Merge and continue.
-                    if *s2 <= e1 + 1 {
+                    if *s2 <= e1.saturating_add(1) {
                         merge_chain = Closed(s1, std::cmp::max(e1, *e2));
                     }
                     // No overlap. Add current range then start a new merge chain.
@@ -159,7 +400,22 @@ impl Ranges {
         Ranges { ranges: result }
     }
 
-    pub(crate) fn complement(self) -> Ranges {
+    /// Creates a new `Ranges` which selects everything selected by any of the specified ranges.
+    pub fn union<I: IntoIterator<Item = Ranges>>(ranges: I) -> Ranges {
+        let ranges: Vec<CutRange> = ranges
+            .into_iter()
+            .flat_map(|ranges| ranges.ranges)
+            .map(|range| match range {
+                MergedRange::Closed(start, end) => CutRange::Closed(IncreasingRange { start, end }),
+                MergedRange::ToEnd(start) => CutRange::ToEnd(start),
+            })
+            .collect();
+        Ranges::from_ranges(&ranges)
+    }
+
+    /// Creates a new `Ranges` which selects everything not selected by these ranges. A closed range
+    /// which ends at `usize::MAX` leaves nothing after it to select.
+    pub fn complement(self) -> Ranges {
         let mut next = 0usize;
         let mut open = false;
         let mut ranges = Vec::new();
@@ -170,7 +426,11 @@ impl Ranges {
                     if start != 0 {
                         ranges.push(MergedRange::Closed(next, start - 1));
                     }
-                    next = end + 1;
+                    match end.checked_add(1) {
+                        Option::Some(end) => next = end,
+                        // Nothing can follow, as with ToEnd.
+                        Option::None => open = true,
+                    }
                 }
                 MergedRange::ToEnd(start) => {
                     if start != 0 {
@@ -186,6 +446,65 @@ impl Ranges {
         }
         Ranges { ranges }
     }
+
+    /// Creates a new `Ranges` which selects everything not selected by these ranges, out of the
+    /// first `bound` positions, e.g. when the number of fields in each record is known. Unlike
+    /// [`Ranges::complement`], the result only has closed ranges, and selects nothing if `bound`
+    /// is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::range::Ranges;
+    ///
+    /// let ranges: Ranges = "2,4-".parse()?;
+    /// assert_eq!(ranges.complement_within(6), "1,3".parse()?);
+    /// let ranges: Ranges = "2".parse()?;
+    /// assert_eq!(ranges.complement_within(6), "1,3-6".parse()?);
+    /// # Ok::<(), rut::range::ParseRangesError>(())
+    /// ```
+    pub fn complement_within(&self, bound: usize) -> Ranges {
+        let ranges = self
+            .clone()
+            .complement()
+            .ranges
+            .into_iter()
+            // Both are numbered from zero, so a range starting at `bound` is out of bounds.
+            .take_while(|range| range.start() < bound)
+            .map(|range| {
+                let end = range.end().map_or(bound - 1, |end| end.min(bound - 1));
+                MergedRange::Closed(range.start(), end)
+            })
+            .collect();
+        Ranges { ranges }
+    }
+
+    /// Creates a new `Ranges` which selects everything selected by these ranges and not by
+    /// `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rut::range::Ranges;
+    ///
+    /// let ranges: Ranges = "1-10".parse()?;
+    /// assert_eq!(ranges.difference(&"4,6".parse()?), "1-3,5,7-10".parse()?);
+    /// let ranges: Ranges = "2-".parse()?;
+    /// assert_eq!(ranges.difference(&"5-8".parse()?), "2-4,9-".parse()?);
+    /// # Ok::<(), rut::range::ParseRangesError>(())
+    /// ```
+    pub fn difference(&self, other: &Ranges) -> Ranges {
+        // Everything not selected by either the complement of these ranges or `other`.
+        Ranges::union(vec![self.clone().complement(), other.clone()]).complement()
+    }
+}
+
+impl<'a> IntoIterator for &'a Ranges {
+    type Item = &'a MergedRange;
+    type IntoIter = std::slice::Iter<'a, MergedRange>;
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
 }
 
 impl IntoIterator for Ranges {
@@ -196,6 +515,70 @@ impl IntoIterator for Ranges {
     }
 }
 
+/// Collects indices, numbered from zero, which may be in any order and may be repeated.
+///
+/// # Examples
+///
+/// ```
+/// use rut::range::{MergedRange, Ranges};
+///
+/// let mut ranges: Ranges = vec![5, 1, 2, 3, 1].into_iter().collect();
+/// ranges.extend([9, 4]);
+/// assert_eq!(
+///     ranges.as_slice(),
+///     [MergedRange::Closed(1, 5), MergedRange::Closed(9, 9)]
+/// );
+/// ```
+impl FromIterator<usize> for Ranges {
+    fn from_iter<I: IntoIterator<Item = usize>>(indices: I) -> Self {
+        let units: Vec<CutRange> = indices.into_iter().map(CutRange::Unit).collect();
+        Ranges::from_ranges(&units)
+    }
+}
+
+impl Extend<usize> for Ranges {
+    fn extend<I: IntoIterator<Item = usize>>(&mut self, indices: I) {
+        // The ranges are merged again along with the new indices.
+        let ranges: Vec<CutRange> = self
+            .ranges
+            .iter()
+            .map(|range| match *range {
+                MergedRange::Closed(start, end) => CutRange::Closed(IncreasingRange { start, end }),
+                MergedRange::ToEnd(start) => CutRange::ToEnd(start),
+            })
+            .chain(indices.into_iter().map(CutRange::Unit))
+            .collect();
+        *self = Ranges::from_ranges(&ranges);
+    }
+}
+
+/// Selects the indices of the range, numbered from zero. An empty range selects nothing.
+impl From<Range<usize>> for Ranges {
+    fn from(range: Range<usize>) -> Self {
+        if range.is_empty() {
+            Ranges { ranges: Vec::new() }
+        } else {
+            Ranges {
+                ranges: vec![MergedRange::Closed(range.start, range.end - 1)],
+            }
+        }
+    }
+}
+
+/// Selects the indices of the range, numbered from zero. An empty range selects nothing.
+impl From<RangeInclusive<usize>> for Ranges {
+    fn from(range: RangeInclusive<usize>) -> Self {
+        if range.is_empty() {
+            Ranges { ranges: Vec::new() }
+        } else {
+            let (start, end) = range.into_inner();
+            Ranges {
+                ranges: vec![MergedRange::Closed(start, end)],
+            }
+        }
+    }
+}
+
 impl FromStr for Ranges {
     type Err = ParseRangesError;
 
@@ -204,9 +587,42 @@ impl FromStr for Ranges {
     }
 }
 
+/// Formats the merged ranges as they would be written on the command line, numbered from one, so
+/// that parsing the result gives the same ranges. No ranges are formatted as an empty string, which
+/// cannot be parsed.
+///
+/// # Examples
+///
+/// ```
+/// use rut::range::Ranges;
+///
+/// let ranges: Ranges = "7-,1,3-5,4".parse()?;
+/// assert_eq!(ranges.to_string(), "1,3-5,7-");
+/// assert_eq!(ranges.to_string().parse::<Ranges>()?, ranges);
+/// # Ok::<(), rut::range::ParseRangesError>(())
+/// ```
+impl fmt::Display for Ranges {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, range) in self.ranges.iter().enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            // Widened, since a range which ends at usize::MAX is numbered past it.
+            let start = range.start() as u128 + 1;
+            match range.end() {
+                Option::Some(end) if end == range.start() => write!(f, "{}", start)?,
+                Option::Some(end) => write!(f, "{}-{}", start, end as u128 + 1)?,
+                Option::None => write!(f, "{}-", start)?,
+            }
+        }
+        Result::Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     #[test]
     fn from_no_ranges() {
@@ -501,6 +917,628 @@ mod tests {
         assert_complement("3-6,10-20,40-", "1-2,7-9,21-39");
     }
 
+    #[test]
+    fn complement_within_empty() {
+        let ranges: Ranges = "1-".parse().unwrap();
+        assert!(ranges.complement_within(5).ranges.is_empty());
+        let ranges: Ranges = "1-5".parse().unwrap();
+        assert!(ranges.complement_within(5).ranges.is_empty());
+
+        // The complement of an empty selection is everything within the bound.
+        let empty = Ranges::from_ranges(&[]);
+        assert_eq!(
+            empty.complement_within(5).ranges,
+            &[MergedRange::Closed(0, 4)]
+        );
+        assert!(empty.complement_within(0).ranges.is_empty());
+    }
+
+    #[test]
+    fn complement_within_single_range() {
+        assert_complement_within("1", 5, "2-5");
+        assert_complement_within("2", 5, "1,3-5");
+        assert_complement_within("3", 5, "1-2,4-5");
+        assert_complement_within("4", 5, "1-3,5");
+        assert_complement_within("1-2", 5, "3-5");
+        assert_complement_within("3-4", 5, "1-2,5");
+        assert_complement_within("5-10", 12, "1-4,11-12");
+    }
+
+    #[test]
+    fn complement_within_multiple_ranges() {
+        assert_complement_within("1,3", 5, "2,4-5");
+        assert_complement_within("2,4,6,8", 9, "1,3,5,7,9");
+        assert_complement_within("1-3,5-7", 10, "4,8-10");
+        assert_complement_within("2-4,8-16", 20, "1,5-7,17-20");
+        assert_complement_within("1-10,20-", 30, "11-19");
+        assert_complement_within("3-6,10-20,40-", 50, "1-2,7-9,21-39");
+    }
+
+    #[test]
+    fn complement_within_bound_edges() {
+        // Selections which extend past the bound.
+        assert_complement_within("2-10", 5, "1");
+        assert_complement_within("2,4-", 3, "1,3");
+        assert_complement_within("1,3,8", 5, "2,4-5");
+        // A bound before the first selected position.
+        assert_complement_within("4-6", 2, "1-2");
+        assert_complement_within("4-", 3, "1-3");
+        // A bound at the end of a selected range.
+        assert_complement_within("2-3", 3, "1");
+        assert_complement_within("2-4", 5, "1,5");
+        // A bound of one and zero.
+        assert_complement_within("2", 1, "1");
+        assert!("1"
+            .parse::<Ranges>()
+            .unwrap()
+            .complement_within(1)
+            .is_empty());
+        assert!("3"
+            .parse::<Ranges>()
+            .unwrap()
+            .complement_within(0)
+            .is_empty());
+        // The upper bound.
+        let max = usize::MAX;
+        assert_eq!(
+            Ranges::from_ranges(&[CutRange::Unit(max - 1)])
+                .complement_within(max)
+                .ranges,
+            &[MergedRange::Closed(0, max - 2)]
+        );
+        assert_eq!(
+            "2".parse::<Ranges>().unwrap().complement_within(max).ranges,
+            &[MergedRange::Closed(0, 0), MergedRange::Closed(2, max - 1)]
+        );
+    }
+
+    #[test]
+    fn difference() {
+        assert_difference("1-10", "4,6", "1-3,5,7-10");
+        assert_difference("1-", "4,6", "1-3,5,7-");
+        assert_difference("3-", "1-5", "6-");
+        assert_difference("1-3,7-9", "2-8", "1,9");
+        assert_difference("1-3", "5-", "1-3");
+        assert!("2-4"
+            .parse::<Ranges>()
+            .unwrap()
+            .difference(&"1-".parse().unwrap())
+            .is_empty());
+
+        // The upper bound.
+        let max = usize::MAX;
+        assert_eq!(
+            Ranges::from_ranges(&[closed(0, max)])
+                .difference(&Ranges::from_ranges(&[CutRange::Unit(1)]))
+                .ranges,
+            &[MergedRange::Closed(0, 0), MergedRange::ToEnd(2)]
+        );
+    }
+
+    #[test]
+    fn union() {
+        assert_union(&["1-3", "7,9"], "1-3,7,9");
+        assert_union(&["1-3", "2-5"], "1-5");
+        assert_union(&["4,6", "5"], "4-6");
+        assert_union(&["7-", "1,8"], "1,7-");
+        assert_union(&["-2", "10-"], "1-2,10-");
+        assert_union(&["3", "3", "3"], "3");
+        assert!(Ranges::union(Vec::new()).ranges.is_empty());
+    }
+
+    #[test]
+    fn upper_bound() {
+        use MergedRange::{Closed, ToEnd};
+        let max = usize::MAX;
+
+        // Ranges which touch at the upper bound are merged without overflow.
+        assert_eq!(
+            Ranges::from_ranges(&[closed(0, max), closed(3, max)]).ranges,
+            &[Closed(0, max)]
+        );
+        assert_eq!(
+            Ranges::from_ranges(&[closed(0, max), CutRange::ToEnd(5)]).ranges,
+            &[ToEnd(0)]
+        );
+        assert_eq!(
+            Ranges::from_ranges(&[CutRange::Unit(max), CutRange::Unit(max - 1)]).ranges,
+            &[Closed(max - 1, max)]
+        );
+
+        // Nothing follows a range which ends at the upper bound.
+        let complement = Ranges::from_ranges(&[closed(2, max)]).complement();
+        assert_eq!(complement.ranges, &[Closed(0, 1)]);
+        let complement = Ranges::from_ranges(&[CutRange::Unit(max)]).complement();
+        assert_eq!(complement.ranges, &[Closed(0, max - 1)]);
+        let complement = Ranges::from_ranges(&[CutRange::Unit(max - 1)]).complement();
+        assert_eq!(complement.ranges, &[Closed(0, max - 2), ToEnd(max)]);
+    }
+
+    #[test]
+    fn increasing_range() {
+        let range = IncreasingRange::new(2, 5).unwrap();
+        assert_eq!((range.start(), range.end()), (2, 5));
+        assert!(IncreasingRange::new(3, 3).is_ok());
+        assert_eq!(
+            IncreasingRange::new(5, 2),
+            Result::Err(RangeError::DescendingRange { start: 5, end: 2 })
+        );
+    }
+
+    #[test]
+    fn from_merged() {
+        use MergedRange::{Closed, ToEnd};
+
+        let ranges = Ranges::from_merged(vec![ToEnd(9), Closed(4, 6), Closed(0, 0), Closed(5, 7)]);
+        assert_eq!(ranges.unwrap(), "10-,5-8,1".parse().unwrap());
+        assert!(Ranges::from_merged(Vec::new()).unwrap().is_empty());
+        assert_eq!(
+            Ranges::from_merged(vec![Closed(3, 2)]),
+            Result::Err(RangeError::DescendingRange { start: 3, end: 2 })
+        );
+    }
+
+    #[test]
+    fn accessors() {
+        use MergedRange::{Closed, ToEnd};
+
+        let ranges: Ranges = "1,3-5,4,10-".parse().unwrap();
+        assert_eq!(ranges.as_slice(), &[Closed(0, 0), Closed(2, 4), ToEnd(9)]);
+        assert_eq!(
+            ranges.iter().collect::<Vec<_>>(),
+            ranges.as_slice().iter().collect::<Vec<_>>()
+        );
+        assert_eq!((&ranges).into_iter().count(), 3);
+        assert_eq!(ranges.len(), 3);
+        assert!(!ranges.is_empty());
+
+        let contained: Vec<usize> = (0..12).filter(|&i| ranges.contains(i)).collect();
+        assert_eq!(contained, [0, 2, 3, 4, 9, 10, 11]);
+        assert!(ranges.contains(usize::MAX));
+        assert!(!Ranges::from_ranges(&[]).contains(0));
+
+        assert_eq!(
+            (Closed(2, 4).start(), Closed(2, 4).end()),
+            (2, Option::Some(4))
+        );
+        assert_eq!((ToEnd(9).start(), ToEnd(9).end()), (9, Option::None));
+    }
+
+    #[test]
+    fn indices() {
+        let indices = |ranges: &str, bound| {
+            let ranges: Ranges = ranges.parse().unwrap();
+            ranges.indices(bound).collect::<Vec<usize>>()
+        };
+        assert_eq!(indices("1,3-5,4,10-", 12), [0, 2, 3, 4, 9, 10, 11]);
+        assert_eq!(indices("1,3-5,10-", 4), [0, 2, 3]);
+        assert_eq!(indices("10-", 5), Vec::<usize>::new());
+        assert_eq!(indices("1-", 0), Vec::<usize>::new());
+        assert_eq!(indices(&format!("{}", usize::MAX), 3), Vec::<usize>::new());
+        assert_eq!(
+            Ranges::from_ranges(&[closed(1, usize::MAX)])
+                .indices(4)
+                .collect::<Vec<usize>>(),
+            [1, 2, 3]
+        );
+    }
+
+    /// Compares `contains` and `indices` with checking each of the unmerged ranges, for random sets
+    /// of ranges.
+    #[test]
+    fn contains_and_indices_agree_with_brute_force() {
+        // A fixed seed keeps failures reproducible.
+        let mut random = Xorshift(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..500 {
+            let cut_ranges = random_ranges(&mut random);
+            let selected = |index: usize| selected(&cut_ranges, index);
+
+            let ranges = Ranges::from_ranges(&cut_ranges);
+            for index in 0..50 {
+                assert_eq!(
+                    ranges.contains(index),
+                    selected(index),
+                    "{:?} contains {}",
+                    cut_ranges,
+                    index
+                );
+            }
+            let bound = random.below(50);
+            assert_eq!(
+                ranges.indices(bound).collect::<Vec<usize>>(),
+                (0..bound)
+                    .filter(|&index| selected(index))
+                    .collect::<Vec<usize>>(),
+                "{:?} up to {}",
+                cut_ranges,
+                bound
+            );
+        }
+    }
+
+    /// Compares `max_index`, `is_unbounded`, `len_within` and `is_empty` with checking each of the
+    /// unmerged ranges, for random sets of ranges.
+    #[test]
+    fn accessors_agree_with_brute_force() {
+        let mut random = Xorshift(0x2545_f491_4f6c_dd1d);
+        for _ in 0..500 {
+            let cut_ranges = random_ranges(&mut random);
+            let selected = |index: usize| selected(&cut_ranges, index);
+            let ranges = Ranges::from_ranges(&cut_ranges);
+
+            // Every range starts and ends before 40, so only ToEnd ranges select beyond that.
+            let unbounded = selected(1000);
+            assert_eq!(ranges.is_unbounded(), unbounded, "{:?}", cut_ranges);
+            let max_index = (0..50).filter(|&index| selected(index)).max();
+            assert_eq!(
+                ranges.max_index(),
+                if unbounded { Option::None } else { max_index },
+                "{:?}",
+                cut_ranges
+            );
+            assert_eq!(ranges.is_empty(), max_index.is_none(), "{:?}", cut_ranges);
+            for n in 0..50 {
+                assert_eq!(
+                    ranges.len_within(n),
+                    (0..n).filter(|&index| selected(index)).count(),
+                    "{:?} within {}",
+                    cut_ranges,
+                    n
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn max_index_and_len_within() {
+        let ranges = |ranges: &str| ranges.parse::<Ranges>().unwrap();
+        assert_eq!(ranges("3").max_index(), Option::Some(2));
+        assert_eq!(ranges("1-3,7,5").max_index(), Option::Some(6));
+        assert_eq!(ranges("1-3,7-").max_index(), Option::None);
+        assert_eq!(ranges("1-").complement().max_index(), Option::None);
+        assert!(ranges("2-").is_unbounded());
+        assert!(!ranges("-2").is_unbounded());
+        assert!(!ranges("1-").complement().is_unbounded());
+
+        // The complement of a selection of everything is empty.
+        assert!(ranges("1-").complement().is_empty());
+        assert!(!ranges("2-").complement().is_empty());
+
+        assert_eq!(ranges("1-3,7-").len_within(0), 0);
+        assert_eq!(ranges("1-3,7-").len_within(3), 3);
+        assert_eq!(ranges("1-3,7-").len_within(6), 3);
+        assert_eq!(ranges("1-3,7-").len_within(9), 6);
+        assert_eq!(ranges("1-").len_within(usize::MAX), usize::MAX);
+        assert_eq!(
+            Ranges::from_ranges(&[closed(0, usize::MAX)]).len_within(usize::MAX),
+            usize::MAX
+        );
+        assert_eq!(
+            Ranges::from_ranges(&[closed(0, usize::MAX)]).max_index(),
+            Option::Some(usize::MAX)
+        );
+    }
+
+    #[test]
+    fn collect_indices() {
+        use MergedRange::Closed;
+
+        let ranges: Ranges = vec![5, 1, 2, 3, 9].into_iter().collect();
+        assert_eq!(
+            ranges.as_slice(),
+            [Closed(1, 3), Closed(5, 5), Closed(9, 9)]
+        );
+        let ranges: Ranges = vec![3, 3, 0, 3, 1].into_iter().collect();
+        assert_eq!(ranges.as_slice(), [Closed(0, 1), Closed(3, 3)]);
+        let ranges: Ranges = vec![usize::MAX, 0].into_iter().collect();
+        assert_eq!(
+            ranges.as_slice(),
+            [Closed(0, 0), Closed(usize::MAX, usize::MAX)]
+        );
+        assert!(std::iter::empty::<usize>().collect::<Ranges>().is_empty());
+    }
+
+    #[test]
+    fn extend_indices() {
+        let indices = [7, 2, 0, 8, 3, 2, 12, 1, 5];
+        for split in 0..=indices.len() {
+            for second_split in split..=indices.len() {
+                let mut ranges: Ranges = indices[..split].iter().copied().collect();
+                ranges.extend(indices[split..second_split].iter().copied());
+                ranges.extend(indices[second_split..].iter().copied());
+                assert_eq!(ranges, indices.iter().copied().collect::<Ranges>());
+            }
+        }
+
+        // Indices are merged with the existing ranges, including ones to the end of the input.
+        let mut ranges: Ranges = "2,5-".parse().unwrap();
+        ranges.extend([0, 2, 3, 7]);
+        assert_eq!(ranges, "1,2-".parse().unwrap());
+        let mut ranges = Ranges::from_ranges(&[]);
+        ranges.extend(std::iter::empty());
+        assert!(ranges.is_empty());
+    }
+
+    #[test]
+    fn from_std_ranges() {
+        use MergedRange::Closed;
+
+        assert_eq!(Ranges::from(2..5).as_slice(), [Closed(2, 4)]);
+        assert_eq!(Ranges::from(2..3).as_slice(), [Closed(2, 2)]);
+        assert!(Ranges::from(2..2).is_empty());
+        assert!(Ranges::from(Range { start: 3, end: 2 }).is_empty());
+        assert_eq!(Ranges::from(2..=5).as_slice(), [Closed(2, 5)]);
+        assert_eq!(Ranges::from(2..=2).as_slice(), [Closed(2, 2)]);
+        assert!(Ranges::from(RangeInclusive::new(3, 2)).is_empty());
+        assert_eq!(
+            Ranges::from(0..=usize::MAX).as_slice(),
+            [Closed(0, usize::MAX)]
+        );
+    }
+
+    #[test]
+    fn to_slice_range() {
+        use MergedRange::{Closed, ToEnd};
+
+        assert_eq!(Closed(1, 3).to_slice_range(5), 1..4);
+        assert_eq!(Closed(1, 4).to_slice_range(5), 1..5);
+        assert_eq!(Closed(1, 5).to_slice_range(5), 1..5);
+        assert_eq!(Closed(4, 4).to_slice_range(5), 4..5);
+        assert_eq!(ToEnd(0).to_slice_range(5), 0..5);
+        assert_eq!(ToEnd(4).to_slice_range(5), 4..5);
+
+        // Ranges which start at or after the end of the slice are empty.
+        assert_eq!(Closed(5, 7).to_slice_range(5), 5..5);
+        assert_eq!(ToEnd(5).to_slice_range(5), 5..5);
+        assert_eq!(ToEnd(9).to_slice_range(5), 5..5);
+        assert_eq!(Closed(0, 3).to_slice_range(0), 0..0);
+        assert_eq!(ToEnd(0).to_slice_range(0), 0..0);
+
+        assert_eq!(Closed(2, usize::MAX).to_slice_range(5), 2..5);
+        assert_eq!(
+            Closed(0, usize::MAX).to_slice_range(usize::MAX),
+            0..usize::MAX
+        );
+        assert_eq!(
+            ToEnd(usize::MAX).to_slice_range(usize::MAX),
+            usize::MAX..usize::MAX
+        );
+    }
+
+    #[test]
+    fn std_range_conversions() {
+        use std::convert::TryFrom;
+        use std::ops::{RangeFrom, RangeInclusive};
+        use MergedRange::{Closed, ToEnd};
+
+        assert_eq!(RangeInclusive::try_from(Closed(2, 4)), Result::Ok(2..=4));
+        assert_eq!(RangeInclusive::try_from(ToEnd(2)), Result::Err(ToEnd(2)));
+        assert_eq!(RangeFrom::try_from(ToEnd(2)), Result::Ok(2..));
+        assert_eq!(RangeFrom::try_from(Closed(2, 4)), Result::Err(Closed(2, 4)));
+
+        assert_eq!(MergedRange::try_from(2..=4), Result::Ok(Closed(2, 4)));
+        assert_eq!(MergedRange::try_from(3..=3), Result::Ok(Closed(3, 3)));
+        assert_eq!(
+            MergedRange::try_from(RangeInclusive::new(4, 2)),
+            Result::Err(RangeError::DescendingRange { start: 4, end: 2 })
+        );
+        assert_eq!(MergedRange::from(2..), ToEnd(2));
+    }
+
+    #[test]
+    fn display() {
+        let display = |ranges: &[CutRange]| Ranges::from_ranges(ranges).to_string();
+        assert_eq!(display(&[]), "");
+        assert_eq!(display(&[CutRange::Unit(0)]), "1");
+        assert_eq!(display(&[closed(2, 4), CutRange::ToEnd(9)]), "3-5,10-");
+        assert_eq!(
+            display(&[CutRange::FromStart(3), CutRange::Unit(5)]),
+            "1-4,6"
+        );
+        assert_eq!(
+            display(&[CutRange::Unit(usize::MAX)]),
+            (usize::MAX as u128 + 1).to_string()
+        );
+    }
+
+    /// Returns an index which is usually small, but sometimes at the end of `usize`, where ranges
+    /// cannot be extended.
+    fn any_index() -> impl Strategy<Value = usize> + Clone {
+        prop_oneof![4 => 0..64usize, 1 => (usize::MAX - 2)..=usize::MAX]
+    }
+
+    fn any_cut_range(
+        index: impl Strategy<Value = usize> + Clone,
+    ) -> impl Strategy<Value = CutRange> {
+        prop_oneof![
+            index.clone().prop_map(CutRange::Unit),
+            (index.clone(), index.clone()).prop_map(|(a, b)| closed(a.min(b), a.max(b))),
+            index.clone().prop_map(CutRange::FromStart),
+            index.prop_map(CutRange::ToEnd),
+        ]
+    }
+
+    /// Returns the indices worth checking for ranges built from `any_index`: every small index,
+    /// and those at the end of `usize`.
+    fn checked_indices() -> impl Iterator<Item = usize> {
+        (0..80).chain((usize::MAX - 4)..=usize::MAX)
+    }
+
+    /// Formats ranges as they are given on the command line, numbered from one.
+    fn to_spec(cut_ranges: &[CutRange]) -> String {
+        cut_ranges
+            .iter()
+            .map(|range| match *range {
+                CutRange::Unit(n) => format!("{}", n + 1),
+                CutRange::Closed(range) => format!("{}-{}", range.start() + 1, range.end() + 1),
+                CutRange::FromStart(end) => format!("-{}", end + 1),
+                CutRange::ToEnd(start) => format!("{}-", start + 1),
+            })
+            .collect::<Vec<String>>()
+            .join(",")
+    }
+
+    proptest! {
+        #[test]
+        fn merged_ranges_are_sorted_and_disjoint(
+            cut_ranges in prop::collection::vec(any_cut_range(any_index()), 0..8),
+        ) {
+            let ranges = Ranges::from_ranges(&cut_ranges);
+            for range in &ranges {
+                if let MergedRange::Closed(start, end) = *range {
+                    prop_assert!(start <= end, "{:?}", ranges);
+                }
+            }
+            for pair in ranges.ranges.windows(2) {
+                // Only the last range may continue to the end, and there must be a gap before the
+                // next range, or they would have been merged.
+                let end = pair[0].end();
+                prop_assert!(end.is_some(), "{:?}", ranges);
+                prop_assert!(end.unwrap() + 1 < pair[1].start(), "{:?}", ranges);
+            }
+        }
+
+        #[test]
+        fn merged_ranges_select_the_same_indices(
+            cut_ranges in prop::collection::vec(any_cut_range(any_index()), 0..8),
+        ) {
+            let ranges = Ranges::from_ranges(&cut_ranges);
+            for index in checked_indices() {
+                prop_assert_eq!(
+                    ranges.contains(index),
+                    selected(&cut_ranges, index),
+                    "{:?} contains {}",
+                    cut_ranges,
+                    index
+                );
+            }
+        }
+
+        #[test]
+        fn complement_selects_the_other_indices(
+            cut_ranges in prop::collection::vec(any_cut_range(any_index()), 0..8),
+            bound in 0..80usize,
+        ) {
+            let ranges = Ranges::from_ranges(&cut_ranges);
+            let complement = ranges.clone().complement();
+            for index in checked_indices() {
+                prop_assert_eq!(
+                    complement.contains(index),
+                    !ranges.contains(index),
+                    "{:?} complement contains {}",
+                    ranges,
+                    index
+                );
+            }
+
+            // A closed range which ends at usize::MAX becomes ToEnd when complemented twice, so the
+            // ranges are only compared within a bound.
+            let within = |ranges: &Ranges| ranges.indices(bound).collect::<Ranges>();
+            prop_assert_eq!(within(&complement.complement()), within(&ranges));
+            prop_assert_eq!(
+                ranges.complement_within(bound).complement_within(bound),
+                within(&ranges)
+            );
+        }
+
+        #[test]
+        fn difference_selects_indices_not_in_other(
+            cut_ranges in prop::collection::vec(any_cut_range(any_index()), 0..8),
+            other_ranges in prop::collection::vec(any_cut_range(any_index()), 0..8),
+        ) {
+            let ranges = Ranges::from_ranges(&cut_ranges);
+            let other = Ranges::from_ranges(&other_ranges);
+            let difference = ranges.difference(&other);
+            for index in checked_indices() {
+                prop_assert_eq!(
+                    difference.contains(index),
+                    ranges.contains(index) && !other.contains(index),
+                    "{:?} except {:?} contains {}",
+                    ranges,
+                    other,
+                    index
+                );
+            }
+        }
+
+        #[test]
+        fn display_parses_to_the_same_ranges(
+            cut_ranges in prop::collection::vec(any_cut_range(0..100usize), 1..8),
+        ) {
+            let ranges = Ranges::from_ranges(&cut_ranges);
+            prop_assert_eq!(ranges.to_string().parse::<Ranges>(), Result::Ok(ranges));
+        }
+
+        #[test]
+        fn parsing_agrees_with_from_ranges(
+            cut_ranges in prop::collection::vec(any_cut_range(0..100usize), 1..8),
+        ) {
+            let spec = to_spec(&cut_ranges);
+            prop_assert_eq!(
+                spec.parse::<Ranges>().unwrap(),
+                Ranges::from_ranges(&cut_ranges),
+                "{}",
+                spec
+            );
+        }
+    }
+
+    /// Returns up to five random ranges, which start and end before 40.
+    fn random_ranges(random: &mut Xorshift) -> Vec<CutRange> {
+        let count = random.below(6);
+        (0..count)
+            .map(|_| {
+                let a = random.below(40);
+                let b = random.below(40);
+                match random.below(4) {
+                    0 => CutRange::Unit(a),
+                    1 => closed(a.min(b), a.max(b)),
+                    2 => CutRange::FromStart(a),
+                    _ => CutRange::ToEnd(a),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns whether any of the unmerged ranges selects `index`.
+    fn selected(cut_ranges: &[CutRange], index: usize) -> bool {
+        cut_ranges.iter().any(|range| match *range {
+            CutRange::Unit(n) => index == n,
+            CutRange::Closed(range) => range.start() <= index && index <= range.end(),
+            CutRange::FromStart(end) => index <= end,
+            CutRange::ToEnd(start) => index >= start,
+        })
+    }
+
+    /// Minimal pseudorandom number generator, for tests which check many cases from a fixed seed.
+    struct Xorshift(u64);
+
+    impl Xorshift {
+        /// Returns a number from zero up to, but not including, `n`.
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    fn assert_union(ranges: &[&str], union: &str) {
+        let ranges = ranges
+            .iter()
+            .map(|ranges| ranges.parse::<Ranges>().unwrap());
+        assert_eq!(Ranges::union(ranges), union.parse::<Ranges>().unwrap());
+    }
+
+    fn assert_difference(ranges: &str, other: &str, difference: &str) {
+        let actual = ranges
+            .parse::<Ranges>()
+            .unwrap()
+            .difference(&other.parse().unwrap());
+        let expected = difference.parse().unwrap();
+        assert_eq!(actual, expected, "{} except {}", ranges, other);
+    }
+
     fn assert_simplify_to_single_range(input_ranges: &[CutRange], expected_range: MergedRange) {
         let actual_ranges = Ranges::from_ranges(input_ranges);
         let mut elements = actual_ranges.into_iter();
@@ -527,8 +1565,14 @@ mod tests {
         assert_eq!(actual, expected);
     }
 
+    fn assert_complement_within(ranges: &str, bound: usize, complement: &str) {
+        let actual = ranges.parse::<Ranges>().unwrap().complement_within(bound);
+        let expected = complement.parse().unwrap();
+        assert_eq!(actual, expected, "{} within {}", ranges, bound);
+    }
+
     // Helper function to simplify the creation of CutRange::Closed.
     fn closed(start: usize, end: usize) -> CutRange {
-        CutRange::Closed(IncreasingRange::new(start, end))
+        CutRange::Closed(IncreasingRange::new(start, end).unwrap())
     }
 }
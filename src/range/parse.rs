@@ -1,14 +1,21 @@
 use std::fmt;
 use std::iter::Peekable;
-use std::str::Chars;
+use std::ops::Range;
+use std::str::CharIndices;
 
 use super::{CutRange, IncreasingRange, Ranges};
 
-#[derive(Debug)]
-pub(crate) enum Token {
+/// A token of a string of ranges.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Token {
+    /// A position, numbered from one as written.
     Number(usize),
+    /// A hyphen, which separates the start and end of a range.
     Hyphen,
+    /// A comma, which separates ranges.
     Comma,
+    /// A space or tab, which separates ranges.
     Blank(char),
 }
 
@@ -23,25 +30,165 @@ impl fmt::Display for Token {
     }
 }
 
+/// A token, along with the byte offsets of its text in the scanned string.
 #[derive(Debug)]
-pub(crate) enum ParseRangesError {
-    NumberedFromZero,
-    IndecipherableRange(Vec<Token>),
-    DescendingRange,
-    UnexpectedSeparator(Token),
+pub(crate) struct SpannedToken {
+    pub(crate) token: Token,
+    pub(crate) span: Range<usize>,
+}
+
+/// An error parsing ranges. Each variant holds the byte offsets of the offending text in the
+/// parsed string.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseRangesError {
+    /// A position of zero.
+    NumberedFromZero(Range<usize>),
+    /// Tokens which do not form a range, e.g. "1-2-3". An empty range has no tokens.
+    IndecipherableRange(Vec<Token>, Range<usize>),
+    /// A range whose end is before its start, e.g. "3-1".
+    DescendingRange(Range<usize>),
+    /// A separator in an unexpected place.
+    UnexpectedSeparator(Token, Range<usize>),
+    /// A position which is larger than `usize::MAX`.
+    NumberTooLarge(Range<usize>),
+    /// A character which is not part of any token.
     LexError(LexError),
 }
 
-#[derive(Debug)]
-pub(crate) enum LexError {
-    UnrecognizedCharacter(char),
+/// An error scanning a string of ranges into tokens.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum LexError {
+    /// An unrecognized character, and its byte offset.
+    UnrecognizedCharacter(char, usize),
+}
+
+impl ParseRangesError {
+    /// Returns the byte offsets of the offending text in the parsed string.
+    pub fn span(&self) -> Range<usize> {
+        match self {
+            ParseRangesError::NumberedFromZero(span)
+            | ParseRangesError::IndecipherableRange(_, span)
+            | ParseRangesError::DescendingRange(span)
+            | ParseRangesError::UnexpectedSeparator(_, span)
+            | ParseRangesError::NumberTooLarge(span) => span.clone(),
+            ParseRangesError::LexError(LexError::UnrecognizedCharacter(ch, offset)) => {
+                *offset..offset + ch.len_utf8()
+            }
+        }
+    }
+
+    /// Returns the offending text in `input`, the parsed string. This is empty for an empty range
+    /// at the end of the string.
+    pub fn text<'a>(&self, input: &'a str) -> &'a str {
+        let (start, end) = self.clamped_span(input);
+        &input[start..end]
+    }
+
+    /// Returns the position of the offending text in `input`, the parsed string, counted in
+    /// characters, from one.
+    pub fn position(&self, input: &str) -> usize {
+        let (start, _) = self.clamped_span(input);
+        input[..start].chars().count() + 1
+    }
+
+    /// Returns the span, limited to the bounds of `input`, in case it is not the parsed string.
+    fn clamped_span(&self, input: &str) -> (usize, usize) {
+        let span = self.span();
+        let start = span.start.min(input.len());
+        let start = (0..=start)
+            .rev()
+            .find(|&i| input.is_char_boundary(i))
+            .unwrap_or(0);
+        let end = span.end.clamp(start, input.len());
+        let end = (end..=input.len())
+            .find(|&i| input.is_char_boundary(i))
+            .unwrap_or(input.len());
+        (start, end)
+    }
+
+    /// Returns the message followed by `input`, the parsed string, with the offending text
+    /// underlined by carets and its position (counted in characters, from one).
+    pub fn annotate(&self, input: &str) -> String {
+        let (start, _) = self.clamped_span(input);
+        // Tabs are kept so that the carets line up with the input.
+        let indent: String = input[..start]
+            .chars()
+            .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+            .collect();
+        let carets = "^".repeat(self.text(input).chars().count().max(1));
+        format!(
+            "{}\n  {}\n  {}{} at position {}",
+            self,
+            input,
+            indent,
+            carets,
+            self.position(input)
+        )
+    }
+
+    /// Returns the message GNU cut gives for the same error in `input`, the parsed string. `fields`
+    /// is whether the ranges select fields, rather than bytes or characters.
+    pub fn gnu_message(&self, input: &str, fields: bool) -> String {
+        let zero = if fields {
+            "fields are numbered from 1"
+        } else {
+            "byte/character positions are numbered from 1"
+        };
+        let invalid_range = if fields {
+            "invalid field range"
+        } else {
+            "invalid byte or character range"
+        };
+        match self {
+            ParseRangesError::NumberedFromZero(_) => String::from(zero),
+            // GNU cut reads an empty range as zero.
+            ParseRangesError::IndecipherableRange(tokens, _) if tokens.is_empty() => {
+                String::from(zero)
+            }
+            ParseRangesError::IndecipherableRange(tokens, _)
+                if matches!(tokens.as_slice(), [Token::Hyphen]) =>
+            {
+                String::from("invalid range with no endpoint: -")
+            }
+            ParseRangesError::IndecipherableRange(..)
+            | ParseRangesError::UnexpectedSeparator(..) => String::from(invalid_range),
+            ParseRangesError::DescendingRange(_) => String::from("invalid decreasing range"),
+            ParseRangesError::NumberTooLarge(span) => {
+                let number = input.get(span.clone()).unwrap_or_default();
+                if fields {
+                    format!("field number '{}' is too large", number)
+                } else {
+                    format!("byte/character offset '{}' is too large", number)
+                }
+            }
+            ParseRangesError::LexError(LexError::UnrecognizedCharacter(_, offset)) => {
+                let rest = input.get(*offset..).unwrap_or_default();
+                if fields {
+                    format!("invalid field value '{}'", rest)
+                } else {
+                    format!("invalid byte/character position '{}'", rest)
+                }
+            }
+        }
+    }
+}
+
+impl std::error::Error for ParseRangesError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ParseRangesError::LexError(err) => Option::Some(err),
+            _ => Option::None,
+        }
+    }
 }
 
 impl fmt::Display for ParseRangesError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            ParseRangesError::NumberedFromZero => write!(f, "Ranges are numbered from one."),
-            ParseRangesError::IndecipherableRange(tokens) => {
+            ParseRangesError::NumberedFromZero(_) => write!(f, "Ranges are numbered from one."),
+            ParseRangesError::IndecipherableRange(tokens, _) => {
                 let range = tokens
                     .iter()
                     .map(|t| t.to_string())
@@ -49,83 +196,105 @@ impl fmt::Display for ParseRangesError {
                     .join("");
                 write!(f, "Indecipherable range: \"{}\"", range)
             }
-            ParseRangesError::DescendingRange => write!(f, "Ranges must be ascending."),
-            ParseRangesError::UnexpectedSeparator(t) => {
+            ParseRangesError::DescendingRange(_) => write!(f, "Ranges must be ascending."),
+            ParseRangesError::UnexpectedSeparator(t, _) => {
                 write!(f, "Expected separator but found '{}'.", t)
             }
-            ParseRangesError::LexError(lex_err) => match lex_err {
-                LexError::UnrecognizedCharacter(ch) => {
-                    write!(f, "Unrecognized character '{}'.", ch)
-                }
-            },
+            ParseRangesError::NumberTooLarge(_) => {
+                write!(f, "Ranges must be at most {}.", usize::MAX)
+            }
+            ParseRangesError::LexError(lex_err) => write!(f, "{}", lex_err),
+        }
+    }
+}
+
+impl std::error::Error for LexError {}
+
+impl fmt::Display for LexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LexError::UnrecognizedCharacter(ch, _) => {
+                write!(f, "Unrecognized character '{}'.", ch)
+            }
         }
     }
 }
 
 /// Parses a string into `Ranges`.
 pub(crate) fn parse(s: &str) -> Result<Ranges, ParseRangesError> {
-    match scan(s) {
-        Result::Ok(tokens) => parse_tokens(tokens),
-        Result::Err(e) => Result::Err(ParseRangesError::LexError(e)),
-    }
+    parse_tokens(scan(s)?, s.len())
 }
 
-/// Scans a string into a `Vec` of [`Token`]s.
-fn scan(s: &str) -> Result<Vec<Token>, LexError> {
+/// Scans a string into a `Vec` of [`SpannedToken`]s.
+fn scan(s: &str) -> Result<Vec<SpannedToken>, ParseRangesError> {
     let mut tokens = Vec::new();
-    let mut chars = s.chars().peekable();
-    while let Some(ch) = chars.peek() {
-        match ch {
+    let mut chars = s.char_indices().peekable();
+    while let Some(&(start, ch)) = chars.peek() {
+        let token = match ch {
             '-' => {
-                tokens.push(Token::Hyphen);
                 chars.next();
+                Token::Hyphen
             }
             ',' => {
-                tokens.push(Token::Comma);
                 chars.next();
+                Token::Comma
             }
             ' ' | '\t' => {
-                tokens.push(Token::Blank(*ch));
                 chars.next();
+                Token::Blank(ch)
             }
-            c if c.is_digit(10) => tokens.push(Token::Number(scan_number(&mut chars))),
-            _ => return Result::Err(LexError::UnrecognizedCharacter(*ch)),
-        }
+            c if c.is_ascii_digit() => {
+                let number = scan_number(&mut chars);
+                let end = chars.peek().map_or(s.len(), |&(end, _)| end);
+                Token::Number(number.ok_or(ParseRangesError::NumberTooLarge(start..end))?)
+            }
+            _ => {
+                return Result::Err(ParseRangesError::LexError(LexError::UnrecognizedCharacter(
+                    ch, start,
+                )))
+            }
+        };
+        let end = chars.peek().map_or(s.len(), |&(end, _)| end);
+        tokens.push(SpannedToken {
+            token,
+            span: start..end,
+        });
     }
 
     Result::Ok(tokens)
 }
 
-/// Scans and consumes a number.
-fn scan_number(chars: &mut Peekable<Chars>) -> usize {
+/// Scans and consumes a number. Returns `None` if it is too large for a `usize`.
+fn scan_number(chars: &mut Peekable<CharIndices>) -> Option<usize> {
     let mut number = String::new();
 
-    while let Some(ch) = chars.peek() {
-        if ch.is_digit(10) {
-            number.push(*ch);
+    while let Some(&(_, ch)) = chars.peek() {
+        if ch.is_ascii_digit() {
+            number.push(ch);
             chars.next();
         } else {
             break;
         }
     }
 
-    // Since we've only parsed digits, we know it is a valid usize.
-    number.parse::<usize>().unwrap()
+    // Since we've only parsed digits, the only possible error is overflow.
+    number.parse::<usize>().ok()
 }
 
-/// Parses tokens into `Ranges`.
-fn parse_tokens(tokens: Vec<Token>) -> Result<Ranges, ParseRangesError> {
+/// Parses tokens into `Ranges`. `len` is the length of the scanned string, which is the position of
+/// errors at the end of the string.
+fn parse_tokens(tokens: Vec<SpannedToken>, len: usize) -> Result<Ranges, ParseRangesError> {
     let mut cut_ranges = Vec::new();
     let mut tokens = tokens.into_iter().peekable();
     loop {
         // Parse a single range.
-        let result = parse_range(&mut tokens);
+        let result = parse_range(&mut tokens, len);
         match result {
             Result::Ok(cut_range) => cut_ranges.push(cut_range),
             Result::Err(e) => return Result::Err(e),
         }
         // Consume separator.
-        match tokens.peek() {
+        match tokens.peek().map(|spanned| &spanned.token) {
             Option::Some(Token::Comma) | Option::Some(Token::Blank(_)) => {
                 tokens.next();
             }
@@ -133,9 +302,8 @@ fn parse_tokens(tokens: Vec<Token>) -> Result<Ranges, ParseRangesError> {
             Option::None => break,
             // Unexpected token.
             _ => {
-                return Result::Err(ParseRangesError::UnexpectedSeparator(
-                    tokens.next().unwrap(),
-                ))
+                let SpannedToken { token, span } = tokens.next().unwrap();
+                return Result::Err(ParseRangesError::UnexpectedSeparator(token, span));
             }
         }
     }
@@ -144,57 +312,73 @@ fn parse_tokens(tokens: Vec<Token>) -> Result<Ranges, ParseRangesError> {
 }
 
 /// Parse a single range from tokens.
-fn parse_range<I: Iterator<Item = Token>>(
+fn parse_range<I: Iterator<Item = SpannedToken>>(
     tokens: &mut Peekable<I>,
+    len: usize,
 ) -> Result<CutRange, ParseRangesError> {
     // Collect all tokens until the next separator.
     let mut range = Vec::new();
-    while let Some(token) = tokens.peek() {
-        match token {
+    while let Some(spanned) = tokens.peek() {
+        match spanned.token {
             Token::Hyphen | Token::Number(_) => range.push(tokens.next().unwrap()),
             _ => break,
         }
     }
 
+    // An empty range is reported at the following separator, or the end of the string.
+    let span = match (range.first(), range.last()) {
+        (Option::Some(first), Option::Some(last)) => first.span.start..last.span.end,
+        _ => tokens
+            .peek()
+            .map_or(len..len, |spanned| spanned.span.clone()),
+    };
+    let zero = |spanned: &SpannedToken| ParseRangesError::NumberedFromZero(spanned.span.clone());
+    let indecipherable = |range: Vec<SpannedToken>| {
+        let tokens = range.into_iter().map(|spanned| spanned.token).collect();
+        Result::Err(ParseRangesError::IndecipherableRange(tokens, span.clone()))
+    };
+
     // Handle unit.
     if range.len() == 1 {
-        return match range[0] {
+        return match range[0].token {
             Token::Number(n) => match n {
-                0 => Result::Err(ParseRangesError::NumberedFromZero),
+                0 => Result::Err(zero(&range[0])),
                 _ => Result::Ok(CutRange::Unit(n - 1)),
             },
-            _ => Result::Err(ParseRangesError::IndecipherableRange(range)),
+            _ => indecipherable(range),
         };
     }
     // Handle "-n" and "n-".
     if range.len() == 2 {
-        return match (&range[0], &range[1]) {
+        return match (&range[0].token, &range[1].token) {
             (Token::Hyphen, Token::Number(end)) => match end {
-                0 => Result::Err(ParseRangesError::NumberedFromZero),
+                0 => Result::Err(zero(&range[1])),
                 _ => Result::Ok(CutRange::FromStart(end - 1)),
             },
             (Token::Number(start), Token::Hyphen) => match start {
-                0 => Result::Err(ParseRangesError::NumberedFromZero),
+                0 => Result::Err(zero(&range[0])),
                 _ => Result::Ok(CutRange::ToEnd(start - 1)),
             },
-            _ => Result::Err(ParseRangesError::IndecipherableRange(range)),
+            _ => indecipherable(range),
         };
     }
     // Handle "n-m".
     if range.len() == 3 {
-        return match (&range[0], &range[1], &range[2]) {
+        return match (&range[0].token, &range[1].token, &range[2].token) {
             (Token::Number(start), Token::Hyphen, Token::Number(end)) => match (start, end) {
-                (0, _) | (_, 0) => Result::Err(ParseRangesError::NumberedFromZero),
-                _ if start <= end => {
-                    Result::Ok(CutRange::Closed(IncreasingRange::new(start - 1, end - 1)))
-                }
-                _ => Result::Err(ParseRangesError::DescendingRange),
+                (0, _) => Result::Err(zero(&range[0])),
+                (_, 0) => Result::Err(zero(&range[2])),
+                _ if start <= end => Result::Ok(CutRange::Closed(IncreasingRange {
+                    start: start - 1,
+                    end: end - 1,
+                })),
+                _ => Result::Err(ParseRangesError::DescendingRange(span)),
             },
-            _ => Result::Err(ParseRangesError::IndecipherableRange(range)),
+            _ => indecipherable(range),
         };
     }
 
-    Result::Err(ParseRangesError::IndecipherableRange(range))
+    indecipherable(range)
 }
 
 #[cfg(test)]
@@ -307,6 +491,214 @@ mod tests {
         assert!("0- 3-4".parse::<Ranges>().is_err());
         assert!("-0\t4-".parse::<Ranges>().is_err());
         assert!("0-1 5,6-7\t8-".parse::<Ranges>().is_err());
+
+        // Too large.
+        assert!("99999999999999999999999999".parse::<Ranges>().is_err());
+        assert!("1-99999999999999999999999999".parse::<Ranges>().is_err());
+        assert!(format!("{}0", usize::MAX).parse::<Ranges>().is_err());
+    }
+
+    #[test]
+    fn parse_largest_number() {
+        use MergedRange::{Closed, ToEnd};
+        let max = usize::MAX;
+
+        assert_parse_ranges(&max.to_string(), &[Closed(max - 1, max - 1)]);
+        assert_parse_ranges(&format!("2-{}", max), &[Closed(1, max - 1)]);
+        assert_parse_ranges(&format!("{}-", max), &[ToEnd(max - 1)]);
+    }
+
+    #[test]
+    fn error_span() {
+        // Unknown token.
+        assert_error_span(
+            "x",
+            0..1,
+            "Unrecognized character 'x'.\n  x\n  ^ at position 1",
+        );
+        assert_error_span(
+            "11-22 $-5",
+            6..7,
+            "Unrecognized character '$'.\n  11-22 $-5\n        ^ at position 7",
+        );
+        assert_error_span(
+            "1,é",
+            2..4,
+            "Unrecognized character 'é'.\n  1,é\n    ^ at position 3",
+        );
+
+        // Indecipherable range.
+        assert_error_span(
+            "1,2,10-11-12",
+            4..12,
+            "Indecipherable range: \"10-11-12\"\n  1,2,10-11-12\n      ^^^^^^^^ at position 5",
+        );
+        assert_error_span(
+            "23--",
+            0..4,
+            "Indecipherable range: \"23--\"\n  23--\n  ^^^^ at position 1",
+        );
+        // Empty ranges are reported at the following separator, or the end of the string.
+        assert_error_span(
+            "13,,14",
+            3..4,
+            "Indecipherable range: \"\"\n  13,,14\n     ^ at position 4",
+        );
+        assert_error_span(
+            "1,",
+            2..2,
+            "Indecipherable range: \"\"\n  1,\n    ^ at position 3",
+        );
+        assert_error_span(
+            "",
+            0..0,
+            "Indecipherable range: \"\"\n  \n  ^ at position 1",
+        );
+
+        // Descending range.
+        assert_error_span(
+            "1,5-3",
+            2..5,
+            "Ranges must be ascending.\n  1,5-3\n    ^^^ at position 3",
+        );
+        assert_error_span(
+            "5-7\t43-21",
+            4..9,
+            "Ranges must be ascending.\n  5-7\t43-21\n     \t^^^^^ at position 5",
+        );
+
+        // Numbered from 0.
+        assert_error_span(
+            "0,1,2",
+            0..1,
+            "Ranges are numbered from one.\n  0,1,2\n  ^ at position 1",
+        );
+        assert_error_span(
+            "4 -0",
+            3..4,
+            "Ranges are numbered from one.\n  4 -0\n     ^ at position 4",
+        );
+        assert_error_span(
+            "3-00",
+            2..4,
+            "Ranges are numbered from one.\n  3-00\n    ^^ at position 3",
+        );
+
+        // Number too large.
+        assert_error_span(
+            "1,99999999999999999999999999-",
+            2..28,
+            &format!(
+                "Ranges must be at most {}.\n  1,99999999999999999999999999-\n    {} at position 3",
+                usize::MAX,
+                "^".repeat(26)
+            ),
+        );
+
+        // Unexpected separator, which cannot be produced by parsing.
+        let error = super::ParseRangesError::UnexpectedSeparator(super::Token::Hyphen, 1..2);
+        assert_eq!(error.span(), 1..2);
+        assert_eq!(
+            error.annotate("1-"),
+            "Expected separator but found '-'.\n  1-\n   ^ at position 2"
+        );
+    }
+
+    #[test]
+    fn gnu_message() {
+        let gnu = |input: &str, fields| {
+            input
+                .parse::<Ranges>()
+                .err()
+                .unwrap()
+                .gnu_message(input, fields)
+        };
+        assert_eq!(
+            gnu("0", false),
+            "byte/character positions are numbered from 1"
+        );
+        assert_eq!(gnu("0", true), "fields are numbered from 1");
+        assert_eq!(gnu("1,,3", true), "fields are numbered from 1");
+        assert_eq!(gnu("1,", true), "fields are numbered from 1");
+        assert_eq!(gnu("3-1", false), "invalid decreasing range");
+        assert_eq!(gnu("-", false), "invalid range with no endpoint: -");
+        assert_eq!(gnu("10-11-12", false), "invalid byte or character range");
+        assert_eq!(gnu("--5", true), "invalid field range");
+        assert_eq!(gnu("1,2x3", false), "invalid byte/character position 'x3'");
+        assert_eq!(gnu("1x", true), "invalid field value 'x'");
+        let too_large = "99999999999999999999999";
+        assert_eq!(
+            gnu(too_large, false),
+            format!("byte/character offset '{}' is too large", too_large)
+        );
+        assert_eq!(
+            gnu(&format!("1-{}", too_large), true),
+            format!("field number '{}' is too large", too_large)
+        );
+    }
+
+    #[test]
+    fn display() {
+        let display = |input: &str| input.parse::<Ranges>().err().unwrap().to_string();
+        assert_eq!(display("0"), "Ranges are numbered from one.");
+        assert_eq!(display("1-2-3"), "Indecipherable range: \"1-2-3\"");
+        assert_eq!(display("1,,2"), "Indecipherable range: \"\"");
+        assert_eq!(display("3-1"), "Ranges must be ascending.");
+        assert_eq!(
+            display("99999999999999999999999"),
+            format!("Ranges must be at most {}.", usize::MAX)
+        );
+        assert_eq!(display("1#"), "Unrecognized character '#'.");
+
+        // Separators are consumed between ranges, so cannot be unexpected when parsing a string.
+        let unexpected = super::ParseRangesError::UnexpectedSeparator(super::Token::Comma, 1..2);
+        assert_eq!(unexpected.to_string(), "Expected separator but found ','.");
+    }
+
+    #[test]
+    fn error_source() {
+        use std::error::Error;
+
+        let err: Box<dyn Error> = Box::new("1,x".parse::<Ranges>().err().unwrap());
+        let source = err.source().unwrap();
+        assert_eq!(
+            source.downcast_ref::<super::LexError>(),
+            Option::Some(&super::LexError::UnrecognizedCharacter('x', 2))
+        );
+        let err = err.downcast::<super::ParseRangesError>().unwrap();
+        assert_eq!(
+            *err,
+            super::ParseRangesError::LexError(super::LexError::UnrecognizedCharacter('x', 2))
+        );
+
+        assert!("0".parse::<Ranges>().err().unwrap().source().is_none());
+    }
+
+    #[test]
+    fn text_and_position() {
+        let assert_text = |input: &str, text: &str, position: usize| {
+            let err = input.parse::<Ranges>().err().unwrap();
+            assert_eq!(err.text(input), text, "{}", input);
+            assert_eq!(err.position(input), position, "{}", input);
+        };
+        assert_text("1,3-1", "3-1", 3);
+        assert_text("1,2,0", "0", 5);
+        assert_text("1,x", "x", 3);
+        assert_text("1,é", "é", 3);
+        assert_text("1,,3", ",", 3);
+        assert_text("1,", "", 3);
+
+        // Other strings do not cause a panic.
+        let err = "1,é".parse::<Ranges>().err().unwrap();
+        assert_eq!(err.text("1,"), "");
+        assert_eq!(err.text("1,éé"), "é");
+        assert_eq!(err.text("ééé"), "é");
+    }
+
+    fn assert_error_span(input: &str, span: std::ops::Range<usize>, annotated: &str) {
+        let error = input.parse::<Ranges>().err().unwrap();
+        assert_eq!(error.span(), span);
+        assert_eq!(error.annotate(input), annotated);
     }
 
     fn assert_parse_ranges(input: &str, expected: &[MergedRange]) {
@@ -0,0 +1,447 @@
+use crate::args::Compat;
+use crate::config::ConfigError;
+use rut::cut::{InvalidUtf8Error, Reject};
+use std::fmt::Display;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// How diagnostics are written to stderr.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum ErrorFormat {
+    /// One line of text per diagnostic, prefixed with "rut: ".
+    Human,
+    /// One JSON object per line, e.g. for tools which parse the diagnostics.
+    Json,
+}
+
+/// Severity of a diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Level {
+    Error,
+    Warning,
+    /// Progress reported by --verbose.
+    Info,
+}
+
+impl Level {
+    fn name(self) -> &'static str {
+        match self {
+            Level::Error => "error",
+            Level::Warning => "warning",
+            Level::Info => "info",
+        }
+    }
+}
+
+/// What a diagnostic is about, so that tools parsing JSON diagnostics do not need to match on the
+/// message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Kind {
+    /// The command line arguments are invalid.
+    InvalidArgument,
+    /// The config file could not be parsed, or contains an invalid value.
+    InvalidConfig,
+    /// The config file contains a key which is not recognized.
+    UnknownConfigKey,
+    /// A file does not exist.
+    NotFound,
+    /// A file could not be opened due to its permissions.
+    PermissionDenied,
+    /// A file is a directory, where a regular file was expected.
+    IsDirectory,
+    /// A line is not valid UTF-8.
+    InvalidUtf8,
+    /// Any other error while reading or writing.
+    Io,
+    /// The output could not be written, e.g. since the disk is full.
+    Write,
+    /// Summary of the inputs which failed, at the end of a run.
+    InputsFailed,
+    /// The config file was read, with --verbose.
+    ReadConfig,
+    /// A file was opened, with --verbose.
+    Opened,
+    /// A file was cut, with --verbose.
+    Finished,
+}
+
+impl Kind {
+    /// Returns the kind of an I/O error.
+    pub(crate) fn of(err: &io::Error) -> Kind {
+        match err.kind() {
+            io::ErrorKind::NotFound => Kind::NotFound,
+            io::ErrorKind::PermissionDenied => Kind::PermissionDenied,
+            io::ErrorKind::IsADirectory => Kind::IsDirectory,
+            _ if invalid_utf8(err).is_some() => Kind::InvalidUtf8,
+            _ => Kind::Io,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Kind::InvalidArgument => "invalid_argument",
+            Kind::InvalidConfig => "invalid_config",
+            Kind::UnknownConfigKey => "unknown_config_key",
+            Kind::NotFound => "not_found",
+            Kind::PermissionDenied => "permission_denied",
+            Kind::IsDirectory => "is_directory",
+            Kind::InvalidUtf8 => "invalid_utf8",
+            Kind::Io => "io",
+            Kind::Write => "write",
+            Kind::InputsFailed => "inputs_failed",
+            Kind::ReadConfig => "read_config",
+            Kind::Opened => "opened",
+            Kind::Finished => "finished",
+        }
+    }
+}
+
+/// Returns the invalid UTF-8 error wrapped by an I/O error, if there is one.
+fn invalid_utf8(err: &io::Error) -> Option<&InvalidUtf8Error> {
+    err.get_ref()
+        .and_then(|err| err.downcast_ref::<InvalidUtf8Error>())
+}
+
+/// A single message written to stderr.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct Diagnostic<'a> {
+    pub(crate) level: Level,
+    pub(crate) kind: Kind,
+    /// File the diagnostic is about, which is written before the message in the human format.
+    pub(crate) file: Option<&'a str>,
+    /// Line of the file the diagnostic is about. This is only written separately in the JSON
+    /// format; the message includes it where it is relevant.
+    pub(crate) line: Option<usize>,
+    pub(crate) message: String,
+}
+
+impl Diagnostic<'_> {
+    /// Formats the diagnostic, without the line terminator. JSON diagnostics are a single line, but
+    /// human diagnostics have a line for each line of the message, each prefixed with "rut: ". As
+    /// in GNU cut, only the first line is prefixed, with "cut: ", with --compat=gnu.
+    fn format(&self, format: ErrorFormat, compat: Compat) -> String {
+        match (format, self.file) {
+            (ErrorFormat::Human, file) => {
+                let program = compat.program();
+                let message = match compat {
+                    Compat::Rut => self.message.replace('\n', "\nrut: "),
+                    Compat::Gnu => self.message.clone(),
+                };
+                match file {
+                    Option::Some(file) => format!("{}: {}: {}", program, file, message),
+                    Option::None => format!("{}: {}", program, message),
+                }
+            }
+            (ErrorFormat::Json, file) => format!(
+                "{{\"level\":\"{}\",\"kind\":\"{}\",\"file\":{},\"line\":{},\"message\":{}}}",
+                self.level.name(),
+                self.kind.name(),
+                file.map_or_else(|| String::from("null"), json_string),
+                self.line
+                    .map_or_else(|| String::from("null"), |line| line.to_string()),
+                json_string(&self.message)
+            ),
+        }
+    }
+}
+
+/// Quotes and escapes a string for JSON.
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            ch if ch.is_control() => json.push_str(&format!("\\u{:04x}", ch as u32)),
+            ch => json.push(ch),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Writes diagnostics to stderr in the format given by --error-format. Errors and warnings for
+/// individual files are not written with --quiet; these do not stop the remaining files from being
+/// processed, unlike errors in the arguments. With --verbose, also reports what is being done.
+/// Nothing is written to stdout. With --compat=gnu, human diagnostics are written as GNU cut writes
+/// them.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Reporter {
+    pub(crate) format: ErrorFormat,
+    pub(crate) compat: Compat,
+    pub(crate) quiet: bool,
+    /// Level of detail of progress diagnostics, or 0 for none.
+    pub(crate) verbosity: u8,
+}
+
+impl Reporter {
+    pub(crate) fn new(format: ErrorFormat, compat: Compat) -> Reporter {
+        Reporter {
+            format,
+            compat,
+            quiet: false,
+            verbosity: 0,
+        }
+    }
+
+    /// Writes a diagnostic, regardless of --quiet and --verbose. Output already written to stdout is
+    /// flushed first, so that if both are written to the same place, such as a terminal, each
+    /// diagnostic comes after the output of the files before it.
+    pub(crate) fn report(&self, diagnostic: &Diagnostic) {
+        // An error writing stdout is reported when it is next flushed, before rut exits.
+        let _ = io::stdout().flush();
+        eprintln!("{}", diagnostic.format(self.format, self.compat));
+    }
+
+    /// Reports an error which stops rut, optionally about a file.
+    pub(crate) fn error(&self, kind: Kind, file: Option<&str>, message: impl Display) {
+        self.report(&Diagnostic {
+            level: Level::Error,
+            kind,
+            file,
+            line: Option::None,
+            message: message.to_string(),
+        });
+    }
+
+    /// Reports invalid arguments, followed by the usage text in the human format. `message` may
+    /// have several paragraphs, separated by blank lines, of which only the first is the error;
+    /// the others, such as hints, are written as they are in the human format, and not at all in
+    /// the JSON format. With --compat=gnu, GNU cut's hint to use --help is written instead of the
+    /// hints and usage.
+    pub(crate) fn argument_error(&self, message: &str, usage: Option<&str>) {
+        let (error, rest) = match message.split_once("\n\n") {
+            Option::Some((error, rest)) => (error, Option::Some(rest)),
+            Option::None => (message, Option::None),
+        };
+        match self.format {
+            ErrorFormat::Human => {
+                let mut blocks = Vec::new();
+                if !error.is_empty() {
+                    blocks.push(self.format_error(error));
+                }
+                if self.compat == Compat::Gnu {
+                    blocks.push(String::from("Try 'cut --help' for more information."));
+                    eprintln!("{}", blocks.join("\n"));
+                    return;
+                }
+                blocks.extend(rest.map(String::from));
+                blocks.extend(usage.map(String::from));
+                eprintln!("{}", blocks.join("\n\n"));
+            }
+            ErrorFormat::Json => {
+                let error = if error.is_empty() {
+                    "invalid arguments"
+                } else {
+                    error
+                };
+                self.error(Kind::InvalidArgument, Option::None, error);
+            }
+        }
+    }
+
+    /// Formats an error in the arguments, in the human format.
+    fn format_error(&self, message: &str) -> String {
+        Diagnostic {
+            level: Level::Error,
+            kind: Kind::InvalidArgument,
+            file: Option::None,
+            line: Option::None,
+            message: message.to_string(),
+        }
+        .format(ErrorFormat::Human, self.compat)
+    }
+
+    /// Reports an error or warning about the config file.
+    pub(crate) fn config(&self, level: Level, err: &ConfigError) {
+        self.report(&Diagnostic {
+            level,
+            kind: err.kind,
+            file: Option::Some(&err.path),
+            line: err.line,
+            message: err.detail(),
+        });
+    }
+
+    /// Reports that a file could not be opened or read.
+    pub(crate) fn file_error(&self, filename: &Path, err: &io::Error) {
+        if !self.quiet {
+            self.report(&Diagnostic {
+                level: Level::Error,
+                kind: Kind::of(err),
+                file: Option::Some(&filename.display().to_string()),
+                line: invalid_utf8(err).map(|err| err.line),
+                message: self.describe(err),
+            });
+        }
+    }
+
+    /// Reports that stdout could not be written, as GNU cut does, e.g. "write error: No space left
+    /// on device". This is not about any one input, so it is reported even with --quiet.
+    pub(crate) fn write_error(&self, err: &io::Error) {
+        self.error(
+            Kind::Write,
+            Option::None,
+            format_args!("write error: {}", self.describe(err)),
+        );
+    }
+
+    /// Returns the message for an I/O error.
+    fn describe(&self, err: &io::Error) -> String {
+        let mut message = err.to_string();
+        if self.compat == Compat::Gnu && err.raw_os_error().is_some() {
+            // GNU cut writes only the description of the error, without its code.
+            if let Option::Some(index) = message.rfind(" (os error ") {
+                message.truncate(index);
+            }
+        }
+        message
+    }
+
+    /// Reports a line which was skipped by --skip-invalid.
+    pub(crate) fn skipped(&self, filename: &Path, reject: &Reject) {
+        if !self.quiet {
+            self.report(&Diagnostic {
+                level: Level::Warning,
+                kind: Kind::InvalidUtf8,
+                file: Option::Some(&filename.display().to_string()),
+                line: Option::Some(reject.record),
+                message: format!(
+                    "line {} (byte {}): invalid UTF-8, skipped",
+                    reject.record, reject.offset
+                ),
+            });
+        }
+    }
+
+    /// Reports how many inputs failed, after they have each been reported. GNU cut does not write a
+    /// summary.
+    pub(crate) fn failures(&self, failed: usize, inputs: usize) {
+        if !self.quiet && self.compat == Compat::Rut {
+            self.error(
+                Kind::InputsFailed,
+                Option::None,
+                format_args!("{} of {} inputs failed", failed, inputs),
+            );
+        }
+    }
+
+    /// Reports progress, if the verbosity is at least `level`.
+    pub(crate) fn info(&self, level: u8, kind: Kind, file: Option<&str>, message: impl Display) {
+        if self.verbosity >= level {
+            self.report(&Diagnostic {
+                level: Level::Info,
+                kind,
+                file,
+                line: Option::None,
+                message: message.to_string(),
+            });
+        }
+    }
+
+    /// Reports that a file was opened, and how, e.g. whether it was decompressed.
+    pub(crate) fn opened(&self, filename: &Path, details: &[String]) {
+        let filename = filename.display().to_string();
+        let file = Option::Some(filename.as_str());
+        if details.is_empty() {
+            self.info(1, Kind::Opened, file, "opened");
+        } else {
+            let message = format!("opened ({})", details.join(", "));
+            self.info(1, Kind::Opened, file, message);
+        }
+    }
+
+    /// Reports the number of lines written from a file, and how long it took to cut.
+    pub(crate) fn finished(&self, filename: &Path, lines: usize, elapsed: Duration) {
+        let filename = filename.display().to_string();
+        let file = Option::Some(filename.as_str());
+        let noun = if lines == 1 { "line" } else { "lines" };
+        if self.verbosity >= 2 {
+            let millis = elapsed.as_secs_f64() * 1000.0;
+            let message = format!("wrote {} {} in {:.3} ms", lines, noun, millis);
+            self.info(2, Kind::Finished, file, message);
+        } else {
+            self.info(
+                1,
+                Kind::Finished,
+                file,
+                format_args!("wrote {} {}", lines, noun),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Diagnostic, ErrorFormat, Kind, Level};
+    use crate::args::Compat;
+    use rut::cut::InvalidUtf8Error;
+    use std::io;
+
+    #[test]
+    fn format() {
+        let diagnostic = Diagnostic {
+            level: Level::Error,
+            kind: Kind::InvalidUtf8,
+            file: Option::Some("a \"b\".txt"),
+            line: Option::Some(2),
+            message: String::from("line 2 (byte 6): invalid UTF-8"),
+        };
+        assert_eq!(
+            diagnostic.format(ErrorFormat::Human, Compat::Rut),
+            "rut: a \"b\".txt: line 2 (byte 6): invalid UTF-8"
+        );
+        assert_eq!(
+            diagnostic.format(ErrorFormat::Json, Compat::Rut),
+            "{\"level\":\"error\",\"kind\":\"invalid_utf8\",\"file\":\"a \\\"b\\\".txt\",\
+             \"line\":2,\"message\":\"line 2 (byte 6): invalid UTF-8\"}"
+        );
+
+        let diagnostic = Diagnostic {
+            level: Level::Info,
+            kind: Kind::InputsFailed,
+            file: Option::None,
+            line: Option::None,
+            message: String::from("a\tb\\\n\u{1}"),
+        };
+        // Each line is prefixed in the human format.
+        assert_eq!(
+            diagnostic.format(ErrorFormat::Human, Compat::Rut),
+            "rut: a\tb\\\nrut: \u{1}"
+        );
+        assert_eq!(
+            diagnostic.format(ErrorFormat::Human, Compat::Gnu),
+            "cut: a\tb\\\n\u{1}"
+        );
+        assert_eq!(
+            diagnostic.format(ErrorFormat::Json, Compat::Rut),
+            "{\"level\":\"info\",\"kind\":\"inputs_failed\",\"file\":null,\"line\":null,\
+             \"message\":\"a\\tb\\\\\\n\\u0001\"}"
+        );
+    }
+
+    #[test]
+    fn kind_of() {
+        let kind = |kind| Kind::of(&io::Error::from(kind));
+        assert_eq!(kind(io::ErrorKind::NotFound), Kind::NotFound);
+        assert_eq!(
+            kind(io::ErrorKind::PermissionDenied),
+            Kind::PermissionDenied
+        );
+        assert_eq!(kind(io::ErrorKind::IsADirectory), Kind::IsDirectory);
+        assert_eq!(kind(io::ErrorKind::InvalidData), Kind::Io);
+        assert_eq!(kind(io::ErrorKind::BrokenPipe), Kind::Io);
+
+        let err = io::Error::new(
+            io::ErrorKind::InvalidData,
+            InvalidUtf8Error { line: 3, offset: 8 },
+        );
+        assert_eq!(Kind::of(&err), Kind::InvalidUtf8);
+        assert_eq!(super::invalid_utf8(&err).unwrap().line, 3);
+    }
+}
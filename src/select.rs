@@ -0,0 +1,226 @@
+//! Selecting elements of any iterator by their index, using [`Ranges`].
+
+use crate::range::{MergedRange, Ranges};
+use std::iter::FusedIterator;
+use std::slice;
+
+/// Iterator adapter which yields the elements of another iterator whose indices, numbered from
+/// zero, are selected by [`Ranges`].
+///
+/// Elements which are not selected are skipped without being returned, and the inner iterator is
+/// not advanced past the last selected element.
+///
+/// # Examples
+///
+/// ```
+/// use rut::range::Ranges;
+/// use rut::select::SelectIndices;
+///
+/// let ranges: Ranges = "1,3-".parse()?;
+/// let words = vec!["one", "two", "three", "four"];
+/// let selected: Vec<&str> = SelectIndices::new(words, &ranges).collect();
+/// assert_eq!(selected, ["one", "three", "four"]);
+/// # Ok::<(), rut::range::ParseRangesError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct SelectIndices<'a, I> {
+    inner: I,
+    /// Index of the next element of `inner`.
+    next_index: usize,
+    /// Ranges after the current one.
+    ranges: slice::Iter<'a, MergedRange>,
+    /// Range which the next selected element is in, or `None` once every range has been used or
+    /// `inner` has ended.
+    current_range: Option<MergedRange>,
+}
+
+impl<'a, I: Iterator> SelectIndices<'a, I> {
+    /// Creates an iterator over the elements of `inner` which are selected by `ranges`.
+    pub fn new<T: IntoIterator<IntoIter = I>>(inner: T, ranges: &'a Ranges) -> Self {
+        let mut ranges = ranges.iter();
+        let current_range = ranges.next().copied();
+        SelectIndices {
+            inner: inner.into_iter(),
+            next_index: 0,
+            ranges,
+            current_range,
+        }
+    }
+
+    /// Returns the number of elements selected from the indices `from` up to, but not including,
+    /// `to`, from the current range onwards.
+    fn selected_between(&self, from: usize, to: usize) -> usize {
+        self.current_range
+            .iter()
+            .chain(self.ranges.clone())
+            .map(|range| {
+                let span = range.to_slice_range(to);
+                span.end.saturating_sub(span.start.max(from))
+            })
+            .sum()
+    }
+}
+
+impl<I: Iterator> Iterator for SelectIndices<'_, I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // A loop rather than recursion, since any number of ranges may be passed over.
+        loop {
+            let range = self.current_range?;
+            if let Option::Some(end) = range.end() {
+                if self.next_index > end {
+                    self.current_range = self.ranges.next().copied();
+                    continue;
+                }
+            }
+
+            let item = if self.next_index < range.start() {
+                let skip = range.start() - self.next_index;
+                self.next_index = range.start();
+                self.inner.nth(skip)
+            } else {
+                self.inner.next()
+            };
+            self.next_index = self.next_index.saturating_add(1);
+            if item.is_none() {
+                // Do not call the inner iterator again, in case it is not fused.
+                self.current_range = Option::None;
+            }
+            return item;
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        if self.current_range.is_none() {
+            return (0, Option::Some(0));
+        }
+        let (lower, upper) = self.inner.size_hint();
+        let lower = self.selected_between(self.next_index, self.next_index.saturating_add(lower));
+        let last_range = self
+            .ranges
+            .as_slice()
+            .last()
+            .or(self.current_range.as_ref());
+        let bounded = !matches!(last_range, Option::Some(MergedRange::ToEnd(_)));
+        let upper = match upper {
+            Option::Some(upper) => Option::Some(
+                self.selected_between(self.next_index, self.next_index.saturating_add(upper)),
+            ),
+            Option::None if bounded => {
+                Option::Some(self.selected_between(self.next_index, usize::MAX))
+            }
+            Option::None => Option::None,
+        };
+        (lower, upper)
+    }
+}
+
+impl<I: Iterator> FusedIterator for SelectIndices<'_, I> {}
+
+#[cfg(test)]
+mod tests {
+    use super::SelectIndices;
+    use crate::range::{CutRange, MergedRange, Ranges};
+
+    fn select(ranges: &str, len: usize) -> Vec<usize> {
+        let ranges: Ranges = ranges.parse().unwrap();
+        SelectIndices::new(0..len, &ranges).collect()
+    }
+
+    #[test]
+    fn select_indices() {
+        assert_eq!(select("1", 5), [0]);
+        assert_eq!(select("2,4", 5), [1, 3]);
+        assert_eq!(select("2-3,5-", 7), [1, 2, 4, 5, 6]);
+        assert_eq!(select("-2,4", 5), [0, 1, 3]);
+        // Ranges beyond the end of the input select nothing.
+        assert_eq!(select("3,10-20", 5), [2]);
+        assert_eq!(select("10", 5), Vec::<usize>::new());
+        assert_eq!(select("1-", 0), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn empty_ranges() {
+        let ranges = Ranges::from_ranges(&[]);
+        let mut select = SelectIndices::new(0..5, &ranges);
+        assert_eq!(select.size_hint(), (0, Option::Some(0)));
+        assert_eq!(select.next(), Option::None);
+    }
+
+    #[test]
+    fn to_end_only() {
+        assert_eq!(select("1-", 4), [0, 1, 2, 3]);
+        assert_eq!(select("3-", 4), [2, 3]);
+        let ranges: Ranges = "3-".parse().unwrap();
+        let select = SelectIndices::new(0.., &ranges);
+        assert_eq!(select.size_hint(), (usize::MAX - 2, Option::None));
+        assert_eq!(select.take(3).collect::<Vec<usize>>(), [2, 3, 4]);
+    }
+
+    #[test]
+    fn many_tiny_ranges() {
+        // Every other element, as separate ranges, would overflow the stack if each range
+        // boundary were crossed by recursion.
+        let count = 200_000;
+        let units: Vec<CutRange> = (0..count).map(|i| CutRange::Unit(i * 2)).collect();
+        let ranges = Ranges::from_ranges(&units);
+        assert_eq!(ranges.len(), count);
+
+        let selected: Vec<usize> = SelectIndices::new(0..count * 2, &ranges).collect();
+        assert_eq!(selected.len(), count);
+        assert!(selected
+            .iter()
+            .enumerate()
+            .all(|(i, &index)| index == i * 2));
+
+        // Ranges which are all beyond the input are passed over without recursion too.
+        let far =
+            Ranges::from_merged((0..count).map(|i| MergedRange::Closed(i * 2, i * 2))).unwrap();
+        assert_eq!(SelectIndices::new(0..1, &far).collect::<Vec<usize>>(), [0]);
+    }
+
+    #[test]
+    fn size_hint() {
+        let ranges: Ranges = "2-3,5,8-".parse().unwrap();
+        let mut select = SelectIndices::new(0..10, &ranges);
+        assert_eq!(select.size_hint(), (6, Option::Some(6)));
+        select.next();
+        assert_eq!(select.size_hint(), (5, Option::Some(5)));
+        for _ in 0..3 {
+            select.next();
+        }
+        assert_eq!(select.size_hint(), (2, Option::Some(2)));
+
+        // The upper bound is known when the ranges are, even if the inner iterator is unbounded.
+        let ranges: Ranges = "2-3,5".parse().unwrap();
+        assert_eq!(
+            SelectIndices::new(0.., &ranges).size_hint(),
+            (3, Option::Some(3))
+        );
+        // Filtered inner iterators have no lower bound.
+        let select = SelectIndices::new((0..10).filter(|i| i % 2 == 0), &ranges);
+        assert_eq!(select.size_hint(), (0, Option::Some(3)));
+        assert_eq!(select.collect::<Vec<i32>>(), [2, 4, 8]);
+    }
+
+    #[test]
+    fn fused() {
+        // An iterator which returns None then Some again, to check that it is not called again.
+        let mut calls = 0;
+        let inner = std::iter::from_fn(move || {
+            calls += 1;
+            if calls == 2 {
+                Option::None
+            } else {
+                Option::Some(calls)
+            }
+        });
+        let ranges: Ranges = "1-".parse().unwrap();
+        let mut select = SelectIndices::new(inner, &ranges);
+        assert_eq!(select.next(), Option::Some(1));
+        assert_eq!(select.next(), Option::None);
+        assert_eq!(select.next(), Option::None);
+        assert_eq!(select.size_hint(), (0, Option::Some(0)));
+    }
+}
@@ -0,0 +1,290 @@
+//! Templates for writing selected fields, as an alternative to joining them with a delimiter.
+
+use crate::range::Ranges;
+use std::fmt;
+use std::io::{self, Write};
+use std::iter::Peekable;
+use std::str::{CharIndices, FromStr};
+
+/// An output template which substitutes fields into literal text, e.g. `"{2} <{1}>"`.
+///
+/// `{N}` is replaced by field `N` (numbered from one) and `{N:-default}` is replaced by field `N`, or
+/// by `default` if the record has fewer than `N` fields. `{{` and `}}` produce literal braces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Template {
+    parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Field {
+        /// Index of the field, numbered from zero.
+        index: usize,
+        default: Option<String>,
+    },
+}
+
+/// Error from parsing a template which is not valid.
+#[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseTemplateError {
+    /// A `{` without a matching `}`, at the given position, counted in characters from one.
+    UnclosedBrace(usize),
+    /// A `}` which is not part of a field or escaped as `}}`, at the given position, counted in
+    /// characters from one.
+    UnmatchedBrace(usize),
+    /// Field reference which is not a valid number, e.g. `{x}`.
+    InvalidField(String),
+    /// Field reference to field zero.
+    NumberedFromZero,
+    /// Template does not reference any fields.
+    NoFields,
+}
+
+impl fmt::Display for ParseTemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseTemplateError::UnclosedBrace(position) => {
+                write!(f, "Unclosed '{{' at position {}.", position)
+            }
+            ParseTemplateError::UnmatchedBrace(position) => write!(
+                f,
+                "Unmatched '}}' at position {}. Use '}}}}' for a literal brace.",
+                position
+            ),
+            ParseTemplateError::InvalidField(field) => {
+                write!(f, "Invalid field reference \"{{{}}}\".", field)
+            }
+            ParseTemplateError::NumberedFromZero => write!(f, "Fields are numbered from one."),
+            ParseTemplateError::NoFields => write!(f, "Template does not reference any fields."),
+        }
+    }
+}
+
+impl std::error::Error for ParseTemplateError {}
+
+impl Template {
+    /// Returns the fields referenced by this template.
+    pub fn ranges(&self) -> Ranges {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                TemplatePart::Field { index, .. } => Option::Some(*index),
+                TemplatePart::Literal(_) => Option::None,
+            })
+            .collect()
+    }
+
+    /// Returns the number of fields which must be read from a record to render this template.
+    pub(crate) fn field_count(&self) -> usize {
+        self.parts
+            .iter()
+            .filter_map(|part| match part {
+                TemplatePart::Field { index, .. } => Option::Some(index + 1),
+                TemplatePart::Literal(_) => Option::None,
+            })
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Renders the template with the fields of `line` at the specified byte spans, and writes it to
+    /// the output.
+    pub(crate) fn write<W: Write>(
+        &self,
+        writer: &mut W,
+        line: &[u8],
+        fields: &[(usize, usize)],
+    ) -> io::Result<()> {
+        for part in &self.parts {
+            match part {
+                TemplatePart::Literal(text) => writer.write_all(text.as_bytes())?,
+                TemplatePart::Field { index, default } => match (fields.get(*index), default) {
+                    (Option::Some(&(start, end)), _) => writer.write_all(&line[start..end])?,
+                    (Option::None, Option::Some(default)) => {
+                        writer.write_all(default.as_bytes())?
+                    }
+                    (Option::None, Option::None) => {}
+                },
+            }
+        }
+        Result::Ok(())
+    }
+}
+
+impl FromStr for Template {
+    type Err = ParseTemplateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut chars = s.char_indices().peekable();
+        // Positions in errors are counted in characters from one, as in range parse errors.
+        let position = |offset: usize| s[..offset].chars().count() + 1;
+
+        while let Some((offset, ch)) = chars.next() {
+            match ch {
+                '{' if chars.peek().map(|(_, ch)| *ch) == Option::Some('{') => {
+                    chars.next();
+                    literal.push('{');
+                }
+                '}' if chars.peek().map(|(_, ch)| *ch) == Option::Some('}') => {
+                    chars.next();
+                    literal.push('}');
+                }
+                '{' => {
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(parse_field(&mut chars, position(offset))?);
+                }
+                '}' => return Result::Err(ParseTemplateError::UnmatchedBrace(position(offset))),
+                _ => literal.push(ch),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+
+        let template = Template { parts };
+        if template.field_count() == 0 {
+            return Result::Err(ParseTemplateError::NoFields);
+        }
+        Result::Ok(template)
+    }
+}
+
+/// Parses a field reference, after the opening brace at `start`, up to and including the closing
+/// brace.
+fn parse_field(
+    chars: &mut Peekable<CharIndices>,
+    start: usize,
+) -> Result<TemplatePart, ParseTemplateError> {
+    let mut reference = String::new();
+    loop {
+        match chars.next() {
+            Option::Some((_, '}')) => break,
+            Option::Some((_, ch)) => reference.push(ch),
+            Option::None => return Result::Err(ParseTemplateError::UnclosedBrace(start)),
+        }
+    }
+
+    let (number, default) = match reference.find(":-") {
+        Option::Some(i) => (
+            &reference[..i],
+            Option::Some(reference[i + 2..].to_string()),
+        ),
+        Option::None => (&reference[..], Option::None),
+    };
+    if number.is_empty() || !number.chars().all(|ch| ch.is_ascii_digit()) {
+        return Result::Err(ParseTemplateError::InvalidField(reference));
+    }
+    match number.parse::<usize>() {
+        Result::Ok(0) => Result::Err(ParseTemplateError::NumberedFromZero),
+        Result::Ok(n) => Result::Ok(TemplatePart::Field {
+            index: n - 1,
+            default,
+        }),
+        Result::Err(_) => Result::Err(ParseTemplateError::InvalidField(reference)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ParseTemplateError, Template};
+    use crate::range::Ranges;
+
+    #[test]
+    fn render() {
+        assert_render("{1}", &["a", "b"], "a");
+        assert_render("{2}:{1}", &["a", "b"], "b:a");
+        assert_render(
+            "{2} <{1}> seen {3} times",
+            &["x", "y", "5"],
+            "y <x> seen 5 times",
+        );
+        assert_render("{1}{1}{1}", &["ab"], "ababab");
+
+        // Escaped braces.
+        assert_render("{{{1}}}", &["a"], "{a}");
+        assert_render("{{}} {1}", &["a"], "{} a");
+        assert_render("{1}{{2}}", &["a", "b"], "a{2}");
+
+        // Missing fields.
+        assert_render("{1}-{3}", &["a", "b"], "a-");
+        assert_render("{3:-none}", &["a", "b"], "none");
+        assert_render("{1:-none}", &["a", "b"], "a");
+        assert_render("[{2:-}]", &["a"], "[]");
+        assert_render("{2:-a:-b}", &["a"], "a:-b");
+
+        // Multi-byte characters.
+        assert_render("{2}→{1}", &["αβ", "😀"], "😀→αβ");
+        assert_render("«{4:-∅}»", &["αβ"], "«∅»");
+    }
+
+    #[test]
+    fn ranges() {
+        assert_eq!(template("{1}").ranges(), "1".parse::<Ranges>().unwrap());
+        assert_eq!(
+            template("{3}:{1}").ranges(),
+            "1,3".parse::<Ranges>().unwrap()
+        );
+        assert_eq!(
+            template("{2}{3:-x}{2}").ranges(),
+            "2-3".parse::<Ranges>().unwrap()
+        );
+
+        assert_eq!(template("{1}").field_count(), 1);
+        assert_eq!(template("{3}:{1}").field_count(), 3);
+    }
+
+    #[test]
+    fn parse_error() {
+        assert_parse_error("", ParseTemplateError::NoFields);
+        assert_parse_error("abc", ParseTemplateError::NoFields);
+        assert_parse_error("{{1}}", ParseTemplateError::NoFields);
+        assert_parse_error("{1", ParseTemplateError::UnclosedBrace(1));
+        assert_parse_error("ab {1} {2", ParseTemplateError::UnclosedBrace(8));
+        assert_parse_error("{1}}", ParseTemplateError::UnmatchedBrace(4));
+        assert_parse_error("}", ParseTemplateError::UnmatchedBrace(1));
+        // Positions are counted in characters, not bytes.
+        assert_parse_error("é→ {1", ParseTemplateError::UnclosedBrace(4));
+        assert_parse_error("{1} ü}", ParseTemplateError::UnmatchedBrace(6));
+        assert_eq!(
+            "αβ {1".parse::<Template>().unwrap_err().to_string(),
+            "Unclosed '{' at position 4."
+        );
+        assert_parse_error("{0}", ParseTemplateError::NumberedFromZero);
+        assert_parse_error("{}", ParseTemplateError::InvalidField(String::new()));
+        assert_parse_error("{x}", ParseTemplateError::InvalidField("x".to_string()));
+        assert_parse_error("{-1}", ParseTemplateError::InvalidField("-1".to_string()));
+        assert_parse_error("{1:x}", ParseTemplateError::InvalidField("1:x".to_string()));
+        assert_parse_error(
+            "{99999999999999999999999}",
+            ParseTemplateError::InvalidField("99999999999999999999999".to_string()),
+        );
+    }
+
+    fn assert_render(template_str: &str, fields: &[&str], expected: &str) {
+        let line = fields.join("\t");
+        let mut spans = Vec::new();
+        let mut start = 0;
+        for field in fields {
+            spans.push((start, start + field.len()));
+            start += field.len() + 1;
+        }
+        let mut output = Vec::new();
+        template(template_str)
+            .write(&mut output, line.as_bytes(), &spans)
+            .unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+
+    fn assert_parse_error(template: &str, expected: ParseTemplateError) {
+        assert_eq!(template.parse::<Template>(), Result::Err(expected));
+    }
+
+    fn template(template: &str) -> Template {
+        template.parse().unwrap()
+    }
+}
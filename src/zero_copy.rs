@@ -0,0 +1,209 @@
+use crate::input::{self, InputOptions};
+#[cfg(target_os = "linux")]
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, Write};
+#[cfg(target_os = "linux")]
+use std::os::unix::{
+    fs::{FileExt, FileTypeExt},
+    io::{AsFd, AsRawFd, BorrowedFd, RawFd},
+};
+#[cfg(target_os = "linux")]
+use std::ptr;
+
+/// Output which files can be copied to by the kernel, without being read into memory.
+pub(crate) trait Output: Write {
+    /// Returns the file descriptor which the output is written to, or `None` if it is not written
+    /// to one, in which case files are always read and written as usual.
+    #[cfg(target_os = "linux")]
+    fn fd(&self) -> Option<BorrowedFd<'_>> {
+        Option::None
+    }
+}
+
+impl Output for io::Stdout {
+    #[cfg(target_os = "linux")]
+    fn fd(&self) -> Option<BorrowedFd<'_>> {
+        Option::Some(self.as_fd())
+    }
+}
+
+impl Output for Vec<u8> {}
+
+impl<W: Output + ?Sized> Output for &mut W {
+    #[cfg(target_os = "linux")]
+    fn fd(&self) -> Option<BorrowedFd<'_>> {
+        (**self).fd()
+    }
+}
+
+/// Copies a file to the output as cutting it would, for a mode which copies its input unchanged,
+/// with `terminator` added if the file does not end with it. The file is copied by the kernel,
+/// using splice(2) if the output is a pipe or sendfile(2) otherwise, so that it is never read into
+/// memory. If the kernel cannot copy to the output part way through, the rest is copied as usual,
+/// so errors are returned by writing to the output or reading the file as usual.
+///
+/// Returns `false`, without writing anything, if the file should be read and cut as usual instead:
+/// if it is not a regular file, if it needs to be decompressed or decoded, if the output is not a
+/// file descriptor, or on platforms other than Linux.
+#[cfg(target_os = "linux")]
+pub(crate) fn copy_file<W: Output + ?Sized>(
+    file: &File,
+    options: &InputOptions,
+    terminator: &[u8],
+    output: &mut W,
+) -> io::Result<bool> {
+    let fd = match output.fd() {
+        Option::Some(fd) => fd.as_raw_fd(),
+        Option::None => return Result::Ok(false),
+    };
+    let metadata = file.metadata()?;
+    if !metadata.is_file() || options.encoding.is_some() {
+        return Result::Ok(false);
+    }
+    let len = metadata.len();
+    // Offsets are passed to the kernel as signed integers, which may be 32 bits.
+    if libc::off_t::try_from(len).is_err() {
+        return Result::Ok(false);
+    }
+
+    let mut head = [0; input::HEAD_LEN];
+    let n = read_at(file, &mut head, 0)?;
+    let mut offset = match input::content_start(&head[..n], len, options) {
+        Option::Some(start) => start,
+        Option::None => return Result::Ok(false),
+    };
+    // A line delimiter is added after the last record if it does not have one.
+    let mut tail = vec![0; terminator.len()];
+    let terminated = offset == len
+        || (len - offset >= terminator.len() as u64
+            && read_at(file, &mut tail, len - terminator.len() as u64)? == tail.len()
+            && tail == terminator);
+
+    // Anything already written must come first.
+    output.flush()?;
+    // SAFETY: `fd` is borrowed from the output, which is open until this function returns.
+    let pipe = File::from(unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned()?)
+        .metadata()?
+        .file_type()
+        .is_fifo();
+    while offset < len {
+        match transfer(file.as_raw_fd(), fd, &mut offset, len, pipe) {
+            // The file was truncated while it was copied.
+            Result::Ok(0) => break,
+            Result::Ok(_) => {}
+            Result::Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            // The kernel cannot copy to this output, e.g. since it was opened for appending, or it
+            // is non-blocking and full. Any other error, e.g. the disk being full, occurs again
+            // when copying as usual, where it is known whether it was reading or writing.
+            Result::Err(_) => {
+                copy_range(file, offset, len, output)?;
+                break;
+            }
+        }
+    }
+    if !terminated {
+        output.write_all(terminator)?;
+        output.flush()?;
+    }
+    Result::Ok(true)
+}
+
+/// Copies a file to the output as cutting it would, for a mode which copies its input unchanged.
+/// Only supported on Linux, so this always returns `false` and the file is cut as usual.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn copy_file<W: Output + ?Sized>(
+    _file: &File,
+    _options: &InputOptions,
+    _terminator: &[u8],
+    _output: &mut W,
+) -> io::Result<bool> {
+    Result::Ok(false)
+}
+
+/// Maximum number of bytes copied by each system call.
+#[cfg(target_os = "linux")]
+const MAX_TRANSFER: u64 = 1 << 30;
+
+/// Copies bytes of the file at `input`, from `offset` up to `end`, to `output`, and advances
+/// `offset` past the bytes which were copied. Returns the number of bytes copied, which may be
+/// fewer than requested.
+#[cfg(target_os = "linux")]
+fn transfer(
+    input: RawFd,
+    output: RawFd,
+    offset: &mut u64,
+    end: u64,
+    pipe: bool,
+) -> io::Result<usize> {
+    let count = (end - *offset).min(MAX_TRANSFER) as usize;
+    // Both offsets were checked to fit by `copy_file`. The kernel updates its copy, rather than
+    // the position of the file.
+    // SAFETY: both descriptors are open for the duration of the call, and the only pointer passed
+    // is to a local offset.
+    let n = if pipe {
+        let mut position = *offset as libc::loff_t;
+        unsafe { libc::splice(input, &mut position, output, ptr::null_mut(), count, 0) }
+    } else {
+        let mut position = *offset as libc::off_t;
+        unsafe { libc::sendfile(output, input, &mut position, count) }
+    };
+    if n < 0 {
+        return Result::Err(io::Error::last_os_error());
+    }
+    *offset += n as u64;
+    Result::Ok(n as usize)
+}
+
+/// Copies the bytes of the file from `start` to `end` to the output by reading them as usual.
+#[cfg(target_os = "linux")]
+fn copy_range<W: Write + ?Sized>(
+    file: &File,
+    start: u64,
+    end: u64,
+    output: &mut W,
+) -> io::Result<()> {
+    let mut buf = vec![0; 64 * 1024];
+    let mut offset = start;
+    while offset < end {
+        let len = buf.len().min((end - offset) as usize);
+        let n = read_at(file, &mut buf[..len], offset)?;
+        if n == 0 {
+            break;
+        }
+        output.write_all(&buf[..n])?;
+        offset += n as u64;
+    }
+    Result::Ok(())
+}
+
+/// Reads into `buf` from `offset`, until it is full or the end of the file. Returns the number of
+/// bytes read.
+#[cfg(target_os = "linux")]
+fn read_at(file: &File, buf: &mut [u8], offset: u64) -> io::Result<usize> {
+    let mut n = 0;
+    while n < buf.len() {
+        match file.read_at(&mut buf[n..], offset + n as u64) {
+            Result::Ok(0) => break,
+            Result::Ok(read) => n += read,
+            Result::Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Result::Err(err) => return Result::Err(err),
+        }
+    }
+    Result::Ok(n)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::copy_file;
+    use crate::input::InputOptions;
+    use std::fs::File;
+
+    #[test]
+    fn vec_is_not_copied_to() {
+        let file = File::open("tests/files/ascii.txt").unwrap();
+        let mut output = Vec::new();
+        assert!(!copy_file(&file, &InputOptions::default(), b"\n", &mut output).unwrap());
+        assert!(output.is_empty());
+    }
+}
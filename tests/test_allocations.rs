@@ -0,0 +1,88 @@
+// Counts the allocations made while cutting many small inputs, to check that buffers reused with
+// `Mode::cut_with` are not allocated again for each input.
+
+use rut::cut::{Buffers, Mode, Options, RunState};
+use rut::template::Template;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::io;
+
+/// Allocator which counts the allocations made by each thread, so that allocations by other
+/// tests, or by the test harness, are not counted.
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count_allocation() {
+    // The count cannot be accessed while the thread is exiting.
+    let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count_allocation();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count_allocation();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+const FILES: usize = 1000;
+
+/// Returns the number of allocations made by `f`.
+fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[test]
+fn buffers_are_reused_for_each_input() {
+    let mut template_mode = Mode::fields(',', "1-".parse().unwrap());
+    if let Mode::FieldsChar(_, _, field_options) = &mut template_mode {
+        field_options.template = Option::Some("{2}={1}".parse::<Template>().unwrap());
+    }
+    let modes = [
+        Mode::bytes("2-4".parse().unwrap()),
+        Mode::characters("1,3-".parse().unwrap()),
+        Mode::fields(',', "1,3".parse().unwrap()),
+        template_mode,
+    ];
+    let input = "α,b,c\nd,e,f,g\nhij\n".as_bytes();
+    let options = Options::default();
+
+    for mode in &modes {
+        // The first input allocates the buffers, which are reused for the others.
+        let mut buffers = Buffers::default();
+        let cut = |buffers: &mut Buffers| {
+            let mut state = RunState::default();
+            mode.cut_with(
+                &mut &input[..],
+                &mut io::sink(),
+                &options,
+                &mut state,
+                buffers,
+            )
+            .unwrap()
+        };
+        cut(&mut buffers);
+        let reused = allocations(|| (1..FILES).for_each(|_| cut(&mut buffers)));
+        assert_eq!(reused, 0, "{:?}", mode);
+
+        // Without reusing buffers, each input allocates them again.
+        let new = allocations(|| (1..FILES).for_each(|_| cut(&mut Buffers::default())));
+        assert!(new >= FILES - 1, "{:?}: {} allocations", mode, new);
+    }
+}
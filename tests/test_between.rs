@@ -0,0 +1,94 @@
+pub mod util;
+
+#[test]
+fn first_occurrence() {
+    util::test_command()
+        .options(&["--between", "user=", "&"])
+        .build()
+        .write_stdin("id=1&user=amy&user=bob&\nuser=cy&\nid=2\n")
+        .assert()
+        .code(0)
+        .stdout("amy\ncy\n\n");
+}
+
+#[test]
+fn missing_end_marker() {
+    // The line is written empty, or suppressed with -s.
+    util::test_command()
+        .options(&["--between", "user=", "&"])
+        .build()
+        .write_stdin("id=1&user=amy\n&user=bob&\n")
+        .assert()
+        .code(0)
+        .stdout("\nbob\n");
+
+    util::test_command()
+        .options(&["--between", "user=", "&", "-s"])
+        .build()
+        .write_stdin("id=1&user=amy\n&user=bob&\n")
+        .assert()
+        .code(0)
+        .stdout("bob\n");
+
+    // An end marker before the start marker does not end it.
+    util::test_command()
+        .options(&["--between", "(", ")", "-s"])
+        .build()
+        .write_stdin(") a (b\n")
+        .assert()
+        .code(0)
+        .stdout("");
+}
+
+#[test]
+fn overlapping_markers() {
+    // The end marker is searched for after the start marker.
+    util::test_command()
+        .options(&["--between", "aba", "ab"])
+        .build()
+        .write_stdin("ababab\nabab\nabaab\n")
+        .assert()
+        .code(0)
+        .stdout("b\n\n\n");
+
+    util::test_command()
+        .options(&["--between", "'", "'", "--all-occurrences", "-o,"])
+        .build()
+        .write_stdin("'a' 'b' 'c\n'''\n")
+        .assert()
+        .code(0)
+        .stdout("a,b\n\n");
+}
+
+#[test]
+fn multi_byte_markers() {
+    util::test_command()
+        .options(&["--between", "«", "»", "--all-occurrences"])
+        .build()
+        .write_stdin("«αβ» and «😀»\n«»\n")
+        .assert()
+        .code(0)
+        .stdout("αβ\t😀\n\n");
+
+    util::test_command()
+        .options(&["--between", "→", "←"])
+        .build()
+        .write_stdin("x→é←→y←\n")
+        .assert()
+        .code(0)
+        .stdout("é\n");
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn regex_markers() {
+    util::test_command()
+        .options(&["--between-regex", "[a-z]+=", "[&;]|$"])
+        .option("--all-occurrences")
+        .option("-o,")
+        .build()
+        .write_stdin("a=1&bb=2;c=3\nnone\n")
+        .assert()
+        .code(0)
+        .stdout("1,2,3\n\n");
+}
@@ -0,0 +1,69 @@
+pub mod util;
+
+#[test]
+fn bom_characters() {
+    util::test_command()
+        .option("-c1-2")
+        .file("tests/files/utf8-bom.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("id\n1,\n");
+}
+
+#[test]
+fn bom_fields() {
+    util::test_command()
+        .option("-f1")
+        .option("-d,")
+        .file("tests/files/utf8-bom.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("id\n1\n");
+
+    util::test_command()
+        .option("-f2,1")
+        .option("-d,")
+        .option("--header-out=first,second")
+        .file("tests/files/utf8-bom.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("first,second\nid,name\n1,α\n");
+}
+
+#[test]
+fn bom_bytes() {
+    util::test_command()
+        .option("-b1-2")
+        .file("tests/files/utf8-bom.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("id\n1,\n");
+
+    util::test_command()
+        .option("-b1-5")
+        .option("--keep-bom")
+        .file("tests/files/utf8-bom.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("\u{feff}id\n1,α\n");
+}
+
+#[test]
+fn bom_each_file() {
+    util::test_command()
+        .option("-f1")
+        .option("-d,")
+        .file("tests/files/utf8-bom.txt")
+        .file("-")
+        .file("tests/files/utf8-bom.txt")
+        .build()
+        .write_stdin("\u{feff}x,y\n")
+        .assert()
+        .code(0)
+        .stdout("id\n1\nx\nid\n1\n");
+}
@@ -0,0 +1,86 @@
+pub mod util;
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+/// Runs rut with the input on stdin, reads the first line of its output and then closes the output,
+/// as `head -1` would. Returns the first line, the exit code and what was written to stderr.
+fn cut_and_close(options: &[&str], input: &[u8]) -> (String, Option<i32>, String) {
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("rut"))
+        .args(options)
+        .env_remove("RUT_CONFIG")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let mut stdin = child.stdin.take().unwrap();
+    let input = input.to_vec();
+    // rut stops reading once its output is closed, so writing the rest of the input may fail.
+    let writer = std::thread::spawn(move || {
+        let _ = stdin.write_all(&input);
+    });
+
+    let mut first = String::new();
+    BufReader::new(child.stdout.take().unwrap())
+        .read_line(&mut first)
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+    writer.join().unwrap();
+    (
+        first,
+        output.status.code(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+/// Returns enough lines that rut is still writing them after the first one is read.
+fn many_lines() -> Vec<u8> {
+    (0..200_000)
+        .flat_map(|i| format!("{},{}\n", i, "x".repeat(20)).into_bytes())
+        .collect()
+}
+
+#[test]
+fn closed_output_is_not_an_error() {
+    let input = many_lines();
+    for options in [
+        &["-f1", "-d,"][..],
+        &["-b1-"],
+        &["-c1-3"],
+        &["-f1", "-r,"],
+        &["-f1", "-d,", "--line-buffered"],
+    ] {
+        let (first, code, stderr) = cut_and_close(options, &input);
+        assert!(first.starts_with('0'), "{:?}", options);
+        assert_eq!(code, Option::Some(0), "{:?}", options);
+        assert_eq!(stderr, "", "{:?}", options);
+    }
+}
+
+#[test]
+fn closed_output_stops_at_the_current_file() {
+    let dir = util::TempDir::new("broken-pipe");
+    let path = dir.write("input.txt", many_lines());
+    let path = path.to_str().unwrap();
+
+    // Later files, including missing ones, are not read.
+    for options in [
+        &["-f1", "-d,", path, "does-not-exist.txt"][..],
+        &["-f1", "-d,", "-j2", path, path, "does-not-exist.txt"],
+        &[
+            "-b1-",
+            "--keep-cr",
+            "--zero-copy",
+            path,
+            "does-not-exist.txt",
+        ],
+    ] {
+        let (first, code, stderr) = cut_and_close(options, b"");
+        assert!(first.starts_with('0'), "{:?}", options);
+        assert_eq!(code, Option::Some(0), "{:?}", options);
+        assert_eq!(stderr, "", "{:?}", options);
+    }
+}
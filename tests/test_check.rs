@@ -0,0 +1,52 @@
+pub mod util;
+
+#[test]
+fn check_valid() {
+    util::test_command()
+        .options(&["-f2", "-d,", "--check"])
+        .file("tests/files/ascii.txt")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_invalid_range() {
+    util::test_command()
+        .options(&["-f2-1", "--check"])
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("");
+}
+
+#[test]
+fn check_missing_file() {
+    util::test_command()
+        .options(&["-b1", "--check"])
+        .file("tests/files/ascii.txt")
+        .file("tests/files/does-not-exist.txt")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "rut: tests/files/does-not-exist.txt: ",
+        ));
+}
+
+#[test]
+fn check_does_not_read_stdin() {
+    util::test_command()
+        .options(&["-b1", "--check"])
+        .build()
+        .write_stdin("abc\n")
+        .assert()
+        .code(0)
+        .stdout("");
+}
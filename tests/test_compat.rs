@@ -0,0 +1,187 @@
+pub mod util;
+
+// Expected output was recorded from GNU coreutils cut 9.1, run as "cut".
+
+const TRY_HELP: &str = "Try 'cut --help' for more information.\n";
+
+fn assert_gnu_error(options: &[&str], message: &str) {
+    util::test_command()
+        .option("--compat=gnu")
+        .options(options)
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(format!("cut: {}\n{}", message, TRY_HELP));
+}
+
+#[test]
+fn delimiter_without_fields() {
+    let message = "an input delimiter may be specified only when operating on fields";
+    assert_gnu_error(&["-d,", "-b1"], message);
+    assert_gnu_error(&["-d,", "-c1"], message);
+}
+
+#[test]
+fn only_delimited_without_fields() {
+    let message = "suppressing non-delimited lines makes sense\n\tonly when operating on fields";
+    assert_gnu_error(&["-s", "-b1"], message);
+    assert_gnu_error(&["-s", "-c1"], message);
+}
+
+#[test]
+fn lists() {
+    assert_gnu_error(
+        &[],
+        "you must specify a list of bytes, characters, or fields",
+    );
+    assert_gnu_error(&["-b1", "-f1"], "only one list may be specified");
+    assert_gnu_error(&["-b1", "-b2"], "only one list may be specified");
+    assert_gnu_error(&["-f1", "-f2"], "only one list may be specified");
+}
+
+#[test]
+fn ranges() {
+    assert_gnu_error(&["-b0"], "byte/character positions are numbered from 1");
+    assert_gnu_error(&["-c1,,3"], "byte/character positions are numbered from 1");
+    assert_gnu_error(&["-f0"], "fields are numbered from 1");
+    assert_gnu_error(&["-f1,,3"], "fields are numbered from 1");
+    assert_gnu_error(&["-b3-1"], "invalid decreasing range");
+    assert_gnu_error(&["-bx"], "invalid byte/character position 'x'");
+    assert_gnu_error(&["-c1,2x3"], "invalid byte/character position 'x3'");
+    assert_gnu_error(&["-f1x"], "invalid field value 'x'");
+    assert_gnu_error(&["-b-"], "invalid range with no endpoint: -");
+    assert_gnu_error(&["-b1-2-"], "invalid byte or character range");
+    assert_gnu_error(&["-f--5"], "invalid field range");
+    assert_gnu_error(
+        &["-b99999999999999999999999"],
+        "byte/character offset '99999999999999999999999' is too large",
+    );
+    assert_gnu_error(
+        &["-f99999999999999999999"],
+        "field number '99999999999999999999' is too large",
+    );
+}
+
+#[test]
+fn delimiter() {
+    let message = "the delimiter must be a single character";
+    assert_gnu_error(&["-d", "ab", "-f1"], message);
+    // Escape sequences are not replaced.
+    assert_gnu_error(&["-d", "\\t", "-f1"], message);
+}
+
+#[test]
+fn unknown_option() {
+    assert_gnu_error(&["-b1", "--bogus"], "unrecognized option '--bogus'");
+    assert_gnu_error(&["-b1", "-x"], "invalid option -- 'x'");
+}
+
+#[test]
+fn missing_file() {
+    // Other files are still cut, and there is no summary of the failures.
+    util::test_command()
+        .options(&["--compat=gnu", "-b1"])
+        .file("tests/files/does-not-exist.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("a\na\na\na\n")
+        .stderr("cut: tests/files/does-not-exist.txt: No such file or directory\n");
+}
+
+#[test]
+fn output_delimiter_is_literal() {
+    util::test_command()
+        .options(&["--compat=gnu", "-d,", "-f1,2", "--output-delimiter=\\t"])
+        .build()
+        .write_stdin("a,b\n")
+        .assert()
+        .success()
+        .stdout("a\\tb\n");
+}
+
+#[test]
+fn input_bytes_are_kept() {
+    // The byte order mark and the carriage return of a CRLF line ending are not removed.
+    util::test_command()
+        .options(&["--compat=gnu", "-d,", "-f2"])
+        .build()
+        .write_stdin(&b"\xef\xbb\xbfa,b\r\n"[..])
+        .assert()
+        .success()
+        .stdout("b\r\n");
+    util::test_command()
+        .options(&["--compat=gnu", "-b1-"])
+        .build()
+        .write_stdin(&b"\xef\xbb\xbfab\r\n"[..])
+        .assert()
+        .success()
+        .stdout(&b"\xef\xbb\xbfab\r\n"[..]);
+
+    // Compressed input is cut as it is, and given a line delimiter at the end.
+    let mut compressed = std::fs::read("tests/files/ascii.txt.gz").unwrap();
+    compressed.push(b'\n');
+    util::test_command()
+        .options(&["--compat=gnu", "-b1-"])
+        .file("tests/files/ascii.txt.gz")
+        .build()
+        .assert()
+        .success()
+        .stdout(compressed);
+}
+
+#[test]
+fn rut_is_default() {
+    util::test_command()
+        .options(&["-d", "ab", "-f1"])
+        .build()
+        .assert()
+        .code(1)
+        .stderr(predicates::str::starts_with(
+            "rut: '--delimiter <delim>' must be a single character",
+        ));
+}
+
+#[cfg(unix)]
+#[test]
+fn run_as_cut() {
+    use std::os::unix::process::CommandExt;
+
+    let mut command = std::process::Command::new(assert_cmd::cargo::cargo_bin("rut"));
+    command
+        .arg0("cut")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist");
+    assert_cmd::Command::from_std(command)
+        .args(["-d", "ab", "-f1"])
+        .assert()
+        .code(1)
+        .stderr(format!(
+            "cut: the delimiter must be a single character\n{}",
+            TRY_HELP
+        ));
+
+    let mut command = std::process::Command::new(assert_cmd::cargo::cargo_bin("rut"));
+    command
+        .arg0("cut")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist");
+    assert_cmd::Command::from_std(command)
+        .arg("-b1-")
+        .write_stdin("a\r\n")
+        .assert()
+        .success()
+        .stdout("a\r\n");
+
+    // --compat overrides the name of the program.
+    let mut command = std::process::Command::new(assert_cmd::cargo::cargo_bin("rut"));
+    command
+        .arg0("cut")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist");
+    assert_cmd::Command::from_std(command)
+        .args(["--compat=rut", "-b0"])
+        .assert()
+        .code(1)
+        .stderr(predicates::str::starts_with("rut: "));
+}
@@ -0,0 +1,54 @@
+pub mod util;
+
+/// Asserts that the completion script includes the long options. Fish lists them without the
+/// leading dashes, e.g. "-l regex-delimiter".
+fn assert_completions(shell: &str) {
+    util::test_command()
+        .option("--completions")
+        .option(shell)
+        .build()
+        .assert()
+        .success()
+        .stdout(predicates::str::contains("regex-delimiter"))
+        .stderr("");
+}
+
+#[test]
+fn bash() {
+    assert_completions("bash");
+}
+
+#[test]
+fn zsh() {
+    assert_completions("zsh");
+}
+
+#[test]
+fn fish() {
+    assert_completions("fish");
+}
+
+#[test]
+fn powershell() {
+    assert_completions("powershell");
+}
+
+#[test]
+fn unknown_shell() {
+    util::test_command()
+        .option("--completions=tcsh")
+        .build()
+        .assert()
+        .failure()
+        .stdout("");
+}
+
+#[test]
+fn with_other_arguments() {
+    util::test_command()
+        .options(&["--completions=bash", "-b1"])
+        .build()
+        .assert()
+        .failure()
+        .stdout("");
+}
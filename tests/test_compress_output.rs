@@ -0,0 +1,113 @@
+pub mod util;
+
+use flate2::read::GzDecoder;
+use std::fs::File;
+use std::io::Read;
+use std::process::{Command, Stdio};
+
+/// Options to compare, covering each mode, --jobs, and options which would otherwise copy files
+/// without reading them.
+const OPTIONS: &[&[&str]] = &[
+    &["-b2-5"],
+    &["-c3,1"],
+    &["-f2,4", "-d_"],
+    &["-f1-", "-j2"],
+    &["-b1-", "--keep-cr", "--zero-copy"],
+    &["-b1", "--line-buffered"],
+];
+
+const FILES: &[&str] = &["tests/files/ascii.txt", "tests/files/ascii.txt.gz"];
+
+/// Runs rut with stdout redirected to a new .gz file, and returns the decompressed contents of the
+/// file.
+fn cut_to_gz(name: &str, options: &[&str]) -> Vec<u8> {
+    let dir = util::TempDir::new(name);
+    let path = dir.path("output.gz");
+    let status = Command::new(assert_cmd::cargo::cargo_bin("rut"))
+        .args(options)
+        .args(FILES)
+        .env_remove("RUT_CONFIG")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist")
+        .stdin(Stdio::null())
+        .stdout(File::create(&path).unwrap())
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let mut contents = Vec::new();
+    GzDecoder::new(File::open(&path).unwrap())
+        .read_to_end(&mut contents)
+        .unwrap();
+    contents
+}
+
+#[test]
+fn same_output_as_uncompressed() {
+    for options in OPTIONS {
+        let mut command = util::test_command().options(options);
+        for file in FILES {
+            command = command.file(file);
+        }
+        let expected = command.build().output().unwrap().stdout;
+        assert!(!expected.is_empty());
+
+        for level in &[
+            "--compress-output",
+            "--compress-output=0",
+            "--compress-output=9",
+        ] {
+            let compressed = cut_to_gz("compress-output", &[*options, &[*level]].concat());
+            assert_eq!(compressed, expected, "{:?} {}", options, level);
+        }
+    }
+}
+
+#[test]
+fn failed_input() {
+    // The output of the other inputs is still complete.
+    let output = util::test_command()
+        .options(&["-f2", "-d,", "--compress-output"])
+        .file("-")
+        .file("tests/does-not-exist")
+        .build()
+        .write_stdin("a,b\nc,d\n")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Option::Some(1));
+    let mut stdout = String::new();
+    GzDecoder::new(&output.stdout[..])
+        .read_to_string(&mut stdout)
+        .unwrap();
+    assert_eq!(stdout, "b\nd\n");
+}
+
+#[test]
+fn decompressed_as_input() {
+    let compressed = util::test_command()
+        .options(&["-c2-", "--compress-output"])
+        .build()
+        .write_stdin("abc\ndef\n")
+        .output()
+        .unwrap()
+        .stdout;
+    util::test_command()
+        .option("-b1-")
+        .build()
+        .write_stdin(compressed)
+        .assert()
+        .code(0)
+        .stdout("bc\nef\n");
+}
+
+#[test]
+fn invalid_level() {
+    for level in &["10", "-1", "x", ""] {
+        util::test_command()
+            .options(&["-b1", &format!("--compress-output={}", level)])
+            .build()
+            .write_stdin("a\n")
+            .assert()
+            .code(1)
+            .stdout("");
+    }
+}
@@ -0,0 +1,143 @@
+pub mod util;
+
+use std::fs;
+
+fn cut_with_config(config: &str) -> assert_cmd::assert::Assert {
+    util::test_command()
+        .options(&["-f1,2", "--config", config])
+        .build()
+        .write_stdin("a,b\n")
+        .assert()
+}
+
+#[test]
+fn good() {
+    cut_with_config("tests/config/good.toml")
+        .success()
+        .stdout("a|b\n")
+        .stderr("");
+}
+
+#[test]
+fn options_and_environment_take_precedence() {
+    util::test_command()
+        .options(&["-f1,2", "-d:", "-o;", "--config=tests/config/good.toml"])
+        .build()
+        .write_stdin("a:b\n")
+        .assert()
+        .success()
+        .stdout("a;b\n");
+    util::test_command()
+        .options(&["-f1,2", "--config=tests/config/good.toml"])
+        .build()
+        .env("RUT_OUTPUT_DELIMITER", "+")
+        .write_stdin("a,b\n")
+        .assert()
+        .success()
+        .stdout("a+b\n");
+}
+
+#[test]
+fn unknown_key() {
+    util::test_command()
+        .options(&["-f1,2", "-d,", "--config", "tests/config/unknown-key.toml"])
+        .build()
+        .write_stdin("a,b\n")
+        .assert()
+        .success()
+        .stdout("a|b\n")
+        .stderr("rut: tests/config/unknown-key.toml: line 2: unknown key \"color\", ignored\n");
+}
+
+#[test]
+fn unknown_values() {
+    util::test_command()
+        .options(&[
+            "-f1,2",
+            "-d,",
+            "--config",
+            "tests/config/unknown-values.toml",
+        ])
+        .build()
+        .write_stdin("a,b\n")
+        .assert()
+        .success()
+        .stdout("a|b\n")
+        .stderr(
+            "rut: tests/config/unknown-values.toml: line 2: unknown key \"columns\", ignored\n\
+             rut: tests/config/unknown-values.toml: line 3: unknown table \"display\", ignored\n",
+        );
+}
+
+#[test]
+fn malformed() {
+    cut_with_config("tests/config/malformed.toml")
+        .failure()
+        .stdout("")
+        .stderr("rut: tests/config/malformed.toml: line 3: unterminated string\n");
+}
+
+#[test]
+fn invalid_value() {
+    cut_with_config("tests/config/invalid-delimiter.toml")
+        .failure()
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "(from config file tests/config/invalid-delimiter.toml)",
+        ));
+}
+
+#[test]
+fn missing() {
+    cut_with_config("tests/config/does-not-exist.toml")
+        .failure()
+        .stderr(predicates::str::starts_with(
+            "rut: tests/config/does-not-exist.toml: ",
+        ));
+}
+
+#[test]
+fn environment_variable() {
+    util::test_command()
+        .options(&["-f1,2"])
+        .build()
+        .env("RUT_CONFIG", "tests/config/good.toml")
+        .write_stdin("a,b\n")
+        .assert()
+        .success()
+        .stdout("a|b\n");
+}
+
+#[test]
+fn no_config() {
+    util::test_command()
+        .options(&["-f1,2", "-d,", "--no-config"])
+        .build()
+        .env("RUT_CONFIG", "tests/config/malformed.toml")
+        .write_stdin("a,b\n")
+        .assert()
+        .success()
+        .stdout("a,b\n");
+    util::test_command()
+        .options(&["-f1", "--no-config", "--config=tests/config/good.toml"])
+        .build()
+        .assert()
+        .failure();
+}
+
+#[test]
+fn default_path() {
+    let dir = util::TempDir::new("config");
+    let config = fs::read("tests/config/good.toml").unwrap();
+    dir.write("rut/config.toml", config);
+
+    util::test_command()
+        .options(&["-f1,2"])
+        .build()
+        .env("XDG_CONFIG_HOME", dir.root())
+        .env("APPDATA", dir.root())
+        .write_stdin("a,b\n")
+        .assert()
+        .success()
+        .stdout("a|b\n");
+}
@@ -0,0 +1,78 @@
+pub mod util;
+
+#[test]
+fn crlf_bytes() {
+    util::test_command()
+        .option("-b5-")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("def,ghi\n,γδ\nz\n");
+
+    util::test_command()
+        .option("-b5-")
+        .option("--keep-cr")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("def,ghi\r\n,γδ\r\nz\r\n");
+}
+
+#[test]
+fn crlf_characters() {
+    util::test_command()
+        .option("-c1-3")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abc\nαβ,\nx\ry\n");
+}
+
+#[test]
+fn crlf_fields() {
+    util::test_command()
+        .option("-f2-")
+        .option("-d,")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("def,ghi\nγδ\nz\n");
+
+    #[cfg(feature = "regex")]
+    util::test_command()
+        .option("-f1")
+        .option("-r,")
+        .option("--keep-cr")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abc\nαβ\nx\ry\n");
+
+    util::test_command()
+        .option("-f3")
+        .option("-d,")
+        .option("--keep-cr")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("ghi\r\n\n\n");
+}
+
+#[test]
+fn crlf_zero_terminated() {
+    util::test_command()
+        .option("-f1")
+        .option("-d,")
+        .option("-z")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abc\0");
+}
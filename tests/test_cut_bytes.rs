@@ -113,6 +113,24 @@ fn ranges_complement() {
         );
 }
 
+#[test]
+fn ranges_exclude() {
+    util::test_command()
+        .option("-b5-")
+        .option("-e9-12")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout(
+            "efghm
+γδηθικλμν
+βcdefεζ
+😁😃😄😅😆😇😈
+",
+        );
+}
+
 #[test]
 fn empty_ranges() {
     util::test_command()
@@ -265,4 +283,66 @@ abα
 😀
 ",
         );
-}
\ No newline at end of file
+}
+
+#[test]
+fn hex_binary_bytes() {
+    util::test_command()
+        .option("-b1-")
+        .option("--hex")
+        .file("tests/files/bytes.bin")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("0102030405\n0b0c0d0e0f\n1516171819\n1f20212223\n");
+
+    util::test_command()
+        .option("-b1,3,5")
+        .option("--hex")
+        .option("--hex-format=spaced")
+        .file("tests/files/bytes.bin")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("01 03 05\n0b 0d 0f\n15 17 19\n1f 21 23\n");
+
+    util::test_command()
+        .option("-b2-3")
+        .option("--hex")
+        .option("--hex-format=prefixed")
+        .file("tests/files/bytes.bin")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("0x02 0x03\n0x0c 0x0d\n0x16 0x17\n0x20 0x21\n");
+}
+
+#[test]
+fn base64_binary_bytes() {
+    util::test_command()
+        .option("-b1-")
+        .option("--base64")
+        .file("tests/files/bytes.bin")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("AQIDBAU=\nCwwNDg8=\nFRYXGBk=\nHyAhIiM=\n");
+
+    util::test_command()
+        .option("-b2-")
+        .option("--base64=url")
+        .file("tests/files/bytes.bin")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("AgMEBQ\nDA0ODw\nFhcYGQ\nICEiIw\n");
+
+    util::test_command()
+        .option("-b10-")
+        .option("--base64")
+        .file("tests/files/bytes.bin")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("\n\n\n\n");
+}
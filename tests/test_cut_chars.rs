@@ -86,6 +86,24 @@ acdγ
         );
 }
 
+#[test]
+fn ranges_exclude() {
+    util::test_command()
+        .option("-c1-7")
+        .option("--exclude=2-4")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout(
+            "aefg
+αεζη
+acdγ
+😀😄😅😆
+",
+        );
+}
+
 #[test]
 fn empty_ranges() {
     util::test_command()
@@ -112,6 +112,39 @@ abαβcdγδefεζ
 }
 
 #[test]
+fn escaped_char_delimiter() {
+    util::test_command()
+        .option("-f1,3")
+        .option("-d\\t")
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("a\tc\nd\tf\n");
+
+    util::test_command()
+        .option("-f2")
+        .option("-d\\x09")
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("b\ne\n");
+
+    util::test_command()
+        .option("-f2")
+        .option("-d\\q")
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains(
+            "Invalid escape sequence \"\\q\".",
+        ));
+}
+
+#[test]
+#[cfg(feature = "regex")]
 fn ascii_regex_delimiter() {
     util::test_command()
         .option("-f2,4")
@@ -130,6 +163,7 @@ _b_c_d_\t_j_k_l_m
 }
 
 #[test]
+#[cfg(feature = "regex")]
 fn utf8_regex_delimiter() {
     util::test_command()
         .option("-f2")
@@ -148,6 +182,26 @@ fn utf8_regex_delimiter() {
 }
 
 #[test]
+fn repeated_fields() {
+    util::test_command()
+        .options(&["-f3", "-f1"])
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("a\tc\nd\tf\n");
+
+    util::test_command()
+        .options(&["-f1", "-f3", "--complement"])
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("b\ne\n");
+}
+
+#[test]
+#[cfg(feature = "regex")]
 fn ranges_complement() {
     util::test_command()
         .option("-f3-6,9-12")
@@ -167,6 +221,69 @@ a\tb\tg\th\tm
 }
 
 #[test]
+#[cfg(feature = "regex")]
+fn ranges_exclude() {
+    // Without -f, every field is selected except those excluded.
+    util::test_command()
+        .option("-e3-6,9-12")
+        .option("--only-delimited")
+        .option("--regex-delimiter=[ _:]")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout(
+            "a\tb\tg\th\tm
+a\tb\tg\th\tm
+a\tb\tg\th\tm
+",
+        );
+
+    util::test_command()
+        .options(&["-f1-10", "-e4,6", "-e9-", "-d,"])
+        .build()
+        .write_stdin("1,2,3,4,5,6,7,8,9,10,11\n1,2,3,4\n")
+        .assert()
+        .code(0)
+        .stdout("1,2,3,5,7,8\n1,2,3\n");
+}
+
+#[test]
+fn ranges_exclude_after_complement() {
+    util::test_command()
+        .options(&["-f2", "--complement", "-e4"])
+        .build()
+        .write_stdin("a\tb\tc\td\te\n")
+        .assert()
+        .code(0)
+        .stdout("a\tc\te\n");
+}
+
+#[test]
+fn ranges_complement_within() {
+    util::test_command()
+        .option("-f2")
+        .option("--complement=4")
+        .build()
+        .write_stdin("a\tb\tc\td\te\nf\tg\nh\n")
+        .assert()
+        .code(0)
+        .stdout("a\tc\td\nf\nh\n");
+
+    // The bound is of the fields to select from, so nothing is selected past it.
+    util::test_command()
+        .option("-f1,3-")
+        .option("--complement=2")
+        .option("-s")
+        .build()
+        .write_stdin("a\tb\tc\nd\te\n")
+        .assert()
+        .code(0)
+        .stdout("b\ne\n");
+}
+
+#[test]
+#[cfg(feature = "regex")]
 fn empty_ranges() {
     util::test_command()
         .option("-f1-")
@@ -231,6 +348,7 @@ fn output_delimiter_and_suppress() {
         .code(0)
         .stdout("e___j\n");
 
+    #[cfg(feature = "regex")]
     util::test_command()
         .option("-f2,4,6")
         .option(r"-r_\w_")
@@ -242,6 +360,7 @@ fn output_delimiter_and_suppress() {
         .code(0)
         .stdout("c#g#k\n");
 
+    #[cfg(feature = "regex")]
     util::test_command()
         .option("-f2,4,6")
         .option(r"-r\s\w\s")
@@ -254,6 +373,54 @@ fn output_delimiter_and_suppress() {
         .stdout("c***g***k\n");
 }
 
+#[test]
+fn escaped_output_delimiter() {
+    // NUL separated fields, e.g. for xargs -0.
+    let output = util::test_command()
+        .option("-f1-3")
+        .option(r"-d\t")
+        .option(r"-o\0")
+        .file("tests/files/tabs.tsv")
+        .build()
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Option::Some(0));
+    assert_eq!(output.stdout, b"a\0b\0c\nd\0e\0f\n");
+
+    // One field per line. Each line still ends with its own line delimiter.
+    util::test_command()
+        .option("-f1,3")
+        .option(r"-d\t")
+        .option(r"-o\n")
+        .option("--allow-ambiguous-output")
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("a\nc\nd\nf\n");
+
+    util::test_command()
+        .option("-f1,3")
+        .option(r"-d\t")
+        .option(r"-o\x2c\x20")
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("a, c\nd, f\n");
+
+    // Byte escapes need not form valid UTF-8.
+    util::test_command()
+        .option("-f1,3")
+        .option(r"-d\t")
+        .option(r"-o\xff")
+        .file("tests/files/tabs.tsv")
+        .build()
+        .assert()
+        .code(0)
+        .stdout(&b"a\xffc\nd\xfff\n"[..]);
+}
+
 #[test]
 fn from_stdin() {
     util::test_command()
@@ -266,6 +433,7 @@ fn from_stdin() {
 }
 
 #[test]
+#[cfg(feature = "regex")]
 fn file_and_stdin() {
     util::test_command()
         .option("-f2,4")
@@ -287,6 +455,7 @@ y\tw
 }
 
 #[test]
+#[cfg(feature = "regex")]
 fn multiple_files() {
     util::test_command()
         .option("-f2-3")
@@ -320,3 +489,169 @@ fn missing_file() {
         .code(1)
         .stdout("c_f_i_l\n");
 }
+
+#[test]
+fn byte_offsets() {
+    util::test_command()
+        .option("-f1-")
+        .option("-dγ")
+        .option("-s")
+        .option("-o:")
+        .option("--byte-offsets")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("0-4:6-26\n0-8:10-18\n");
+
+    #[cfg(feature = "regex")]
+    util::test_command()
+        .option("-f2")
+        .option("-r[βδ]")
+        .option("--byte-offsets")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("0-13\n4-6\n6-10\n0-36\n");
+}
+
+#[test]
+fn header_out() {
+    util::test_command()
+        .option("-f3,6")
+        .option("-d_")
+        .option("-s")
+        .option("--header-out")
+        .file("tests/files/ascii.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("f3_f6\nc_f\nc_f\n");
+
+    util::test_command()
+        .option("-f12-")
+        .option("-d:")
+        .option("-s")
+        .option("-o,")
+        .option("--header-out=first")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("first,f13\nl,m\n");
+}
+
+#[test]
+fn format_template() {
+    util::test_command()
+        .option("--format={3}-{1} [{20:-none}]")
+        .option("-d_")
+        .option("-s")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("c-a [none]\n");
+
+    util::test_command()
+        .option("--format={{{2}}}")
+        .option("-dβ")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("{}\n{γδεζηθικλμν}\n{cdγδefεζ}\n{}\n");
+
+    util::test_command()
+        .option("--format={2}:{1}")
+        .option("-d ")
+        .option("-z")
+        .file("tests/files/ascii-zero.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout(":abcdef\0b:a\0:a_b_c\0:a:b:c\0");
+}
+
+#[test]
+fn prefix_suffix() {
+    util::test_command()
+        .option("-f2,4")
+        .option("-d:")
+        .option("-o','")
+        .option("--prefix='")
+        .option("--suffix='")
+        .option("-s")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("'b','d'\n");
+
+    util::test_command()
+        .option("-f1")
+        .option("-d ")
+        .option("--prefix=\\t")
+        .option("--suffix=\\x21")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("\tabcdefghijklmnopqrstuvwxyz!\n\ta!\n\ta_b_c_d_e_f_g_h_i_j_k_l_m!\n\ta:b:c:d:e:f:g:h:i:j:k:l:m!\n");
+}
+
+#[test]
+fn sub_fields() {
+    util::test_command()
+        .options(&["-d,", "-f3", "--sub-delimiter=;", "--sub-fields=2"])
+        .build()
+        .write_stdin("2024-01-02T10:11:12,host,key=a;b;c\nx,y,z\n")
+        .assert()
+        .code(0)
+        .stdout("b\nz\n");
+
+    // Fields without the sub-delimiter are written whole.
+    util::test_command()
+        .options(&["-d,", "-f2-", "--sub-delimiter=;", "--sub-fields=1,3"])
+        .option("--sub-output-delimiter=|")
+        .build()
+        .write_stdin("t,host,a;b;c\n")
+        .assert()
+        .code(0)
+        .stdout("host,a|c\n");
+}
+
+#[test]
+fn sub_fields_only_delimited() {
+    // -s only applies to the outer delimiter, so a line without it is suppressed even if it has the
+    // sub-delimiter, and a line with it is written even if a field lacks the sub-delimiter.
+    util::test_command()
+        .options(&["-d,", "-f1", "-s", "--sub-delimiter=;", "--sub-fields=2"])
+        .build()
+        .write_stdin("a;b\nc;d,e\nf,g;h\n")
+        .assert()
+        .code(0)
+        .stdout("d\nf\n");
+
+    util::test_command()
+        .options(&["-d,", "-f1", "--sub-delimiter=;", "--sub-fields=2"])
+        .build()
+        .write_stdin("a;b\n")
+        .assert()
+        .code(0)
+        .stdout("a;b\n");
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn sub_fields_regex_delimiter() {
+    util::test_command()
+        .options(&["-r", " +", "-f2", "--sub-delimiter=:", "--sub-fields=2-"])
+        .build()
+        .write_stdin("10:00  k:v:w  x\n")
+        .assert()
+        .code(0)
+        .stdout("v:w\n");
+}
@@ -0,0 +1,54 @@
+pub mod util;
+
+/// Creates a directory containing files whose names start with a dash.
+fn dash_files(name: &str) -> util::TempDir {
+    let dir = util::TempDir::new(name);
+    dir.write("--strange", "a\tb\n");
+    dir.write("-b1.txt", "c\td\n");
+    dir
+}
+
+#[test]
+fn files_after_double_dash() {
+    let dir = dash_files("double-dash");
+
+    util::test_command()
+        .options(&["-f2", "--"])
+        .file("--strange")
+        .file("-b1.txt")
+        .build()
+        .current_dir(dir.root())
+        .assert()
+        .success()
+        .stdout("b\nd\n");
+}
+
+#[test]
+fn dash_after_double_dash_is_stdin() {
+    let dir = dash_files("double-dash-stdin");
+
+    util::test_command()
+        .options(&["-f1", "--"])
+        .file("--strange")
+        .file("-")
+        .build()
+        .current_dir(dir.root())
+        .write_stdin("e\tf\n")
+        .assert()
+        .success()
+        .stdout("a\ne\n");
+}
+
+#[test]
+fn dash_name_without_double_dash_is_option() {
+    let dir = dash_files("no-double-dash");
+
+    util::test_command()
+        .option("-f2")
+        .file("--strange")
+        .build()
+        .current_dir(dir.root())
+        .assert()
+        .code(1)
+        .stdout("");
+}
@@ -0,0 +1,80 @@
+pub mod util;
+
+use std::fs;
+
+#[test]
+fn decompress_file() {
+    let mut options: Vec<&[&str]> = vec![&["-b2-5"], &["-c3,1"], &["-f2,4", "-d_"]];
+    if cfg!(feature = "regex") {
+        options.push(&["-f1", "-r[ :]"]);
+    }
+    for options in &options {
+        let output = |files: &[&str]| {
+            let mut command = util::test_command().options(options);
+            for file in files {
+                command = command.file(file);
+            }
+            let output = command.build().output().unwrap();
+            assert!(output.status.success());
+            output.stdout
+        };
+
+        let expected = output(&["tests/files/ascii.txt"]);
+        assert_eq!(output(&["tests/files/ascii.txt.gz"]), expected);
+        assert_eq!(
+            output(&["tests/files/ascii.txt.gz", "tests/files/ascii.txt"]),
+            [&expected[..], &expected[..]].concat()
+        );
+    }
+}
+
+#[test]
+fn decompress_stdin() {
+    util::test_command()
+        .option("-f2")
+        .option("-d_")
+        .build()
+        .write_stdin(fs::read("tests/files/ascii.txt.gz").unwrap())
+        .assert()
+        .code(0)
+        .stdout(
+            "abcdefghijklmnopqrstuvwxyz\na b c d e f g h i j k l m\nb\na:b:c:d:e:f:g:h:i:j:k:l:m\n",
+        );
+}
+
+#[test]
+fn no_decompress() {
+    let compressed = fs::read("tests/files/ascii.txt.gz").unwrap();
+
+    let output = util::test_command()
+        .option("-b1-")
+        .option("--no-decompress")
+        .file("tests/files/ascii.txt.gz")
+        .build()
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(&compressed[..2]));
+
+    let output = util::test_command()
+        .option("-b1-")
+        .option("--no-decompress")
+        .build()
+        .write_stdin(compressed.clone())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stdout.starts_with(&compressed[..2]));
+}
+
+#[test]
+fn corrupt_gzip() {
+    let compressed = fs::read("tests/files/ascii.txt.gz").unwrap();
+    util::test_command()
+        .option("-b1-")
+        .build()
+        .write_stdin(&compressed[..compressed.len() / 2])
+        .assert()
+        .code(1)
+        .stderr(predicates::str::starts_with("rut: -: "));
+}
@@ -0,0 +1,29 @@
+pub mod util;
+
+#[test]
+fn directory_argument() {
+    util::test_command()
+        .option("-c1-3")
+        .file("tests/files/ascii.txt")
+        .file("tests/files")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("abc\na b\na_b\na:b\nabc\nαβγ\nabα\n😀😁😂\n")
+        .stderr("rut: tests/files: Is a directory\nrut: 1 of 3 inputs failed\n");
+}
+
+#[test]
+fn directory_check() {
+    util::test_command()
+        .option("-c1")
+        .option("--check")
+        .file("tests/files/ascii.txt")
+        .file("tests")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("rut: tests: Is a directory\nrut: 1 of 2 inputs failed\n");
+}
@@ -0,0 +1,72 @@
+pub mod util;
+
+#[test]
+fn utf16le_characters() {
+    util::test_command()
+        .option("-c2-3")
+        .option("--encoding=utf-16le")
+        .file("tests/files/utf16le.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("bc\nβγ\nbα\n😁😂\n");
+}
+
+#[test]
+fn utf16le_fields() {
+    util::test_command()
+        .option("-f2")
+        .option("-dβ")
+        .option("--encoding=utf-16le")
+        .file("tests/files/utf16le.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abcdefghijklm\nγδεζηθικλμν\ncdγδefεζ\n😀😁😂😃😄😅😆😇😈\n");
+
+    #[cfg(feature = "regex")]
+    util::test_command()
+        .option("-f2")
+        .option("-r[αγ]")
+        .option("--encoding=utf-16le")
+        .file("tests/files/utf16le.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abcdefghijklm\nβ\nβcd\n😀😁😂😃😄😅😆😇😈\n");
+}
+
+#[test]
+fn latin1_characters() {
+    util::test_command()
+        .option("-c4")
+        .option("--encoding=latin1")
+        .file("tests/files/latin1.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("é\nà\n");
+}
+
+#[test]
+fn latin1_fields() {
+    util::test_command()
+        .option("-f3,2")
+        .option("-d,")
+        .option("--encoding=latin1")
+        .file("tests/files/latin1.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("naïve,über\n© 2020\n");
+}
+
+#[test]
+fn latin1_without_encoding() {
+    util::test_command()
+        .option("-c1")
+        .file("tests/files/latin1.txt")
+        .build()
+        .assert()
+        .code(1);
+}
@@ -0,0 +1,104 @@
+pub mod util;
+
+use serde_json::Value;
+
+/// Runs rut with --error-format=json and returns each line written to stderr, parsed as JSON.
+fn json_diagnostics(options: &[&str], files: &[&str]) -> Vec<Value> {
+    let mut command = util::test_command()
+        .option("--error-format=json")
+        .options(options);
+    for file in files {
+        command = command.file(file);
+    }
+    let output = command.build().output().unwrap();
+    assert_eq!(output.status.code(), Option::Some(1));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    stderr
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap_or_else(|err| panic!("{}: {}", err, line)))
+        .collect()
+}
+
+#[test]
+fn missing_file() {
+    let diagnostics = json_diagnostics(
+        &["-b1"],
+        &["tests/files/does-not-exist.txt", "tests/files/ascii.txt"],
+    );
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0]["level"], "error");
+    assert_eq!(diagnostics[0]["kind"], "not_found");
+    assert_eq!(diagnostics[0]["file"], "tests/files/does-not-exist.txt");
+    assert_eq!(diagnostics[0]["line"], Value::Null);
+    assert_eq!(
+        diagnostics[0]["message"],
+        "No such file or directory (os error 2)"
+    );
+    assert_eq!(diagnostics[1]["kind"], "inputs_failed");
+    assert_eq!(diagnostics[1]["message"], "1 of 2 inputs failed");
+}
+
+#[test]
+fn bad_range() {
+    let diagnostics = json_diagnostics(&["-b0"], &[]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["kind"], "invalid_argument");
+    assert_eq!(diagnostics[0]["file"], Value::Null);
+    assert_eq!(
+        diagnostics[0]["message"],
+        "Ranges are numbered from one.\n  0\n  ^ at position 1"
+    );
+}
+
+#[test]
+fn invalid_option() {
+    let diagnostics = json_diagnostics(&["-b1", "--no-such-option"], &[]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["kind"], "invalid_argument");
+    assert_eq!(
+        diagnostics[0]["message"],
+        "unexpected argument '--no-such-option' found"
+    );
+}
+
+#[test]
+fn invalid_utf8() {
+    let diagnostics = json_diagnostics(&["-c1"], &["tests/files/invalid-utf8.txt"]);
+    assert_eq!(diagnostics[0]["kind"], "invalid_utf8");
+    assert_eq!(diagnostics[0]["file"], "tests/files/invalid-utf8.txt");
+    assert_eq!(diagnostics[0]["line"], 2);
+    assert_eq!(diagnostics[0]["message"], "line 2 (byte 6): invalid UTF-8");
+
+    let diagnostics = json_diagnostics(
+        &["-c1", "--skip-invalid"],
+        &["tests/files/invalid-utf8.txt"],
+    );
+    assert_eq!(diagnostics[0]["level"], "warning");
+    assert_eq!(diagnostics[0]["kind"], "invalid_utf8");
+    assert_eq!(diagnostics[0]["line"], 2);
+}
+
+#[test]
+fn config_error() {
+    let diagnostics = json_diagnostics(&["-b1", "--config=tests/config/malformed.toml"], &[]);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0]["kind"], "invalid_config");
+    assert_eq!(diagnostics[0]["file"], "tests/config/malformed.toml");
+    assert_eq!(diagnostics[0]["line"], 3);
+    assert_eq!(diagnostics[0]["message"], "line 3: unterminated string");
+}
+
+#[test]
+fn human_is_default() {
+    util::test_command()
+        .options(&["-b1", "--error-format=human"])
+        .file("tests/files/does-not-exist.txt")
+        .build()
+        .assert()
+        .failure()
+        .stderr(
+            "rut: tests/files/does-not-exist.txt: No such file or directory (os error 2)\n\
+             rut: 1 of 1 inputs failed\n",
+        );
+}
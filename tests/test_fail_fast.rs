@@ -0,0 +1,66 @@
+pub mod util;
+
+#[test]
+fn missing_file_continues() {
+    util::test_command()
+        .option("-c1-3")
+        .file("tests/files/does-not-exist.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("abc\na b\na_b\na:b\n")
+        .stderr(
+            "rut: tests/files/does-not-exist.txt: No such file or directory (os error 2)\n\
+             rut: 1 of 2 inputs failed\n",
+        );
+}
+
+#[test]
+fn fail_fast_missing_file() {
+    util::test_command()
+        .option("-c1-3")
+        .option("--fail-fast")
+        .file("tests/files/does-not-exist.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(
+            "rut: tests/files/does-not-exist.txt: No such file or directory (os error 2)\n\
+             rut: 1 of 2 inputs failed\n",
+        );
+}
+
+#[test]
+fn fail_fast_read_error() {
+    // Output written before the error is kept, but later files are not processed.
+    util::test_command()
+        .option("-c1")
+        .option("--fail-fast")
+        .file("tests/files/ascii.txt")
+        .file("tests/files/latin1.txt")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("a\na\na\na\n");
+}
+
+#[test]
+fn fail_fast_check() {
+    util::test_command()
+        .option("-c1")
+        .option("--check")
+        .option("--fail-fast")
+        .file("tests/files/does-not-exist.txt")
+        .file("tests/files/also-does-not-exist.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stderr(
+            "rut: tests/files/does-not-exist.txt: No such file or directory (os error 2)\n\
+             rut: 1 of 2 inputs failed\n",
+        );
+}
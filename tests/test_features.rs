@@ -0,0 +1,47 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Without the `regex` feature, the command line tool still builds and cuts fields separated by a
+/// character, and `--regex-delimiter` is reported as unavailable instead of being unknown.
+#[test]
+fn builds_without_regex() {
+    let rut = build(&["--no-default-features", "--features", "cli"]);
+
+    let output = Command::new(&rut)
+        .args(["-f2", "-d,"])
+        .arg("tests/files/crlf.txt")
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, "def\nγδ\nz\n".as_bytes());
+
+    let output = Command::new(&rut)
+        .args(["-f1", "-r,", "tests/files/crlf.txt"])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Option::Some(1));
+    assert!(output.stdout.is_empty());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("built without regex support"));
+}
+
+/// Builds the binary with the given feature flags, and returns its path.
+fn build(features: &[&str]) -> PathBuf {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // A separate target directory, since the one running the tests is locked.
+    let target_dir = manifest_dir.join("target").join("features-check");
+    let output = Command::new(env!("CARGO"))
+        .args(["build", "--bin", "rut"])
+        .args(features)
+        .current_dir(manifest_dir)
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    target_dir
+        .join("debug")
+        .join(format!("rut{}", std::env::consts::EXE_SUFFIX))
+}
@@ -0,0 +1,90 @@
+pub mod util;
+
+#[test]
+fn files0_from_stdin() {
+    util::test_command()
+        .option("-f2")
+        .option("-d_")
+        .option("--files0-from=-")
+        .build()
+        .write_stdin("tests/files/ascii.txt\0tests/files/does-not-exist.txt\0tests/files/utf8.txt\0")
+        .assert()
+        .code(1)
+        .stdout("abcdefghijklmnopqrstuvwxyz\na b c d e f g h i j k l m\nb\na:b:c:d:e:f:g:h:i:j:k:l:m\nabcdefghijklm\nαβγδεζηθικλμν\nabαβcdγδefεζ\n😀😁😂😃😄😅😆😇😈\n")
+        .stderr(predicates::str::contains("tests/files/does-not-exist.txt: "));
+}
+
+#[test]
+fn files0_from_file() {
+    util::test_command()
+        .option("-c1")
+        .option("--files0-from")
+        .option("tests/files/files0.list")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("a\na\na\na\na\nα\na\n😀\n");
+}
+
+#[test]
+fn files0_from_stdin_conflict() {
+    util::test_command()
+        .option("-b1")
+        .option("--files0-from=-")
+        .build()
+        .write_stdin("tests/files/ascii.txt\0-\0")
+        .assert()
+        .code(1)
+        .stdout("");
+}
+
+#[test]
+fn files0_from_with_file_arguments() {
+    util::test_command()
+        .option("-b1")
+        .option("--files0-from=tests/files/files0.list")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("");
+}
+
+#[test]
+fn files0_from_missing_list() {
+    util::test_command()
+        .option("-b1")
+        .option("--files0-from=tests/files/does-not-exist.list")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::starts_with(
+            "rut: tests/files/does-not-exist.list: ",
+        ));
+}
+
+#[test]
+fn files_from() {
+    util::test_command()
+        .option("-c1")
+        .option("--files-from=tests/files/manifest.txt")
+        .build()
+        .write_stdin("x\ny")
+        .assert()
+        .code(0)
+        .stdout("a\na\na\na\na\nα\na\n😀\nx\ny\n");
+}
+
+#[test]
+fn files_from_stdin_conflict() {
+    util::test_command()
+        .option("-c1")
+        .option("--files-from=-")
+        .build()
+        .write_stdin("tests/files/ascii.txt\n-\n")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::starts_with("rut: -: "));
+}
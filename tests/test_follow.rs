@@ -0,0 +1,45 @@
+pub mod util;
+
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::Duration;
+
+#[test]
+fn follow() {
+    let dir = util::TempDir::new("follow");
+    let path = dir.write("app.log", "1 a x\n2 b y\n");
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin("rut"))
+        .args(["-F", "--follow-interval=10", "-f2-", "-d "])
+        .arg(&path)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    let (sender, receiver) = mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines() {
+            if sender.send(line.unwrap()).is_err() {
+                break;
+            }
+        }
+    });
+    let next_line = || receiver.recv_timeout(Duration::from_secs(10)).unwrap();
+
+    assert_eq!(next_line(), "a x");
+    assert_eq!(next_line(), "b y");
+
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(b"3 c z\n").unwrap();
+    assert_eq!(next_line(), "c z");
+
+    // Truncated file is read from the start.
+    fs::write(&path, "4 d\n").unwrap();
+    assert_eq!(next_line(), "d");
+
+    child.kill().unwrap();
+    child.wait().unwrap();
+}
@@ -0,0 +1,39 @@
+// Runs the checks of the fuzz targets in fuzz/ on stable Rust: for every input in the seed corpus,
+// and for a bounded number of generated inputs, so regressions are found without cargo-fuzz.
+
+#[path = "../fuzz/src/lib.rs"]
+mod fuzz;
+
+use proptest::prelude::*;
+use std::fs;
+
+#[test]
+fn parse_ranges_corpus() {
+    let mut checked = 0;
+    for entry in fs::read_dir("fuzz/corpus/parse_ranges").unwrap() {
+        let input = fs::read(entry.unwrap().path()).unwrap();
+        // libFuzzer skips inputs which are not UTF-8 for a &str target.
+        if let Result::Ok(input) = String::from_utf8(input) {
+            fuzz::check_parse_ranges(&input);
+            checked += 1;
+        }
+    }
+    assert!(checked > 0);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(2000))]
+
+    #[test]
+    fn parse_ranges_generated(
+        input in prop_oneof![
+            // Mostly the characters of the range syntax, so that many inputs parse.
+            4 => "[0-9, \t-]{0,32}",
+            // Numbers around usize::MAX.
+            1 => "[0-9]{18,22}(-[0-9]{0,22})?(,[0-9]{1,22}-?)?",
+            1 => ".{0,16}",
+        ],
+    ) {
+        fuzz::check_parse_ranges(&input);
+    }
+}
@@ -0,0 +1,91 @@
+pub mod util;
+
+#[test]
+fn glob() {
+    let root = util::TempDir::new("glob");
+    root.write("b.csv", "b,2\n");
+    root.write("a.csv", "a,1\n");
+    root.write("c.txt", "c,3\n");
+    root.write("logs/d.csv", "d,4\n");
+    root.write("old/e.csv", "e,5\n");
+    let path = |name: &str| root.filename(name);
+
+    // Matches are sorted by name, and names without wildcards are kept as is.
+    util::test_command()
+        .options(&["-f2", "-d,", "--glob"])
+        .file(&path("c.txt"))
+        .file(&path("*.csv"))
+        .file(&path("*/?.csv"))
+        .file(&path("[ab].csv"))
+        .build()
+        .assert()
+        .code(0)
+        .stdout("3\n1\n2\n4\n5\n1\n2\n");
+
+    // "-" is stdin, not a pattern.
+    util::test_command()
+        .options(&["-f2", "-d,", "--glob"])
+        .file("-")
+        .file(&path("*.txt"))
+        .build()
+        .write_stdin("s,0\n")
+        .assert()
+        .code(0)
+        .stdout("0\n3\n");
+
+    // Without --glob, patterns are file names.
+    util::test_command()
+        .options(&["-f2", "-d,"])
+        .file(&path("*.csv"))
+        .build()
+        .assert()
+        .code(1)
+        .stdout("");
+}
+
+#[test]
+fn glob_no_match() {
+    let root = util::TempDir::new("glob_no_match");
+    root.write("a.csv", "a,1\n");
+    let path = |name: &str| root.filename(name);
+
+    util::test_command()
+        .options(&["-f2", "-d,", "--glob"])
+        .file(&path("*.json"))
+        .file(&path("a.csv"))
+        .build()
+        .assert()
+        .code(1)
+        .stdout("1\n")
+        .stderr(format!(
+            "rut: {}: no files match the pattern\nrut: 1 of 2 inputs failed\n",
+            path("*.json")
+        ));
+
+    util::test_command()
+        .options(&["-f2", "-d,", "--glob", "--glob-allow-empty"])
+        .file(&path("*.json"))
+        .file(&path("a.csv"))
+        .build()
+        .assert()
+        .code(0)
+        .stdout("1\n")
+        .stderr("");
+}
+
+#[test]
+fn glob_recursive() {
+    let root = util::TempDir::new("glob_recursive");
+    root.write("2020/a.csv", "a,1\n");
+    root.write("2021/b/c.csv", "c,2\n");
+    root.write("other/d.csv", "d,3\n");
+    let path = |name: &str| root.filename(name);
+
+    util::test_command()
+        .options(&["-f2", "-d,", "--glob", "-R"])
+        .file(&path("20*"))
+        .build()
+        .assert()
+        .code(0)
+        .stdout("1\n2\n");
+}
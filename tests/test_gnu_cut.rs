@@ -0,0 +1,329 @@
+pub mod util;
+
+// Compares rut with GNU cut, for commands which should behave the same, both as rut and with
+// --compat=gnu. The tests pass without checking anything unless GNU cut is found, either as `cut`
+// on the PATH or at the path given by RUT_GNU_CUT.
+
+use std::env;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+/// Environment variable which gives the path of GNU cut.
+const GNU_CUT_VAR: &str = "RUT_GNU_CUT";
+
+const FIXTURES: &[&str] = &[
+    "tests/files/ascii.txt",
+    "tests/files/ascii-zero.txt",
+    "tests/files/tabs.tsv",
+    "tests/files/mixed.csv",
+    "tests/files/bytes.bin",
+    "tests/files/bytes-zero.bin",
+    "tests/files/utf8.txt",
+    "tests/files/utf8-zero.txt",
+    "tests/files/crlf.txt",
+    "tests/files/invalid-utf8.txt",
+    "tests/files/latin1.txt",
+    "tests/files/utf8-bom.txt",
+    "tests/files/ascii.txt.gz",
+];
+
+/// Known differences from GNU cut. Each applies to the commands it matches, whose output is not
+/// compared.
+const DIVERGENCES: &[Divergence] = &[
+    Divergence {
+        reason: "-c selects UTF-8 characters, where GNU cut selects bytes",
+        applies: |case| case.mode == 'c' && !case.is_ascii(),
+        compat: true,
+    },
+    Divergence {
+        reason: "-f requires UTF-8 input",
+        applies: |case| case.mode == 'f' && !case.is_utf8(),
+        compat: true,
+    },
+    Divergence {
+        reason: "the carriage return of CRLF line endings is removed, without --keep-cr",
+        applies: |case| case.line_delimiter() == b'\n' && case.input.contains(&b'\r'),
+        compat: false,
+    },
+    Divergence {
+        reason: "a UTF-8 byte order mark is removed, without --keep-bom",
+        applies: |case| case.input.starts_with(b"\xef\xbb\xbf"),
+        compat: false,
+    },
+    Divergence {
+        reason: "gzip input is decompressed, without --no-decompress",
+        applies: |case| case.input.starts_with(b"\x1f\x8b"),
+        compat: false,
+    },
+];
+
+struct Divergence {
+    reason: &'static str,
+    applies: fn(&Case) -> bool,
+    /// Whether the difference remains with --compat=gnu.
+    compat: bool,
+}
+
+/// A command to run with both implementations.
+struct Case {
+    mode: char,
+    args: Vec<String>,
+    fixture: &'static str,
+    input: Vec<u8>,
+}
+
+impl Case {
+    fn line_delimiter(&self) -> u8 {
+        if self.args.iter().any(|arg| arg == "-z") {
+            b'\0'
+        } else {
+            b'\n'
+        }
+    }
+
+    fn is_ascii(&self) -> bool {
+        self.input.is_ascii()
+    }
+
+    fn is_utf8(&self) -> bool {
+        std::str::from_utf8(&self.input).is_ok()
+    }
+
+    /// Returns the command line, as it would be typed in a shell.
+    fn command_line(&self) -> String {
+        let quote = |arg: &String| {
+            if arg
+                .bytes()
+                .all(|b| b.is_ascii_alphanumeric() || b"-=,./".contains(&b))
+            {
+                arg.clone()
+            } else {
+                format!("'{}'", arg)
+            }
+        };
+        let args: Vec<String> = self.args.iter().map(quote).collect();
+        format!("cut {} {}", args.join(" "), self.fixture)
+    }
+}
+
+/// Returns every command to compare: each list of ranges, with each combination of the options
+/// which apply to its mode.
+fn cases() -> Vec<Case> {
+    let lists: &[(char, &[&str])] = &[
+        ('b', &["1", "2-4", "-3,6-", "5,3,1", "2-", "1-100", "9-"]),
+        ('c', &["1", "2-4", "-3,6-", "3-"]),
+        ('f', &["1", "2", "1,3", "2-", "-2", "3-5", "9"]),
+    ];
+    let field_delimiters: &[Option<&str>] = &[Option::None, Option::Some(","), Option::Some(":")];
+    let fixtures: Vec<(&'static str, Vec<u8>)> = FIXTURES
+        .iter()
+        .map(|&fixture| (fixture, std::fs::read(fixture).unwrap()))
+        .collect();
+
+    let mut cases = Vec::new();
+    for &(mode, ranges) in lists {
+        let mut option_sets = vec![Vec::new()];
+        add_optional(&mut option_sets, "--complement");
+        add_optional(&mut option_sets, "-z");
+        if mode == 'f' {
+            add_optional(&mut option_sets, "-s");
+            add_optional(&mut option_sets, "--output-delimiter=|");
+            option_sets = field_delimiters
+                .iter()
+                .flat_map(|delimiter| {
+                    option_sets.iter().map(move |options| {
+                        let mut options = options.clone();
+                        if let Option::Some(delimiter) = delimiter {
+                            options.push(format!("-d{}", delimiter));
+                        }
+                        options
+                    })
+                })
+                .collect();
+        }
+
+        for range in ranges {
+            for options in &option_sets {
+                for (fixture, input) in &fixtures {
+                    let mut args = vec![format!("-{}{}", mode, range)];
+                    args.extend(options.iter().cloned());
+                    cases.push(Case {
+                        mode,
+                        args,
+                        fixture,
+                        input: input.clone(),
+                    });
+                }
+            }
+        }
+    }
+    cases
+}
+
+/// Doubles the option sets: each one is kept as it is, and also given with `option`.
+fn add_optional(option_sets: &mut Vec<Vec<String>>, option: &str) {
+    let with_option: Vec<Vec<String>> = option_sets
+        .iter()
+        .map(|options| {
+            let mut options = options.clone();
+            options.push(option.to_string());
+            options
+        })
+        .collect();
+    option_sets.extend(with_option);
+}
+
+/// Returns the path of GNU cut, or `None` if it cannot be found.
+fn gnu_cut() -> Option<PathBuf> {
+    if let Option::Some(path) = env::var_os(GNU_CUT_VAR) {
+        let path = PathBuf::from(path);
+        assert!(
+            is_gnu_cut(&path),
+            "{} is set, but {:?} is not GNU cut",
+            GNU_CUT_VAR,
+            path
+        );
+        return Option::Some(path);
+    }
+    let path = env::var_os("PATH").unwrap_or_default();
+    env::split_paths(&path)
+        .map(|dir| dir.join("cut"))
+        .find(|path| path.is_file() && is_gnu_cut(path))
+}
+
+fn is_gnu_cut(path: &Path) -> bool {
+    Command::new(path)
+        .arg("--version")
+        .output()
+        .map(|output| String::from_utf8_lossy(&output.stdout).contains("GNU coreutils"))
+        .unwrap_or(false)
+}
+
+fn run_gnu(gnu_cut: &Path, case: &Case) -> Output {
+    Command::new(gnu_cut)
+        .args(&case.args)
+        .arg(case.fixture)
+        .env("LC_ALL", "C")
+        .output()
+        .unwrap()
+}
+
+fn run_rut(case: &Case, compat: bool) -> Output {
+    let args: Vec<&str> = case.args.iter().map(String::as_str).collect();
+    let compat: &[&str] = if compat { &["--compat=gnu"] } else { &[] };
+    util::test_command()
+        .options(compat)
+        .options(&args)
+        .file(case.fixture)
+        .build()
+        .output()
+        .unwrap()
+}
+
+/// Describes how the outputs of a command differ, or returns `None` if they are the same.
+fn compare(case: &Case, compat: bool, gnu: &Output, rut: &Output) -> Option<String> {
+    if gnu.stdout == rut.stdout && gnu.status.code() == rut.status.code() {
+        return Option::None;
+    }
+    let mut report = format!("{}\n", case.command_line());
+    if compat {
+        report += "  rut run with --compat=gnu\n";
+    }
+    if gnu.status.code() != rut.status.code() {
+        report += &format!(
+            "  exit code: gnu {:?}, rut {:?}\n  stderr: gnu {:?}\n          rut {:?}\n",
+            gnu.status.code(),
+            rut.status.code(),
+            String::from_utf8_lossy(&gnu.stderr),
+            String::from_utf8_lossy(&rut.stderr),
+        );
+    }
+    if gnu.stdout != rut.stdout {
+        report += &first_difference(&gnu.stdout, &rut.stdout, case.line_delimiter());
+    }
+    Option::Some(report)
+}
+
+/// Shows the first line which differs, escaped so that delimiters and missing line delimiters at
+/// the end of the output are visible.
+fn first_difference(gnu: &[u8], rut: &[u8], line_delimiter: u8) -> String {
+    let gnu_lines: Vec<&[u8]> = gnu.split_inclusive(|&b| b == line_delimiter).collect();
+    let rut_lines: Vec<&[u8]> = rut.split_inclusive(|&b| b == line_delimiter).collect();
+    let line = (0..)
+        .find(|&i| gnu_lines.get(i) != rut_lines.get(i))
+        .unwrap();
+    let show = |lines: &[&[u8]]| match lines.get(line) {
+        Option::Some(line) => format!("{:?}", String::from_utf8_lossy(line)),
+        Option::None => String::from("(no more lines)"),
+    };
+    format!(
+        "  line {}: gnu {}\n  {:>width$} rut {}\n  lines: gnu {}, rut {}\n",
+        line + 1,
+        show(&gnu_lines),
+        "",
+        show(&rut_lines),
+        gnu_lines.len(),
+        rut_lines.len(),
+        width = format!("line {}:", line + 1).len(),
+    )
+}
+
+#[test]
+fn same_as_gnu_cut() {
+    let gnu_cut = match gnu_cut() {
+        Option::Some(path) => path,
+        Option::None => {
+            eprintln!(
+                "GNU cut was not found; set {} to compare rut with it",
+                GNU_CUT_VAR
+            );
+            return;
+        }
+    };
+
+    let mut differences = Vec::new();
+    let mut compared = 0;
+    for case in cases() {
+        let gnu = run_gnu(&gnu_cut, &case);
+        for compat in [false, true] {
+            if DIVERGENCES
+                .iter()
+                .any(|divergence| (!compat || divergence.compat) && (divergence.applies)(&case))
+            {
+                continue;
+            }
+            compared += 1;
+            let rut = run_rut(&case, compat);
+            differences.extend(compare(&case, compat, &gnu, &rut));
+        }
+    }
+
+    assert!(compared > 0);
+    assert!(
+        differences.is_empty(),
+        "{} of {} commands differ from GNU cut:\n{}",
+        differences.len(),
+        compared,
+        differences.join("\n")
+    );
+}
+
+#[test]
+fn divergences_still_differ() {
+    let gnu_cut = match gnu_cut() {
+        Option::Some(path) => path,
+        Option::None => return,
+    };
+
+    // An allowed divergence which no longer differs for any command should be removed.
+    for divergence in DIVERGENCES {
+        let differs = cases()
+            .iter()
+            .filter(|case| (divergence.applies)(case))
+            .any(|case| {
+                let gnu = run_gnu(&gnu_cut, case);
+                compare(case, false, &gnu, &run_rut(case, false)).is_some()
+            });
+        assert!(differs, "no longer differs: {}", divergence.reason);
+    }
+}
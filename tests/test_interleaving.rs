@@ -0,0 +1,60 @@
+pub mod util;
+
+use std::fs::File;
+use std::process::{Command, Stdio};
+
+/// Runs rut with stdout and stderr written to the same file, as when both are written to a
+/// terminal or merged with `2>&1`, and returns what was written.
+fn cut_merged(args: &[&str]) -> Vec<u8> {
+    let dir = util::TempDir::new("merged");
+    let path = dir.path("output");
+    let output = File::create(&path).unwrap();
+    let status = Command::new(assert_cmd::cargo::cargo_bin("rut"))
+        .args(args)
+        .env_remove("RUT_CONFIG")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist")
+        .stdin(Stdio::null())
+        .stdout(output.try_clone().unwrap())
+        .stderr(output)
+        .status()
+        .unwrap();
+    assert_eq!(status.code(), Option::Some(1));
+    std::fs::read(&path).unwrap()
+}
+
+#[test]
+fn errors_follow_earlier_output() {
+    let files = [
+        "tests/files/ascii.txt",
+        "tests/files/does-not-exist.txt",
+        "tests/files/ascii.txt",
+    ];
+    let error = "rut: tests/files/does-not-exist.txt: No such file or directory (os error 2)\n";
+    let summary = "rut: 1 of 3 inputs failed\n";
+    // Output which does not end with a newline, e.g. with -z, is not written by stdout's line
+    // buffering until it is flushed.
+    for options in [
+        &["-b1-"][..],
+        &["-b1-", "-z"],
+        &["-b1-", "-z", "-j2"],
+        &["-b1-", "-z", "--zero-copy"],
+        &["-c1-", "-z"],
+    ] {
+        let output = util::test_command()
+            .options(options)
+            .file(files[0])
+            .build()
+            .output()
+            .unwrap()
+            .stdout;
+        let mut args = options.to_vec();
+        args.extend(files);
+        let expected = [&output, error.as_bytes(), &output, summary.as_bytes()].concat();
+        assert_eq!(
+            String::from_utf8_lossy(&cut_merged(&args)),
+            String::from_utf8_lossy(&expected),
+            "{:?}",
+            options
+        );
+    }
+}
@@ -1,15 +1,15 @@
 pub mod util;
 
+use predicates::prelude::*;
+
 #[test]
 fn multiple_modes() {
-    assert_invalid_options(&["-b1", "-b1"]);
-    assert_invalid_options(&["-c1", "-c1"]);
-    assert_invalid_options(&["-f1", "-f1"]);
-
     assert_invalid_options(&["-b1", "-c1"]);
     assert_invalid_options(&["-b1", "-f1"]);
     assert_invalid_options(&["-c1", "-f1"]);
     assert_invalid_options(&["-b1", "-c1", "-f1"]);
+    assert_invalid_options(&["-b1", "-b2", "-f1"]);
+    assert_invalid_options(&["-f1", "-f2", "--format={1}"]);
 }
 
 #[test]
@@ -21,6 +21,10 @@ fn missing_or_invalid_ranges() {
     assert_invalid_options(&["-b2-1"]);
     assert_invalid_options(&["-c0-3"]);
     assert_invalid_options(&["-fxyz"]);
+
+    assert_invalid_options(&["-b99999999999999999999999999-"]);
+    assert_invalid_options(&["-c1-99999999999999999999999999", "--complement"]);
+    assert_invalid_options(&["-f99999999999999999999999999"]);
 }
 
 #[test]
@@ -58,5 +62,77 @@ fn assert_invalid_options(options: &[&str]) {
         .options(options)
         .build()
         .assert()
-        .failure();
+        .code(1);
+}
+
+#[test]
+fn format_with_other_modes() {
+    assert_invalid_options(&["-b1", "--format={1}"]);
+    assert_invalid_options(&["-c1", "--format={1}"]);
+    assert_invalid_options(&["-f1", "--format={1}"]);
+    assert_invalid_options(&["--format={1}", "-o,"]);
+    assert_invalid_options(&["--format=no fields"]);
+}
+
+#[test]
+fn usage_error() {
+    util::test_command()
+        .options(&["-b1", "-f1"])
+        .build()
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "Usage: rut -b <ranges> [file]...",
+        ));
+
+    util::test_command()
+        .options(&["-b0"])
+        .build()
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "Usage: rut -b <ranges> [file]...",
+        ));
+}
+
+#[test]
+fn runtime_errors_do_not_print_usage() {
+    util::test_command()
+        .option("-b1")
+        .file("tests/files/does-not-exist.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("a\na\na\na\n")
+        .stderr(predicates::str::contains("Usage:").not())
+        .stderr(predicates::str::ends_with("rut: 1 of 2 inputs failed\n"));
+
+    util::test_command()
+        .options(&["-c1", "--check"])
+        .file("tests/files/does-not-exist.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("Usage:").not())
+        .stderr(predicates::str::ends_with("rut: 1 of 1 inputs failed\n"));
+}
+
+#[test]
+fn help_and_version() {
+    util::test_command()
+        .option("--help")
+        .build()
+        .assert()
+        .code(0)
+        .stdout(predicates::str::contains("--bytes <ranges>"));
+
+    util::test_command()
+        .option("--version")
+        .build()
+        .assert()
+        .code(0)
+        .stdout(predicates::str::starts_with("rut "));
 }
@@ -0,0 +1,71 @@
+pub mod util;
+
+use std::fs;
+
+/// Writes many small files, including some which are empty or not valid UTF-8, and returns their
+/// names in order.
+fn write_files(name: &str) -> (util::TempDir, Vec<String>) {
+    let dir = util::TempDir::new(name);
+
+    let mut filenames = Vec::new();
+    for i in 0..50 {
+        let contents: Vec<u8> = match i % 5 {
+            0 => Vec::new(),
+            1 => format!("{}\n", i).into_bytes(),
+            2 => b"a\xffb,c\n".to_vec(),
+            _ => (0..i)
+                .map(|j| format!("{},{},{}\n", i, j, "x".repeat(j)))
+                .collect::<String>()
+                .into_bytes(),
+        };
+        let path = dir.write(format!("{:02}.txt", i), contents);
+        filenames.push(path.to_str().unwrap().to_string());
+    }
+    (dir, filenames)
+}
+
+fn assert_same_output(filenames: &[String], options: &[&str]) {
+    let run = |jobs: &str| {
+        let mut command = util::test_command().options(options).option(jobs);
+        for filename in filenames {
+            command = command.file(filename);
+        }
+        command.build().output().unwrap()
+    };
+    let serial = run("-j1");
+    for jobs in &["-j2", "-j3", "-j8", "-j100"] {
+        let parallel = run(jobs);
+        assert_eq!(parallel.status, serial.status, "{} {:?}", jobs, options);
+        assert_eq!(parallel.stdout, serial.stdout, "{} {:?}", jobs, options);
+        assert_eq!(parallel.stderr, serial.stderr, "{} {:?}", jobs, options);
+    }
+}
+
+#[test]
+fn same_output_as_serial() {
+    let (_dir, mut filenames) = write_files("jobs");
+    filenames.insert(10, "tests/files/does-not-exist.txt".to_string());
+    filenames.push("tests/files/ascii.txt.gz".to_string());
+
+    assert_same_output(&filenames, &["-b1-"]);
+    assert_same_output(&filenames, &["-b2-4", "--max-lines-per-file=3"]);
+    assert_same_output(&filenames, &["-c1-3", "--lossy"]);
+    assert_same_output(&filenames, &["-f2", "-d,", "--skip-invalid"]);
+    assert_same_output(&filenames, &["-f1,3", "-r,", "-s"]);
+    assert_same_output(&filenames, &["-c1-", "--fail-fast"]);
+}
+
+#[test]
+fn order_is_kept() {
+    let utf8 = fs::read_to_string("tests/files/utf8.txt").unwrap() + "\n";
+    let ascii = fs::read_to_string("tests/files/ascii.txt").unwrap() + "\n";
+    util::test_command()
+        .options(&["-b1-", "-j4"])
+        .file("tests/files/utf8.txt")
+        .file("tests/files/ascii.txt")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .success()
+        .stdout([utf8.clone(), ascii, utf8].concat());
+}
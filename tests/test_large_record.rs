@@ -0,0 +1,34 @@
+use assert_cmd::Command;
+
+/// Size of the generated record, which is larger than the memory available to rut.
+const RECORD_SIZE: usize = 100 * 1024 * 1024;
+
+/// Runs rut with the specified options on a single record of `RECORD_SIZE` bytes, with virtual
+/// memory limited to 64 MiB.
+#[cfg(target_os = "linux")]
+fn cut_large_record(options: &str) -> assert_cmd::assert::Assert {
+    let script = format!(
+        "head -c {} /dev/zero | tr '\\0' a | (ulimit -v 65536 && exec {} {})",
+        RECORD_SIZE,
+        assert_cmd::cargo::cargo_bin("rut").display(),
+        options
+    );
+    Command::new("sh").arg("-c").arg(script).assert()
+}
+
+#[test]
+#[cfg(target_os = "linux")]
+fn large_record() {
+    cut_large_record(&format!("-b1-2,{}-", RECORD_SIZE))
+        .success()
+        .stdout("aaa\n");
+
+    // Input contains no zero bytes, so it is a single record.
+    cut_large_record(&format!("-z -b{}", RECORD_SIZE / 2))
+        .success()
+        .stdout("a\0");
+
+    cut_large_record("--hex -b5-8")
+        .success()
+        .stdout("61616161\n");
+}
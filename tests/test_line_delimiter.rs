@@ -0,0 +1,161 @@
+pub mod util;
+
+#[test]
+fn crlf_line_delimiter() {
+    util::test_command()
+        .option("-b5-")
+        .option("--line-delimiter=\\r\\n")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("def,ghi\r\n,γδ\r\nz\r\n");
+
+    // A lone CR is part of the record.
+    util::test_command()
+        .option("-f1")
+        .option("-d,")
+        .option("--line-delimiter=\\r\\n")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abc\r\nαβ\r\nx\ry\r\n");
+}
+
+#[test]
+fn multi_byte_line_delimiter() {
+    util::test_command()
+        .option("-f2")
+        .option("-d,")
+        .option("--line-delimiter=;;")
+        .build()
+        .write_stdin("a,b;;c;d,e;;f,g;")
+        .assert()
+        .code(0)
+        .stdout("b;;e;;g;;;");
+
+    util::test_command()
+        .option("-c2-")
+        .option("--line-delimiter")
+        .option("<eol>")
+        .build()
+        .write_stdin("αβγ<eol><eo<eol>\n<eol>")
+        .assert()
+        .code(0)
+        .stdout("βγ<eol>eo<eol><eol>");
+
+    util::test_command()
+        .option("-f1")
+        .option("-s")
+        .option("--line-delimiter=\\x00\\x00")
+        .build()
+        .write_stdin("a\tb\0\0c\0d\0\0e\tf")
+        .assert()
+        .code(0)
+        .stdout("a\0\0e\0\0");
+}
+
+#[test]
+fn output_line_delimiter() {
+    util::test_command()
+        .option("-f2")
+        .option("-d,")
+        .option("--output-line-delimiter=\\r\\n")
+        .file("tests/files/crlf.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("def\r\nγδ\r\nz\r\n");
+
+    util::test_command()
+        .option("-b1")
+        .option("-z")
+        .option("--output-line-delimiter=\\n")
+        .build()
+        .write_stdin("ab\0cd\0ef")
+        .assert()
+        .code(0)
+        .stdout("a\nc\ne\n");
+
+    util::test_command()
+        .option("-b1")
+        .option("--line-delimiter=;;")
+        .option("--output-line-delimiter=")
+        .build()
+        .write_stdin("ab;;cd;;")
+        .assert()
+        .code(0)
+        .stdout("ac");
+}
+
+#[test]
+fn line_delimiter_across_reads() {
+    // Records around the size of the read buffer, so some delimiters are split between reads.
+    let records: Vec<String> = (8180..8200).map(|len| "x".repeat(len)).collect();
+    let input = records.join("\r\n");
+    let expected: String = records
+        .iter()
+        .map(|record| format!("{}\r\n", &record[1..]))
+        .collect();
+    for mode in &["-b2-", "-c2-"] {
+        util::test_command()
+            .option(mode)
+            .option("--line-delimiter=\\r\\n")
+            .build()
+            .write_stdin(input.clone())
+            .assert()
+            .code(0)
+            .stdout(expected.clone());
+    }
+}
+
+#[test]
+fn line_delimiter_conflicts() {
+    util::test_command()
+        .option("-f1")
+        .option("-z")
+        .option("--line-delimiter=;")
+        .build()
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains("cannot be used with"));
+
+    util::test_command()
+        .option("-f1")
+        .option("-d;")
+        .option("--line-delimiter=;")
+        .build()
+        .assert()
+        .code(1)
+        .stderr(predicates::str::contains(
+            "cannot be the line delimiter (\";\")",
+        ));
+}
+
+#[test]
+#[cfg(feature = "regex")]
+fn regex_delimiter_matching_line_delimiter() {
+    // Records are split first, so only the matches of ',' separate fields.
+    util::test_command()
+        .option("-f2")
+        .options(&["-r", "[;,]"])
+        .option("--line-delimiter=;")
+        .build()
+        .write_stdin("a,b;c,d;")
+        .assert()
+        .code(0)
+        .stdout("b;d;");
+
+    util::test_command()
+        .option("-f2")
+        .options(&["-r", "\\n"])
+        .build()
+        .write_stdin("a\nb\n")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::contains(
+            "must match more than text containing the line delimiter (\"\\n\")",
+        ));
+}
@@ -0,0 +1,68 @@
+pub mod util;
+
+/// Returns the long options listed by --help, e.g. "regex-delimiter".
+fn long_options() -> Vec<String> {
+    let output = util::test_command()
+        .option("--help")
+        .build()
+        .output()
+        .unwrap();
+    let help = String::from_utf8(output.stdout).unwrap();
+    help.split_whitespace()
+        .filter_map(|word| word.strip_prefix("--"))
+        .map(|option| {
+            option
+                .split(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .next()
+                .unwrap()
+                .to_string()
+        })
+        .filter(|option| !option.is_empty())
+        .collect()
+}
+
+fn man() -> String {
+    let output = util::test_command()
+        .option("--man")
+        .build()
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    String::from_utf8(output.stdout).unwrap()
+}
+
+#[test]
+fn title() {
+    assert!(man().contains(".TH rut 1"));
+}
+
+#[test]
+fn options() {
+    let man = man();
+    let options = long_options();
+    assert!(options.len() > 30);
+    for option in options {
+        // Dashes are escaped in roff.
+        let option = format!("\\-\\-{}", option.replace('-', "\\-"));
+        assert!(man.contains(&option), "{}", option);
+    }
+}
+
+#[test]
+fn ranges_and_examples() {
+    let man = man();
+    assert!(man.contains(".SH RANGES"));
+    assert!(man.contains(".SH EXAMPLES"));
+    assert!(man.contains("\\fBrut \\-b1\\-5 file.txt\\fR"));
+}
+
+#[test]
+fn with_other_arguments() {
+    util::test_command()
+        .options(&["--man", "-b1"])
+        .build()
+        .assert()
+        .failure()
+        .stdout("");
+}
@@ -0,0 +1,81 @@
+pub mod util;
+
+use std::fs;
+
+/// Writes files named by number, each containing its own number, and returns the directory along
+/// with the arguments to cut them: every 37th file is missing, and a directory and stdin are among
+/// the files.
+fn write_files(name: &str, count: usize) -> (util::TempDir, Vec<String>) {
+    let dir = util::TempDir::new(name);
+
+    let mut filenames = Vec::new();
+    for i in 0..count {
+        let path = dir.path(format!("{:03}.txt", i));
+        if i == 100 {
+            fs::create_dir(&path).unwrap();
+        } else if i % 37 != 36 {
+            fs::write(&path, format!("{},{}\n", i, "x".repeat(i))).unwrap();
+        }
+        let filename = if i == 150 {
+            String::from("-")
+        } else {
+            path.to_str().unwrap().to_string()
+        };
+        filenames.push(filename);
+    }
+    (dir, filenames)
+}
+
+fn command(filenames: &[String], options: &[&str]) -> assert_cmd::Command {
+    let mut command = util::test_command()
+        .options(&["-f1", "-d,"])
+        .options(options);
+    for filename in filenames {
+        command = command.file(filename);
+    }
+    let mut command = command.build();
+    command.write_stdin("stdin,x\n");
+    command
+}
+
+#[test]
+fn output_and_errors_are_in_order() {
+    let count = 300;
+    let (_dir, filenames) = write_files("many-files", count);
+
+    let mut stdout = String::new();
+    let mut stderr = String::new();
+    for (i, filename) in filenames.iter().enumerate() {
+        if i == 150 {
+            stdout.push_str("stdin\n");
+        } else if i == 100 {
+            stderr.push_str(&format!("rut: {}: Is a directory\n", filename));
+        } else if i % 37 == 36 {
+            stderr.push_str(&format!(
+                "rut: {}: No such file or directory (os error 2)\n",
+                filename
+            ));
+        } else {
+            stdout.push_str(&format!("{}\n", i));
+        }
+    }
+    let failed = count / 37 + 1;
+    stderr.push_str(&format!("rut: {} of {} inputs failed\n", failed, count));
+    command(&filenames, &[])
+        .assert()
+        .code(1)
+        .stdout(stdout)
+        .stderr(stderr);
+
+    // Files opened ahead of the one which failed are not cut.
+    let stdout: String = (0..36).map(|i| format!("{}\n", i)).collect();
+    let stderr = format!(
+        "rut: {}: No such file or directory (os error 2)\nrut: 1 of {} inputs failed\n",
+        filenames[36], count
+    );
+    command(&filenames, &["--fail-fast"])
+        .assert()
+        .code(1)
+        .stdout(stdout)
+        .stderr(stderr);
+}
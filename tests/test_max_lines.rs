@@ -0,0 +1,54 @@
+pub mod util;
+
+#[test]
+fn max_lines() {
+    util::test_command()
+        .option("-b1-3")
+        .option("--max-lines=6")
+        .file("tests/files/ascii.txt")
+        .file("tests/files/ascii.txt")
+        .file("tests/files/does-not-exist.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abc\na b\na_b\na:b\nabc\na b\n");
+
+    util::test_command()
+        .option("-f2")
+        .option("-d_")
+        .option("-s")
+        .option("--max-lines=1")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("b\n");
+}
+
+#[test]
+fn max_lines_per_file() {
+    util::test_command()
+        .option("-c1")
+        .option("--max-lines-per-file=2")
+        .file("tests/files/ascii.txt")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("a\na\na\nα\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn max_lines_infinite_input() {
+    util::test_command()
+        .option("-b1")
+        .option("-z")
+        .option("--max-lines=3")
+        .file("/dev/zero")
+        .build()
+        .timeout(std::time::Duration::from_secs(10))
+        .assert()
+        .code(0)
+        .stdout("\0\0\0");
+}
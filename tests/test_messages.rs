@@ -0,0 +1,80 @@
+pub mod util;
+
+/// Asserts that every line written to stderr, other than the usage text, starts with "rut: ".
+fn assert_prefixed(options: &[&str], files: &[&str], first_line: &str) {
+    let mut command = util::test_command().options(options);
+    for file in files {
+        command = command.file(file);
+    }
+    let output = command.build().output().unwrap();
+    assert_eq!(output.status.code(), Option::Some(1));
+
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let messages = stderr.split("\n\n").next().unwrap();
+    assert_eq!(
+        messages.lines().next(),
+        Option::Some(first_line),
+        "{}",
+        stderr
+    );
+    for line in messages.lines() {
+        assert!(line.starts_with("rut: "), "{}", stderr);
+    }
+}
+
+#[test]
+fn file_not_found() {
+    assert_prefixed(
+        &["-b1"],
+        &["tests/files/does-not-exist.txt", "tests/files/ascii.txt"],
+        "rut: tests/files/does-not-exist.txt: No such file or directory (os error 2)",
+    );
+}
+
+#[test]
+fn invalid_range() {
+    assert_prefixed(&["-b0"], &[], "rut: Ranges are numbered from one.");
+    assert_prefixed(&["-c2-1"], &[], "rut: Ranges must be ascending.");
+}
+
+#[test]
+fn invalid_range_position() {
+    util::test_command()
+        .option("-f1,2,10-11-12")
+        .build()
+        .assert()
+        .code(1)
+        .stderr(predicates::str::starts_with(
+            "rut: Indecipherable range: \"10-11-12\"\n\
+             rut:   1,2,10-11-12\n\
+             rut:       ^^^^^^^^ at position 5\n\nUsage:",
+        ));
+}
+
+#[test]
+fn invalid_utf8() {
+    assert_prefixed(
+        &["-c1"],
+        &["tests/files/invalid-utf8.txt"],
+        "rut: tests/files/invalid-utf8.txt: line 2 (byte 6): invalid UTF-8",
+    );
+    assert_prefixed(
+        &["-c1", "--skip-invalid"],
+        &["tests/files/invalid-utf8.txt"],
+        "rut: tests/files/invalid-utf8.txt: line 2 (byte 6): invalid UTF-8, skipped",
+    );
+}
+
+#[test]
+fn invalid_option() {
+    assert_prefixed(
+        &["-b1", "--no-such-option"],
+        &[],
+        "rut: unexpected argument '--no-such-option' found",
+    );
+    assert_prefixed(
+        &["-b1", "-f1"],
+        &[],
+        "rut: the argument '--bytes <ranges>' cannot be used with '--fields <ranges>'",
+    );
+}
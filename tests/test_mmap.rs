@@ -0,0 +1,77 @@
+pub mod util;
+
+use std::fs;
+
+/// Options to compare, covering each mode and the options which change where reading starts.
+const OPTIONS: &[&[&str]] = &[
+    &["-b1-"],
+    &["-b2,4-6"],
+    &["-b1-", "-z"],
+    &["-b1-", "--keep-bom"],
+    &["-b1-", "--no-decompress"],
+    &["-b1-", "--skip-bytes=3"],
+    &["-b1-", "--skip-bytes=1000000"],
+    &["-b1-", "--skip-lines=2"],
+    &["-b1-", "--keep-cr"],
+    &["-c1-3", "--lossy"],
+    &["-c2-", "--encoding=latin1"],
+    &["-f1", "-d,", "--skip-invalid"],
+    &["-f2-", "-d ", "-s"],
+    &["-f1", "-r[,:]"],
+];
+
+#[test]
+fn same_output_as_streaming() {
+    let mut fixtures = fs::read_dir("tests/files")
+        .unwrap()
+        .map(|entry| entry.unwrap().path().to_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+
+    for fixture in &fixtures {
+        for options in OPTIONS {
+            let streamed = util::test_command()
+                .options(options)
+                .file(fixture)
+                .build()
+                .output()
+                .unwrap();
+            let mapped = util::test_command()
+                .options(options)
+                .option("--mmap")
+                .file(fixture)
+                .build()
+                .output()
+                .unwrap();
+            assert_eq!(mapped.status, streamed.status, "{} {:?}", fixture, options);
+            assert_eq!(mapped.stdout, streamed.stdout, "{} {:?}", fixture, options);
+            assert_eq!(mapped.stderr, streamed.stderr, "{} {:?}", fixture, options);
+        }
+    }
+}
+
+#[test]
+fn empty_file() {
+    let dir = util::TempDir::new("mmap-empty");
+    let path = dir.write("empty.txt", "");
+
+    util::test_command()
+        .options(&["-b1-", "--mmap"])
+        .file(path.to_str().unwrap())
+        .build()
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn stdin_is_read_as_usual() {
+    util::test_command()
+        .options(&["-c2-3", "--mmap"])
+        .build()
+        .write_stdin("abcd\nefgh\n")
+        .assert()
+        .success()
+        .stdout("bc\nfg\n");
+}
@@ -0,0 +1,120 @@
+#![cfg(unix)]
+
+pub mod util;
+
+use std::ffi::OsStr;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::path::{Path, PathBuf};
+
+/// Creates a directory holding a file whose name is not valid UTF-8, and returns the directory
+/// along with the path of the file.
+fn non_utf8_file(name: &str) -> (util::TempDir, PathBuf) {
+    let dir = util::TempDir::new(name);
+    let file = dir.write(OsStr::from_bytes(b"a\xff.csv"), "a,b\nc,d\n");
+    (dir, file)
+}
+
+#[test]
+fn cut_file() {
+    let (_dir, file) = non_utf8_file("non-utf8-file");
+    assert!(file.to_str().is_none());
+    util::test_command()
+        .options(&["-f2", "-d,", "--verbose"])
+        .build()
+        .arg(&file)
+        .assert()
+        .success()
+        .stdout("b\nd\n")
+        .stderr(format!(
+            "rut: {0}: opened\nrut: {0}: wrote 2 lines\n",
+            file.display()
+        ));
+}
+
+#[test]
+fn missing_file() {
+    let (dir, _) = non_utf8_file("non-utf8-missing");
+    let missing = dir.path(OsStr::from_bytes(b"b\xfe.csv"));
+    util::test_command()
+        .option("-f1")
+        .build()
+        .arg(&missing)
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(format!(
+            "rut: {}/b\u{fffd}.csv: No such file or directory (os error 2)\n\
+             rut: 1 of 1 inputs failed\n",
+            dir.root().display()
+        ));
+}
+
+#[test]
+fn file_list() {
+    let (_dir, file) = non_utf8_file("non-utf8-list");
+    let mut list = file.into_os_string().into_vec();
+    list.push(0);
+    util::test_command()
+        .options(&["-f1", "-d,", "--files0-from=-"])
+        .build()
+        .write_stdin(list)
+        .assert()
+        .success()
+        .stdout("a\nc\n");
+}
+
+#[test]
+fn recursive() {
+    let (dir, _) = non_utf8_file("non-utf8-recursive");
+    util::test_command()
+        .options(&["-f2", "-d,", "--recursive"])
+        .file(dir.root().to_str().unwrap())
+        .build()
+        .assert()
+        .success()
+        .stdout("b\nd\n");
+}
+
+#[test]
+fn check() {
+    let (_dir, file) = non_utf8_file("non-utf8-check");
+    util::test_command()
+        .options(&["-f1", "--check"])
+        .build()
+        .arg(&file)
+        .arg(Path::new("-"))
+        .assert()
+        .success();
+}
+
+#[test]
+fn file_list_path() {
+    let (dir, file) = non_utf8_file("non-utf8-list-path");
+    let mut names = file.into_os_string().into_vec();
+    names.push(b'\n');
+    let list = dir.write(OsStr::from_bytes(b"list\xff.txt"), names);
+    util::test_command()
+        .options(&["-f1", "-d,"])
+        .build()
+        .arg("--files-from")
+        .arg(&list)
+        .assert()
+        .success()
+        .stdout("a\nc\n");
+}
+
+#[test]
+fn rejects_path() {
+    let dir = util::TempDir::new("non-utf8-rejects");
+    let rejects = dir.path(OsStr::from_bytes(b"rejects\xff.txt"));
+    util::test_command()
+        .options(&["-f1", "--skip-invalid"])
+        .build()
+        .arg("--rejects")
+        .arg(&rejects)
+        .write_stdin(&b"a\n\xff\nb\n"[..])
+        .assert()
+        .code(1)
+        .stdout("a\nb\n");
+    assert_eq!(std::fs::read(&rejects).unwrap(), b"\xff\n");
+}
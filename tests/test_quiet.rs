@@ -0,0 +1,66 @@
+pub mod util;
+
+/// Asserts that --quiet only removes the messages written to stderr, which must not be empty
+/// without it.
+fn assert_quiet(options: &[&str], files: &[&str]) {
+    let run = |quiet: bool| {
+        let mut command = util::test_command().options(options);
+        if quiet {
+            command = command.option("--quiet");
+        }
+        for file in files {
+            command = command.file(file);
+        }
+        command.build().output().unwrap()
+    };
+    let normal = run(false);
+    let quiet = run(true);
+    assert!(!normal.stderr.is_empty(), "{:?} {:?}", options, files);
+    assert_eq!(
+        String::from_utf8_lossy(&quiet.stderr),
+        "",
+        "{:?} {:?}",
+        options,
+        files
+    );
+    assert_eq!(quiet.status, normal.status, "{:?} {:?}", options, files);
+    assert_eq!(quiet.stdout, normal.stdout, "{:?} {:?}", options, files);
+}
+
+#[test]
+fn missing_files() {
+    let files = [
+        "tests/files/does-not-exist.txt",
+        "tests/files/ascii.txt",
+        "tests/files/also-missing.txt",
+    ];
+    assert_quiet(&["-b1-3"], &files);
+    assert_quiet(&["-b1-3", "--fail-fast"], &files);
+    assert_quiet(&["-b1-3", "-j2"], &files);
+    assert_quiet(&["-b1-3", "--check"], &files);
+}
+
+#[test]
+fn directories() {
+    assert_quiet(&["-c1"], &["tests/files", "tests/files/utf8.txt"]);
+    assert_quiet(&["-c1", "-R"], &["tests/does-not-exist", "tests/files"]);
+}
+
+#[test]
+fn invalid_utf8() {
+    assert_quiet(&["-c1-3"], &["tests/files/invalid-utf8.txt"]);
+    assert_quiet(
+        &["-f1", "--skip-invalid"],
+        &["tests/files/invalid-utf8.txt"],
+    );
+}
+
+#[test]
+fn argument_errors_are_reported() {
+    util::test_command()
+        .options(&["-q", "-b0"])
+        .build()
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains("rut: "));
+}
@@ -0,0 +1,57 @@
+pub mod util;
+
+#[test]
+fn recursive() {
+    let root = util::TempDir::new("recursive");
+    root.write("logs/b.csv", "b,1\nb,2\n");
+    root.write("logs/a/z.csv", "z,3\n");
+    root.write("logs/a/c.csv", "c,4\n");
+    root.write("logs/a/nested/d.csv", "d,5\n");
+    root.write("top.csv", "t,0\n");
+    let path = |name: &str| root.filename(name);
+
+    util::test_command()
+        .options(&["-f2", "-d,", "-R"])
+        .file(&path("top.csv"))
+        .file(&path("logs"))
+        .build()
+        .assert()
+        .code(0)
+        .stdout("0\n4\n5\n3\n1\n2\n");
+
+    // Without -R, directories are errors.
+    util::test_command()
+        .options(&["-f2", "-d,"])
+        .file(&path("logs"))
+        .file(&path("top.csv"))
+        .build()
+        .assert()
+        .code(1)
+        .stdout("0\n");
+}
+
+#[test]
+#[cfg(unix)]
+fn recursive_symlinks() {
+    let root = util::TempDir::new("recursive_symlinks");
+    root.write("dir/a.csv", "a,1\n");
+    root.write("other/b.csv", "b,2\n");
+    std::os::unix::fs::symlink(root.path("other"), root.path("dir/link")).unwrap();
+    let path = |name: &str| root.filename(name);
+
+    util::test_command()
+        .options(&["-f2", "-d,", "-R"])
+        .file(&path("dir"))
+        .build()
+        .assert()
+        .code(0)
+        .stdout("1\n");
+
+    util::test_command()
+        .options(&["-f2", "-d,", "-R", "--follow-symlinks"])
+        .file(&path("dir"))
+        .build()
+        .assert()
+        .code(0)
+        .stdout("1\n2\n");
+}
@@ -0,0 +1,78 @@
+pub mod util;
+
+#[test]
+fn skip_lines() {
+    util::test_command()
+        .option("-b1-3")
+        .option("--skip-lines=2")
+        .file("tests/files/ascii.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("a_b\na:b\na_b\na:b\n");
+
+    util::test_command()
+        .option("-c2")
+        .option("--skip-lines=1")
+        .build()
+        .write_stdin("αβ\nγδ\nεζ")
+        .assert()
+        .code(0)
+        .stdout("δ\nζ\n");
+
+    util::test_command()
+        .option("-f2")
+        .option("-d:")
+        .option("--skip-lines=3")
+        .option("-z")
+        .file("tests/files/ascii-zero.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("b\0");
+}
+
+#[test]
+fn skip_bytes() {
+    util::test_command()
+        .option("-b1-3")
+        .option("--skip-bytes=24")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("yz\na b\na_b\na:b\n");
+
+    util::test_command()
+        .option("-f1")
+        .option("-d,")
+        .option("--skip-bytes=4")
+        .option("--skip-lines=1")
+        .build()
+        .write_stdin("\x00\x01\x02\x03header,1\nvalue,2\n")
+        .assert()
+        .code(0)
+        .stdout("value\n");
+}
+
+#[test]
+fn skip_past_end() {
+    util::test_command()
+        .option("-c1")
+        .option("--skip-lines=100")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("");
+
+    util::test_command()
+        .option("-f1")
+        .option("--skip-bytes=100000")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("");
+}
@@ -0,0 +1,158 @@
+pub mod util;
+
+use std::fs;
+
+#[test]
+fn invalid_utf8_fails() {
+    util::test_command()
+        .option("-c1-2")
+        .file("tests/files/invalid-utf8.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("ab\n")
+        .stderr(
+            "rut: tests/files/invalid-utf8.txt: line 2 (byte 6): invalid UTF-8\n\
+             rut: 1 of 1 inputs failed\n",
+        );
+}
+
+#[test]
+fn skip_invalid() {
+    util::test_command()
+        .option("-c1-2")
+        .option("--skip-invalid")
+        .file("tests/files/invalid-utf8.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("ab\nfg\nab\na \na_\na:\n")
+        .stderr(
+            "rut: tests/files/invalid-utf8.txt: line 2 (byte 6): invalid UTF-8, skipped\n\
+             rut: tests/files/invalid-utf8.txt: line 4 (byte 18): invalid UTF-8, skipped\n\
+             rut: 1 of 2 inputs failed\n",
+        );
+
+    util::test_command()
+        .option("-f2")
+        .option("-d,")
+        .option("--skip-invalid")
+        .file("tests/files/invalid-utf8.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("1\n3\n");
+}
+
+#[test]
+fn invalid_after_selected_characters() {
+    // The whole line is checked, even though only its first character is selected.
+    util::test_command()
+        .option("-c1")
+        .build()
+        .write_stdin(&b"ab\xff\ncd\n"[..])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("rut: -: line 1 (byte 0): invalid UTF-8\nrut: 1 of 1 inputs failed\n");
+
+    util::test_command()
+        .options(&["-c1", "--skip-invalid"])
+        .build()
+        .write_stdin(&b"ab\xff\ncd\n"[..])
+        .assert()
+        .code(1)
+        .stdout("c\n")
+        .stderr("rut: -: line 1 (byte 0): invalid UTF-8, skipped\nrut: 1 of 1 inputs failed\n");
+
+    util::test_command()
+        .options(&["-c1", "--lossy"])
+        .build()
+        .write_stdin(&b"ab\xff\ncd\n"[..])
+        .assert()
+        .code(0)
+        .stdout("a\nc\n");
+}
+
+#[test]
+fn skip_invalid_rejects() {
+    let dir = util::TempDir::new("rejects");
+    let rejects = dir.path("rejects.txt");
+    util::test_command()
+        .option("-f1")
+        .option("-d,")
+        .option("--skip-invalid")
+        .option(&format!("--rejects={}", rejects.to_str().unwrap()))
+        .file("tests/files/invalid-utf8.txt")
+        .build()
+        .assert()
+        .code(1)
+        .stdout("abc\nfgh\n");
+    assert_eq!(fs::read(&rejects).unwrap(), b"d\xffe,2\n\xfe\n");
+}
+
+#[test]
+fn rejects_is_an_input() {
+    let dir = util::TempDir::new("rejects-input");
+    let contents = fs::read("tests/files/invalid-utf8.txt").unwrap();
+    let input = dir.write("input.txt", &contents);
+    let input = input.to_str().unwrap();
+    // The same file, by another name.
+    let link = dir.path("link.txt");
+    fs::hard_link(input, &link).unwrap();
+    let link = link.to_str().unwrap();
+
+    for rejects in [input, link] {
+        util::test_command()
+            .option("-f1")
+            .option("--skip-invalid")
+            .option(&format!("--rejects={}", rejects))
+            .file("tests/files/utf8.txt")
+            .file(input)
+            .build()
+            .assert()
+            .code(1)
+            .stdout("")
+            .stderr(format!(
+                "rut: {}: --rejects would overwrite the input file '{}'\n",
+                rejects, input
+            ));
+        assert_eq!(fs::read(input).unwrap(), contents);
+    }
+}
+
+#[test]
+fn skip_invalid_valid_input() {
+    util::test_command()
+        .option("-c1")
+        .option("--skip-invalid")
+        .file("tests/files/utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stderr("");
+}
+
+#[test]
+fn lossy() {
+    util::test_command()
+        .option("-c1-3")
+        .option("--lossy")
+        .build()
+        .write_stdin(&b"\xffab"[..])
+        .assert()
+        .code(0)
+        .stdout("\u{fffd}ab\n");
+
+    util::test_command()
+        .option("-f1")
+        .option("-d,")
+        .option("--lossy")
+        .file("tests/files/invalid-utf8.txt")
+        .build()
+        .assert()
+        .code(0)
+        .stdout("abc\nd\u{fffd}e\nfgh\n\u{fffd}\n")
+        .stderr("");
+}
@@ -0,0 +1,53 @@
+pub mod util;
+
+#[test]
+fn stdin_repeated() {
+    util::test_command()
+        .option("-f1")
+        .file("-")
+        .file("-")
+        .build()
+        .write_stdin("a\tb\nc\td\n")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::contains("--reuse-stdin"));
+}
+
+#[test]
+fn stdin_repeated_in_file_list() {
+    util::test_command()
+        .option("-f1")
+        .option("--files0-from=tests/files/stdin-twice.list")
+        .build()
+        .write_stdin("a\tb\n")
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr(predicates::str::contains("--reuse-stdin"));
+}
+
+#[test]
+fn reuse_stdin() {
+    util::test_command()
+        .option("-f2")
+        .option("--reuse-stdin")
+        .file("-")
+        .file("tests/files/ascii.txt")
+        .file("-")
+        .build()
+        .write_stdin("a\tb\nc\td\n")
+        .assert()
+        .code(0)
+        .stdout("b\nd\nabcdefghijklmnopqrstuvwxyz\na b c d e f g h i j k l m\na_b_c_d_e_f_g_h_i_j_k_l_m\na:b:c:d:e:f:g:h:i:j:k:l:m\nb\nd\n");
+
+    util::test_command()
+        .option("-f1")
+        .option("--reuse-stdin")
+        .option("--files0-from=tests/files/stdin-twice.list")
+        .build()
+        .write_stdin("a\tb\n")
+        .assert()
+        .code(0)
+        .stdout("a\na\n");
+}
@@ -0,0 +1,101 @@
+pub mod util;
+
+const FILES: &[&str] = &["tests/files/ascii.txt", "tests/files/ascii.txt.gz"];
+
+fn run(options: &[&str]) -> std::process::Output {
+    let mut command = util::test_command().options(options);
+    for file in FILES {
+        command = command.file(file);
+    }
+    command.build().output().unwrap()
+}
+
+#[test]
+fn verbose() {
+    let output = run(&["-b1-3", "-v"]);
+    assert!(output.status.success());
+    assert_eq!(output.stdout, run(&["-b1-3"]).stdout);
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "rut: tests/files/ascii.txt: opened\n\
+         rut: tests/files/ascii.txt: wrote 4 lines\n\
+         rut: tests/files/ascii.txt.gz: opened (gzip decompressed)\n\
+         rut: tests/files/ascii.txt.gz: wrote 4 lines\n"
+    );
+}
+
+#[test]
+fn more_verbose() {
+    let output = run(&["-f1", "-d,", "-vv", "--config=tests/config/good.toml"]);
+    assert!(output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let lines = stderr.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 5, "{}", stderr);
+    assert_eq!(lines[0], "rut: read config file tests/config/good.toml");
+    assert_eq!(lines[1], "rut: tests/files/ascii.txt: opened");
+    assert!(lines[2].starts_with("rut: tests/files/ascii.txt: wrote 4 lines in "));
+    assert!(lines[2].ends_with(" ms"));
+    assert_eq!(
+        lines[3],
+        "rut: tests/files/ascii.txt.gz: opened (gzip decompressed)"
+    );
+    assert!(lines[4].starts_with("rut: tests/files/ascii.txt.gz: wrote 4 lines in "));
+}
+
+#[test]
+fn one_line() {
+    util::test_command()
+        .options(&["-b1", "-v"])
+        .build()
+        .write_stdin("abc\n")
+        .assert()
+        .success()
+        .stdout("a\n")
+        .stderr("rut: -: opened\nrut: -: wrote 1 line\n");
+
+    let output = util::test_command()
+        .options(&["-b1", "-vv"])
+        .build()
+        .write_stdin("abc\n")
+        .output()
+        .unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("rut: -: wrote 1 line in "), "{}", stderr);
+}
+
+#[test]
+fn jobs() {
+    assert_eq!(
+        run(&["-c2-", "-v", "-j2"]).stderr,
+        run(&["-c2-", "-v"]).stderr
+    );
+}
+
+#[test]
+fn errors_are_still_reported() {
+    util::test_command()
+        .options(&["-b1", "-v"])
+        .file("tests/files/does-not-exist.txt")
+        .file("tests/files/ascii.txt")
+        .build()
+        .assert()
+        .failure()
+        .stdout("a\na\na\na\n")
+        .stderr(predicates::str::starts_with(
+            "rut: tests/files/does-not-exist.txt: ",
+        ))
+        .stderr(predicates::str::ends_with(
+            "rut: tests/files/ascii.txt: opened\n\
+             rut: tests/files/ascii.txt: wrote 4 lines\n\
+             rut: 1 of 2 inputs failed\n",
+        ));
+}
+
+#[test]
+fn conflicts_with_quiet() {
+    util::test_command()
+        .options(&["-b1", "-v", "-q"])
+        .build()
+        .assert()
+        .failure();
+}
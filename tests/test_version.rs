@@ -0,0 +1,49 @@
+pub mod util;
+
+use predicates::prelude::*;
+
+#[test]
+fn short_version() {
+    let output = util::test_command()
+        .option("--version")
+        .build()
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Option::Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.starts_with("rut 0."), "{}", stdout);
+    assert_eq!(stdout.lines().count(), 1, "{}", stdout);
+}
+
+#[test]
+fn long_version() {
+    for options in &[
+        &["--version", "--verbose"],
+        &["-v", "--version"],
+        &["-V", "-vv"],
+    ] {
+        util::test_command()
+            .options(*options)
+            .build()
+            .assert()
+            .code(0)
+            .stdout(predicates::str::starts_with("rut 0."))
+            .stdout(predicates::str::contains("\ncommit: "))
+            .stdout(predicates::str::contains("\nbuild date: "))
+            .stdout(predicates::str::contains(format!(
+                "\ntarget: {}",
+                std::env::consts::ARCH
+            )))
+            .stdout(predicates::str::contains(std::env::consts::OS))
+            .stdout(predicates::str::contains("\nrustc: rustc "))
+            .stdout(predicates::str::contains("\nfeatures: "));
+    }
+
+    // -v after "--" is a file name.
+    util::test_command()
+        .options(&["--version", "--", "-v"])
+        .build()
+        .assert()
+        .code(0)
+        .stdout(predicates::str::contains("commit:").not());
+}
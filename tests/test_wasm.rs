@@ -0,0 +1,46 @@
+use std::path::Path;
+use std::process::Command;
+
+const TARGET: &str = "wasm32-unknown-unknown";
+
+/// The library, without the command line tool's features, must build for WebAssembly, which has no
+/// file system, process or standard streams. Skipped if the target is not installed.
+#[test]
+fn library_builds_for_wasm() {
+    if !target_installed() {
+        eprintln!("skipping: the {} target is not installed", TARGET);
+        return;
+    }
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "check",
+            "--lib",
+            "--no-default-features",
+            "--target",
+            TARGET,
+        ])
+        .current_dir(manifest_dir)
+        // A separate target directory, since the one running the tests is locked.
+        .env(
+            "CARGO_TARGET_DIR",
+            manifest_dir.join("target").join("wasm-check"),
+        )
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn target_installed() -> bool {
+    Command::new("rustc")
+        .args(["--print", "target-libdir", "--target", TARGET])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .is_some_and(|libdir| Path::new(libdir.trim()).is_dir())
+}
@@ -0,0 +1,113 @@
+#![cfg(target_os = "linux")]
+
+use std::fs::File;
+use std::process::{Command, Output, Stdio};
+
+/// Runs rut with stdout redirected to /dev/full, where every write fails with "No space left on
+/// device".
+fn cut_to_full_device(args: &[&str]) -> Output {
+    Command::new(assert_cmd::cargo::cargo_bin("rut"))
+        .args(args)
+        .env_remove("RUT_CONFIG")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist")
+        .stdin(Stdio::null())
+        .stdout(File::create("/dev/full").unwrap())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn write_error_is_not_about_the_input() {
+    for args in [
+        &[
+            "-f1",
+            "-d,",
+            "tests/files/ascii.txt",
+            "tests/files/utf8.txt",
+        ][..],
+        &["-b1-", "-z", "tests/files/ascii.txt"],
+        &["-b1-", "-z", "--zero-copy", "tests/files/ascii.txt"],
+        &[
+            "-c1",
+            "-j2",
+            "tests/files/ascii.txt",
+            "tests/files/utf8.txt",
+        ],
+        &["-c1", "--line-buffered", "tests/files/ascii.txt"],
+        &["-c1", "--fail-fast", "tests/files/ascii.txt"],
+        &["-c1", "--quiet", "tests/files/ascii.txt"],
+        &["-b1", "--compress-output", "tests/files/ascii.txt"],
+        &["-b1", "--compress-output", "-j2", "tests/files/ascii.txt"],
+    ] {
+        let output = cut_to_full_device(args);
+        assert_eq!(output.status.code(), Option::Some(1), "{:?}", args);
+        assert_eq!(
+            String::from_utf8(output.stderr).unwrap(),
+            "rut: write error: No space left on device (os error 28)\n",
+            "{:?}",
+            args
+        );
+    }
+}
+
+#[test]
+fn write_error_with_failed_inputs() {
+    let output = cut_to_full_device(&["-c1", "does-not-exist.txt", "tests/files/ascii.txt"]);
+    assert_eq!(output.status.code(), Option::Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "rut: does-not-exist.txt: No such file or directory (os error 2)\n\
+         rut: write error: No space left on device (os error 28)\n\
+         rut: 1 of 2 inputs failed\n"
+    );
+}
+
+#[test]
+fn write_error_gnu_format() {
+    let output = cut_to_full_device(&["--compat=gnu", "-c1", "tests/files/ascii.txt"]);
+    assert_eq!(output.status.code(), Option::Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "cut: write error: No space left on device\n"
+    );
+}
+
+#[test]
+fn write_error_json_format() {
+    // Compressed output is only written once it is finished, which is also when it is flushed.
+    for args in [
+        &["--error-format=json", "-c1", "tests/files/ascii.txt"][..],
+        &[
+            "--error-format=json",
+            "--compress-output",
+            "-c1",
+            "tests/files/ascii.txt",
+        ],
+    ] {
+        let output = cut_to_full_device(args);
+        assert_eq!(output.status.code(), Option::Some(1), "{:?}", args);
+        let stderr = String::from_utf8(output.stderr).unwrap();
+        let diagnostics: Vec<serde_json::Value> = stderr
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(diagnostics.len(), 1, "{:?}: {}", args, stderr);
+        assert_eq!(diagnostics[0]["kind"], "write", "{:?}", args);
+    }
+}
+
+#[test]
+fn rejects_file_write_error() {
+    let output = Command::new(assert_cmd::cargo::cargo_bin("rut"))
+        .args(["-c1", "--skip-invalid", "--quiet", "--rejects", "/dev/full"])
+        .arg("tests/files/invalid-utf8.txt")
+        .env_remove("RUT_CONFIG")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Option::Some(1));
+    assert_eq!(
+        String::from_utf8(output.stderr).unwrap(),
+        "rut: /dev/full: No space left on device (os error 28)\n"
+    );
+}
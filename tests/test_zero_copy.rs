@@ -0,0 +1,115 @@
+pub mod util;
+
+use std::fs::{self, OpenOptions};
+use std::process::{Command, Stdio};
+
+/// Options to compare, covering the options which let files be copied and some which do not.
+const OPTIONS: &[&[&str]] = &[
+    &["-b1-", "--keep-cr"],
+    &["-b1-", "-z"],
+    &["-b1-", "--keep-cr", "--keep-bom"],
+    &["-b1-", "--keep-cr", "--no-decompress"],
+    &["-b1-", "--keep-cr", "--skip-bytes=3"],
+    &["-b1-", "--keep-cr", "--skip-bytes=1000000"],
+    &["-b1-", "--keep-cr", "--encoding=latin1"],
+    &["-b1-", "--keep-cr", "--skip-lines=2"],
+    &["-b1-"],
+    &["-b2-", "--keep-cr"],
+    &["-f1-", "-d,", "--keep-cr"],
+];
+
+fn fixtures() -> Vec<String> {
+    let mut fixtures = fs::read_dir("tests/files")
+        .unwrap()
+        .map(|entry| entry.unwrap().path().to_str().unwrap().to_string())
+        .collect::<Vec<_>>();
+    fixtures.sort();
+    assert!(!fixtures.is_empty());
+    fixtures
+}
+
+#[test]
+fn same_output_as_cutting() {
+    let fixtures = fixtures();
+    for options in OPTIONS {
+        let cut = |zero_copy: &[&str]| {
+            let mut command = util::test_command().options(options).options(zero_copy);
+            for fixture in &fixtures {
+                command = command.file(fixture);
+            }
+            command.build().output().unwrap()
+        };
+        let streamed = cut(&[]);
+        let copied = cut(&["--zero-copy"]);
+        assert_eq!(copied.status, streamed.status, "{:?}", options);
+        assert_eq!(copied.stdout, streamed.stdout, "{:?}", options);
+        assert_eq!(copied.stderr, streamed.stderr, "{:?}", options);
+    }
+}
+
+/// Runs rut with stdout redirected to a new file, opened for appending if `append` is set, and
+/// returns the contents of the file.
+fn cut_to_file(name: &str, options: &[&str], files: &[String], append: bool) -> Vec<u8> {
+    let dir = util::TempDir::new(name);
+    let path = dir.path("stdout");
+    let output = OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(append)
+        .open(&path)
+        .unwrap();
+    let status = Command::new(assert_cmd::cargo::cargo_bin("rut"))
+        .args(options)
+        .args(files)
+        .env_remove("RUT_CONFIG")
+        .env("XDG_CONFIG_HOME", "tests/does-not-exist")
+        .stdin(Stdio::null())
+        .stdout(output)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    fs::read(&path).unwrap()
+}
+
+#[test]
+fn stdout_is_a_file() {
+    let fixtures = fixtures();
+    let mut command = util::test_command().options(&["-b1-", "-z"]);
+    for fixture in &fixtures {
+        command = command.file(fixture);
+    }
+    let streamed = command.build().output().unwrap().stdout;
+
+    // Files are copied to a regular file with sendfile, but copied as usual when it is opened for
+    // appending.
+    let options = ["-b1-", "-z", "--zero-copy"];
+    for append in [false, true] {
+        let copied = cut_to_file("zero-copy-stdout", &options, &fixtures, append);
+        assert_eq!(copied, streamed, "append {}", append);
+    }
+}
+
+#[test]
+fn missing_line_delimiter_is_added() {
+    let dir = util::TempDir::new("zero-copy-unterminated");
+    dir.write("unterminated", "a\nb");
+    dir.write("empty", "");
+    dir.write("terminated", "c\n");
+
+    let mut command = util::test_command().options(&["-b1-", "--keep-cr", "--zero-copy"]);
+    for name in &["unterminated", "empty", "terminated"] {
+        command = command.file(&dir.filename(name));
+    }
+    command.build().assert().success().stdout("a\nb\nc\n");
+}
+
+#[test]
+fn stdin_is_cut_as_usual() {
+    util::test_command()
+        .options(&["-b1-", "--keep-cr", "--zero-copy"])
+        .build()
+        .write_stdin("ab\r\ncd")
+        .assert()
+        .success()
+        .stdout("ab\r\ncd\n");
+}
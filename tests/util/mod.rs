@@ -1,3 +1,6 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
 pub fn test_command() -> TestCommandBuilder {
     TestCommandBuilder {
         options: Vec::new(),
@@ -17,8 +20,7 @@ impl TestCommandBuilder {
     }
 
     pub fn options(mut self, options: &[&str]) -> TestCommandBuilder {
-        self.options
-            .extend(options.into_iter().map(|s| s.to_string()));
+        self.options.extend(options.iter().map(|s| s.to_string()));
         self
     }
 
@@ -33,8 +35,65 @@ impl TestCommandBuilder {
         args.extend(self.files);
 
         let mut command = assert_cmd::Command::cargo_bin("rut").unwrap();
+        // Do not read option defaults from the user's environment or config file.
+        for name in &[
+            "RUT_CONFIG",
+            "RUT_DELIMITER",
+            "RUT_OUTPUT_DELIMITER",
+            "RUT_LINE_BUFFERED",
+        ] {
+            command.env_remove(name);
+        }
+        command.env("XDG_CONFIG_HOME", "tests/does-not-exist");
+        command.env("APPDATA", "tests/does-not-exist");
 
         command.args(args);
         command
     }
 }
+
+/// A directory for the files of a test, which is removed when dropped.
+pub struct TempDir {
+    root: PathBuf,
+}
+
+impl TempDir {
+    /// Creates an empty directory in the system's temp directory. `name` must be unique among the
+    /// tests, which run concurrently.
+    pub fn new(name: &str) -> TempDir {
+        let root = std::env::temp_dir().join(format!("rut-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        TempDir { root }
+    }
+
+    /// Returns the path of the directory.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Returns the path of a file within the directory.
+    pub fn path<P: AsRef<Path>>(&self, name: P) -> PathBuf {
+        self.root.join(name)
+    }
+
+    /// Returns the path of a file within the directory as a string, to pass to
+    /// `TestCommandBuilder::file`.
+    pub fn filename(&self, name: &str) -> String {
+        self.path(name).to_str().unwrap().to_string()
+    }
+
+    /// Writes a file within the directory, creating its parent directories, and returns its path.
+    pub fn write<P: AsRef<Path>, C: AsRef<[u8]>>(&self, name: P, contents: C) -> PathBuf {
+        let path = self.path(name);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, contents).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+    }
+}